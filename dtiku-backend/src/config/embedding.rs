@@ -1,8 +0,0 @@
-use serde::Deserialize;
-use spring::config::Configurable;
-
-#[derive(Debug, Deserialize, Configurable)]
-#[config_prefix = "embedding"]
-pub struct EmbeddingConfig {
-    pub(crate) url: String,
-}
@@ -0,0 +1,11 @@
+use dtiku_paper::domain::ingest::IngestRules;
+use serde::Deserialize;
+use spring::config::Configurable;
+
+#[derive(Debug, Clone, Default, Deserialize, Configurable)]
+#[config_prefix = "ingest"]
+pub struct IngestConfig {
+    /// 默认的真题文档识别规则，上传时可以逐项覆盖
+    #[serde(default)]
+    pub rules: IngestRules,
+}
@@ -1,2 +1,2 @@
-pub mod embedding;
+pub mod ingest;
 pub mod openai;
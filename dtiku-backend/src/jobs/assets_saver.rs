@@ -1,5 +1,5 @@
-use crate::plugins::embedding::Embedding;
 use anyhow::Context as _;
+use dtiku_base::embedding::Embedding;
 use dtiku_base::model::{schedule_task, ScheduleTask};
 use dtiku_paper::model::{assets, Assets};
 use futures::future;
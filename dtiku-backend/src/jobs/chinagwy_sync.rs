@@ -1,18 +1,21 @@
 use super::{JobScheduler, PaperSyncer};
 use crate::{
     jobs::{MaterialIdNumber, QuestionIdNumber},
-    plugins::embedding::Embedding,
+    service::dedup::load_deduplicator,
     utils::regex as regex_util,
 };
 use anyhow::Context;
+use dtiku_base::embedding::Embedding;
 use dtiku_base::model::schedule_task::{self, Progress, TaskInstance};
+use dtiku_paper::domain::dedup::DedupConfig;
 use dtiku_paper::model::{
     label, material,
     paper::{self, Chapters, EssayCluster, PaperBlock, PaperChapter, PaperExtra},
     paper_material, paper_question,
     question::{self, QuestionExtra},
+    revision::RevisionSource,
     solution::{self, MultiChoice, SingleChoice, SolutionExtra, StepByStepAnswer, TrueFalseChoice},
-    ExamCategory, FromType, Label,
+    ExamCategory, FromType, KeyPoint, KeyPointMapping, Label, PaperQuestion,
 };
 use futures::StreamExt as _;
 use itertools::Itertools as _;
@@ -35,6 +38,8 @@ pub struct ChinaGwySyncService {
     target_db: DbConn,
     #[inject(component)]
     embedding: Embedding,
+    #[inject(config)]
+    dedup_config: DedupConfig,
     task: schedule_task::Model,
     #[allow(unused)]
     instance: TaskInstance,
@@ -245,6 +250,7 @@ impl ChinaGwySyncService {
             self.save_material(m, paper.id, *num).await?;
         }
 
+        let dedup = load_deduplicator(&self.target_db, &self.dedup_config).await?;
         for q in questions {
             let num = qid_num_map
                 .get(&q.id)
@@ -253,7 +259,11 @@ impl ChinaGwySyncService {
             question.exam_id = Set(paper.exam_id);
             question.paper_type = Set(paper.paper_type);
             let q_in_db = question
-                .insert_on_conflict(&self.target_db)
+                .insert_on_conflict(
+                    &self.target_db,
+                    &RevisionSource::vendor(FromType::Chinagwy),
+                    &dedup,
+                )
                 .await
                 .context("insert question failed")?;
             let mut solution = q.to_solution()?;
@@ -271,11 +281,38 @@ impl ChinaGwySyncService {
             .insert_on_conflict(&self.target_db)
             .await
             .context("insert paper_question failed")?;
+            self.fill_chapter_keypoint_path(paper, q_in_db.id, *num).await?;
         }
 
         Ok(())
     }
 
+    /// 按题目所在的章节名称查找知识点，章节被合并到规范知识点时通过映射找到
+    async fn fill_chapter_keypoint_path(
+        &self,
+        paper: &paper::Model,
+        question_id: i32,
+        num: i32,
+    ) -> anyhow::Result<()> {
+        let Some(chapter_name) = paper.extra.compute_chapter_name(num) else {
+            return Ok(());
+        };
+        let kp = KeyPointMapping::find_key_point_by_name(
+            &self.target_db,
+            FromType::Chinagwy,
+            paper.paper_type,
+            &chapter_name,
+        )
+        .await?;
+        let Some(kp) = kp else {
+            return Ok(());
+        };
+        if let Some(path) = KeyPoint::query_keypoint_path(&self.target_db, kp.id).await? {
+            PaperQuestion::fill_keypoint_path(&self.target_db, question_id, &path).await?;
+        }
+        Ok(())
+    }
+
     async fn save_material(
         &self,
         m: OriginMaterial,
@@ -284,7 +321,9 @@ impl ChinaGwySyncService {
     ) -> Result<(), anyhow::Error> {
         let source_material_id = m.id;
         let material = TryInto::<material::ActiveModel>::try_into(m)?;
-        let m_in_db = material.insert_on_conflict(&self.target_db).await?;
+        let m_in_db = material
+            .insert_on_conflict(&self.target_db, &RevisionSource::vendor(FromType::Chinagwy))
+            .await?;
         paper_material::ActiveModel {
             paper_id: Set(paper_id),
             material_id: Set(m_in_db.id),
@@ -398,7 +437,7 @@ impl OriginPaper {
                 extra: Set(extra),
                 ..Default::default()
             }
-            .insert_on_conflict(db)
+            .insert_on_conflict(db, &RevisionSource::vendor(FromType::Chinagwy))
             .await
             .context("paper insert failed")
         } else {
@@ -427,7 +466,7 @@ impl OriginPaper {
                 extra: Set(extra),
                 ..Default::default()
             }
-            .insert_on_conflict(db)
+            .insert_on_conflict(db, &RevisionSource::vendor(FromType::Chinagwy))
             .await
             .context("paper insert failed")
         }
@@ -0,0 +1,85 @@
+use dtiku_base::model::{schedule_task, ScheduleTask};
+use dtiku_paper::{
+    domain::question::QuestionCorrectRatio,
+    model::{ExerciseAnswer, PaperQuestion},
+};
+use sea_orm::{ActiveValue::Set, EntityTrait as _};
+use serde_json::Value;
+use spring::{plugin::Service, tracing};
+use spring_sea_orm::DbConn;
+use std::collections::HashMap;
+
+/// 每批统计的题目数
+const BATCH_SIZE: u64 = 500;
+
+#[derive(Debug, Service)]
+#[service(prototype)]
+pub struct CorrectRatioStatsService {
+    #[inject(component)]
+    db: DbConn,
+    task: schedule_task::Model,
+}
+
+impl CorrectRatioStatsService {
+    pub async fn start(&mut self) {
+        self.stats_correct_ratio()
+            .await
+            .expect("stats correct ratio failed");
+
+        let _ = ScheduleTask::update(schedule_task::ActiveModel {
+            id: Set(self.task.id),
+            version: Set(self.task.version + 1),
+            active: Set(false),
+            ..Default::default()
+        })
+        .exec(&self.db)
+        .await
+        .is_err_and(|e| {
+            tracing::error!("update task error: {:?}", e);
+            false
+        });
+    }
+
+    /// 汇总用户作答，按题目id分批更新正确率，全部完成后重置进度以便下次重新统计
+    async fn stats_correct_ratio(&mut self) -> anyhow::Result<()> {
+        let mut last_id = match &self.task.context {
+            Value::Number(last_id) => last_id.as_i64().unwrap_or_default() as i32,
+            _ => 0,
+        };
+        tracing::warn!("stats_correct_ratio({last_id}) started");
+
+        loop {
+            let stats =
+                ExerciseAnswer::stats_by_question_id_gt(&self.db, last_id, BATCH_SIZE).await?;
+            if stats.is_empty() {
+                tracing::warn!("stats_correct_ratio() finished");
+                self.task = self.task.update_context(0, &self.db).await?;
+                return Ok(());
+            }
+            let qids = stats.iter().map(|s| s.question_id).collect();
+            let mut vendor_ratios: HashMap<i32, Vec<(i32, Option<f32>)>> = HashMap::new();
+            for (paper_id, question_id, ratio) in
+                PaperQuestion::find_vendor_correct_ratio_by_question_id_in(&self.db, qids).await?
+            {
+                vendor_ratios
+                    .entry(question_id)
+                    .or_default()
+                    .push((paper_id, ratio));
+            }
+            for s in stats {
+                let qid = s.question_id;
+                for (paper_id, vendor) in vendor_ratios.remove(&qid).unwrap_or_default() {
+                    let ratio = QuestionCorrectRatio::new(vendor, s.total, s.correct);
+                    if let Err(e) =
+                        PaperQuestion::update_user_correct_ratio(&self.db, paper_id, qid, &ratio)
+                            .await
+                    {
+                        tracing::error!("update_user_correct_ratio({paper_id},{qid}) error: {e:?}");
+                    }
+                }
+                last_id = qid.max(last_id);
+            }
+            self.task = self.task.update_context(last_id, &self.db).await?;
+        }
+    }
+}
@@ -1,15 +1,18 @@
 use super::PaperSyncer;
 use crate::jobs::{JobScheduler, MaterialIdNumber, QuestionIdNumber};
-use crate::plugins::embedding::Embedding;
+use crate::service::dedup::load_deduplicator;
 use crate::utils::regex as regex_util;
 use anyhow::Context;
+use dtiku_base::embedding::Embedding;
 use dtiku_base::model::schedule_task;
 use dtiku_base::model::schedule_task::{Progress, TaskInstance};
+use dtiku_paper::domain::dedup::DedupConfig;
 use dtiku_paper::model::paper::EssayCluster;
 use dtiku_paper::model::paper::PaperBlock;
 use dtiku_paper::model::paper::PaperChapter;
 use dtiku_paper::model::paper::{Chapters, PaperExtra};
 use dtiku_paper::model::question;
+use dtiku_paper::model::revision::RevisionSource;
 use dtiku_paper::model::solution::AnswerAnalysis;
 use dtiku_paper::model::solution::FillBlank;
 use dtiku_paper::model::solution::MultiChoice;
@@ -21,7 +24,7 @@ use dtiku_paper::model::solution::TrueFalseChoice;
 use dtiku_paper::model::solution::{self, BlankAnswer};
 use dtiku_paper::model::FromType;
 use dtiku_paper::model::Label;
-use dtiku_paper::model::{exam_category, KeyPoint};
+use dtiku_paper::model::{exam_category, KeyPoint, KeyPointMapping};
 use dtiku_paper::model::{key_point, material};
 use dtiku_paper::model::{label, ExamCategory};
 use dtiku_paper::model::{paper, question_material};
@@ -66,6 +69,8 @@ pub struct FenbiSyncService {
     target_db: DbConn,
     #[inject(component)]
     embedding: Embedding,
+    #[inject(config)]
+    dedup_config: DedupConfig,
     task: schedule_task::Model,
     instance: TaskInstance,
 }
@@ -212,7 +217,7 @@ impl FenbiSyncService {
                             Box::pin(async move {
                                 for c in extra.0 {
                                     Self::save_question_category_to_keypoint(
-                                        c, 0, paper_type, exam_id, tx,
+                                        c, 0, "", paper_type, exam_id, tx,
                                     )
                                     .await?;
                                 }
@@ -234,31 +239,52 @@ impl FenbiSyncService {
         Ok(())
     }
 
+    /// parent_path为粉笔知识点树中父知识点名称的路径，用于查找已经合并或移动的知识点
     async fn save_question_category_to_keypoint<C: ConnectionTrait>(
         c: QuestionCategory,
         parent_id: i32,
+        parent_path: &str,
         paper_type: i16,
         exam_id: i16,
         target_db: &C,
     ) -> anyhow::Result<()> {
-        let kp = key_point::ActiveModel {
-            name: Set(c.name),
-            pid: Set(parent_id),
-            paper_type: Set(paper_type),
-            exam_id: Set(exam_id),
-            ..Default::default()
-        }
-        .insert_on_conflict(target_db)
+        // 已经合并到规范知识点的厂商知识点，不再重复创建
+        let mapped = KeyPointMapping::find_key_point(
+            target_db,
+            FromType::Fenbi,
+            paper_type,
+            parent_path,
+            &c.name,
+        )
         .await?;
-
-        let c_parent_id = kp.id;
+        let path = match parent_path {
+            "" => c.name.clone(),
+            _ => format!("{parent_path}/{}", c.name),
+        };
+        let c_parent_id = match mapped {
+            Some(kp) => kp.id,
+            None => {
+                key_point::ActiveModel {
+                    name: Set(c.name),
+                    pid: Set(parent_id),
+                    paper_type: Set(paper_type),
+                    exam_id: Set(exam_id),
+                    ..Default::default()
+                }
+                .insert_on_conflict(target_db)
+                .await?
+                .id
+            }
+        };
 
         if let Some(cs) = c.children {
             for c in cs {
+                let path = &path;
                 Box::pin(async move {
                     Self::save_question_category_to_keypoint(
                         c,
                         c_parent_id,
+                        path,
                         paper_type,
                         exam_id,
                         target_db,
@@ -552,6 +578,7 @@ impl FenbiSyncService {
             self.save_material(m, paper.id, *num).await?;
         }
 
+        let dedup = load_deduplicator(&self.target_db, &self.dedup_config).await?;
         for mut q in questions {
             let correct_ratio = q.correct_ratio;
             let num = qid_num_map
@@ -569,7 +596,11 @@ impl FenbiSyncService {
             question.exam_id = Set(paper.exam_id);
             question.paper_type = Set(paper.paper_type);
             let q_in_db = question
-                .insert_on_conflict(&self.target_db)
+                .insert_on_conflict(
+                    &self.target_db,
+                    &RevisionSource::vendor(FromType::Fenbi),
+                    &dedup,
+                )
                 .await
                 .context("insert question failed")?;
             let qid_in_db = q_in_db.id;
@@ -642,22 +673,20 @@ impl FenbiSyncService {
                     let mut keypoint_ids = vec![];
                     for kp in keypoints.0 {
                         let paper_type = paper.paper_type;
-                        let keypoint_name = kp.name;
-                        let kp = KeyPoint::find_by_paper_type_and_name(
+                        let kp = KeyPointMapping::find_key_point_by_name(
                             &self.target_db,
+                            FromType::Fenbi,
                             paper_type,
-                            &keypoint_name,
+                            &kp.name,
                         )
-                        .await
-                        .with_context(|| {
-                            format!("find paper_type#{paper_type} keypoint({keypoint_name}) failed")
-                        })?;
+                        .await?;
 
                         if let Some(keypoint) = kp {
                             question_keypoint::ActiveModel {
                                 question_id: Set(q_in_db.id),
                                 key_point_id: Set(keypoint.id),
                                 year: Set(paper.year),
+                                confidence: Set(None),
                             }
                             .insert_on_conflict(&self.target_db)
                             .await
@@ -670,15 +699,13 @@ impl FenbiSyncService {
                 None => {
                     let paper_type = paper.paper_type;
                     if let Some(chapter_name) = paper.extra.compute_chapter_name(*num) {
-                        let kp = KeyPoint::find_by_paper_type_and_name(
+                        let kp = KeyPointMapping::find_key_point_by_name(
                             &self.target_db,
+                            FromType::Fenbi,
                             paper_type,
                             &chapter_name,
                         )
-                        .await
-                        .with_context(|| {
-                            format!("find paper_type#{paper_type} keypoint({chapter_name}) failed")
-                        })?;
+                        .await?;
                         if let Some(keypoint) = kp {
                             KeyPoint::query_common_keypoint_path(
                                 &self.target_db,
@@ -767,7 +794,9 @@ impl FenbiSyncService {
     ) -> Result<(), anyhow::Error> {
         let source_material_id = m.id;
         let material = TryInto::<material::ActiveModel>::try_into(m)?;
-        let m_in_db = material.insert_on_conflict(&self.target_db).await?;
+        let m_in_db = material
+            .insert_on_conflict(&self.target_db, &RevisionSource::vendor(FromType::Fenbi))
+            .await?;
         paper_material::ActiveModel {
             paper_id: Set(paper_id),
             material_id: Set(m_in_db.id),
@@ -944,7 +973,7 @@ impl OriginPaper {
         active_model.extra = Set(extra_value);
 
         let paper = active_model
-            .insert_on_conflict(db)
+            .insert_on_conflict(db, &RevisionSource::vendor(FromType::Fenbi))
             .await
             .context("insert paper failed")?;
 
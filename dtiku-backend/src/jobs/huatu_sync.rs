@@ -1,17 +1,20 @@
 use super::{JobScheduler, PaperSyncer};
 use crate::jobs::{MaterialIdNumber, QuestionIdNumber};
-use crate::plugins::embedding::Embedding;
+use crate::service::dedup::load_deduplicator;
 use anyhow::{anyhow, Context};
+use dtiku_base::embedding::Embedding;
 use dtiku_base::model::schedule_task::{self, Progress, TaskInstance};
+use dtiku_paper::domain::dedup::DedupConfig;
 use dtiku_paper::model::paper::{Chapters, EssayCluster, PaperChapter, PaperExtra};
 use dtiku_paper::model::question::QuestionExtra;
+use dtiku_paper::model::revision::RevisionSource;
 use dtiku_paper::model::solution::{
     AnswerAnalysis, BlankAnswer, FillBlank, MultiChoice, OtherAnswer, SingleChoice, SolutionExtra,
     StepAnalysis, StepByStepAnswer, TrueFalseChoice,
 };
 use dtiku_paper::model::{
     exam_category, label, material, paper, paper_material, question, question_keypoint, solution,
-    ExamCategory, FromType, KeyPoint, Label,
+    ExamCategory, FromType, KeyPoint, KeyPointMapping, Label,
 };
 use futures::StreamExt;
 use itertools::Itertools;
@@ -39,6 +42,8 @@ pub struct HuatuSyncService {
     target_db: DbConn,
     #[inject(component)]
     embedding: Embedding,
+    #[inject(config)]
+    dedup_config: DedupConfig,
     task: schedule_task::Model,
     #[allow(unused)]
     instance: TaskInstance,
@@ -379,6 +384,7 @@ impl HuatuSyncService {
             self.save_material(m, paper.id, *num).await?;
         }
 
+        let dedup = load_deduplicator(&self.target_db, &self.dedup_config).await?;
         for q in questions {
             let correct_ratio = 1.0 - q.difficult / 10.0;
             let num = qid_num_map
@@ -388,7 +394,11 @@ impl HuatuSyncService {
             question.exam_id = Set(paper.exam_id);
             question.paper_type = Set(paper.paper_type);
             let q_in_db = question
-                .insert_on_conflict(&self.target_db)
+                .insert_on_conflict(
+                    &self.target_db,
+                    &RevisionSource::vendor(FromType::Huatu),
+                    &dedup,
+                )
                 .await
                 .context("insert question failed")?;
             let mut solution = q.to_solution()?;
@@ -401,7 +411,7 @@ impl HuatuSyncService {
                     content: Set(m),
                     ..Default::default()
                 }
-                .insert_on_conflict(&self.target_db)
+                .insert_on_conflict(&self.target_db, &RevisionSource::vendor(FromType::Huatu))
                 .await?;
                 let num = material_num;
                 material_num += 1;
@@ -420,21 +430,20 @@ impl HuatuSyncService {
                     let mut keypoint_ids = vec![];
                     for keypoint_name in keypoints.0 {
                         let paper_type = paper.paper_type;
-                        let kp = KeyPoint::find_by_paper_type_and_name(
+                        let kp = KeyPointMapping::find_key_point_by_name(
                             &self.target_db,
+                            FromType::Huatu,
                             paper_type,
                             &keypoint_name,
                         )
-                        .await
-                        .with_context(|| {
-                            format!("find paper_type#{paper_type} keypoint({keypoint_name}) failed")
-                        })?;
+                        .await?;
 
                         if let Some(keypoint) = kp {
                             question_keypoint::ActiveModel {
                                 question_id: Set(q_in_db.id),
                                 key_point_id: Set(keypoint.id),
                                 year: Set(paper.year),
+                                confidence: Set(None),
                             }
                             .insert_on_conflict(&self.target_db)
                             .await
@@ -447,15 +456,13 @@ impl HuatuSyncService {
                 None => {
                     let paper_type = paper.paper_type;
                     if let Some(chapter_name) = paper.extra.compute_chapter_name(*num) {
-                        let kp = KeyPoint::find_by_paper_type_and_name(
+                        let kp = KeyPointMapping::find_key_point_by_name(
                             &self.target_db,
+                            FromType::Huatu,
                             paper_type,
                             &chapter_name,
                         )
-                        .await
-                        .with_context(|| {
-                            format!("find paper_type#{paper_type} keypoint({chapter_name}) failed")
-                        })?;
+                        .await?;
                         if let Some(keypoint) = kp {
                             KeyPoint::query_common_keypoint_path(
                                 &self.target_db,
@@ -529,7 +536,9 @@ impl HuatuSyncService {
     ) -> Result<(), anyhow::Error> {
         let source_material_id = m.id;
         let material = TryInto::<material::ActiveModel>::try_into(m)?;
-        let m_in_db = material.insert_on_conflict(&self.target_db).await?;
+        let m_in_db = material
+            .insert_on_conflict(&self.target_db, &RevisionSource::vendor(FromType::Huatu))
+            .await?;
         paper_material::ActiveModel {
             paper_id: Set(paper_id),
             material_id: Set(m_in_db.id),
@@ -720,7 +729,7 @@ impl OriginPaper {
             extra: Set(extra),
             ..Default::default()
         }
-        .insert_on_conflict(db)
+        .insert_on_conflict(db, &RevisionSource::vendor(FromType::Huatu))
         .await
         .context("paper insert failed")
     }
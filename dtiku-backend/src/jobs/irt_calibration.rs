@@ -0,0 +1,122 @@
+use dtiku_base::model::{schedule_task, ScheduleTask};
+use dtiku_paper::{
+    domain::irt::{
+        ability_keypoint_ids, calibrate, estimate_keypoint_abilities, IrtParams, Response,
+    },
+    model::{ExerciseAnswer, PaperQuestion, QuestionIrt, UserAbility},
+};
+use itertools::Itertools;
+use sea_orm::{ActiveValue::Set, EntityTrait as _};
+use serde_json::Value;
+use spring::{plugin::Service, tracing};
+use spring_sea_orm::DbConn;
+use std::collections::HashMap;
+
+/// 每次批量写入的行数
+const BATCH_SIZE: usize = 1000;
+
+#[derive(Debug, Service)]
+#[service(prototype)]
+pub struct IrtCalibrationService {
+    #[inject(component)]
+    db: DbConn,
+    task: schedule_task::Model,
+}
+
+impl IrtCalibrationService {
+    pub async fn start(&mut self) {
+        self.calibrate().await.expect("irt calibration failed");
+
+        let _ = ScheduleTask::update(schedule_task::ActiveModel {
+            id: Set(self.task.id),
+            version: Set(self.task.version + 1),
+            active: Set(false),
+            ..Default::default()
+        })
+        .exec(&self.db)
+        .await
+        .is_err_and(|e| {
+            tracing::error!("update task error: {:?}", e);
+            false
+        });
+    }
+
+    /// 按试卷类型逐个校准，进度记录已完成的试卷类型，全部完成后重置
+    async fn calibrate(&mut self) -> anyhow::Result<()> {
+        let last_paper_type = match &self.task.context {
+            Value::Number(n) => n.as_i64().unwrap_or_default() as i16,
+            _ => 0,
+        };
+        tracing::warn!("irt_calibration({last_paper_type}) started");
+
+        for paper_type in PaperQuestion::find_paper_types(&self.db).await? {
+            if paper_type <= last_paper_type {
+                continue;
+            }
+            self.calibrate_paper_type(paper_type).await?;
+            self.task = self.task.update_context(paper_type, &self.db).await?;
+        }
+        tracing::warn!("irt_calibration() finished");
+        self.task = self.task.update_context(0, &self.db).await?;
+        Ok(())
+    }
+
+    /// 以厂商正确率为先验联合估计题目参数和用户能力，没有正确率也没人做过的题目不参与
+    async fn calibrate_paper_type(&self, paper_type: i16) -> anyhow::Result<()> {
+        let profiles =
+            PaperQuestion::find_item_profiles_by_paper_type(&self.db, paper_type).await?;
+        let responses = ExerciseAnswer::find_user_responses_by_paper_type(&self.db, paper_type)
+            .await?
+            .into_iter()
+            .map(|r| Response {
+                user_id: r.user_id,
+                question_id: r.question_id,
+                correct: r.correct,
+            })
+            .collect_vec();
+
+        let mut priors = HashMap::new();
+        let mut keypoints = HashMap::new();
+        for p in profiles {
+            if p.correct_ratio.is_some() {
+                priors.insert(
+                    p.question_id,
+                    IrtParams::from_correct_ratio(p.correct_ratio),
+                );
+            }
+            let mut kp_ids = ability_keypoint_ids(p.keypoint_path.as_deref());
+            kp_ids.remove(0);
+            keypoints.insert(p.question_id, kp_ids);
+        }
+        // 只统计仍在该试卷类型下的题目
+        let responses = responses
+            .into_iter()
+            .filter(|r| keypoints.contains_key(&r.question_id))
+            .collect_vec();
+        tracing::info!(
+            "irt_calibration({paper_type}): {} items, {} responses",
+            priors.len(),
+            responses.len()
+        );
+
+        let calibration = calibrate(&priors, &responses);
+        let answer_counts = responses.iter().map(|r| r.question_id).counts();
+        let items = calibration
+            .items
+            .iter()
+            .map(|(qid, params)| {
+                let count = answer_counts.get(qid).copied().unwrap_or_default();
+                (*qid, *params, count as i32)
+            })
+            .collect_vec();
+        for chunk in items.chunks(BATCH_SIZE) {
+            QuestionIrt::save_batch(&self.db, chunk.to_vec()).await?;
+        }
+
+        let abilities = estimate_keypoint_abilities(&calibration, &responses, &keypoints);
+        for chunk in abilities.chunks(BATCH_SIZE) {
+            UserAbility::save_batch(&self.db, paper_type, chunk.to_vec()).await?;
+        }
+        Ok(())
+    }
+}
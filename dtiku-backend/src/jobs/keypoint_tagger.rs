@@ -0,0 +1,147 @@
+use anyhow::Context;
+use dtiku_base::model::{schedule_task, ScheduleTask};
+use dtiku_paper::{
+    domain::keypoint_tag::{vote, Neighbour, NEIGHBOUR_COUNT},
+    model::{
+        question, ExamCategory, KeyPoint, KeypointTagStatus, PaperQuestion, QuestionKeyPoint,
+        QuestionKeypointTag,
+    },
+};
+use itertools::Itertools;
+use sea_orm::{ActiveValue::Set, EntityTrait as _, TransactionTrait};
+use serde_json::Value;
+use spring::{plugin::Service, tracing};
+use spring_sea_orm::DbConn;
+use std::collections::HashMap;
+
+/// 每批处理的题目数
+const BATCH_SIZE: u64 = 200;
+
+#[derive(Debug, Service)]
+#[service(prototype)]
+pub struct KeypointTaggerService {
+    #[inject(component)]
+    db: DbConn,
+    task: schedule_task::Model,
+}
+
+impl KeypointTaggerService {
+    pub async fn start(&mut self) {
+        let paper_type = ExamCategory::find_category_id_by_path(&self.db, "gwy/xingce")
+            .await
+            .expect("gwy/xingce category found failed")
+            .expect("gwy/xingce category id not found");
+
+        self.fill_keypoint_path(paper_type)
+            .await
+            .expect("fill keypoint path failed");
+        self.tag(paper_type).await.expect("keypoint tag failed");
+
+        let _ = ScheduleTask::update(schedule_task::ActiveModel {
+            id: Set(self.task.id),
+            version: Set(self.task.version + 1),
+            active: Set(false),
+            ..Default::default()
+        })
+        .exec(&self.db)
+        .await
+        .is_err_and(|e| {
+            tracing::error!("update task error: {:?}", e);
+            false
+        });
+    }
+
+    /// 已有知识点的题目，补上试卷中缺失的keypoint_path
+    async fn fill_keypoint_path(&self, paper_type: i16) -> anyhow::Result<()> {
+        let mut last_qid = 0;
+        let mut filled = 0;
+        loop {
+            let qids = PaperQuestion::find_question_ids_missing_keypoint_path(
+                &self.db, paper_type, last_qid, BATCH_SIZE,
+            )
+            .await?;
+            let Some(next_qid) = qids.last().copied() else {
+                break;
+            };
+            let kps = QuestionKeyPoint::find_by_question_ids(&self.db, qids).await?;
+            for (qid, kp_ids) in kps.into_iter().into_group_map() {
+                let path = KeyPoint::query_common_keypoint_path(&self.db, &kp_ids).await?;
+                if let Some(path) = path.filter(|p| !p.is_empty()) {
+                    filled += PaperQuestion::fill_keypoint_path(&self.db, qid, &path).await?;
+                }
+            }
+            last_qid = next_qid;
+        }
+        tracing::warn!("fill_keypoint_path({paper_type}) finished, {filled} rows filled");
+        Ok(())
+    }
+
+    /// 按题目id增量处理没有知识点的题目，进度记录最后处理的题目id
+    async fn tag(&mut self, paper_type: i16) -> anyhow::Result<()> {
+        let mut last_id = match &self.task.context {
+            Value::Number(n) => n.as_i64().unwrap_or_default() as i32,
+            _ => 0,
+        };
+        tracing::warn!("keypoint_tag({paper_type}, {last_id}) started");
+
+        let (mut auto, mut pending) = (0, 0);
+        loop {
+            let questions = QuestionKeypointTag::find_untagged_questions(
+                &self.db, paper_type, last_id, BATCH_SIZE,
+            )
+            .await?;
+            let Some(next_id) = questions.last().map(|q| q.id) else {
+                break;
+            };
+            for q in questions {
+                match self.tag_question(&q).await {
+                    Ok(Some(KeypointTagStatus::Auto)) => auto += 1,
+                    Ok(Some(_)) => pending += 1,
+                    Ok(None) => {}
+                    Err(e) => tracing::error!("tag_question({}) error: {e:?}", q.id),
+                }
+            }
+            last_id = next_id;
+            self.task = self.task.update_context(last_id, &self.db).await?;
+        }
+        tracing::warn!("keypoint_tag({paper_type}, {last_id}) finished, {auto} auto tagged, {pending} pending review");
+        Ok(())
+    }
+
+    /// 近邻都没有知识点时返回None
+    async fn tag_question(&self, q: &question::Model) -> anyhow::Result<Option<KeypointTagStatus>> {
+        let distances =
+            QuestionKeypointTag::find_tagged_neighbours(&self.db, q, NEIGHBOUR_COUNT).await?;
+        let qids = distances.iter().map(|(qid, _)| *qid).collect();
+        let mut paths: HashMap<i32, Vec<Vec<i32>>> = HashMap::new();
+        for (qid, kp_id) in QuestionKeyPoint::find_by_question_ids(&self.db, qids).await? {
+            if let Some(path) = KeyPoint::query_keypoint_path(&self.db, kp_id).await? {
+                let path = path.split('.').filter_map(|id| id.parse().ok()).collect();
+                paths.entry(qid).or_default().push(path);
+            }
+        }
+        let neighbours = distances
+            .into_iter()
+            .map(|(qid, distance)| Neighbour {
+                distance,
+                paths: paths.remove(&qid).unwrap_or_default(),
+            })
+            .collect_vec();
+        let Some(decision) = vote(&neighbours) else {
+            return Ok(None);
+        };
+
+        let status = if decision.auto_accept() {
+            KeypointTagStatus::Auto
+        } else {
+            KeypointTagStatus::Pending
+        };
+        let tx = self.db.begin().await.context("begin transaction failed")?;
+        let tag = QuestionKeypointTag::save_decision(&tx, q.id, decision, status).await?;
+        if status == KeypointTagStatus::Auto {
+            tag.apply(&tx).await?;
+        }
+        tx.commit().await.context("commit transaction failed")?;
+        Ok(Some(status))
+    }
+}
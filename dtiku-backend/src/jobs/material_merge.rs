@@ -0,0 +1,93 @@
+use dtiku_base::model::{schedule_task, ScheduleTask};
+use dtiku_paper::{
+    domain::material::{is_same_material, material_text, merge_conflict},
+    model::{Material, MaterialDuplicate, MaterialPaperStats},
+};
+use sea_orm::{ActiveValue::Set, EntityTrait as _};
+use serde_json::Value;
+use spring::{plugin::Service, tracing};
+use spring_sea_orm::DbConn;
+
+/// 每批处理的材料数
+const BATCH_SIZE: u64 = 200;
+/// content_sim_hash海明距离不超过该值的材料才做文本对比
+const MAX_HAMMING_DISTANCE: i32 = 12;
+
+#[derive(Debug, Service)]
+#[service(prototype)]
+pub struct MaterialMergeService {
+    #[inject(component)]
+    db: DbConn,
+    task: schedule_task::Model,
+}
+
+impl MaterialMergeService {
+    pub async fn start(&mut self) {
+        self.merge().await.expect("material merge failed");
+
+        let _ = ScheduleTask::update(schedule_task::ActiveModel {
+            id: Set(self.task.id),
+            version: Set(self.task.version + 1),
+            active: Set(false),
+            ..Default::default()
+        })
+        .exec(&self.db)
+        .await
+        .is_err_and(|e| {
+            tracing::error!("update task error: {:?}", e);
+            false
+        });
+    }
+
+    /// 按材料id增量处理，每份材料只和id更小的相似材料对比，
+    /// 文本校验通过且数字、图片一致的放入核对队列，由管理员确认后合并，
+    /// 进度记录最后处理的材料id
+    async fn merge(&mut self) -> anyhow::Result<()> {
+        let mut last_id = match &self.task.context {
+            Value::Number(n) => n.as_i64().unwrap_or_default() as i32,
+            _ => 0,
+        };
+        tracing::warn!("material_merge({last_id}) started");
+
+        let mut queued = 0;
+        loop {
+            let materials = Material::find_merge_batch(&self.db, last_id, BATCH_SIZE).await?;
+            let Some(last) = materials.last() else {
+                break;
+            };
+            let next_id = last.id;
+
+            for m in materials {
+                let candidates =
+                    Material::find_similar_before(&self.db, m.id, MAX_HAMMING_DISTANCE).await?;
+                if candidates.is_empty() {
+                    continue;
+                }
+                let text = material_text(&m.content);
+                for c in candidates {
+                    let c_text = material_text(&c.content);
+                    if !is_same_material(&m.content, &text, &c.content, &c_text) {
+                        continue;
+                    }
+                    if let Some(conflict) = merge_conflict(&m.content, &text, &c.content, &c_text) {
+                        tracing::info!("material#{} skip material#{}: {conflict}", m.id, c.id);
+                        continue;
+                    }
+                    let reason = if m.content == c.content {
+                        "内容完全相同"
+                    } else {
+                        "纯文本相似度超过95%"
+                    };
+                    MaterialDuplicate::save_candidate(&self.db, m.id, c.id, reason).await?;
+                    queued += 1;
+                }
+            }
+
+            last_id = next_id;
+            self.task = self.task.update_context(last_id, &self.db).await?;
+        }
+        MaterialPaperStats::refresh(&self.db).await?;
+        tracing::warn!("material_merge({last_id}) finished, {queued} candidates queued");
+        Ok(())
+    }
+}
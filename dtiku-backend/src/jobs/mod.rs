@@ -1,18 +1,28 @@
 mod assets_saver;
 mod chinagwy_sync;
+mod correct_ratio_stats;
 mod fenbi_sync;
 mod huatu_sync;
 mod idiom_fetch;
+mod irt_calibration;
+mod keypoint_tagger;
+mod material_merge;
 mod offcn_sync;
 mod pay_trade_fetcher;
+mod question_search_index;
 mod shenlun_categorize;
 mod web_solution_collect;
 
 use crate::jobs::assets_saver::AssetsSaveService;
 use crate::jobs::chinagwy_sync::ChinaGwySyncService;
+use crate::jobs::correct_ratio_stats::CorrectRatioStatsService;
 use crate::jobs::huatu_sync::HuatuSyncService;
 use crate::jobs::idiom_fetch::IdiomStatsService;
+use crate::jobs::irt_calibration::IrtCalibrationService;
+use crate::jobs::keypoint_tagger::KeypointTaggerService;
+use crate::jobs::material_merge::MaterialMergeService;
 use crate::jobs::offcn_sync::OffcnSyncService;
+use crate::jobs::question_search_index::QuestionSearchIndexService;
 use crate::jobs::shenlun_categorize::ShenlunCategorizeService;
 use crate::jobs::web_solution_collect::WebSolutionCollectService;
 use crate::plugins::jobs::RunningJobs;
@@ -91,6 +101,36 @@ async fn task_schedule(
                 .start()
                 .await
         }
+        ScheduleTaskType::CorrectRatioStats => {
+            CorrectRatioStatsService::build(task)
+                .expect("build correct ratio stats service failed")
+                .start()
+                .await
+        }
+        ScheduleTaskType::IrtCalibration => {
+            IrtCalibrationService::build(task)
+                .expect("build irt calibration service failed")
+                .start()
+                .await
+        }
+        ScheduleTaskType::QuestionSearchIndex => {
+            QuestionSearchIndexService::build(task)
+                .expect("build question search index service failed")
+                .start()
+                .await
+        }
+        ScheduleTaskType::MaterialMerge => {
+            MaterialMergeService::build(task)
+                .expect("build material merge service failed")
+                .start()
+                .await
+        }
+        ScheduleTaskType::KeypointTag => {
+            KeypointTaggerService::build(task)
+                .expect("build keypoint tagger service failed")
+                .start()
+                .await
+        }
     };
     running_jobs.remove(&ty);
 }
@@ -0,0 +1,116 @@
+use dtiku_base::model::{schedule_task, ScheduleTask};
+use dtiku_paper::{
+    domain::search::{index_tokens, question_text},
+    model::{
+        question::QuestionIndexSelect, question_search_index::IndexDocument, Material, Question,
+        QuestionMaterial, QuestionSearchIndex,
+    },
+};
+use itertools::Itertools;
+use sea_orm::{ActiveValue::Set, EntityTrait as _};
+use serde_json::Value;
+use spring::{plugin::Service, tracing};
+use spring_sea_orm::DbConn;
+use std::collections::HashMap;
+
+/// 每批建索引的题目数
+const BATCH_SIZE: u64 = 200;
+
+#[derive(Debug, Service)]
+#[service(prototype)]
+pub struct QuestionSearchIndexService {
+    #[inject(component)]
+    db: DbConn,
+    task: schedule_task::Model,
+}
+
+impl QuestionSearchIndexService {
+    pub async fn start(&mut self) {
+        self.index().await.expect("question search index failed");
+
+        let _ = ScheduleTask::update(schedule_task::ActiveModel {
+            id: Set(self.task.id),
+            version: Set(self.task.version + 1),
+            active: Set(false),
+            ..Default::default()
+        })
+        .exec(&self.db)
+        .await
+        .is_err_and(|e| {
+            tracing::error!("update task error: {:?}", e);
+            false
+        });
+    }
+
+    /// 按题目id增量建索引，进度记录最后一个已建索引的题目id；
+    /// 然后重建索引写入后又有修订的题目，并清理已合并题目的索引
+    async fn index(&mut self) -> anyhow::Result<()> {
+        let mut last_id = match &self.task.context {
+            Value::Number(n) => n.as_i64().unwrap_or_default() as i32,
+            _ => 0,
+        };
+        tracing::warn!("question_search_index({last_id}) started");
+
+        loop {
+            let questions = Question::find_index_batch(&self.db, last_id, BATCH_SIZE).await?;
+            let Some(last) = questions.last() else {
+                break;
+            };
+            last_id = last.id;
+            self.save_index(questions).await?;
+            self.task = self.task.update_context(last_id, &self.db).await?;
+        }
+        tracing::warn!("question_search_index({last_id}) finished");
+
+        let mut stale_id = 0;
+        let mut reindexed = 0;
+        loop {
+            let questions =
+                Question::find_stale_index_batch(&self.db, stale_id, BATCH_SIZE).await?;
+            let Some(last) = questions.last() else {
+                break;
+            };
+            stale_id = last.id;
+            reindexed += questions.len();
+            self.save_index(questions).await?;
+        }
+        let deleted = QuestionSearchIndex::delete_orphans(&self.db).await?;
+        tracing::warn!(
+            "question_search_index reindexed {reindexed} stale, deleted {deleted} orphans"
+        );
+        Ok(())
+    }
+
+    async fn save_index(&self, questions: Vec<QuestionIndexSelect>) -> anyhow::Result<()> {
+        let qids = questions.iter().map(|q| q.id).collect_vec();
+        let qid_mids = QuestionMaterial::find_by_qids(&self.db, qids).await?;
+        let mids = qid_mids.values().flatten().copied().unique().collect_vec();
+        let materials: HashMap<i32, String> = Material::find_by_ids(&self.db, mids)
+            .await?
+            .into_iter()
+            .map(|m| (m.id, m.content))
+            .collect();
+
+        let docs = questions
+            .into_iter()
+            .map(|q| {
+                let ms = qid_mids
+                    .get(&q.id)
+                    .into_iter()
+                    .flatten()
+                    .filter_map(|mid| materials.get(mid).map(|m| m.as_str()))
+                    .collect_vec();
+                let text = question_text(&q.content, &q.extra, &ms);
+                IndexDocument {
+                    question_id: q.id,
+                    exam_id: q.exam_id,
+                    paper_type: q.paper_type,
+                    question_type: q.extra.to_string(),
+                    tokens: index_tokens(&text),
+                    text,
+                }
+            })
+            .collect_vec();
+        QuestionSearchIndex::save_batch(&self.db, docs).await
+    }
+}
@@ -6,8 +6,9 @@ mod service;
 mod utils;
 mod views;
 
+use dtiku_base::embedding::EmbeddingPlugin;
 use dtiku_pay::PayPlugin;
-use plugins::{embedding::EmbeddingPlugin, jobs::RunningJobsPlugin};
+use plugins::jobs::RunningJobsPlugin;
 use spring::{auto_config, App};
 use spring_job::JobPlugin;
 use spring_opendal::OpenDALPlugin;
@@ -1,2 +1 @@
-pub mod embedding;
 pub mod jobs;
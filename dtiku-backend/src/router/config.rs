@@ -4,13 +4,14 @@ use dtiku_base::model::{
     enums::SystemConfigKey,
     system_config::{self, Entity as SystemConfig},
 };
+use dtiku_paper::domain::shenlun_category::{ShenlunCategoryConfig, ShenlunClassifier};
 use itertools::Itertools;
 use sea_orm::ActiveModelTrait;
 use sea_orm::Set;
 use spring_sea_orm::DbConn;
 use spring_web::{
     axum::{response::IntoResponse, Json},
-    error::Result,
+    error::{KnownWebError, Result},
     extractor::{Component, Path},
     get, put,
 };
@@ -39,6 +40,12 @@ async fn save_config(
     body: String,
 ) -> Result<impl IntoResponse> {
     let value: serde_json::Value = serde_json::from_str(&body).context("parse json failed")?;
+    if key == SystemConfigKey::ShenlunCategory {
+        let config: ShenlunCategoryConfig = serde_json::from_value(value.clone())
+            .map_err(|e| KnownWebError::bad_request(format!("申论分类规则格式错误: {e}")))?;
+        ShenlunClassifier::new(&config)
+            .map_err(|e| KnownWebError::bad_request(format!("{e:#}")))?;
+    }
     let model = SystemConfig::find_by_key(&db, key).await?;
     let active_model = match model {
         Some(m) => system_config::ActiveModel {
@@ -0,0 +1,77 @@
+use crate::{service::ingest::IngestService, views::ingest::IngestSaveRequest};
+use anyhow::Context;
+use axum::extract::Multipart;
+use dtiku_paper::domain::ingest::IngestRules;
+use spring_web::{
+    axum::{response::IntoResponse, Json},
+    error::{KnownWebError, Result},
+    extractor::Component,
+    post,
+};
+
+/// 上传DOCX/PDF真题，返回识别出的试卷结构供人工核对。
+/// 表单字段`file`为文件，可选字段`rules`为JSON格式的识别规则，覆盖配置中的同名规则
+#[post("/api/ingest/preview")]
+async fn preview_ingest(
+    Component(is): Component<IngestService>,
+    mut multipart: Multipart,
+) -> Result<impl IntoResponse> {
+    let mut file = None;
+    let mut rules = is.default_rules().clone();
+    while let Some(field) = multipart
+        .next_field()
+        .await
+        .map_err(|e| KnownWebError::bad_request(e.to_string()))?
+    {
+        let name = field.name().map(str::to_string);
+        match name.as_deref() {
+            Some("file") => {
+                let file_name = field.file_name().unwrap_or_default().to_lowercase();
+                if !file_name.ends_with(".docx") && !file_name.ends_with(".pdf") {
+                    return Err(KnownWebError::bad_request("只支持DOCX和PDF文件").into());
+                }
+                let data = field
+                    .bytes()
+                    .await
+                    .map_err(|e| KnownWebError::bad_request(e.to_string()))?;
+                file = Some(data.to_vec());
+            }
+            Some("rules") => {
+                let text = field
+                    .text()
+                    .await
+                    .map_err(|e| KnownWebError::bad_request(e.to_string()))?;
+                let mut value = serde_json::to_value(&rules).context("serialize rules failed")?;
+                let overrides: serde_json::Value = serde_json::from_str(&text)
+                    .map_err(|e| KnownWebError::bad_request(format!("识别规则格式错误:{e}")))?;
+                if let (Some(value), Some(overrides)) =
+                    (value.as_object_mut(), overrides.as_object())
+                {
+                    value.extend(overrides.clone());
+                }
+                rules = serde_json::from_value::<IngestRules>(value)
+                    .map_err(|e| KnownWebError::bad_request(format!("识别规则格式错误:{e}")))?;
+            }
+            _ => {}
+        }
+    }
+    let file = file.ok_or_else(|| KnownWebError::bad_request("请上传真题文件"))?;
+    let paper = is
+        .preview(file, &rules)
+        .await
+        .map_err(|e| KnownWebError::bad_request(format!("{e:#}")))?;
+    Ok(Json(paper))
+}
+
+/// 保存核对后的试卷
+#[post("/api/ingest/save")]
+async fn save_ingest(
+    Component(is): Component<IngestService>,
+    Json(req): Json<IngestSaveRequest>,
+) -> Result<impl IntoResponse> {
+    if req.paper.chapters.iter().all(|c| c.questions.is_empty()) {
+        return Err(KnownWebError::bad_request("试卷中没有题目").into());
+    }
+    let paper = is.save(req.paper_type, req.label_id, &req.paper).await?;
+    Ok(Json(paper))
+}
@@ -0,0 +1,46 @@
+use crate::views::keypoint_tag::{AcceptKeypointTagRequest, KeypointTagQuery};
+use dtiku_paper::{model::KeypointTagStatus, service::keypoint_tag::KeypointTagService};
+use spring_sea_orm::pagination::Pagination;
+use spring_web::{
+    axum::{response::IntoResponse, Json},
+    error::{KnownWebError, Result},
+    extractor::{Component, Path, Query},
+    get, post,
+};
+
+/// 知识点自动标注结果，默认只看置信度低、待审核的
+#[get("/api/question/keypoint_tag")]
+async fn list_keypoint_tag(
+    Component(ks): Component<KeypointTagService>,
+    Query(query): Query<KeypointTagQuery>,
+    pagination: Pagination,
+) -> Result<impl IntoResponse> {
+    let status = query.status.unwrap_or(KeypointTagStatus::Pending);
+    let page = ks.find_page(status, &pagination).await?;
+    Ok(Json(page))
+}
+
+/// 采纳标注结果，可以改选其他知识点
+#[post("/api/question/keypoint_tag/{id}/accept")]
+async fn accept_keypoint_tag(
+    Component(ks): Component<KeypointTagService>,
+    Path(id): Path<i32>,
+    Json(req): Json<AcceptKeypointTagRequest>,
+) -> Result<impl IntoResponse> {
+    if !ks.accept(id, req.key_point_id).await? {
+        return Err(KnownWebError::not_found("标注记录或知识点不存在").into());
+    }
+    Ok(Json("success"))
+}
+
+/// 驳回标注结果，已自动写入的知识点会被撤销
+#[post("/api/question/keypoint_tag/{id}/reject")]
+async fn reject_keypoint_tag(
+    Component(ks): Component<KeypointTagService>,
+    Path(id): Path<i32>,
+) -> Result<impl IntoResponse> {
+    if !ks.reject(id).await? {
+        return Err(KnownWebError::not_found("标注记录不存在").into());
+    }
+    Ok(Json("success"))
+}
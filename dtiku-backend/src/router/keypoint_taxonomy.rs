@@ -0,0 +1,101 @@
+use crate::views::{
+    keypoint_taxonomy::{
+        DeleteMappingRequest, MergeKeyPointRequest, MoveKeyPointRequest, RenameKeyPointRequest,
+        SaveMappingRequest,
+    },
+    GetListResult,
+};
+use dtiku_paper::service::keypoint_taxonomy::{KeyPointTaxonomyService, TaxonomyOutcome};
+use spring_web::{
+    axum::{response::IntoResponse, Json},
+    error::{KnownWebError, Result},
+    extractor::{Component, Path},
+    get, post,
+};
+
+fn into_response(outcome: TaxonomyOutcome) -> Result<impl IntoResponse> {
+    match outcome {
+        TaxonomyOutcome::Done => Ok(Json("success")),
+        TaxonomyOutcome::NotFound => Err(KnownWebError::not_found("知识点不存在").into()),
+        TaxonomyOutcome::Invalid(msg) => Err(KnownWebError::bad_request(msg).into()),
+    }
+}
+
+/// 把各厂商同步的重复知识点合并到规范知识点，子知识点、题目和统计一起迁移
+#[post("/api/keypoint_taxonomy/{id}/merge")]
+async fn merge_keypoint(
+    Component(ts): Component<KeyPointTaxonomyService>,
+    Path(id): Path<i32>,
+    Json(req): Json<MergeKeyPointRequest>,
+) -> Result<impl IntoResponse> {
+    into_response(ts.merge(id, req.target_id).await?)
+}
+
+/// 调整知识点在树中的位置
+#[post("/api/keypoint_taxonomy/{id}/move")]
+async fn move_keypoint(
+    Component(ts): Component<KeyPointTaxonomyService>,
+    Path(id): Path<i32>,
+    Json(req): Json<MoveKeyPointRequest>,
+) -> Result<impl IntoResponse> {
+    into_response(ts.move_node(id, req.pid).await?)
+}
+
+/// 重命名知识点，旧名称会保留为厂商映射
+#[post("/api/keypoint_taxonomy/{id}/rename")]
+async fn rename_keypoint(
+    Component(ts): Component<KeyPointTaxonomyService>,
+    Path(id): Path<i32>,
+    Json(req): Json<RenameKeyPointRequest>,
+) -> Result<impl IntoResponse> {
+    into_response(ts.rename(id, &req.name).await?)
+}
+
+/// 删除没有子知识点的知识点，题目归到父知识点下
+#[post("/api/keypoint_taxonomy/{id}/delete")]
+async fn delete_keypoint(
+    Component(ts): Component<KeyPointTaxonomyService>,
+    Path(id): Path<i32>,
+) -> Result<impl IntoResponse> {
+    into_response(ts.delete(id).await?)
+}
+
+#[get("/api/keypoint_taxonomy/mapping/{paper_type}")]
+async fn list_keypoint_mapping(
+    Component(ts): Component<KeyPointTaxonomyService>,
+    Path(paper_type): Path<i16>,
+) -> Result<impl IntoResponse> {
+    let mappings = ts.find_mappings(paper_type).await?;
+    Ok(Json(GetListResult::from(mappings)))
+}
+
+/// 该厂商同步时，父路径和名称相同的知识点会归到映射的规范知识点下
+#[post("/api/keypoint_taxonomy/mapping")]
+async fn save_keypoint_mapping(
+    Component(ts): Component<KeyPointTaxonomyService>,
+    Json(req): Json<SaveMappingRequest>,
+) -> Result<impl IntoResponse> {
+    let saved = ts.save_mapping(
+        req.from_ty,
+        req.paper_type,
+        &req.parent_path,
+        &req.name,
+        req.key_point_id,
+    );
+    if !saved.await? {
+        return Err(KnownWebError::bad_request("知识点不存在或试卷类型不一致").into());
+    }
+    Ok(Json("success"))
+}
+
+#[post("/api/keypoint_taxonomy/mapping/delete")]
+async fn delete_keypoint_mapping(
+    Component(ts): Component<KeyPointTaxonomyService>,
+    Json(req): Json<DeleteMappingRequest>,
+) -> Result<impl IntoResponse> {
+    let deleted = ts.delete_mapping(req.from_ty, req.paper_type, &req.parent_path, &req.name);
+    if !deleted.await? {
+        return Err(KnownWebError::not_found("知识点映射不存在").into());
+    }
+    Ok(Json("success"))
+}
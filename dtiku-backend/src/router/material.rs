@@ -0,0 +1,59 @@
+use crate::views::material::{MaterialDuplicateQuery, MergeMaterialRequest, SharedMaterialQuery};
+use dtiku_paper::{model::DuplicateStatus, service::material::MaterialService};
+use spring_sea_orm::pagination::Pagination;
+use spring_web::{
+    axum::{response::IntoResponse, Json},
+    error::{KnownWebError, Result},
+    extractor::{Component, Path, Query},
+    get, post,
+};
+
+/// 被多份试卷共用的材料，数据来自material_paper_stats物化视图
+#[get("/api/material/shared")]
+async fn list_shared_material(
+    Component(ms): Component<MaterialService>,
+    Query(query): Query<SharedMaterialQuery>,
+    pagination: Pagination,
+) -> Result<impl IntoResponse> {
+    let min_papers = query.min_papers.unwrap_or(2);
+    let page = ms.find_shared_page(min_papers, &pagination).await?;
+    Ok(Json(page))
+}
+
+/// 定时任务找到的相似材料，默认只看待核对的
+#[get("/api/material/duplicate")]
+async fn list_material_duplicate(
+    Component(ms): Component<MaterialService>,
+    Query(query): Query<MaterialDuplicateQuery>,
+    pagination: Pagination,
+) -> Result<impl IntoResponse> {
+    let status = query.status.unwrap_or(DuplicateStatus::Pending);
+    let page = ms.find_duplicate_page(status, &pagination).await?;
+    Ok(Json(page))
+}
+
+/// 核对后确认不是同一份材料
+#[post("/api/material/duplicate/{id}/ignore")]
+async fn ignore_material_duplicate(
+    Component(ms): Component<MaterialService>,
+    Path(id): Path<i32>,
+) -> Result<impl IntoResponse> {
+    ms.ignore_duplicate(id).await?;
+    Ok(Json("success"))
+}
+
+/// 把remove_id的材料合并到keep_id
+#[post("/api/material/merge")]
+async fn merge_material(
+    Component(ms): Component<MaterialService>,
+    Json(req): Json<MergeMaterialRequest>,
+) -> Result<impl IntoResponse> {
+    if req.keep_id == req.remove_id {
+        return Err(KnownWebError::bad_request("不能合并同一份材料").into());
+    }
+    let conflict = ms.merge(req.keep_id, req.remove_id, req.duplicate_id);
+    if let Some(conflict) = conflict.await? {
+        return Err(KnownWebError::bad_request(conflict).into());
+    }
+    Ok(Json("success"))
+}
@@ -1,8 +1,17 @@
 mod config;
 mod exam;
+mod ingest;
 mod keypoint;
+mod keypoint_tag;
+mod keypoint_taxonomy;
+mod material;
 mod matviews;
+mod paper;
 mod pay;
+mod question_duplicate;
+mod question_feedback;
+mod revision;
+mod shenlun_category;
 mod stats;
 mod task;
 mod test;
@@ -10,9 +19,14 @@ mod user;
 
 use spring::tracing::Level;
 use spring_opentelemetry::trace;
-use spring_web::Router;
+use spring_web::{axum::extract::DefaultBodyLimit, Router};
+
+/// 上传真题文档的大小上限
+const MAX_UPLOAD_SIZE: usize = 32 * 1024 * 1024;
 
 pub fn routers() -> Router {
     let http_tracing_layer = trace::HttpLayer::server(Level::INFO);
-    spring_web::handler::auto_router().layer(http_tracing_layer)
+    spring_web::handler::auto_router()
+        .layer(DefaultBodyLimit::max(MAX_UPLOAD_SIZE))
+        .layer(http_tracing_layer)
 }
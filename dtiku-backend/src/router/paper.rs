@@ -0,0 +1,20 @@
+use crate::views::paper::MockPaperRequest;
+use dtiku_paper::service::mock_exam::MockExamService;
+use spring_web::{
+    axum::{response::IntoResponse, Json},
+    error::{KnownWebError, Result},
+    extractor::Component,
+    post,
+};
+
+#[post("/api/paper/mock")]
+async fn generate_mock_paper(
+    Component(ms): Component<MockExamService>,
+    Json(req): Json<MockPaperRequest>,
+) -> Result<impl IntoResponse> {
+    let paper = ms
+        .generate(req.paper_type, req.label_id, None)
+        .await?
+        .ok_or_else(|| KnownWebError::not_found("没有可参考的真题"))?;
+    Ok(Json(paper))
+}
@@ -0,0 +1,45 @@
+use crate::views::question_duplicate::{DuplicateQuery, MergeQuestionRequest};
+use dtiku_paper::{model::DuplicateStatus, service::question_duplicate::QuestionDuplicateService};
+use spring_sea_orm::pagination::Pagination;
+use spring_web::{
+    axum::{response::IntoResponse, Json},
+    error::{KnownWebError, Result},
+    extractor::{Component, Path, Query},
+    get, post,
+};
+
+/// 去重时相似但未合并的题目，默认只看待核对的
+#[get("/api/question/duplicate")]
+async fn list_question_duplicate(
+    Component(ds): Component<QuestionDuplicateService>,
+    Query(query): Query<DuplicateQuery>,
+    pagination: Pagination,
+) -> Result<impl IntoResponse> {
+    let status = query.status.unwrap_or(DuplicateStatus::Pending);
+    let page = ds.find_page(status, &pagination).await?;
+    Ok(Json(page))
+}
+
+/// 核对后确认不是重复题
+#[post("/api/question/duplicate/{id}/ignore")]
+async fn ignore_question_duplicate(
+    Component(ds): Component<QuestionDuplicateService>,
+    Path(id): Path<i32>,
+) -> Result<impl IntoResponse> {
+    ds.ignore(id).await?;
+    Ok(Json("success"))
+}
+
+/// 把remove_id的题目合并到keep_id
+#[post("/api/question/merge")]
+async fn merge_question(
+    Component(ds): Component<QuestionDuplicateService>,
+    Json(req): Json<MergeQuestionRequest>,
+) -> Result<impl IntoResponse> {
+    if req.keep_id == req.remove_id {
+        return Err(KnownWebError::bad_request("不能合并同一道题").into());
+    }
+    ds.merge(req.keep_id, req.remove_id, req.duplicate_id)
+        .await?;
+    Ok(Json("success"))
+}
@@ -0,0 +1,60 @@
+use crate::views::question_feedback::{
+    AcceptFeedbackRequest, FeedbackQuery, RejectFeedbackRequest,
+};
+use dtiku_paper::{
+    model::FeedbackStatus,
+    service::question_feedback::{FeedbackResolution, QuestionFeedbackService},
+};
+use spring_sea_orm::pagination::Pagination;
+use spring_web::{
+    axum::{response::IntoResponse, Json},
+    error::{KnownWebError, Result},
+    extractor::{Component, Path, Query},
+    get, post,
+};
+
+/// 用户提交的纠错，默认只看待处理的
+#[get("/api/question/feedback")]
+async fn list_question_feedback(
+    Component(fs): Component<QuestionFeedbackService>,
+    Query(query): Query<FeedbackQuery>,
+    pagination: Pagination,
+) -> Result<impl IntoResponse> {
+    let status = query.status.unwrap_or(FeedbackStatus::Open);
+    let page = fs.find_page(status, &pagination).await?;
+    Ok(Json(page))
+}
+
+/// 采纳纠错，同时修改题目或解答
+#[post("/api/question/feedback/{id}/accept")]
+async fn accept_question_feedback(
+    Component(fs): Component<QuestionFeedbackService>,
+    Path(id): Path<i32>,
+    Json(req): Json<AcceptFeedbackRequest>,
+) -> Result<impl IntoResponse> {
+    let resolution = fs.accept(id, req.fix, req.reply, req.user_id).await?;
+    resolved(resolution)?;
+    Ok(Json("success"))
+}
+
+#[post("/api/question/feedback/{id}/reject")]
+async fn reject_question_feedback(
+    Component(fs): Component<QuestionFeedbackService>,
+    Path(id): Path<i32>,
+    Json(req): Json<RejectFeedbackRequest>,
+) -> Result<impl IntoResponse> {
+    let resolution = fs.reject(id, req.reply, req.user_id).await?;
+    resolved(resolution)?;
+    Ok(Json("success"))
+}
+
+/// 纠错不存在时返回404，已被处理过时返回409
+fn resolved(resolution: FeedbackResolution) -> Result<()> {
+    match resolution {
+        FeedbackResolution::Resolved => Ok(()),
+        FeedbackResolution::NotFound => Err(KnownWebError::not_found("纠错不存在").into()),
+        FeedbackResolution::AlreadyResolved => {
+            Err(KnownWebError::conflict("纠错已处理，不能重复处理").into())
+        }
+    }
+}
@@ -0,0 +1,32 @@
+use crate::views::{revision::RollbackRequest, GetListResult};
+use dtiku_paper::{model::revision::RevisionKind, service::revision::RevisionService};
+use spring_web::{
+    axum::{response::IntoResponse, Json},
+    error::{KnownWebError, Result},
+    extractor::{Component, Path},
+    get, post,
+};
+
+/// 查看题目、材料、解答或试卷的修订记录，kind为question、material、solution、paper
+#[get("/api/revision/{kind}/{target_id}")]
+async fn list_revision(
+    Component(rs): Component<RevisionService>,
+    Path((kind, target_id)): Path<(RevisionKind, i32)>,
+) -> Result<impl IntoResponse> {
+    let revisions = rs.list(kind, target_id).await?;
+    Ok(Json(GetListResult::from(revisions)))
+}
+
+/// 回滚到指定的修订版本，返回回滚后的数据
+#[post("/api/revision/{kind}/{id}/rollback")]
+async fn rollback_revision(
+    Component(rs): Component<RevisionService>,
+    Path((kind, id)): Path<(RevisionKind, i32)>,
+    Json(req): Json<RollbackRequest>,
+) -> Result<impl IntoResponse> {
+    let restored = rs
+        .rollback(kind, id, req.user_id)
+        .await?
+        .ok_or_else(|| KnownWebError::not_found("修订记录不存在"))?;
+    Ok(Json(restored))
+}
@@ -0,0 +1,27 @@
+use crate::{
+    service::shenlun_category::load_shenlun_config, views::shenlun_category::EvaluateShenlunRequest,
+};
+use dtiku_paper::domain::shenlun_category::{evaluate, LabelledSample, ShenlunClassifier};
+use spring_sea_orm::DbConn;
+use spring_web::{
+    axum::{response::IntoResponse, Json},
+    error::{KnownWebError, Result},
+    extractor::Component,
+    post,
+};
+
+/// 用人工标注集评估申论分类规则的准确率和召回率
+#[post("/api/shenlun/category/evaluate")]
+async fn evaluate_shenlun_category(
+    Component(db): Component<DbConn>,
+    Json(req): Json<EvaluateShenlunRequest>,
+) -> Result<impl IntoResponse> {
+    let config = match req.config {
+        Some(config) => config,
+        None => load_shenlun_config(&db).await?,
+    };
+    let classifier = ShenlunClassifier::new(&config)
+        .map_err(|e| KnownWebError::bad_request(format!("{e:#}")))?;
+    let samples = req.samples.unwrap_or_else(LabelledSample::builtin);
+    Ok(Json(evaluate(&classifier, &samples)))
+}
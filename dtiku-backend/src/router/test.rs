@@ -1,5 +1,4 @@
 use crate::config::openai::OpenAIConfig;
-use crate::plugins::embedding::Embedding;
 use crate::utils::regex as regex_util;
 use crate::views::test::WebExtractReq;
 use crate::{
@@ -9,6 +8,7 @@ use crate::{
 use anyhow::Context as _;
 use axum::body::Body;
 use axum::http::{HeaderValue, Response};
+use dtiku_base::embedding::Embedding;
 use dtiku_paper::model::{question, Question};
 use gaoya::minhash::{MinHasher, MinHasher64V1};
 use gaoya::simhash::SimHashBits;
@@ -0,0 +1,14 @@
+use dtiku_base::model::{enums::SystemConfigKey, SystemConfig};
+use dtiku_paper::domain::dedup::{DedupConfig, SimilarityDeduplicator};
+use sea_orm::ConnectionTrait;
+
+/// 后台系统配置中的去重阈值优先，没有配置时使用app.toml中的[dedup]
+pub async fn load_deduplicator<C: ConnectionTrait>(
+    db: &C,
+    default: &DedupConfig,
+) -> anyhow::Result<SimilarityDeduplicator> {
+    let config = SystemConfig::decode_cached_value(db, SystemConfigKey::QuestionDedup)
+        .await?
+        .unwrap_or_else(|| default.clone());
+    Ok(SimilarityDeduplicator::new(config))
+}
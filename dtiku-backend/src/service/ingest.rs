@@ -0,0 +1,161 @@
+use crate::{config::ingest::IngestConfig, service::dedup::load_deduplicator};
+use anyhow::Context;
+use dtiku_base::embedding::Embedding;
+use dtiku_paper::{
+    domain::{
+        dedup::DedupConfig,
+        ingest::{self, text_to_html, IngestRules, ParsedPaper},
+    },
+    model::{
+        material, paper, paper_material, paper_question, question, question_material,
+        revision::RevisionSource, solution, ExamCategory, FromType, Label,
+    },
+};
+use extractous::Extractor;
+use sea_orm::{prelude::PgVector, ActiveValue::Set, EntityTrait, TransactionTrait};
+use spring::plugin::service::Service;
+use spring_sea_orm::DbConn;
+use std::collections::HashMap;
+
+#[derive(Clone, Service)]
+pub struct IngestService {
+    #[inject(component)]
+    db: DbConn,
+    #[inject(component)]
+    embedding: Embedding,
+    #[inject(config)]
+    config: IngestConfig,
+    #[inject(config)]
+    dedup_config: DedupConfig,
+}
+
+impl IngestService {
+    pub fn default_rules(&self) -> &IngestRules {
+        &self.config.rules
+    }
+
+    /// 抽取DOCX/PDF中的文字并识别试卷结构，只预览不入库
+    pub async fn preview(&self, data: Vec<u8>, rules: &IngestRules) -> anyhow::Result<ParsedPaper> {
+        let text = tokio::task::spawn_blocking(move || {
+            Extractor::new()
+                .extract_bytes_to_string(&data)
+                .map(|(text, _metadata)| text)
+                .context("extract document text failed")
+        })
+        .await
+        .context("extract document task failed")??;
+        ingest::parse_paper(&text, rules)
+    }
+
+    /// 保存确认后的试卷，题目和资料复用同步任务的去重逻辑
+    pub async fn save(
+        &self,
+        paper_type: i16,
+        label_id: i32,
+        parsed: &ParsedPaper,
+    ) -> anyhow::Result<paper::Model> {
+        let exam = ExamCategory::find_root_by_id(&self.db, paper_type)
+            .await?
+            .with_context(|| format!("paper_type#{paper_type} exam root not found"))?;
+        let label = Label::find_by_id(label_id)
+            .one(&self.db)
+            .await
+            .with_context(|| format!("find label#{label_id} failed"))?
+            .filter(|l| l.paper_type == paper_type)
+            .with_context(|| format!("label#{label_id} not found in paper_type#{paper_type}"))?;
+        let year = parsed.year.context("paper year is required")?;
+
+        // embedding是远程调用，放在事务之外
+        let questions: Vec<_> = parsed.chapters.iter().flat_map(|c| &c.questions).collect();
+        let mut embeddings = Vec::with_capacity(questions.len());
+        for q in &questions {
+            embeddings.push(self.embedding.text_embedding(q.text()).await?);
+        }
+
+        let source = RevisionSource::vendor(FromType::Upload);
+        let dedup = load_deduplicator(&self.db, &self.dedup_config).await?;
+        let tx = self.db.begin().await.context("begin transaction failed")?;
+        let paper = paper::ActiveModel {
+            title: Set(parsed.title.clone()),
+            year: Set(year),
+            exam_id: Set(exam.id),
+            paper_type: Set(paper_type),
+            label_id: Set(label.id),
+            extra: Set(parsed.paper_extra()),
+            ..Default::default()
+        }
+        .insert_on_conflict(&tx, &source)
+        .await
+        .context("paper insert failed")?;
+
+        let mut material_ids = HashMap::new();
+        for m in &parsed.materials {
+            let m_in_db = material::ActiveModel {
+                content: Set(text_to_html(&m.content)),
+                extra: Set(vec![]),
+                ..Default::default()
+            }
+            .insert_on_conflict(&tx, &source)
+            .await?;
+            paper_material::ActiveModel {
+                paper_id: Set(paper.id),
+                material_id: Set(m_in_db.id),
+                sort: Set(m.num as i16),
+            }
+            .insert_on_conflict(&tx)
+            .await
+            .context("insert paper_material failed")?;
+            material_ids.insert(m.num, m_in_db.id);
+        }
+
+        for (q, embedding) in questions.into_iter().zip(embeddings) {
+            let q_in_db = question::ActiveModel {
+                content: Set(text_to_html(&q.content)),
+                extra: Set(q.question_extra()),
+                embedding: Set(PgVector::from(embedding)),
+                exam_id: Set(exam.id),
+                paper_type: Set(paper_type),
+                ..Default::default()
+            }
+            .insert_on_conflict(&tx, &source, &dedup)
+            .await
+            .with_context(|| format!("insert question#{} failed", q.num))?;
+            if let Some(extra) = q.solution_extra() {
+                solution::ActiveModel {
+                    question_id: Set(q_in_db.id),
+                    from_ty: Set(FromType::Upload),
+                    extra: Set(extra),
+                    ..Default::default()
+                }
+                .insert_on_conflict(&tx)
+                .await?;
+            }
+            for material_id in q.material_nums.iter().filter_map(|n| material_ids.get(n)) {
+                question_material::ActiveModel {
+                    question_id: Set(q_in_db.id),
+                    material_id: Set(*material_id),
+                }
+                .insert_on_conflict(&tx)
+                .await
+                .with_context(|| {
+                    format!(
+                        "insert question_material({},{material_id}) failed",
+                        q_in_db.id
+                    )
+                })?;
+            }
+            paper_question::ActiveModel {
+                paper_id: Set(paper.id),
+                question_id: Set(q_in_db.id),
+                sort: Set(q.num as i16),
+                paper_type: Set(paper_type),
+                ..Default::default()
+            }
+            .insert_on_conflict(&tx)
+            .await
+            .context("insert paper_question failed")?;
+        }
+        tx.commit().await.context("commit transaction failed")?;
+        Ok(paper)
+    }
+}
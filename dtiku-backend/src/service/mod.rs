@@ -1 +1,4 @@
+pub mod dedup;
+pub mod ingest;
 pub mod nlp;
+pub mod shenlun_category;
@@ -1,8 +1,8 @@
 use crate::config::openai::OpenAIConfig;
-use crate::plugins::embedding::Embedding;
 use crate::utils::hnsw::{HNSWIndex, IdAndEmbedding};
 use crate::utils::regex as regex_util;
 use anyhow::Context;
+use dtiku_base::embedding::Embedding;
 use dtiku_paper::model::{Material, Question, Solution};
 use itertools::Itertools;
 use scraper::Html;
@@ -0,0 +1,20 @@
+use dtiku_base::model::{enums::SystemConfigKey, SystemConfig};
+use dtiku_paper::domain::shenlun_category::{ShenlunCategoryConfig, ShenlunClassifier};
+use sea_orm::ConnectionTrait;
+
+/// 后台系统配置中的分类规则优先，没有配置时使用内置规则
+pub async fn load_shenlun_config<C: ConnectionTrait>(
+    db: &C,
+) -> anyhow::Result<ShenlunCategoryConfig> {
+    Ok(
+        SystemConfig::decode_cached_value(db, SystemConfigKey::ShenlunCategory)
+            .await?
+            .unwrap_or_default(),
+    )
+}
+
+pub async fn load_shenlun_classifier<C: ConnectionTrait>(
+    db: &C,
+) -> anyhow::Result<ShenlunClassifier> {
+    ShenlunClassifier::new(&load_shenlun_config(db).await?)
+}
@@ -0,0 +1,10 @@
+use dtiku_paper::domain::ingest::ParsedPaper;
+use serde::Deserialize;
+
+#[derive(Debug, Deserialize)]
+pub struct IngestSaveRequest {
+    pub paper_type: i16,
+    pub label_id: i32,
+    /// 预览返回的识别结果，可以先人工修改
+    pub paper: ParsedPaper,
+}
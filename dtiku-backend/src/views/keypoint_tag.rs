@@ -0,0 +1,13 @@
+use dtiku_paper::model::KeypointTagStatus;
+use serde::Deserialize;
+
+#[derive(Debug, Deserialize)]
+pub struct KeypointTagQuery {
+    pub status: Option<KeypointTagStatus>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct AcceptKeypointTagRequest {
+    /// 审核时改选的知识点，为空时采纳投票结果
+    pub key_point_id: Option<i32>,
+}
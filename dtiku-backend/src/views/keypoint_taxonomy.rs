@@ -0,0 +1,40 @@
+use dtiku_paper::model::FromType;
+use serde::Deserialize;
+
+#[derive(Debug, Deserialize)]
+pub struct MergeKeyPointRequest {
+    /// 合并到的规范知识点
+    pub target_id: i32,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct MoveKeyPointRequest {
+    /// 新的父知识点，0表示移动到根节点
+    pub pid: i32,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct RenameKeyPointRequest {
+    pub name: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct SaveMappingRequest {
+    pub from_ty: FromType,
+    pub paper_type: i16,
+    /// 厂商知识点树中父知识点名称用/连接的路径，只按名称查找时为空
+    #[serde(default)]
+    pub parent_path: String,
+    /// 厂商知识点名称
+    pub name: String,
+    pub key_point_id: i32,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct DeleteMappingRequest {
+    pub from_ty: FromType,
+    pub paper_type: i16,
+    #[serde(default)]
+    pub parent_path: String,
+    pub name: String,
+}
@@ -0,0 +1,21 @@
+use dtiku_paper::model::DuplicateStatus;
+use serde::Deserialize;
+
+#[derive(Debug, Deserialize)]
+pub struct SharedMaterialQuery {
+    /// 至少被多少份试卷共用，默认2
+    pub min_papers: Option<i64>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct MergeMaterialRequest {
+    pub keep_id: i32,
+    pub remove_id: i32,
+    /// 从核对队列发起合并时对应的记录
+    pub duplicate_id: Option<i32>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct MaterialDuplicateQuery {
+    pub status: Option<DuplicateStatus>,
+}
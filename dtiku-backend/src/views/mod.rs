@@ -2,6 +2,15 @@ use serde::Serialize;
 
 pub mod config;
 pub mod exam;
+pub mod ingest;
+pub mod keypoint_tag;
+pub mod keypoint_taxonomy;
+pub mod material;
+pub mod paper;
+pub mod question_duplicate;
+pub mod question_feedback;
+pub mod revision;
+pub mod shenlun_category;
 pub mod task;
 pub mod test;
 
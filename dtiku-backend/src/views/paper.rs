@@ -0,0 +1,7 @@
+use serde::Deserialize;
+
+#[derive(Debug, Deserialize)]
+pub struct MockPaperRequest {
+    pub paper_type: i16,
+    pub label_id: i32,
+}
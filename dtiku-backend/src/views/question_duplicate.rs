@@ -0,0 +1,15 @@
+use dtiku_paper::model::DuplicateStatus;
+use serde::Deserialize;
+
+#[derive(Debug, Deserialize)]
+pub struct DuplicateQuery {
+    pub status: Option<DuplicateStatus>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct MergeQuestionRequest {
+    pub keep_id: i32,
+    pub remove_id: i32,
+    /// 从核对队列发起合并时对应的记录
+    pub duplicate_id: Option<i32>,
+}
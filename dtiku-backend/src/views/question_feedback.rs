@@ -0,0 +1,22 @@
+use dtiku_paper::{domain::question_feedback::FeedbackFix, model::FeedbackStatus};
+use serde::Deserialize;
+
+#[derive(Debug, Deserialize)]
+pub struct FeedbackQuery {
+    pub status: Option<FeedbackStatus>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct AcceptFeedbackRequest {
+    /// 给用户的回复
+    pub reply: Option<String>,
+    pub user_id: Option<i32>,
+    #[serde(flatten)]
+    pub fix: FeedbackFix,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct RejectFeedbackRequest {
+    pub reply: Option<String>,
+    pub user_id: Option<i32>,
+}
@@ -0,0 +1,7 @@
+use serde::Deserialize;
+
+#[derive(Debug, Default, Deserialize)]
+pub struct RollbackRequest {
+    /// 执行回滚的管理员
+    pub user_id: Option<i32>,
+}
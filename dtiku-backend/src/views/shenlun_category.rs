@@ -0,0 +1,10 @@
+use dtiku_paper::domain::shenlun_category::{LabelledSample, ShenlunCategoryConfig};
+use serde::Deserialize;
+
+#[derive(Debug, Deserialize)]
+pub struct EvaluateShenlunRequest {
+    /// 待评估的规则，为空时评估系统配置中当前生效的规则
+    pub config: Option<ShenlunCategoryConfig>,
+    /// 人工标注集，为空时使用内置标注集
+    pub samples: Option<Vec<LabelledSample>>,
+}
@@ -1,53 +1,63 @@
-use std::time::Duration;
-
-use crate::config::embedding::EmbeddingConfig;
 use anyhow::Context;
 use itertools::Itertools;
-use reqwest::header::HeaderMap;
 use reqwest_middleware::{ClientBuilder, ClientWithMiddleware};
 use reqwest_retry::{policies::ExponentialBackoff, RetryTransientMiddleware};
 use reqwest_tracing::TracingMiddleware;
+use serde::Deserialize;
 use spring::{
     app::AppBuilder,
     async_trait,
-    config::ConfigRegistry,
+    config::{ConfigRegistry, Configurable},
     plugin::{MutableComponentRegistry, Plugin},
 };
+use std::time::Duration;
+
+#[derive(Debug, Configurable, Deserialize)]
+#[config_prefix = "embedding"]
+pub struct EmbeddingConfig {
+    url: String,
+    /// 读超时秒数，批量计算向量的任务需要更长的超时
+    #[serde(default = "default_timeout")]
+    timeout: u64,
+    /// 请求失败的重试次数，0表示不重试
+    #[serde(default)]
+    max_retries: u32,
+}
+
+fn default_timeout() -> u64 {
+    30
+}
 
 pub struct EmbeddingPlugin;
 
 #[async_trait]
 impl Plugin for EmbeddingPlugin {
     async fn build(&self, app: &mut AppBuilder) {
-        let embedding_config = app
+        let config = app
             .get_config::<EmbeddingConfig>()
-            .expect("load huggingface config failed");
-
-        let headers = HeaderMap::new();
+            .expect("load embedding config failed");
 
         let client = reqwest::Client::builder()
-            .default_headers(headers)
-            .read_timeout(Duration::from_secs(500)) // Set a longer timeout for embedding requests
+            .read_timeout(Duration::from_secs(config.timeout))
             .build()
             .expect("create embedding client failed");
 
-        let retry_policy = ExponentialBackoff::builder()
-            .retry_bounds(Duration::from_secs(5), Duration::from_secs(10 * 60))
-            .build_with_max_retries(3);
-        let client = ClientBuilder::new(client)
-            // Trace HTTP requests. See the tracing crate to make use of these traces.
-            .with(TracingMiddleware::default())
-            // Retry failed requests.
-            .with(RetryTransientMiddleware::new_with_policy(retry_policy))
-            .build();
+        let mut client = ClientBuilder::new(client).with(TracingMiddleware::default());
+        if config.max_retries > 0 {
+            let retry_policy = ExponentialBackoff::builder()
+                .retry_bounds(Duration::from_secs(5), Duration::from_secs(10 * 60))
+                .build_with_max_retries(config.max_retries);
+            client = client.with(RetryTransientMiddleware::new_with_policy(retry_policy));
+        }
 
         app.add_component(Embedding {
-            url: embedding_config.url,
-            client,
+            url: config.url,
+            client: client.build(),
         });
     }
 }
 
+/// 文本向量服务，用于题目去重和语义检索
 #[derive(Debug, Clone)]
 pub struct Embedding {
     url: String,
@@ -64,11 +74,7 @@ impl Embedding {
             .send()
             .await
             .context("embedding service text_embedding call failed")?;
-        let embedding = resp
-            .json()
-            .await
-            .context("parse embedding response failed")?;
-        Ok(embedding)
+        resp.json().await.context("parse embedding response failed")
     }
 
     pub async fn batch_text_embedding<S: Into<String> + Clone>(
@@ -77,7 +83,7 @@ impl Embedding {
     ) -> anyhow::Result<Vec<Vec<f32>>> {
         let Self { url, client } = self;
         let texts = texts
-            .into_iter()
+            .iter()
             .map(|t| Into::<String>::into(t.clone()))
             .collect_vec();
         let resp = client
@@ -86,10 +92,8 @@ impl Embedding {
             .send()
             .await
             .context("embedding service batch_text_embedding call failed")?;
-        let embeddings = resp
-            .json()
+        resp.json()
             .await
-            .context("parse embeddings response failed")?;
-        Ok(embeddings)
+            .context("parse embeddings response failed")
     }
 }
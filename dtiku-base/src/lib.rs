@@ -1,3 +1,4 @@
+pub mod embedding;
 pub mod error;
 pub mod model;
 pub mod query;
@@ -88,6 +88,12 @@ pub enum SystemConfigKey {
 
     #[strum(message = "ip黑名单，用逗号隔开")]
     IpBlacklist,
+
+    #[strum(message = "题目去重阈值，JSON格式，paper_types按试卷类型覆盖默认阈值")]
+    QuestionDedup,
+
+    #[strum(message = "申论题目分类规则，JSON格式，rules按顺序匹配，可以用评估接口验证")]
+    ShenlunCategory,
 }
 
 #[derive(
@@ -127,4 +133,14 @@ pub enum ScheduleTaskType {
     WebSolutionCollect,
     #[strum(message = "资源保存")]
     AssetsSave,
+    #[strum(message = "正确率统计")]
+    CorrectRatioStats,
+    #[strum(message = "IRT难度校准")]
+    IrtCalibration,
+    #[strum(message = "题目全文索引")]
+    QuestionSearchIndex,
+    #[strum(message = "材料合并")]
+    MaterialMerge,
+    #[strum(message = "知识点自动标注")]
+    KeypointTag,
 }
@@ -65,6 +65,21 @@ impl Entity {
             .with_context(|| format!("Issue::find_by_id({id}) failed"))
     }
 
+    /// (id, title)
+    pub async fn find_titles_by_ids<C: ConnectionTrait>(
+        db: &C,
+        ids: Vec<i32>,
+    ) -> anyhow::Result<Vec<(i32, String)>> {
+        Entity::find()
+            .select_only()
+            .columns([Column::Id, Column::Title])
+            .filter(Column::Id.is_in(ids))
+            .into_tuple()
+            .all(db)
+            .await
+            .context("Issue::find_titles_by_ids() failed")
+    }
+
     pub async fn find_pins_by_topic<C: ConnectionTrait>(
         db: &C,
         topic: Option<TopicType>,
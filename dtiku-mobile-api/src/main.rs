@@ -1,6 +1,7 @@
 mod router;
 mod service;
 
+use dtiku_base::embedding::EmbeddingPlugin;
 use dtiku_pay::PayPlugin;
 use spring::App;
 use spring_opentelemetry::{
@@ -28,6 +29,7 @@ async fn main() {
         .add_plugin(SeaOrmPlugin)
         .add_plugin(OpenTelemetryPlugin)
         .add_plugin(PayPlugin)
+        .add_plugin(EmbeddingPlugin)
         .run()
         .await
 }
@@ -0,0 +1,347 @@
+use crate::router::{question::QuestionResponse, Claims, OptionalClaims};
+use dtiku_paper::service::question::QuestionService;
+use dtiku_stats::{
+    domain::collection::{
+        normalize_tags, validate_name, AddItemOutcome, CollectionDetail, CollectionSummary, ItemKey,
+    },
+    model::{collection, sea_orm_active_enums::CollectionItemType},
+    service::collection::CollectionService,
+};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use spring_web::{
+    axum::Json,
+    error::{KnownWebError, Result},
+    extractor::{Component, Path, Query},
+    get_api, post_api,
+};
+use std::str::FromStr;
+
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct CollectionNameRequest {
+    pub name: String,
+}
+
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct CollectionShareRequest {
+    pub shared: bool,
+}
+
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct CollectionListQuery {
+    /// 传入条目时标记哪些收藏夹已收藏了该条目
+    pub item_type: Option<String>,
+    pub item_id: Option<i32>,
+}
+
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct CollectionDetailQuery {
+    /// 分享链接中的key
+    pub key: Option<String>,
+    pub tag: Option<String>,
+}
+
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct CollectionItemKey {
+    /// question、paper、idiom、issue
+    pub item_type: String,
+    pub item_id: i32,
+}
+
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct CollectionItemRequest {
+    /// question、paper、idiom、issue
+    pub item_type: String,
+    pub item_id: i32,
+    #[serde(default)]
+    pub tags: Vec<String>,
+}
+
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct CollectionOrderRequest {
+    pub items: Vec<CollectionItemKey>,
+}
+
+#[derive(Debug, Serialize, JsonSchema)]
+pub struct CollectionResponse {
+    pub id: i32,
+    pub name: String,
+    /// 开启分享后才有
+    pub share_key: Option<String>,
+    pub item_count: u64,
+    pub contains: bool,
+    pub modified: String,
+}
+
+#[derive(Debug, Serialize, JsonSchema)]
+pub struct CollectionItemResponse {
+    pub item_type: String,
+    pub item_id: i32,
+    pub title: String,
+    pub url: String,
+    pub tags: Vec<String>,
+}
+
+#[derive(Debug, Serialize, JsonSchema)]
+pub struct CollectionDetailResponse {
+    pub id: i32,
+    pub name: String,
+    pub share_key: Option<String>,
+    /// 收藏夹的主人才能编辑
+    pub owner: bool,
+    pub items: Vec<CollectionItemResponse>,
+    /// 收藏夹中用到的所有标签
+    pub tags: Vec<String>,
+}
+
+impl From<CollectionSummary> for CollectionResponse {
+    fn from(s: CollectionSummary) -> Self {
+        Self {
+            id: s.collection.id,
+            name: s.collection.name,
+            share_key: s.collection.share_key,
+            item_count: s.item_count,
+            contains: s.contains,
+            modified: s.collection.modified.to_string(),
+        }
+    }
+}
+
+impl From<collection::Model> for CollectionResponse {
+    fn from(c: collection::Model) -> Self {
+        Self {
+            id: c.id,
+            name: c.name,
+            share_key: c.share_key,
+            item_count: 0,
+            contains: false,
+            modified: c.modified.to_string(),
+        }
+    }
+}
+
+impl CollectionDetailResponse {
+    fn new(detail: CollectionDetail, user_id: Option<i32>) -> Self {
+        let c = detail.collection;
+        Self {
+            id: c.id,
+            owner: user_id == Some(c.user_id),
+            // 分享key只给主人看，避免被再次转发
+            share_key: c.share_key.filter(|_| user_id == Some(c.user_id)),
+            name: c.name,
+            items: detail
+                .items
+                .into_iter()
+                .map(|i| CollectionItemResponse {
+                    item_type: i.item.item_type.to_string(),
+                    item_id: i.item.item_id,
+                    title: i.title,
+                    url: i.url,
+                    tags: i.item.tags.0,
+                })
+                .collect(),
+            tags: detail.tags,
+        }
+    }
+}
+
+fn parse_item(item_type: &str, item_id: i32) -> Result<ItemKey> {
+    let item_type = CollectionItemType::from_str(item_type)
+        .map_err(|_| KnownWebError::bad_request("item_type参数错误"))?;
+    Ok(ItemKey { item_type, item_id })
+}
+
+async fn find_owned(cs: &CollectionService, user_id: i32, id: i32) -> Result<collection::Model> {
+    Ok(cs
+        .find_owned(user_id, id)
+        .await?
+        .ok_or_else(|| KnownWebError::not_found("收藏夹不存在"))?)
+}
+
+/// GET /api/collections
+#[get_api("/api/collections")]
+async fn api_collections(
+    claims: Claims,
+    Component(cs): Component<CollectionService>,
+    Query(q): Query<CollectionListQuery>,
+) -> Result<Json<Vec<CollectionResponse>>> {
+    let item = match (q.item_type, q.item_id) {
+        (Some(item_type), Some(item_id)) => Some(parse_item(&item_type, item_id)?),
+        _ => None,
+    };
+    let collections = cs.find_user_collections(claims.user_id, item).await?;
+    Ok(Json(
+        collections
+            .into_iter()
+            .map(CollectionResponse::from)
+            .collect(),
+    ))
+}
+
+/// POST /api/collections
+#[post_api("/api/collections")]
+async fn api_create_collection(
+    claims: Claims,
+    Component(cs): Component<CollectionService>,
+    Json(req): Json<CollectionNameRequest>,
+) -> Result<Json<CollectionResponse>> {
+    let name = validate_name(&req.name).map_err(KnownWebError::bad_request)?;
+    let c = cs.create(claims.user_id, name).await?;
+    Ok(Json(CollectionResponse::from(c)))
+}
+
+/// GET /api/collection/{id}
+#[get_api("/api/collection/{id}")]
+async fn api_collection_detail(
+    claims: OptionalClaims,
+    Path(id): Path<i32>,
+    Component(cs): Component<CollectionService>,
+    Query(q): Query<CollectionDetailQuery>,
+) -> Result<Json<CollectionDetailResponse>> {
+    let user_id = claims.as_ref().map(|c| c.user_id);
+    let c = cs
+        .find_accessible(id, user_id, q.key.as_deref())
+        .await?
+        .ok_or_else(|| KnownWebError::not_found("收藏夹不存在"))?;
+    let detail = cs.find_detail(c, q.tag.as_deref()).await?;
+    Ok(Json(CollectionDetailResponse::new(detail, user_id)))
+}
+
+/// 按收藏顺序返回题目，用于练习
+/// GET /api/collection/{id}/questions
+#[get_api("/api/collection/{id}/questions")]
+async fn api_collection_questions(
+    claims: OptionalClaims,
+    Path(id): Path<i32>,
+    Component(cs): Component<CollectionService>,
+    Component(qs): Component<QuestionService>,
+    Query(q): Query<CollectionDetailQuery>,
+) -> Result<Json<Vec<QuestionResponse>>> {
+    let user_id = claims.as_ref().map(|c| c.user_id);
+    let c = cs
+        .find_accessible(id, user_id, q.key.as_deref())
+        .await?
+        .ok_or_else(|| KnownWebError::not_found("收藏夹不存在"))?;
+    let qids = cs.find_question_ids(c.id, q.tag.as_deref()).await?;
+    let (questions, _) = qs.find_single_paper_by_ids(qids).await?;
+    Ok(Json(
+        questions.into_iter().map(QuestionResponse::from).collect(),
+    ))
+}
+
+/// POST /api/collection/{id}/rename
+#[post_api("/api/collection/{id}/rename")]
+async fn api_rename_collection(
+    claims: Claims,
+    Path(id): Path<i32>,
+    Component(cs): Component<CollectionService>,
+    Json(req): Json<CollectionNameRequest>,
+) -> Result<Json<CollectionResponse>> {
+    let name = validate_name(&req.name).map_err(KnownWebError::bad_request)?;
+    let c = cs
+        .rename(claims.user_id, id, name)
+        .await?
+        .ok_or_else(|| KnownWebError::not_found("收藏夹不存在"))?;
+    Ok(Json(CollectionResponse::from(c)))
+}
+
+/// POST /api/collection/{id}/share
+#[post_api("/api/collection/{id}/share")]
+async fn api_share_collection(
+    claims: Claims,
+    Path(id): Path<i32>,
+    Component(cs): Component<CollectionService>,
+    Json(req): Json<CollectionShareRequest>,
+) -> Result<Json<CollectionResponse>> {
+    let c = cs
+        .set_shared(claims.user_id, id, req.shared)
+        .await?
+        .ok_or_else(|| KnownWebError::not_found("收藏夹不存在"))?;
+    Ok(Json(CollectionResponse::from(c)))
+}
+
+/// POST /api/collection/{id}/delete
+#[post_api("/api/collection/{id}/delete")]
+async fn api_delete_collection(
+    claims: Claims,
+    Path(id): Path<i32>,
+    Component(cs): Component<CollectionService>,
+) -> Result<Json<serde_json::Value>> {
+    if !cs.delete(claims.user_id, id).await? {
+        return Err(KnownWebError::not_found("收藏夹不存在").into());
+    }
+    Ok(Json(serde_json::json!({"success": true})))
+}
+
+/// POST /api/collection/{id}/items
+#[post_api("/api/collection/{id}/items")]
+async fn api_add_collection_item(
+    claims: Claims,
+    Path(id): Path<i32>,
+    Component(cs): Component<CollectionService>,
+    Json(req): Json<CollectionItemRequest>,
+) -> Result<Json<serde_json::Value>> {
+    let item = parse_item(&req.item_type, req.item_id)?;
+    let tags = normalize_tags(&req.tags).map_err(KnownWebError::bad_request)?;
+    let c = find_owned(&cs, claims.user_id, id).await?;
+    match cs.add_item(c.id, item, tags).await? {
+        AddItemOutcome::Added | AddItemOutcome::Existed => {
+            Ok(Json(serde_json::json!({"success": true})))
+        }
+        AddItemOutcome::NotFound => Err(KnownWebError::not_found("收藏的内容不存在").into()),
+        AddItemOutcome::Full => {
+            Err(KnownWebError::bad_request("收藏夹已满，请新建一个收藏夹").into())
+        }
+    }
+}
+
+/// POST /api/collection/{id}/items/delete
+#[post_api("/api/collection/{id}/items/delete")]
+async fn api_remove_collection_item(
+    claims: Claims,
+    Path(id): Path<i32>,
+    Component(cs): Component<CollectionService>,
+    Json(req): Json<CollectionItemKey>,
+) -> Result<Json<serde_json::Value>> {
+    let item = parse_item(&req.item_type, req.item_id)?;
+    let c = find_owned(&cs, claims.user_id, id).await?;
+    if !cs.remove_item(c.id, item).await? {
+        return Err(KnownWebError::not_found("收藏的内容不存在").into());
+    }
+    Ok(Json(serde_json::json!({"success": true})))
+}
+
+/// POST /api/collection/{id}/items/tags
+#[post_api("/api/collection/{id}/items/tags")]
+async fn api_set_collection_item_tags(
+    claims: Claims,
+    Path(id): Path<i32>,
+    Component(cs): Component<CollectionService>,
+    Json(req): Json<CollectionItemRequest>,
+) -> Result<Json<serde_json::Value>> {
+    let item = parse_item(&req.item_type, req.item_id)?;
+    let tags = normalize_tags(&req.tags).map_err(KnownWebError::bad_request)?;
+    let c = find_owned(&cs, claims.user_id, id).await?;
+    if !cs.set_tags(c.id, item, tags).await? {
+        return Err(KnownWebError::not_found("收藏的内容不存在").into());
+    }
+    Ok(Json(serde_json::json!({"success": true})))
+}
+
+/// POST /api/collection/{id}/items/order
+#[post_api("/api/collection/{id}/items/order")]
+async fn api_reorder_collection_items(
+    claims: Claims,
+    Path(id): Path<i32>,
+    Component(cs): Component<CollectionService>,
+    Json(req): Json<CollectionOrderRequest>,
+) -> Result<Json<serde_json::Value>> {
+    let order = req
+        .items
+        .iter()
+        .map(|i| parse_item(&i.item_type, i.item_id))
+        .collect::<Result<Vec<_>>>()?;
+    let c = find_owned(&cs, claims.user_id, id).await?;
+    cs.reorder(c.id, order).await?;
+    Ok(Json(serde_json::json!({"success": true})))
+}
@@ -0,0 +1,126 @@
+use crate::router::{question::PaginatedResponse, Claims};
+use dtiku_paper::{
+    domain::question_feedback::FeedbackSubmit, model::question_feedback,
+    service::question_feedback::QuestionFeedbackService,
+};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use spring_sea_orm::pagination::Pagination;
+use spring_web::{
+    axum::Json,
+    error::{KnownWebError, Result},
+    extractor::{Component, Query},
+    get_api, post_api,
+};
+use std::str::FromStr;
+
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct FeedbackRequest {
+    pub question_id: i32,
+    /// wrong_answer、wrong_analysis、broken_image、garbled_option、wrong_content、other
+    pub category: String,
+    #[serde(default)]
+    pub content: String,
+    pub suggested_answer: Option<String>,
+}
+
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct FeedbackPageQuery {
+    pub page: Option<u64>,
+    pub page_size: Option<u64>,
+}
+
+#[derive(Debug, Serialize, JsonSchema)]
+pub struct FeedbackResponse {
+    pub id: i32,
+    pub question_id: i32,
+    pub category: String,
+    pub content: String,
+    pub suggested_answer: Option<String>,
+    /// open、accepted、rejected
+    pub status: String,
+    pub reply: Option<String>,
+    /// 处理结果是否已查看
+    pub read: bool,
+    pub created: String,
+}
+
+#[derive(Debug, Serialize, JsonSchema)]
+pub struct UnreadFeedbackResponse {
+    pub count: u64,
+}
+
+impl From<question_feedback::Model> for FeedbackResponse {
+    fn from(f: question_feedback::Model) -> Self {
+        Self {
+            id: f.id,
+            question_id: f.question_id,
+            category: f.category.to_string(),
+            content: f.content,
+            suggested_answer: f.suggested_answer,
+            status: f.status.to_string(),
+            reply: f.reply,
+            read: f.user_read,
+            created: f.created.to_string(),
+        }
+    }
+}
+
+/// POST /api/question/feedback
+#[post_api("/api/question/feedback")]
+async fn api_question_feedback(
+    claims: Claims,
+    Component(fs): Component<QuestionFeedbackService>,
+    Json(req): Json<FeedbackRequest>,
+) -> Result<Json<FeedbackResponse>> {
+    let submit = FeedbackSubmit {
+        question_id: req.question_id,
+        category: FromStr::from_str(&req.category)
+            .map_err(|_| KnownWebError::bad_request("category参数错误"))?,
+        content: req.content,
+        suggested_answer: req.suggested_answer,
+    };
+    submit.validate().map_err(KnownWebError::bad_request)?;
+    let feedback = fs
+        .submit(claims.user_id, submit)
+        .await?
+        .ok_or_else(|| KnownWebError::not_found("题目不存在"))?;
+    Ok(Json(FeedbackResponse::from(feedback)))
+}
+
+/// GET /api/user/feedback
+#[get_api("/api/user/feedback")]
+async fn api_user_feedback(
+    claims: Claims,
+    Component(fs): Component<QuestionFeedbackService>,
+    Query(q): Query<FeedbackPageQuery>,
+) -> Result<Json<PaginatedResponse<FeedbackResponse>>> {
+    // Pagination的页码从0开始
+    let page = q.page.unwrap_or_default();
+    let page_size = q.page_size.unwrap_or(20).clamp(1, 100);
+    let pagination = Pagination {
+        page,
+        size: page_size,
+    };
+    let result = fs.find_user_page(claims.user_id, &pagination).await?;
+    Ok(Json(PaginatedResponse {
+        data: result
+            .content
+            .into_iter()
+            .map(FeedbackResponse::from)
+            .collect(),
+        total: result.total_elements,
+        page,
+        page_size,
+    }))
+}
+
+/// GET /api/user/feedback/unread
+#[get_api("/api/user/feedback/unread")]
+async fn api_user_feedback_unread(
+    claims: Claims,
+    Component(fs): Component<QuestionFeedbackService>,
+) -> Result<Json<UnreadFeedbackResponse>> {
+    let count = fs.count_unread(claims.user_id).await?;
+    Ok(Json(UnreadFeedbackResponse { count }))
+}
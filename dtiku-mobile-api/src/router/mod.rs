@@ -1,8 +1,11 @@
+mod collection;
+mod feedback;
 mod idiom;
 mod issue;
 mod paper;
 mod pay;
 mod question;
+mod review;
 mod system;
 mod user;
 
@@ -1,9 +1,12 @@
+use dtiku_base::embedding::Embedding;
 use dtiku_paper::{
     domain::question::QuestionSearch,
-    model::question::{self, QuestionWithPaper},
+    model::question::{self, QuestionSinglePaper, QuestionWithPaper},
     service::question::QuestionService,
 };
 use serde::{Deserialize, Serialize};
+use spring::tracing;
+use spring_sea_orm::pagination::Pagination;
 use spring_web::{
     axum::Json,
     error::{KnownWebError, Result},
@@ -20,6 +23,8 @@ pub struct QuestionSearchQuery {
     pub page: Option<u64>,
     pub page_size: Option<u64>,
     pub exam_id: Option<i16>,
+    /// 混合检索：关键词和语义相似度融合排序
+    pub hybrid: Option<bool>,
 }
 
 #[derive(Debug, Deserialize, JsonSchema)]
@@ -72,10 +77,22 @@ impl From<QuestionWithPaper> for QuestionResponse {
     }
 }
 
+impl From<QuestionSinglePaper> for QuestionResponse {
+    fn from(q: QuestionSinglePaper) -> Self {
+        Self {
+            id: q.id,
+            content: q.content,
+            exam_id: q.paper.paper.exam_id,
+            paper_type: q.paper.paper.paper_type,
+        }
+    }
+}
+
 /// GET /api/question/search
 #[get_api("/api/question/search")]
 async fn api_question_search(
     Component(qs): Component<QuestionService>,
+    Component(embedding): Component<Embedding>,
     Query(q): Query<QuestionSearchQuery>,
 ) -> Result<Json<PaginatedResponse<QuestionResponse>>> {
     let keyword = q.keyword.unwrap_or_default();
@@ -84,29 +101,45 @@ async fn api_question_search(
         return Ok(Json(PaginatedResponse {
             data: vec![],
             total: 0,
-            page: q.page.unwrap_or(1),
+            page: q.page.unwrap_or_default(),
             page_size: q.page_size.unwrap_or(20),
         }));
     }
 
+    // Pagination的页码从0开始
+    let page = q.page.unwrap_or_default();
+    let page_size = q.page_size.unwrap_or(20).clamp(1, 100);
+    let pagination = Pagination {
+        page,
+        size: page_size,
+    };
     let search = QuestionSearch {
         content: keyword,
         exam_id: q.exam_id,
-        paper_type: None,
+        question_type: q.question_type,
+        hybrid: q.hybrid.unwrap_or_default(),
+        ..Default::default()
     };
 
-    let mut questions = qs.search_question(&search).await?;
-    
-    let page_size = q.page_size.unwrap_or(20) as usize;
-    questions.truncate(page_size);
-
-    let total = questions.len() as u64;
-    let page = q.page.unwrap_or(1);
-    let page_size = q.page_size.unwrap_or(20);
+    let result = if search.hybrid {
+        match embedding.text_embedding(search.content.trim()).await {
+            Ok(vector) => qs.hybrid_search_question(&search, vector, &pagination).await?,
+            Err(e) => {
+                tracing::warn!("embedding query({}) failed: {e:?}", search.content);
+                qs.search_question(&search, &pagination).await?
+            }
+        }
+    } else {
+        qs.search_question(&search, &pagination).await?
+    };
 
     Ok(Json(PaginatedResponse {
-        data: questions.into_iter().map(QuestionResponse::from).collect(),
-        total,
+        data: result
+            .content
+            .into_iter()
+            .map(|hit| QuestionResponse::from(hit.question))
+            .collect(),
+        total: result.total_elements,
         page,
         page_size,
     }))
@@ -0,0 +1,121 @@
+use crate::router::Claims;
+use dtiku_stats::{
+    domain::review::{ReviewContent, ReviewGrade, ReviewItem},
+    model::{review_card, sea_orm_active_enums::ReviewItemType},
+    service::review::ReviewService,
+};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use spring_web::{
+    axum::Json,
+    error::{KnownWebError, Result},
+    extractor::Component,
+    get_api, post_api,
+};
+use std::str::FromStr;
+
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct ReviewGradeRequest {
+    /// question 或 idiom
+    pub item_type: String,
+    pub item_id: i32,
+    /// again、hard、good、easy
+    pub grade: String,
+}
+
+#[derive(Debug, Serialize, JsonSchema)]
+pub struct ReviewItemResponse {
+    pub item_type: String,
+    pub item_id: i32,
+    /// 题目内容或成语
+    pub content: String,
+    /// 参考答案或成语释义
+    pub answer: Option<String>,
+    pub repetitions: i16,
+    pub interval_days: i32,
+    pub is_new: bool,
+}
+
+#[derive(Debug, Serialize, JsonSchema)]
+pub struct ReviewCardResponse {
+    pub item_type: String,
+    pub item_id: i32,
+    pub ease: f32,
+    pub interval_days: i32,
+    pub repetitions: i16,
+    pub lapses: i16,
+    pub due: String,
+}
+
+impl From<ReviewItem> for ReviewItemResponse {
+    fn from(item: ReviewItem) -> Self {
+        let (content, answer) = match item.content {
+            ReviewContent::Question(q) => {
+                let answer = q.get_answer();
+                (q.content, answer)
+            }
+            ReviewContent::Idiom(idiom) => (idiom.text, Some(idiom.explain.definition)),
+        };
+        Self {
+            item_type: item.item_type.to_string(),
+            item_id: item.item_id,
+            content,
+            answer,
+            repetitions: item
+                .card
+                .as_ref()
+                .map(|c| c.repetitions)
+                .unwrap_or_default(),
+            interval_days: item
+                .card
+                .as_ref()
+                .map(|c| c.interval_days)
+                .unwrap_or_default(),
+            is_new: item.card.is_none(),
+        }
+    }
+}
+
+impl From<review_card::Model> for ReviewCardResponse {
+    fn from(card: review_card::Model) -> Self {
+        Self {
+            item_type: card.item_type.to_string(),
+            item_id: card.item_id,
+            ease: card.ease,
+            interval_days: card.interval_days,
+            repetitions: card.repetitions,
+            lapses: card.lapses,
+            due: card.due.to_string(),
+        }
+    }
+}
+
+/// GET /api/review/queue
+#[get_api("/api/review/queue")]
+async fn api_review_queue(
+    claims: Claims,
+    Component(rs): Component<ReviewService>,
+) -> Result<Json<Vec<ReviewItemResponse>>> {
+    let items = rs.daily_queue(claims.user_id).await?;
+    Ok(Json(
+        items.into_iter().map(ReviewItemResponse::from).collect(),
+    ))
+}
+
+/// POST /api/review/grade
+#[post_api("/api/review/grade")]
+async fn api_review_grade(
+    claims: Claims,
+    Component(rs): Component<ReviewService>,
+    Json(req): Json<ReviewGradeRequest>,
+) -> Result<Json<ReviewCardResponse>> {
+    let item_type = ReviewItemType::from_str(&req.item_type)
+        .map_err(|_| KnownWebError::bad_request("item_type参数错误"))?;
+    let grade = ReviewGrade::from_str(&req.grade)
+        .map_err(|_| KnownWebError::bad_request("grade参数错误"))?;
+    let card = rs
+        .grade(claims.user_id, item_type, req.item_id, grade)
+        .await?
+        .ok_or_else(|| KnownWebError::not_found("复习内容不存在"))?;
+    Ok(Json(ReviewCardResponse::from(card)))
+}
@@ -0,0 +1,309 @@
+use crate::{
+    model::question::{get_re_punct, get_re_whitespace, QuestionExtra},
+    util::html,
+};
+use itertools::Itertools;
+use scraper::Html;
+use serde::{Deserialize, Serialize};
+use spring::config::Configurable;
+use std::collections::HashMap;
+
+/// 题目去重的各项阈值
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct DedupThresholds {
+    /// html内容完全相同时，纯文本超过该长度直接判定为重复，否则还要对比选项
+    pub exact_min_text_length: usize,
+    /// 两道题的纯文本都超过该长度才做levenshtein对比
+    pub levenshtein_min_text_length: usize,
+    /// levenshtein相似度(1-编辑距离/最大长度)超过该值判定为重复
+    pub levenshtein_similarity: f64,
+    /// jaro_winkler或jaccard相似度超过该值判定为重复
+    pub text_similarity: f64,
+    /// 语义距离小于semantic_distance时，文本相似度只需超过该值
+    pub loose_text_similarity: f64,
+    pub semantic_distance: f64,
+    /// 未判定为重复，但文本相似度超过该值的进入人工核对队列
+    pub near_duplicate_similarity: f64,
+}
+
+impl Default for DedupThresholds {
+    fn default() -> Self {
+        Self {
+            exact_min_text_length: 20,
+            levenshtein_min_text_length: 100,
+            levenshtein_similarity: 0.95,
+            text_similarity: 0.90,
+            loose_text_similarity: 0.85,
+            semantic_distance: 0.01,
+            near_duplicate_similarity: 0.8,
+        }
+    }
+}
+
+/// 去重配置，paper_types按试卷类型覆盖默认阈值，key为paper_type
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize, Configurable)]
+#[config_prefix = "dedup"]
+#[serde(default)]
+pub struct DedupConfig {
+    #[serde(flatten)]
+    pub thresholds: DedupThresholds,
+    pub paper_types: HashMap<String, DedupThresholds>,
+}
+
+impl DedupConfig {
+    pub fn thresholds(&self, paper_type: i16) -> &DedupThresholds {
+        self.paper_types
+            .get(&paper_type.to_string())
+            .unwrap_or(&self.thresholds)
+    }
+}
+
+/// 参与去重对比的题目，预先计算好纯文本
+#[derive(Debug, Clone)]
+pub struct DedupText<'a> {
+    pub paper_type: i16,
+    pub content: &'a str,
+    pub extra: &'a QuestionExtra,
+    /// 题干的纯文本
+    pub text: String,
+    /// 题干和选项去掉标点、合并空白后的纯文本
+    pub normalized: String,
+    pub has_media: bool,
+}
+
+impl<'a> DedupText<'a> {
+    pub fn new(paper_type: i16, content: &'a str, extra: &'a QuestionExtra) -> Self {
+        let text = html_text(content);
+        let options = html_text(&extra.options_html());
+        let normalized = get_re_punct()
+            .replace_all(&format!("{text}\n{options}"), "")
+            .into_owned();
+        let normalized = get_re_whitespace()
+            .replace_all(&normalized, " ")
+            .into_owned();
+        Self {
+            paper_type,
+            content,
+            extra,
+            text,
+            normalized,
+            has_media: html::contains_media(content),
+        }
+    }
+}
+
+fn html_text(html: &str) -> String {
+    Html::parse_fragment(html).root_element().text().join("")
+}
+
+/// 判定为重复的依据
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DuplicateReason {
+    /// html内容完全相同且足够长
+    SameContent,
+    /// html内容和选项都完全相同
+    SameContentAndExtra,
+    Levenshtein,
+    TextSimilarity,
+}
+
+/// 两道题的相似度，steps记录各项对比失败的原因
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Similarity {
+    pub semantic_distance: f64,
+    pub levenshtein: Option<usize>,
+    pub jaro_winkler: f64,
+    pub jaccard: f64,
+    pub content_has_media: bool,
+    pub candidate_has_media: bool,
+    pub steps: Vec<String>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum DedupDecision {
+    /// 重复，直接使用已有的题目
+    Duplicate(DuplicateReason),
+    /// 不能自动判定，需要人工核对
+    NearDuplicate(Similarity),
+    Distinct(Similarity),
+}
+
+pub trait QuestionDeduplicator: Send + Sync {
+    /// 对比新题目和语义距离为semantic_distance的已有题目
+    fn compare(
+        &self,
+        question: &DedupText,
+        candidate: &DedupText,
+        semantic_distance: f64,
+    ) -> DedupDecision;
+}
+
+/// 基于文本相似度的默认去重实现
+#[derive(Debug, Clone, Default)]
+pub struct SimilarityDeduplicator {
+    config: DedupConfig,
+}
+
+impl SimilarityDeduplicator {
+    pub fn new(config: DedupConfig) -> Self {
+        Self { config }
+    }
+}
+
+impl QuestionDeduplicator for SimilarityDeduplicator {
+    fn compare(
+        &self,
+        question: &DedupText,
+        candidate: &DedupText,
+        semantic_distance: f64,
+    ) -> DedupDecision {
+        let t = self.config.thresholds(question.paper_type);
+        let mut steps = vec![];
+        if question.content == candidate.content {
+            // 完全相同，包括图片等html内容
+            if question.text.chars().count() > t.exact_min_text_length {
+                return DedupDecision::Duplicate(DuplicateReason::SameContent);
+            }
+            if question.extra == candidate.extra {
+                return DedupDecision::Duplicate(DuplicateReason::SameContentAndExtra);
+            }
+            steps.push("question.extra对比失败".to_string());
+        }
+        steps.push("question.content对比失败".to_string());
+
+        let text = &question.normalized;
+        let candidate_text = &candidate.normalized;
+        let text_length = text.chars().count();
+        let candidate_text_length = candidate_text.chars().count();
+        let mut levenshtein = None;
+        if text_length > t.levenshtein_min_text_length
+            && candidate_text_length > t.levenshtein_min_text_length
+        {
+            let edit_distance = textdistance::str::levenshtein(candidate_text, text);
+            let max_length = text_length.max(candidate_text_length);
+            if 1.0 - (edit_distance as f64 / max_length as f64) > t.levenshtein_similarity {
+                return DedupDecision::Duplicate(DuplicateReason::Levenshtein);
+            }
+            levenshtein = Some(edit_distance);
+            steps.push(format!(
+                "levenshtein距离对比失败: levenshtein={edit_distance}"
+            ));
+        } else {
+            steps.push(format!("不满足levenshtein对比条件; candidate_text_length={candidate_text_length} text_length={text_length}"));
+        }
+
+        let jaro_winkler = textdistance::str::jaro_winkler(candidate_text, text);
+        let jaccard = textdistance::str::jaccard(candidate_text, text);
+        let content_has_media = question.has_media;
+        let candidate_has_media = candidate.has_media;
+        if jaro_winkler > t.text_similarity
+            || jaccard > t.text_similarity
+            || (jaro_winkler > t.loose_text_similarity || jaccard > t.loose_text_similarity)
+                && semantic_distance < t.semantic_distance
+        {
+            // 纯文本相似，图片等内容无法对比，只有都不含图片才能判定为重复
+            if !content_has_media && !candidate_has_media {
+                return DedupDecision::Duplicate(DuplicateReason::TextSimilarity);
+            }
+            steps.push(format!(
+                "jaro_winkler距离对比失败: jaro_winkler={jaro_winkler} jaccard={jaccard} distance={semantic_distance}"
+            ));
+        } else {
+            steps.push(format!("不满足jaro_winkler|jaccard对比条件; candidate_has_media={candidate_has_media} content_has_media={content_has_media}"));
+        }
+        let similarity = Similarity {
+            semantic_distance,
+            levenshtein,
+            jaro_winkler,
+            jaccard,
+            content_has_media,
+            candidate_has_media,
+            steps,
+        };
+        if jaro_winkler.max(jaccard) > t.near_duplicate_similarity {
+            DedupDecision::NearDuplicate(similarity)
+        } else {
+            DedupDecision::Distinct(similarity)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        DedupConfig, DedupDecision, DedupText, DedupThresholds, QuestionDeduplicator,
+        SimilarityDeduplicator,
+    };
+    use crate::model::question::QuestionExtra;
+    use serde::Deserialize;
+
+    #[derive(Deserialize)]
+    struct FixtureQuestion {
+        content: String,
+        extra: QuestionExtra,
+    }
+
+    #[derive(Deserialize)]
+    struct Fixture {
+        name: String,
+        #[serde(default)]
+        paper_type: i16,
+        semantic_distance: f64,
+        expect: String,
+        question: FixtureQuestion,
+        candidate: FixtureQuestion,
+    }
+
+    fn decision_name(decision: &DedupDecision) -> String {
+        match decision {
+            DedupDecision::Duplicate(reason) => serde_json::to_value(reason)
+                .unwrap()
+                .as_str()
+                .unwrap()
+                .to_string(),
+            DedupDecision::NearDuplicate(_) => "near_duplicate".to_string(),
+            DedupDecision::Distinct(_) => "distinct".to_string(),
+        }
+    }
+
+    #[test]
+    fn test_dedup_fixtures() {
+        let fixtures: Vec<Fixture> =
+            serde_json::from_str(include_str!("../../tests/fixtures/dedup_pairs.json")).unwrap();
+        let mut config = DedupConfig::default();
+        config.paper_types.insert(
+            "2".to_string(),
+            DedupThresholds {
+                text_similarity: 0.99,
+                loose_text_similarity: 0.99,
+                ..Default::default()
+            },
+        );
+        let dedup = SimilarityDeduplicator::new(config);
+        for f in fixtures {
+            let question = DedupText::new(f.paper_type, &f.question.content, &f.question.extra);
+            let candidate = DedupText::new(f.paper_type, &f.candidate.content, &f.candidate.extra);
+            let decision = dedup.compare(&question, &candidate, f.semantic_distance);
+            assert_eq!(
+                decision_name(&decision),
+                f.expect,
+                "{}: {decision:?}",
+                f.name
+            );
+        }
+    }
+
+    #[test]
+    fn test_dedup_config() {
+        let config: DedupConfig = serde_json::from_str(
+            r#"{"text_similarity":0.8,"paper_types":{"3":{"levenshtein_similarity":0.9}}}"#,
+        )
+        .unwrap();
+        assert_eq!(config.thresholds(1).text_similarity, 0.8);
+        assert_eq!(config.thresholds(1).levenshtein_similarity, 0.95);
+        assert_eq!(config.thresholds(3).levenshtein_similarity, 0.9);
+        assert_eq!(config.thresholds(3).text_similarity, 0.9);
+    }
+}
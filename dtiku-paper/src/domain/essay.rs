@@ -0,0 +1,370 @@
+use crate::{
+    domain::question::FullQuestion,
+    model::{
+        essay_grade::{EssayBlockFeedback, EssayFeedback},
+        question::QuestionExtra,
+        solution::SolutionExtra,
+    },
+};
+use itertools::Itertools;
+use regex::Regex;
+use std::{
+    collections::{BTreeSet, HashMap},
+    sync::OnceLock,
+};
+
+/// 作答句子与要点的向量相似度达到该值即视为答到了这个要点
+const COVERED_SIMILARITY: f32 = 0.75;
+/// 要点覆盖在总分中的占比，其余为字数分
+const COVERAGE_WEIGHT: f32 = 0.8;
+/// 字数少于要求的这个比例视为过少
+const MIN_WORD_RATIO: f32 = 0.6;
+/// 太短的句子不作为要点
+const MIN_KEY_POINT_CHARS: usize = 4;
+
+static RE_LINE_BREAK: OnceLock<Regex> = OnceLock::new();
+static RE_SENTENCE_END: OnceLock<Regex> = OnceLock::new();
+static RE_NUMBERING: OnceLock<Regex> = OnceLock::new();
+
+fn get_re_line_break() -> &'static Regex {
+    RE_LINE_BREAK.get_or_init(|| Regex::new(r"(?i)<br\s*/?>|</p>|</div>|</li>").unwrap())
+}
+
+fn get_re_sentence_end() -> &'static Regex {
+    RE_SENTENCE_END.get_or_init(|| Regex::new(r"[。！？；;!?\n]+").unwrap())
+}
+
+/// 匹配“1.”、“（2）”、“三、”之类的序号
+fn get_re_numbering() -> &'static Regex {
+    RE_NUMBERING
+        .get_or_init(|| Regex::new(r"^[（(]?(?:\d+|[一二三四五六七八九十]+)[)）.、．]\s*").unwrap())
+}
+
+/// 一道申论题的得分(0~100)和批改意见
+#[derive(Debug, Clone)]
+pub struct EssayScore {
+    pub question_id: i32,
+    pub score: f32,
+    pub feedback: EssayFeedback,
+}
+
+struct EssayBlock {
+    title: String,
+    word_limit: Option<i16>,
+    answer: String,
+    sentences: Vec<String>,
+}
+
+struct EssayQuestion {
+    question_id: i32,
+    key_points: Vec<String>,
+    blocks: Vec<EssayBlock>,
+}
+
+/// 申论批改分两步：先通过texts()取出需要计算向量的要点和作答句子，
+/// 再把按同样顺序算好的向量交给grade()
+pub struct EssayGrading {
+    questions: Vec<EssayQuestion>,
+}
+
+impl EssayGrading {
+    /// user_answer为每道题各问的作答，只批改有作答的题目
+    pub fn new(questions: &[FullQuestion], user_answer: &HashMap<i32, Vec<String>>) -> Self {
+        let questions = questions
+            .iter()
+            .filter_map(|q| {
+                let answers = user_answer.get(&q.id)?;
+                let key_points = q
+                    .solutions
+                    .iter()
+                    .flatten()
+                    .find(|s| {
+                        matches!(
+                            s.extra,
+                            SolutionExtra::ClosedEndedQA(_)
+                                | SolutionExtra::OpenEndedQA(_)
+                                | SolutionExtra::OtherQA(_)
+                        )
+                    })
+                    .map(|s| key_points(&s.extra.get_html()))
+                    .unwrap_or_default();
+                let blocks = qa_blocks(&q.extra)
+                    .into_iter()
+                    .enumerate()
+                    .map(|(i, (title, word_limit))| {
+                        let answer = answers.get(i).map(|a| a.trim()).unwrap_or_default();
+                        EssayBlock {
+                            title,
+                            word_limit,
+                            answer: answer.to_string(),
+                            sentences: split_sentences(answer),
+                        }
+                    })
+                    .collect();
+                Some(EssayQuestion {
+                    question_id: q.id,
+                    key_points,
+                    blocks,
+                })
+            })
+            .collect();
+        Self { questions }
+    }
+
+    /// 需要计算向量的文本：每道题先是参考要点，然后是各问作答的句子
+    pub fn texts(&self) -> Vec<String> {
+        self.questions
+            .iter()
+            .flat_map(|q| {
+                q.key_points
+                    .iter()
+                    .chain(q.blocks.iter().flat_map(|b| b.sentences.iter()))
+                    .cloned()
+            })
+            .collect()
+    }
+
+    pub fn grade(self, embeddings: &[Vec<f32>]) -> anyhow::Result<Vec<EssayScore>> {
+        let expected: usize = self
+            .questions
+            .iter()
+            .map(|q| q.key_points.len() + q.blocks.iter().map(|b| b.sentences.len()).sum::<usize>())
+            .sum();
+        if embeddings.len() != expected {
+            anyhow::bail!(
+                "essay embeddings count mismatch: expected {expected}, got {}",
+                embeddings.len()
+            );
+        }
+        let mut offset = 0;
+        let mut scores = Vec::with_capacity(self.questions.len());
+        for q in self.questions {
+            let points = &embeddings[offset..offset + q.key_points.len()];
+            offset += q.key_points.len();
+
+            let mut covered_all = BTreeSet::new();
+            let mut word_scores = Vec::with_capacity(q.blocks.len());
+            let mut blocks = Vec::with_capacity(q.blocks.len());
+            for b in q.blocks {
+                let sentences = &embeddings[offset..offset + b.sentences.len()];
+                offset += b.sentences.len();
+
+                let covered = points
+                    .iter()
+                    .enumerate()
+                    .filter(|(_, p)| {
+                        sentences
+                            .iter()
+                            .any(|s| cosine_similarity(p, s) >= COVERED_SIMILARITY)
+                    })
+                    .map(|(i, _)| i)
+                    .collect_vec();
+                covered_all.extend(covered.iter().cloned());
+
+                let word_count = word_count(&b.answer);
+                word_scores.push(word_score(word_count, b.word_limit));
+
+                let mut comments = vec![];
+                if b.answer.is_empty() {
+                    comments.push("未作答".to_string());
+                } else {
+                    if let Some(limit) = b.word_limit {
+                        if word_count > limit as usize {
+                            comments.push(format!("字数{word_count}，超出了{limit}字的要求"));
+                        } else if (word_count as f32) < limit as f32 * MIN_WORD_RATIO {
+                            comments.push(format!("字数{word_count}，明显少于{limit}字的要求"));
+                        }
+                    }
+                    if !points.is_empty() && covered.is_empty() {
+                        comments.push("没有答到参考答案中的要点".to_string());
+                    }
+                }
+                blocks.push(EssayBlockFeedback {
+                    title: b.title,
+                    word_count,
+                    word_limit: b.word_limit,
+                    covered: covered
+                        .into_iter()
+                        .map(|i| q.key_points[i].clone())
+                        .collect(),
+                    comments,
+                });
+            }
+
+            let word_score = if word_scores.is_empty() {
+                0.0
+            } else {
+                word_scores.iter().sum::<f32>() / word_scores.len() as f32
+            };
+            let coverage = if q.key_points.is_empty() {
+                None
+            } else {
+                Some(covered_all.len() as f32 / q.key_points.len() as f32)
+            };
+            let score = match coverage {
+                Some(c) => COVERAGE_WEIGHT * c + (1.0 - COVERAGE_WEIGHT) * word_score,
+                None => word_score,
+            };
+            let missed = q
+                .key_points
+                .into_iter()
+                .enumerate()
+                .filter(|(i, _)| !covered_all.contains(i))
+                .map(|(_, p)| p)
+                .collect();
+            scores.push(EssayScore {
+                question_id: q.question_id,
+                score: (score * 1000.0).round() / 10.0,
+                feedback: EssayFeedback {
+                    coverage,
+                    missed,
+                    blocks,
+                },
+            });
+        }
+        Ok(scores)
+    }
+}
+
+/// 申论题的每一问及其字数要求，非问答题视为只有一问
+fn qa_blocks(extra: &QuestionExtra) -> Vec<(String, Option<i16>)> {
+    match extra.qa() {
+        [] => vec![(String::new(), None)],
+        qa => qa
+            .iter()
+            .map(|qa| (qa.title.clone(), qa.word_count))
+            .collect(),
+    }
+}
+
+/// 从参考答案的html中拆出要点
+fn key_points(html: &str) -> Vec<String> {
+    let html = get_re_line_break().replace_all(html, "\n");
+    let text = scraper::Html::parse_fragment(&html)
+        .root_element()
+        .text()
+        .join("");
+    split_sentences(&text)
+        .into_iter()
+        .map(|s| get_re_numbering().replace(&s, "").trim().to_string())
+        .filter(|s| s.chars().count() >= MIN_KEY_POINT_CHARS)
+        .unique()
+        .collect()
+}
+
+fn split_sentences(text: &str) -> Vec<String> {
+    get_re_sentence_end()
+        .split(text)
+        .map(|s| s.trim_matches(|c: char| c.is_whitespace() || "，,、：:".contains(c)))
+        .filter(|s| !s.is_empty())
+        .map(|s| s.to_string())
+        .collect()
+}
+
+/// 申论按字符计数，标点也算字数，空白不算
+fn word_count(text: &str) -> usize {
+    text.chars().filter(|c| !c.is_whitespace()).count()
+}
+
+/// 字数分(0~1)：达到要求的60%即满分，超出字数按超出比例扣分
+fn word_score(word_count: usize, word_limit: Option<i16>) -> f32 {
+    if word_count == 0 {
+        return 0.0;
+    }
+    let Some(limit) = word_limit.filter(|l| *l > 0) else {
+        return 1.0;
+    };
+    let (count, limit) = (word_count as f32, limit as f32);
+    if count > limit {
+        (1.0 - 2.0 * (count - limit) / limit).max(0.0)
+    } else {
+        (count / (limit * MIN_WORD_RATIO)).min(1.0)
+    }
+}
+
+fn cosine_similarity(a: &[f32], b: &[f32]) -> f32 {
+    let dot: f32 = a.iter().zip(b).map(|(x, y)| x * y).sum();
+    let norm_a = a.iter().map(|x| x * x).sum::<f32>().sqrt();
+    let norm_b = b.iter().map(|x| x * x).sum::<f32>().sqrt();
+    if norm_a == 0.0 || norm_b == 0.0 {
+        0.0
+    } else {
+        dot / (norm_a * norm_b)
+    }
+}
+
+/// 申论作答在exercise_answer中以json数组保存各问的作答
+pub fn encode_essay_answer(answers: &[String]) -> String {
+    serde_json::to_string(answers).unwrap_or_default()
+}
+
+pub fn decode_essay_answer(answer: &str) -> Vec<String> {
+    serde_json::from_str(answer).unwrap_or_else(|_| vec![answer.to_string()])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{key_points, word_count, word_score, EssayBlock, EssayGrading, EssayQuestion};
+
+    #[test]
+    fn test_key_points() {
+        let html = "<p>1.加强基层治理能力。</p><p>（2）完善公共服务体系；推进数字化</p><br/>好。";
+        assert_eq!(
+            key_points(html),
+            vec!["加强基层治理能力", "完善公共服务体系", "推进数字化"]
+        );
+    }
+
+    #[test]
+    fn test_word_score() {
+        assert_eq!(word_count(" 一二 三\n"), 3);
+        assert!((word_score(0, Some(200)) - 0.0).abs() < 1e-4);
+        assert!((word_score(150, None) - 1.0).abs() < 1e-4);
+        assert!((word_score(150, Some(200)) - 1.0).abs() < 1e-4);
+        assert!((word_score(60, Some(200)) - 0.5).abs() < 1e-4);
+        assert!((word_score(220, Some(200)) - 0.8).abs() < 1e-4);
+        assert!((word_score(400, Some(200)) - 0.0).abs() < 1e-4);
+    }
+
+    #[test]
+    fn test_grade() {
+        let grading = EssayGrading {
+            questions: vec![EssayQuestion {
+                question_id: 1,
+                key_points: vec!["要点一".to_string(), "要点二".to_string()],
+                blocks: vec![EssayBlock {
+                    title: "概括".to_string(),
+                    word_limit: Some(10),
+                    answer: "答到要点一。无关内容".to_string(),
+                    sentences: vec!["答到要点一".to_string(), "无关内容".to_string()],
+                }],
+            }],
+        };
+        assert_eq!(grading.texts().len(), 4);
+        let embeddings = vec![
+            vec![1.0, 0.0],
+            vec![0.0, 1.0],
+            vec![0.9, 0.1],
+            vec![-1.0, 0.0],
+        ];
+        let scores = grading.grade(&embeddings).unwrap();
+        let feedback = &scores[0].feedback;
+        assert_eq!(feedback.coverage, Some(0.5));
+        assert_eq!(feedback.missed, vec!["要点二"]);
+        assert_eq!(feedback.blocks[0].covered, vec!["要点一"]);
+        assert_eq!(feedback.blocks[0].word_count, 10);
+        assert_eq!(scores[0].score, 60.0);
+    }
+
+    #[test]
+    fn test_grade_embeddings_mismatch() {
+        let grading = EssayGrading {
+            questions: vec![EssayQuestion {
+                question_id: 1,
+                key_points: vec!["要点一".to_string()],
+                blocks: vec![],
+            }],
+        };
+        assert!(grading.grade(&[]).is_err());
+    }
+}
@@ -0,0 +1,97 @@
+use crate::model::exercise_record::{self, Column};
+use sea_orm::{sea_query::IntoCondition, ColumnTrait};
+use std::collections::HashMap;
+
+/// 练习记录的归属者：登录用户用user_id，匿名访客用浏览器指纹(x-fp)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ExerciseOwner {
+    User(i32),
+    Fingerprint(String),
+}
+
+impl ExerciseOwner {
+    pub fn user_id(&self) -> Option<i32> {
+        match self {
+            Self::User(user_id) => Some(*user_id),
+            Self::Fingerprint(_) => None,
+        }
+    }
+
+    pub fn fingerprint(&self) -> Option<String> {
+        match self {
+            Self::User(_) => None,
+            Self::Fingerprint(fp) => Some(fp.clone()),
+        }
+    }
+}
+
+impl IntoCondition for ExerciseOwner {
+    fn into_condition(self) -> sea_orm::Condition {
+        match self {
+            Self::User(user_id) => Column::UserId.eq(user_id).into_condition(),
+            Self::Fingerprint(fp) => Column::UserId
+                .is_null()
+                .and(Column::Fingerprint.eq(fp))
+                .into_condition(),
+        }
+    }
+}
+
+/// 一次练习的完整作答：用于重新打开报告或恢复未完成的练习
+pub struct ExerciseDetail {
+    pub record: exercise_record::Model,
+    pub user_answer: HashMap<i32, String>,
+    pub user_time: HashMap<i32, u64>,
+}
+
+/// 限时模考截止后仍接受交卷的宽限秒数，用于抵消网络延迟
+pub const TIMED_GRACE_SECS: i64 = 30;
+
+/// 限时模考的状态
+pub enum TimedExercise {
+    /// 考试进行中，remaining_secs为剩余秒数
+    Running {
+        detail: ExerciseDetail,
+        remaining_secs: i64,
+    },
+    /// 按时交卷
+    Submitted(ExerciseDetail),
+    /// 已过截止时间，按截止前暂存的作答自动交卷
+    Expired(ExerciseDetail),
+}
+
+/// 客户端上报的每题用时不可信：单题用时不超过总用时，合计超出总用时时按比例缩减
+pub fn cap_question_times(user_time: &HashMap<i32, u64>, total_secs: u64) -> HashMap<i32, u64> {
+    let capped: HashMap<i32, u64> = user_time
+        .iter()
+        .map(|(qid, time)| (*qid, (*time).min(total_secs)))
+        .collect();
+    let sum: u64 = capped.values().sum();
+    if sum <= total_secs {
+        return capped;
+    }
+    capped
+        .into_iter()
+        .map(|(qid, time)| (qid, time * total_secs / sum))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::cap_question_times;
+    use std::collections::HashMap;
+
+    #[test]
+    fn test_cap_question_times() {
+        let times = HashMap::from([(1, 30), (2, 50)]);
+        assert_eq!(cap_question_times(&times, 100), times);
+
+        let capped = cap_question_times(&HashMap::from([(1, 500)]), 100);
+        assert_eq!(capped[&1], 100);
+
+        let capped = cap_question_times(&HashMap::from([(1, 90), (2, 30)]), 100);
+        assert_eq!(capped[&1], 75);
+        assert_eq!(capped[&2], 25);
+        assert!(capped.values().sum::<u64>() <= 100);
+    }
+}
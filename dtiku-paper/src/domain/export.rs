@@ -0,0 +1,525 @@
+use crate::{
+    domain::paper::FullPaper,
+    model::{
+        paper::PaperExtra,
+        question::{QuestionExtra, QuestionSinglePaper},
+        solution,
+    },
+};
+use itertools::Itertools;
+use scraper::{ElementRef, Html, Node};
+use serde::Deserialize;
+use std::collections::{HashMap, HashSet};
+use strum::{AsRefStr, Display, EnumString};
+
+/// 会打断段落的html标签
+const BLOCK_TAGS: &[&str] = &[
+    "p",
+    "div",
+    "li",
+    "ul",
+    "ol",
+    "tr",
+    "table",
+    "h1",
+    "h2",
+    "h3",
+    "h4",
+    "h5",
+    "h6",
+    "blockquote",
+    "section",
+];
+
+/// 导出的文档格式
+#[derive(Debug, Default, Clone, Copy, PartialEq, Deserialize, Display, AsRefStr, EnumString)]
+#[strum(serialize_all = "snake_case")]
+#[serde(rename_all = "snake_case")]
+pub enum ExportFormat {
+    #[default]
+    Pdf,
+    Docx,
+    Markdown,
+}
+
+impl ExportFormat {
+    pub fn content_type(&self) -> &'static str {
+        match self {
+            Self::Pdf => "application/pdf",
+            Self::Docx => "application/vnd.openxmlformats-officedocument.wordprocessingml.document",
+            Self::Markdown => "text/markdown; charset=utf-8",
+        }
+    }
+
+    pub fn extension(&self) -> &'static str {
+        match self {
+            Self::Pdf => "pdf",
+            Self::Docx => "docx",
+            Self::Markdown => "md",
+        }
+    }
+}
+
+/// 答案解析的位置
+#[derive(Debug, Default, Clone, Copy, PartialEq, Deserialize, Display, AsRefStr, EnumString)]
+#[strum(serialize_all = "snake_case")]
+#[serde(rename_all = "snake_case")]
+pub enum SolutionPlacement {
+    /// 紧跟在每道题后面
+    Inline,
+    /// 集中放在文末
+    #[default]
+    End,
+    /// 不导出答案解析
+    Omit,
+}
+
+#[derive(Debug, Default, Clone, Deserialize)]
+pub struct ExportOptions {
+    #[serde(default)]
+    pub format: ExportFormat,
+    #[serde(default)]
+    pub solution: SolutionPlacement,
+}
+
+/// 一段文字中格式相同的部分
+#[derive(Debug, Clone, PartialEq)]
+pub struct Run {
+    pub text: String,
+    pub bold: bool,
+}
+
+impl Run {
+    fn bold<S: Into<String>>(text: S) -> Self {
+        Self {
+            text: text.into(),
+            bold: true,
+        }
+    }
+}
+
+/// 与输出格式无关的文档结构，由各格式的渲染器输出
+#[derive(Debug, Clone, PartialEq)]
+pub enum Block {
+    Heading(String),
+    Paragraph(Vec<Run>),
+    /// 图片地址，渲染时下载后嵌入文档
+    Image(String),
+}
+
+#[derive(Debug, Clone)]
+pub struct ExportDocument {
+    pub title: String,
+    pub blocks: Vec<Block>,
+}
+
+/// 导出的一道题
+struct ExportItem<'a> {
+    num: usize,
+    content: &'a str,
+    extra: &'a QuestionExtra,
+    /// 题目出处，按知识点组卷时标注来自哪张试卷
+    source: Option<String>,
+    solutions: Vec<&'a solution::Model>,
+}
+
+impl ExportDocument {
+    /// 整张试卷：行测按章节排列，材料放在引用它的第一道题前；申论先列给定资料再列题目
+    pub fn from_paper(paper: &FullPaper, placement: SolutionPlacement) -> Self {
+        let mut doc = Self {
+            title: paper.p.title.clone(),
+            blocks: vec![],
+        };
+        let mut solutions = paper.ss.iter().map(|s| (s.question_id, s)).into_group_map();
+        let items = paper
+            .qs
+            .iter()
+            .sorted_by_key(|q| q.num)
+            .map(|q| ExportItem {
+                num: q.num as usize,
+                content: &q.content,
+                extra: &q.extra,
+                source: None,
+                solutions: solutions.remove(&q.id).unwrap_or_default(),
+            })
+            .collect_vec();
+
+        let mut end_blocks = vec![];
+        match &paper.p.extra {
+            PaperExtra::Chapters(_) => {
+                let materials: HashMap<i32, &str> = paper
+                    .ms
+                    .iter()
+                    .map(|m| (m.id, m.content.as_str()))
+                    .collect();
+                let mut printed = HashSet::new();
+                for (q, item) in paper.qs.iter().sorted_by_key(|q| q.num).zip(&items) {
+                    if let Some(chapter) = paper.p.extra.compute_chapter(q.num as i32, true) {
+                        doc.blocks.push(Block::Heading(chapter.name));
+                        doc.blocks.extend(html_blocks(&chapter.desc));
+                    }
+                    for mid in paper.qid_mid_map.get(&q.id).into_iter().flatten() {
+                        if let Some(content) = materials.get(mid) {
+                            if printed.insert(*mid) {
+                                doc.blocks.extend(html_blocks(content));
+                            }
+                        }
+                    }
+                    doc.push_item(item, placement, &mut end_blocks);
+                }
+            }
+            PaperExtra::EssayCluster(_) => {
+                if !paper.ms.is_empty() {
+                    doc.blocks.push(Block::Heading("给定资料".to_string()));
+                    for m in paper.ms.iter().sorted_by_key(|m| m.num) {
+                        doc.blocks
+                            .push(Block::Paragraph(vec![Run::bold(format!("资料{}", m.num))]));
+                        doc.blocks.extend(html_blocks(&m.content));
+                    }
+                }
+                doc.blocks.push(Block::Heading("作答要求".to_string()));
+                for item in &items {
+                    doc.push_item(item, placement, &mut end_blocks);
+                }
+            }
+        }
+        doc.push_end_solutions(end_blocks);
+        doc
+    }
+
+    /// 按条件筛选出的题目，重新编号并标注出处
+    pub fn from_questions(
+        title: &str,
+        questions: &[QuestionSinglePaper],
+        placement: SolutionPlacement,
+    ) -> Self {
+        let mut doc = Self {
+            title: title.to_string(),
+            blocks: vec![],
+        };
+        let mut end_blocks = vec![];
+        let mut printed = HashSet::new();
+        for (i, q) in questions.iter().enumerate() {
+            for m in q.materials.iter().flatten() {
+                if printed.insert(m.id) {
+                    doc.blocks.extend(html_blocks(&m.content));
+                }
+            }
+            let item = ExportItem {
+                num: i + 1,
+                content: &q.content,
+                extra: &q.extra,
+                source: Some(format!("（{}第{}题）", q.paper.paper.title, q.paper.num)),
+                solutions: q.solutions.iter().flatten().collect(),
+            };
+            doc.push_item(&item, placement, &mut end_blocks);
+        }
+        doc.push_end_solutions(end_blocks);
+        doc
+    }
+
+    /// 文档中出现的所有图片地址，已去重
+    pub fn image_srcs(&self) -> Vec<String> {
+        self.blocks
+            .iter()
+            .filter_map(|b| match b {
+                Block::Image(src) => Some(src.clone()),
+                _ => None,
+            })
+            .unique()
+            .collect()
+    }
+
+    fn push_item(
+        &mut self,
+        item: &ExportItem,
+        placement: SolutionPlacement,
+        end_blocks: &mut Vec<Block>,
+    ) {
+        let mut prefix = vec![Run::bold(format!("{}. ", item.num))];
+        if let Some(source) = &item.source {
+            prefix.push(Run {
+                text: source.clone(),
+                bold: false,
+            });
+        }
+        push_prefixed(&mut self.blocks, prefix, html_blocks(item.content));
+        for (i, option) in item.extra.options().iter().enumerate() {
+            let label = (b'A' + i as u8) as char;
+            push_prefixed(
+                &mut self.blocks,
+                vec![Run::bold(format!("{label}. "))],
+                html_blocks(option),
+            );
+        }
+        match placement {
+            SolutionPlacement::Inline => self.blocks.extend(solution_blocks(item, None)),
+            SolutionPlacement::End => end_blocks.extend(solution_blocks(item, Some(item.num))),
+            SolutionPlacement::Omit => {}
+        }
+    }
+
+    fn push_end_solutions(&mut self, end_blocks: Vec<Block>) {
+        if !end_blocks.is_empty() {
+            self.blocks
+                .push(Block::Heading("参考答案与解析".to_string()));
+            self.blocks.extend(end_blocks);
+        }
+    }
+}
+
+/// 答案和解析，num不为空时在开头标出题号
+fn solution_blocks(item: &ExportItem, num: Option<usize>) -> Vec<Block> {
+    let mut blocks = vec![];
+    for s in &item.solutions {
+        let mut prefix = vec![];
+        if let Some(num) = num {
+            prefix.push(Run::bold(format!("{num}. ")));
+        }
+        if let Some(answer) = s.extra.get_answer() {
+            prefix.push(Run::bold("【答案】"));
+            prefix.push(Run {
+                text: answer,
+                bold: false,
+            });
+            blocks.push(Block::Paragraph(prefix));
+            prefix = vec![];
+        }
+        prefix.push(Run::bold("【解析】"));
+        push_prefixed(&mut blocks, prefix, html_blocks(&s.extra.get_full_html()));
+    }
+    blocks
+}
+
+/// prefix放在第一段的开头，内容不是以段落开始时单独成段
+fn push_prefixed(blocks: &mut Vec<Block>, mut prefix: Vec<Run>, content: Vec<Block>) {
+    let mut content = content.into_iter();
+    match content.next() {
+        Some(Block::Paragraph(runs)) => {
+            prefix.extend(runs);
+            blocks.push(Block::Paragraph(prefix));
+        }
+        Some(other) => {
+            blocks.push(Block::Paragraph(prefix));
+            blocks.push(other);
+        }
+        None => blocks.push(Block::Paragraph(prefix)),
+    }
+    blocks.extend(content);
+}
+
+/// html片段转为段落和图片，只保留加粗格式
+pub fn html_blocks(html: &str) -> Vec<Block> {
+    let mut builder = BlockBuilder::default();
+    walk_children(
+        Html::parse_fragment(html).root_element(),
+        false,
+        &mut builder,
+    );
+    builder.finish_paragraph();
+    builder.blocks
+}
+
+#[derive(Default)]
+struct BlockBuilder {
+    blocks: Vec<Block>,
+    runs: Vec<Run>,
+}
+
+impl BlockBuilder {
+    /// 连续空白合并为一个空格，段首的空白去掉
+    fn push_text(&mut self, text: &str, bold: bool) {
+        let mut space = self.runs.last().is_none_or(|r| r.text.ends_with(' '));
+        let mut collapsed = String::with_capacity(text.len());
+        for c in text.chars() {
+            if !c.is_whitespace() {
+                collapsed.push(c);
+                space = false;
+            } else if !space {
+                collapsed.push(' ');
+                space = true;
+            }
+        }
+        if collapsed.is_empty() {
+            return;
+        }
+        match self.runs.last_mut() {
+            Some(last) if last.bold == bold => last.text.push_str(&collapsed),
+            _ => self.runs.push(Run {
+                text: collapsed,
+                bold,
+            }),
+        }
+    }
+
+    fn finish_paragraph(&mut self) {
+        while let Some(last) = self.runs.last_mut() {
+            last.text.truncate(last.text.trim_end().len());
+            if !last.text.is_empty() {
+                break;
+            }
+            self.runs.pop();
+        }
+        if !self.runs.is_empty() {
+            self.blocks
+                .push(Block::Paragraph(std::mem::take(&mut self.runs)));
+        }
+    }
+
+    fn push_image(&mut self, src: &str) {
+        self.finish_paragraph();
+        self.blocks.push(Block::Image(src.to_string()));
+    }
+}
+
+fn walk_children(el: ElementRef, bold: bool, builder: &mut BlockBuilder) {
+    for child in el.children() {
+        match child.value() {
+            Node::Text(text) => builder.push_text(text, bold),
+            Node::Element(_) => {
+                if let Some(child) = ElementRef::wrap(child) {
+                    walk_element(child, bold, builder);
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+fn walk_element(el: ElementRef, bold: bool, builder: &mut BlockBuilder) {
+    let name = el.value().name();
+    match name {
+        "img" => {
+            if let Some(src) = el.value().attr("src") {
+                builder.push_image(src);
+            }
+        }
+        "br" => builder.finish_paragraph(),
+        "script" | "style" => {}
+        _ => {
+            let block = BLOCK_TAGS.contains(&name);
+            if block {
+                builder.finish_paragraph();
+            }
+            let bold = bold
+                || matches!(
+                    name,
+                    "b" | "strong" | "h1" | "h2" | "h3" | "h4" | "h5" | "h6"
+                );
+            walk_children(el, bold, builder);
+            if block {
+                builder.finish_paragraph();
+            } else if matches!(name, "td" | "th") {
+                builder.push_text(" ", bold);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{html_blocks, Block, ExportDocument, Run, SolutionPlacement};
+    use crate::model::{
+        paper::{self, Chapters, PaperExtra},
+        question::{PaperWithNum, QuestionExtra, QuestionSinglePaper},
+        solution::{self, SingleChoice, SolutionExtra},
+        FromType,
+    };
+
+    fn run(text: &str, bold: bool) -> Run {
+        Run {
+            text: text.to_string(),
+            bold,
+        }
+    }
+
+    #[test]
+    fn test_html_blocks() {
+        let blocks = html_blocks(
+            "<p>  我国<b>宪法</b>规定\n  </p><p><img src=\"//s.dtiku.cn/a.png\"/>如图</p>行尾<br>下一行",
+        );
+        assert_eq!(
+            blocks,
+            vec![
+                Block::Paragraph(vec![
+                    run("我国", false),
+                    run("宪法", true),
+                    run("规定", false)
+                ]),
+                Block::Image("//s.dtiku.cn/a.png".to_string()),
+                Block::Paragraph(vec![run("如图", false)]),
+                Block::Paragraph(vec![run("行尾", false)]),
+                Block::Paragraph(vec![run("下一行", false)]),
+            ]
+        );
+        assert!(html_blocks("  <p> </p> ").is_empty());
+    }
+
+    #[test]
+    fn test_from_questions_solution_placement() {
+        let question = QuestionSinglePaper {
+            id: 1,
+            content: "<p>题干</p>".to_string(),
+            extra: QuestionExtra::SingleChoice {
+                options: vec!["甲".to_string(), "乙".to_string()],
+            },
+            paper: PaperWithNum {
+                paper: paper::Model {
+                    id: 1,
+                    title: "2024年国考".to_string(),
+                    year: 2024,
+                    exam_id: 1,
+                    paper_type: 1,
+                    label_id: 1,
+                    extra: PaperExtra::Chapters(Chapters {
+                        desc: None,
+                        chapters: vec![],
+                    }),
+                    time_limit: None,
+                },
+                num: 3,
+            },
+            solutions: Some(vec![solution::Model {
+                id: 1,
+                question_id: 1,
+                from_ty: FromType::Fenbi,
+                extra: SolutionExtra::SingleChoice(SingleChoice {
+                    answer: 1,
+                    analysis: "<p>解析</p>".to_string(),
+                }),
+            }]),
+            materials: None,
+        };
+        let questions = [question];
+
+        let inline = ExportDocument::from_questions("练习", &questions, SolutionPlacement::Inline);
+        assert_eq!(
+            inline.blocks,
+            vec![
+                Block::Paragraph(vec![
+                    run("1. ", true),
+                    run("（2024年国考第3题）", false),
+                    run("题干", false),
+                ]),
+                Block::Paragraph(vec![run("A. ", true), run("甲", false)]),
+                Block::Paragraph(vec![run("B. ", true), run("乙", false)]),
+                Block::Paragraph(vec![run("【答案】", true), run("B", false)]),
+                Block::Paragraph(vec![run("【解析】", true), run("解析", false)]),
+            ]
+        );
+
+        let end = ExportDocument::from_questions("练习", &questions, SolutionPlacement::End);
+        assert_eq!(end.blocks[3], Block::Heading("参考答案与解析".to_string()));
+        assert_eq!(
+            end.blocks[4],
+            Block::Paragraph(vec![
+                run("1. ", true),
+                run("【答案】", true),
+                run("B", false)
+            ])
+        );
+
+        let omit = ExportDocument::from_questions("练习", &questions, SolutionPlacement::Omit);
+        assert_eq!(omit.blocks.len(), 3);
+    }
+}
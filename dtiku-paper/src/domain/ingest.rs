@@ -0,0 +1,851 @@
+use crate::model::{
+    paper::{Chapters, EssayCluster, PaperBlock, PaperChapter, PaperExtra},
+    question::QuestionExtra,
+    solution::{
+        MultiChoice, SingleChoice, SolutionExtra, StepAnalysis, StepByStepAnswer, TrueFalseChoice,
+    },
+};
+use anyhow::Context;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::sync::LazyLock;
+
+static RE_YEAR: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"(?:^|\D)((?:19|20)\d{2})(?:\D|$)").unwrap());
+
+/// 识别真题文档结构的规则，都是按行匹配的正则
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct IngestRules {
+    /// 行测的部分标题，如“第一部分 常识判断”
+    pub chapter: String,
+    /// 题号，第1个捕获组为数字题号，第2个为题干。题号后不能紧跟数字，避免把“1.5倍”当成题号
+    pub question: String,
+    /// 选项，第1个捕获组为选项字母，第2个为选项内容
+    pub option: String,
+    /// 资料分析等共用资料的引导语，可以带“回答101~105题”这样的题号范围
+    pub material: String,
+    /// 答案标记，后面紧跟答案
+    pub answer: String,
+    /// 解析标记，后面紧跟解析
+    pub analysis: String,
+    /// 集中放在文末的答案解析部分的标题
+    pub solution_section: String,
+    /// 申论“给定资料”部分的标题
+    pub essay_materials: String,
+    /// 申论的资料标题，第1个捕获组为资料序号
+    pub essay_material: String,
+    /// 申论“作答要求”部分的标题
+    pub essay_questions: String,
+    /// 申论题号，第1个捕获组为序号
+    pub essay_question: String,
+}
+
+impl Default for IngestRules {
+    fn default() -> Self {
+        Self {
+            chapter: r"^第[一二三四五六七八九十]+部分\s*\S+".to_string(),
+            question: r"^(\d+)\s*[.．、]\s*(\D.*)?$".to_string(),
+            option: r"^([A-H])\s*[.．、:：]\s*(.*)$".to_string(),
+            material: r"^(?:根据|阅读)(?:以下|下面|下列|所给)?(?:文字|图表)?(?:资料|材料)"
+                .to_string(),
+            answer: r"【答案】|【参考答案】|^(?:参考)?答案\s*[:：]|正确答案\s*[:：是]".to_string(),
+            analysis: r"【解析】|【答案解析】|^解析\s*[:：]".to_string(),
+            solution_section: r"^(?:参考答案|答案)(?:(?:与|及|和)解析)?\s*$".to_string(),
+            essay_materials: r"^给定(?:资料|材料)\s*$".to_string(),
+            essay_material: r"^(?:资料|材料)\s*(\d+|[一二三四五六七八九十]+)\s*$".to_string(),
+            essay_questions: r"^作答要求\s*$".to_string(),
+            essay_question: r"^(?:第\s*)?(\d+|[一二三四五六七八九十]+)\s*(?:题|[.．、])\s*(.*)$"
+                .to_string(),
+        }
+    }
+}
+
+struct CompiledRules {
+    chapter: Regex,
+    question: Regex,
+    option: Regex,
+    material: Regex,
+    answer: Regex,
+    analysis: Regex,
+    solution_section: Regex,
+    essay_materials: Regex,
+    essay_material: Regex,
+    essay_questions: Regex,
+    essay_question: Regex,
+    /// 共用资料引导语中的题号范围
+    question_range: Regex,
+    /// 申论题干中引用的资料序号
+    material_ref: Regex,
+}
+
+impl IngestRules {
+    fn compile(&self) -> anyhow::Result<CompiledRules> {
+        // groups为规则要求的最少捕获组数
+        let compile = |name: &str, pattern: &str, groups: usize| {
+            let re = Regex::new(pattern)
+                .with_context(|| format!("invalid ingest rule {name}: {pattern}"))?;
+            anyhow::ensure!(
+                re.captures_len() > groups,
+                "ingest rule {name} needs {groups} capture groups: {pattern}"
+            );
+            Ok(re)
+        };
+        Ok(CompiledRules {
+            chapter: compile("chapter", &self.chapter, 0)?,
+            question: compile("question", &self.question, 2)?,
+            option: compile("option", &self.option, 2)?,
+            material: compile("material", &self.material, 0)?,
+            answer: compile("answer", &self.answer, 0)?,
+            analysis: compile("analysis", &self.analysis, 0)?,
+            solution_section: compile("solution_section", &self.solution_section, 0)?,
+            essay_materials: compile("essay_materials", &self.essay_materials, 0)?,
+            essay_material: compile("essay_material", &self.essay_material, 1)?,
+            essay_questions: compile("essay_questions", &self.essay_questions, 0)?,
+            essay_question: compile("essay_question", &self.essay_question, 1)?,
+            question_range: Regex::new(r"(\d+)\s*[~～\-－—至到]+\s*(\d+)").unwrap(),
+            material_ref: Regex::new(r"(?:资料|材料)\s*(\d+|[一二三四五六七八九十]+)").unwrap(),
+        })
+    }
+}
+
+impl CompiledRules {
+    /// 识别题号行，返回题号和同一行中的题干
+    fn question_start<'a>(&self, line: &'a str) -> Option<(i32, &'a str)> {
+        let c = self.question.captures(line)?;
+        let num = c.get(1)?.as_str().parse().ok()?;
+        Some((num, c.get(2).map_or("", |m| m.as_str())))
+    }
+}
+
+/// 从文档中识别出的试卷，管理员确认或修改后再入库
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ParsedPaper {
+    pub title: String,
+    pub year: Option<i16>,
+    /// 申论试卷
+    pub essay: bool,
+    /// 试卷开头的注意事项
+    pub desc: String,
+    pub chapters: Vec<ParsedChapter>,
+    pub materials: Vec<ParsedMaterial>,
+    /// 识别过程中发现的可疑之处，供人工核对
+    pub warnings: Vec<String>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ParsedChapter {
+    pub name: String,
+    pub questions: Vec<ParsedQuestion>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ParsedMaterial {
+    pub num: i32,
+    pub content: String,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ParsedQuestion {
+    pub num: i32,
+    pub content: String,
+    pub options: Vec<String>,
+    pub answer: Option<String>,
+    pub analysis: Option<String>,
+    /// 引用的资料序号
+    pub material_nums: Vec<i32>,
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum Section {
+    Front,
+    Chapters,
+    EssayMaterials,
+    EssayQuestions,
+    Solutions,
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum Field {
+    Content,
+    Option,
+    Answer,
+    Analysis,
+}
+
+/// 共用资料及其适用的题号范围
+struct MaterialScope {
+    num: i32,
+    range: Option<(i32, i32)>,
+}
+
+struct Parser {
+    rules: CompiledRules,
+    paper: ParsedPaper,
+    section: Section,
+    field: Field,
+    material: Option<MaterialScope>,
+    /// 资料引导语之后、下一道题之前的内容属于资料
+    collecting_material: bool,
+    /// 文末答案解析部分当前对应的题号
+    solution_num: Option<i32>,
+}
+
+/// 按行识别文档文本中的试卷结构
+pub fn parse_paper(text: &str, rules: &IngestRules) -> anyhow::Result<ParsedPaper> {
+    let mut parser = Parser {
+        rules: rules.compile()?,
+        paper: ParsedPaper::default(),
+        section: Section::Front,
+        field: Field::Content,
+        material: None,
+        collecting_material: false,
+        solution_num: None,
+    };
+    let lines = text.lines().map(str::trim).filter(|l| !l.is_empty());
+    // 申论的注意事项里也有带序号的条目，需要先确定试卷类型
+    parser.paper.essay = lines
+        .clone()
+        .any(|l| parser.rules.essay_materials.is_match(l));
+    for line in lines {
+        parser.feed(line);
+    }
+    Ok(parser.finish())
+}
+
+impl Parser {
+    fn feed(&mut self, line: &str) {
+        if self.paper.title.is_empty() {
+            self.paper.title = line.to_string();
+            self.paper.year = pick_year(line);
+            return;
+        }
+        let rules = &self.rules;
+        if rules.solution_section.is_match(line) {
+            self.section = Section::Solutions;
+            self.solution_num = None;
+            return;
+        }
+        if rules.essay_materials.is_match(line) {
+            self.paper.essay = true;
+            self.section = Section::EssayMaterials;
+            return;
+        }
+        if rules.essay_questions.is_match(line) {
+            self.paper.essay = true;
+            self.section = Section::EssayQuestions;
+            self.start_chapter(line);
+            return;
+        }
+        match self.section {
+            Section::Front | Section::Chapters => self.feed_chapters(line),
+            Section::EssayMaterials => self.feed_essay_material(line),
+            Section::EssayQuestions => self.feed_essay_question(line),
+            Section::Solutions => self.feed_solution(line),
+        }
+    }
+
+    fn feed_chapters(&mut self, line: &str) {
+        if self.rules.chapter.is_match(line) {
+            self.section = Section::Chapters;
+            self.material = None;
+            self.collecting_material = false;
+            self.start_chapter(line);
+            return;
+        }
+        if self.rules.material.is_match(line) {
+            let num = self.paper.materials.len() as i32 + 1;
+            let range = self.rules.question_range.captures(line).and_then(|c| {
+                Some((
+                    c.get(1)?.as_str().parse().ok()?,
+                    c.get(2)?.as_str().parse().ok()?,
+                ))
+            });
+            self.paper.materials.push(ParsedMaterial {
+                num,
+                content: line.to_string(),
+            });
+            self.material = Some(MaterialScope { num, range });
+            self.collecting_material = true;
+            return;
+        }
+        if self.section == Section::Front && self.paper.essay {
+            push_line(&mut self.paper.desc, line);
+            return;
+        }
+        if let Some((num, content)) = self.rules.question_start(line) {
+            let material_nums = match &self.material {
+                Some(m) if m.range.is_none_or(|(from, to)| (from..=to).contains(&num)) => {
+                    vec![m.num]
+                }
+                _ => vec![],
+            };
+            self.start_question(num, content, material_nums);
+            return;
+        }
+        if self.section == Section::Front {
+            push_line(&mut self.paper.desc, line);
+            return;
+        }
+        if self.collecting_material {
+            if let Some(m) = self.paper.materials.last_mut() {
+                push_line(&mut m.content, line);
+            }
+            return;
+        }
+        self.feed_question_line(line);
+    }
+
+    fn feed_essay_material(&mut self, line: &str) {
+        if let Some(c) = self.rules.essay_material.captures(line) {
+            let num = c
+                .get(1)
+                .and_then(|m| parse_number(m.as_str()))
+                .unwrap_or(self.paper.materials.len() as i32 + 1);
+            self.paper.materials.push(ParsedMaterial {
+                num,
+                content: String::new(),
+            });
+            return;
+        }
+        match self.paper.materials.last_mut() {
+            Some(m) => push_line(&mut m.content, line),
+            None => self
+                .paper
+                .warnings
+                .push(format!("给定资料中无法归属的内容：{line}")),
+        }
+    }
+
+    fn feed_essay_question(&mut self, line: &str) {
+        if let Some(c) = self.rules.essay_question.captures(line) {
+            if let Some(num) = c.get(1).and_then(|m| parse_number(m.as_str())) {
+                let material_nums = self.material_refs(line);
+                self.start_question(num, c.get(2).map_or("", |m| m.as_str()), material_nums);
+                return;
+            }
+        }
+        if self.field == Field::Content {
+            let refs = self.material_refs(line);
+            if let Some(q) = self.current_question_mut() {
+                q.material_nums.extend(refs);
+                q.material_nums.sort_unstable();
+                q.material_nums.dedup();
+            }
+        }
+        self.feed_question_line(line);
+    }
+
+    fn feed_solution(&mut self, line: &str) {
+        let mut rest = line;
+        if let Some((num, content)) = self.rules.question_start(line) {
+            self.solution_num = Some(num);
+            self.field = Field::Answer;
+            rest = content;
+            // “1. A 【解析】……”这种没有答案标记的写法
+            if !self.rules.answer.is_match(rest) {
+                let letters: String = rest
+                    .chars()
+                    .take_while(|c| c.is_ascii_uppercase() || c.is_whitespace())
+                    .filter(|c| !c.is_whitespace())
+                    .collect();
+                if !letters.is_empty() {
+                    self.set_solution(Field::Answer, &letters);
+                    rest = rest.trim_start_matches(|c: char| {
+                        c.is_ascii_uppercase() || c.is_whitespace() || "。.，,".contains(c)
+                    });
+                    self.field = Field::Analysis;
+                }
+            }
+        }
+        if self.solution_num.is_none() {
+            self.paper
+                .warnings
+                .push(format!("答案解析中无法对应题号的内容：{line}"));
+            return;
+        }
+        self.feed_marked(rest, |parser, field, text| parser.set_solution(field, text));
+    }
+
+    /// 题目中答案、解析标记之外的内容按当前所处的位置追加
+    fn feed_question_line(&mut self, line: &str) {
+        if self.current_question().is_none() {
+            self.paper
+                .warnings
+                .push(format!("无法归属到题目的内容：{line}"));
+            return;
+        }
+        if matches!(self.field, Field::Content | Field::Option) {
+            if let Some(c) = self.rules.option.captures(line) {
+                let options = split_options(&self.rules.option, &c);
+                if let Some(q) = self.current_question_mut() {
+                    q.options.extend(options);
+                }
+                self.field = Field::Option;
+                return;
+            }
+        }
+        self.feed_marked(line, |parser, field, text| {
+            let Some(q) = parser.current_question_mut() else {
+                return;
+            };
+            match field {
+                Field::Content => push_line(&mut q.content, text),
+                Field::Option => match q.options.last_mut() {
+                    Some(option) => push_line(option, text),
+                    None => push_line(&mut q.content, text),
+                },
+                Field::Answer => push_line(q.answer.get_or_insert_default(), text),
+                Field::Analysis => push_line(q.analysis.get_or_insert_default(), text),
+            }
+        });
+    }
+
+    /// 按答案、解析标记把一行切分成几段，分别交给`apply`处理
+    fn feed_marked(&mut self, line: &str, apply: impl Fn(&mut Self, Field, &str)) {
+        let mut marks = vec![];
+        for m in self.rules.answer.find_iter(line) {
+            marks.push((m.start(), m.end(), Field::Answer));
+        }
+        for m in self.rules.analysis.find_iter(line) {
+            marks.push((m.start(), m.end(), Field::Analysis));
+        }
+        marks.sort_by_key(|(start, _, _)| *start);
+        let mut offset = 0;
+        let mut field = self.field;
+        for (start, end, next) in marks {
+            if start < offset {
+                continue;
+            }
+            let text = line[offset..start].trim();
+            if !text.is_empty() {
+                apply(self, field, text);
+            }
+            offset = end;
+            field = next;
+        }
+        let text = line[offset..].trim();
+        if !text.is_empty() {
+            apply(self, field, text);
+        }
+        self.field = field;
+    }
+
+    fn set_solution(&mut self, field: Field, text: &str) {
+        let Some(num) = self.solution_num else {
+            return;
+        };
+        let question = self
+            .paper
+            .chapters
+            .iter_mut()
+            .flat_map(|c| c.questions.iter_mut())
+            .find(|q| q.num == num);
+        let Some(q) = question else {
+            self.paper
+                .warnings
+                .push(format!("第{num}题的答案解析找不到对应的题目"));
+            return;
+        };
+        match field {
+            Field::Answer | Field::Content | Field::Option => {
+                push_line(q.answer.get_or_insert_default(), text)
+            }
+            Field::Analysis => push_line(q.analysis.get_or_insert_default(), text),
+        }
+    }
+
+    fn start_chapter(&mut self, name: &str) {
+        self.paper.chapters.push(ParsedChapter {
+            name: name.to_string(),
+            questions: vec![],
+        });
+    }
+
+    fn start_question(&mut self, num: i32, content: &str, material_nums: Vec<i32>) {
+        if self.paper.chapters.is_empty() {
+            self.section = Section::Chapters;
+            self.start_chapter("");
+        }
+        let chapter = self.paper.chapters.last_mut().unwrap();
+        chapter.questions.push(ParsedQuestion {
+            num,
+            material_nums,
+            ..Default::default()
+        });
+        self.field = Field::Content;
+        self.collecting_material = false;
+        if !content.trim().is_empty() {
+            self.feed_question_line(content.trim());
+        }
+    }
+
+    fn current_question(&self) -> Option<&ParsedQuestion> {
+        self.paper.chapters.last()?.questions.last()
+    }
+
+    fn current_question_mut(&mut self) -> Option<&mut ParsedQuestion> {
+        self.paper.chapters.last_mut()?.questions.last_mut()
+    }
+
+    fn material_refs(&self, line: &str) -> Vec<i32> {
+        self.rules
+            .material_ref
+            .captures_iter(line)
+            .filter_map(|c| parse_number(c.get(1)?.as_str()))
+            .collect()
+    }
+
+    fn finish(mut self) -> ParsedPaper {
+        let mut warnings = vec![];
+        let mut nums = std::collections::HashSet::new();
+        for q in self.paper.chapters.iter().flat_map(|c| &c.questions) {
+            if !nums.insert(q.num) {
+                warnings.push(format!("第{}题的题号重复", q.num));
+            }
+            if q.content.is_empty() {
+                warnings.push(format!("第{}题没有识别到题干", q.num));
+            }
+            if q.answer.is_none() {
+                warnings.push(format!("第{}题没有识别到答案", q.num));
+            }
+            if !q.options.is_empty()
+                && q.answer
+                    .as_deref()
+                    .and_then(|a| q.answer_indexes(a))
+                    .is_none()
+            {
+                warnings.push(format!("第{}题的答案不是选项字母", q.num));
+            }
+        }
+        self.paper.warnings.extend(warnings);
+        self.paper
+    }
+}
+
+/// 一行中可能有多个选项，如“A.甲 B.乙 C.丙 D.丁”
+fn split_options(option: &Regex, first: &regex::Captures) -> Vec<String> {
+    let text = first.get(2).map_or("", |m| m.as_str());
+    let next_letter = first
+        .get(1)
+        .and_then(|m| m.as_str().chars().next())
+        .and_then(|c| char::from_u32(c as u32 + 1));
+    let Some(next_letter) = next_letter else {
+        return vec![text.trim().to_string()];
+    };
+    let marker = format!(
+        r"\s+{}\s*[.．、:：]",
+        regex::escape(&next_letter.to_string())
+    );
+    let Ok(marker) = Regex::new(&marker) else {
+        return vec![text.trim().to_string()];
+    };
+    match marker.find(text) {
+        Some(m) => {
+            let rest = text[m.start()..].trim_start();
+            let mut options = vec![text[..m.start()].trim().to_string()];
+            match option.captures(rest) {
+                Some(c) => options.extend(split_options(option, &c)),
+                None => options.push(rest.to_string()),
+            }
+            options
+        }
+        None => vec![text.trim().to_string()],
+    }
+}
+
+fn push_line(target: &mut String, line: &str) {
+    if !target.is_empty() {
+        target.push('\n');
+    }
+    target.push_str(line);
+}
+
+fn pick_year(title: &str) -> Option<i16> {
+    RE_YEAR
+        .captures(title)
+        .and_then(|c| c.get(1)?.as_str().parse().ok())
+}
+
+/// 解析“3”或“十二”这样的序号
+fn parse_number(s: &str) -> Option<i32> {
+    if let Ok(n) = s.parse() {
+        return Some(n);
+    }
+    let digit = |c: char| {
+        "零一二三四五六七八九"
+            .chars()
+            .position(|d| d == c)
+            .map(|d| d as i32)
+    };
+    let chars: Vec<char> = s.chars().collect();
+    match chars.as_slice() {
+        ['十'] => Some(10),
+        ['十', d] => Some(10 + digit(*d)?),
+        [t, '十'] => Some(digit(*t)? * 10),
+        [t, '十', d] => Some(digit(*t)? * 10 + digit(*d)?),
+        [d] => digit(*d),
+        _ => None,
+    }
+}
+
+/// 纯文本转为题库使用的html，每行一个段落
+pub fn text_to_html(text: &str) -> String {
+    text.lines()
+        .map(|line| format!("<p>{}</p>", escape_html(line)))
+        .collect()
+}
+
+fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+}
+
+impl ParsedPaper {
+    pub fn paper_extra(&self) -> PaperExtra {
+        if self.essay {
+            PaperExtra::EssayCluster(EssayCluster {
+                topic: None,
+                blocks: vec![
+                    PaperBlock {
+                        name: "注意事项".to_string(),
+                        desc: text_to_html(&self.desc),
+                    },
+                    PaperBlock {
+                        name: "给定资料".to_string(),
+                        desc: "".to_string(),
+                    },
+                    PaperBlock {
+                        name: "作答要求".to_string(),
+                        desc: "".to_string(),
+                    },
+                ],
+            })
+        } else {
+            PaperExtra::Chapters(Chapters {
+                desc: (!self.desc.is_empty()).then(|| text_to_html(&self.desc)),
+                chapters: self
+                    .chapters
+                    .iter()
+                    .map(|c| PaperChapter {
+                        name: c.name.clone(),
+                        desc: "".to_string(),
+                        count: c.questions.len() as i16,
+                    })
+                    .collect(),
+            })
+        }
+    }
+}
+
+impl ParsedQuestion {
+    /// 题目的纯文本，用于计算embedding
+    pub fn text(&self) -> String {
+        let mut text = self.content.clone();
+        for option in &self.options {
+            push_line(&mut text, option);
+        }
+        text
+    }
+
+    pub fn question_extra(&self) -> QuestionExtra {
+        let options = self.options.iter().map(|o| text_to_html(o)).collect();
+        match self.answer.as_deref().and_then(|a| self.answer_indexes(a)) {
+            _ if self.options.is_empty() && self.true_false_answer().is_some() => {
+                QuestionExtra::TrueFalse
+            }
+            _ if self.options.is_empty() => QuestionExtra::OpenEndedQA { qa: vec![] },
+            Some(answer) if answer.len() > 1 => QuestionExtra::MultiChoice { options },
+            _ => QuestionExtra::SingleChoice { options },
+        }
+    }
+
+    /// 没有识别到答案时不生成解答
+    pub fn solution_extra(&self) -> Option<SolutionExtra> {
+        let answer = self.answer.as_deref()?;
+        let analysis = self
+            .analysis
+            .as_deref()
+            .map(text_to_html)
+            .unwrap_or_default();
+        Some(match self.question_extra() {
+            QuestionExtra::TrueFalse => SolutionExtra::TrueFalse(TrueFalseChoice {
+                answer: self.true_false_answer()?,
+                analysis,
+            }),
+            QuestionExtra::OpenEndedQA { .. } => SolutionExtra::OpenEndedQA(StepByStepAnswer {
+                solution: Some(text_to_html(answer)),
+                analysis: self
+                    .analysis
+                    .iter()
+                    .map(|a| StepAnalysis {
+                        label: "解析".to_string(),
+                        content: text_to_html(a),
+                    })
+                    .collect(),
+            }),
+            QuestionExtra::MultiChoice { .. } => SolutionExtra::MultiChoice(MultiChoice {
+                answer: self.answer_indexes(answer)?,
+                analysis,
+            }),
+            _ => SolutionExtra::SingleChoice(SingleChoice {
+                answer: *self.answer_indexes(answer)?.first()?,
+                analysis,
+            }),
+        })
+    }
+
+    /// 答案字母转为选项下标，超出选项范围时返回None
+    fn answer_indexes(&self, answer: &str) -> Option<Vec<u8>> {
+        let letters: Vec<u8> = answer
+            .chars()
+            .filter(|c| !c.is_whitespace() && !"，,、。".contains(*c))
+            .map(|c| c.is_ascii_uppercase().then(|| c as u8 - b'A'))
+            .collect::<Option<_>>()?;
+        let valid =
+            !letters.is_empty() && letters.iter().all(|i| (*i as usize) < self.options.len());
+        valid.then_some(letters)
+    }
+
+    fn true_false_answer(&self) -> Option<bool> {
+        match self.answer.as_deref()?.trim() {
+            "正确" | "对" | "√" | "是" => Some(true),
+            "错误" | "错" | "×" | "否" => Some(false),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{parse_number, parse_paper, IngestRules};
+    use crate::model::{question::QuestionExtra, solution::SolutionExtra};
+
+    #[test]
+    fn test_parse_chapters_paper() {
+        let text = r#"
+2024年国家公务员考试《行测》真题
+注意事项：本试卷共两部分
+第一部分 常识判断
+1. 下列说法正确的是：
+A. 甲 B. 乙
+C. 丙 D. 丁
+2. 以下属于可再生能源的有：
+A. 风能
+B. 煤炭
+C. 太阳能
+D. 石油
+第二部分 资料分析
+根据以下资料，回答3~4题。
+2023年某市生产总值为100亿元。
+3. 该市生产总值为：
+A. 100亿元 B. 200亿元
+4. 下列说法不正确的是：
+A. 甲 B. 乙
+参考答案及解析
+1. B 【解析】乙正确。
+2. 【答案】AC
+【解析】风能和太阳能是可再生能源。
+3. A
+4. 【答案】A【解析】甲不正确。
+"#;
+        let paper = parse_paper(text, &IngestRules::default()).unwrap();
+        assert_eq!(paper.year, Some(2024));
+        assert!(!paper.essay);
+        assert_eq!(paper.desc, "注意事项：本试卷共两部分");
+        assert_eq!(paper.chapters.len(), 2);
+        assert_eq!(paper.chapters[0].name, "第一部分 常识判断");
+        let q1 = &paper.chapters[0].questions[0];
+        assert_eq!(q1.content, "下列说法正确的是：");
+        assert_eq!(q1.options, vec!["甲", "乙", "丙", "丁"]);
+        assert_eq!(q1.answer.as_deref(), Some("B"));
+        assert_eq!(q1.analysis.as_deref(), Some("乙正确。"));
+        let q2 = &paper.chapters[0].questions[1];
+        assert!(matches!(
+            q2.question_extra(),
+            QuestionExtra::MultiChoice { .. }
+        ));
+        assert!(matches!(
+            q2.solution_extra(),
+            Some(SolutionExtra::MultiChoice(mc)) if mc.answer == vec![0, 2]
+        ));
+        assert_eq!(paper.materials.len(), 1);
+        assert_eq!(
+            paper.materials[0].content,
+            "根据以下资料，回答3~4题。\n2023年某市生产总值为100亿元。"
+        );
+        let q3 = &paper.chapters[1].questions[0];
+        assert_eq!(q3.material_nums, vec![1]);
+        assert_eq!(q3.options, vec!["100亿元", "200亿元"]);
+        assert_eq!(paper.chapters[1].questions[1].answer.as_deref(), Some("A"));
+        assert_eq!(
+            paper.chapters[1].questions[1].analysis.as_deref(),
+            Some("甲不正确。")
+        );
+        assert_eq!(paper.warnings, Vec::<String>::new());
+    }
+
+    #[test]
+    fn test_parse_essay_paper() {
+        let text = r#"
+2023年广东省公务员考试《申论》题
+一、注意事项
+1. 本题本由给定资料与作答要求两部分构成。
+给定资料
+资料1
+第一段资料。
+资料2
+第二段资料。
+作答要求
+第一题 根据给定资料1，概括主要做法。（15分）
+要求：全面准确，不超过200字。
+第二题 结合给定资料2谈谈你的理解。
+"#;
+        let paper = parse_paper(text, &IngestRules::default()).unwrap();
+        assert!(paper.essay);
+        assert_eq!(paper.materials.len(), 2);
+        assert_eq!(paper.materials[1].content, "第二段资料。");
+        let questions = &paper.chapters[0].questions;
+        assert_eq!(questions.len(), 2);
+        assert_eq!(
+            questions[0].content,
+            "根据给定资料1，概括主要做法。（15分）\n要求：全面准确，不超过200字。"
+        );
+        assert_eq!(questions[0].material_nums, vec![1]);
+        assert_eq!(questions[1].material_nums, vec![2]);
+        assert!(matches!(
+            questions[0].question_extra(),
+            QuestionExtra::OpenEndedQA { .. }
+        ));
+    }
+
+    #[test]
+    fn test_parse_number() {
+        assert_eq!(parse_number("3"), Some(3));
+        assert_eq!(parse_number("十"), Some(10));
+        assert_eq!(parse_number("十二"), Some(12));
+        assert_eq!(parse_number("二十一"), Some(21));
+        assert_eq!(parse_number("甲"), None);
+    }
+
+    #[test]
+    fn test_custom_rules() {
+        let rules = IngestRules {
+            question: r"^\d+\.".to_string(),
+            ..Default::default()
+        };
+        assert!(parse_paper("标题\n1. 题干", &rules).is_err());
+
+        // 捕获组可以匹配空字符串时不能panic
+        let rules = IngestRules {
+            question: r"^(\d*)\s*[.．]\s*(.*)$".to_string(),
+            option: r"^([A-H]?)\s*[.．]\s*(.*)$".to_string(),
+            ..Default::default()
+        };
+        let paper = parse_paper("标题\n. 说明\n1. 题干\n. 甲 B. 乙", &rules).unwrap();
+        let questions = &paper.chapters[0].questions;
+        assert_eq!(questions.len(), 1);
+        assert_eq!(questions[0].options, vec!["甲 B. 乙"]);
+    }
+}
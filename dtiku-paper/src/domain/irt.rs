@@ -0,0 +1,313 @@
+use itertools::Itertools;
+use std::collections::HashMap;
+
+/// 能力值、难度值的取值范围
+pub const MAX_LOGIT: f32 = 4.0;
+/// 不区分知识点的整体能力
+pub const OVERALL_KEYPOINT: i32 = 0;
+/// 自适应练习默认让用户有七成把握答对
+pub const DEFAULT_TARGET_PROBABILITY: f32 = 0.7;
+/// 作答数达到该值的题目才估计区分度(2PL)，否则按1PL固定为1
+pub const MIN_2PL_ANSWERS: usize = 30;
+/// 联合估计题目参数和用户能力的轮数
+const CALIBRATE_ITERATIONS: usize = 10;
+const NEWTON_ITERATIONS: usize = 20;
+const ABILITY_PRIOR_SD: f32 = 1.0;
+/// 难度的先验均值由厂商正确率换算
+const DIFFICULTY_PRIOR_SD: f32 = 1.0;
+/// 区分度的先验均值为1
+const DISCRIMINATION_PRIOR_SD: f32 = 0.5;
+const MIN_DISCRIMINATION: f32 = 0.25;
+const MAX_DISCRIMINATION: f32 = 3.0;
+
+/// 题目的IRT参数
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct IrtParams {
+    pub difficulty: f32,
+    pub discrimination: f32,
+}
+
+impl Default for IrtParams {
+    fn default() -> Self {
+        Self {
+            difficulty: 0.0,
+            discrimination: 1.0,
+        }
+    }
+}
+
+impl IrtParams {
+    /// 按正确率(百分数)换算1PL难度：能力为0的用户答对概率即为该正确率
+    pub fn from_correct_ratio(correct_ratio: Option<f32>) -> Self {
+        Self {
+            difficulty: correct_ratio
+                .map(|ratio| -logit(ratio / 100.0))
+                .unwrap_or_default(),
+            discrimination: 1.0,
+        }
+    }
+
+    /// 能力为ability的用户答对的概率
+    pub fn probability(&self, ability: f32) -> f32 {
+        sigmoid(self.discrimination * (ability - self.difficulty))
+    }
+}
+
+/// 一次作答
+#[derive(Debug, Clone, Copy)]
+pub struct Response {
+    pub user_id: i32,
+    pub question_id: i32,
+    pub correct: bool,
+}
+
+/// 用户在某个知识点上的能力估计
+#[derive(Debug, Clone, PartialEq)]
+pub struct KeypointAbility {
+    pub user_id: i32,
+    pub keypoint_id: i32,
+    pub ability: f32,
+    pub answer_count: usize,
+}
+
+/// 联合估计的结果：题目id -> 题目参数，用户id -> 整体能力
+#[derive(Debug, Default)]
+pub struct Calibration {
+    pub items: HashMap<i32, IrtParams>,
+    pub abilities: HashMap<i32, f32>,
+}
+
+/// 交替估计用户能力和题目参数，priors为厂商正确率换算的题目参数
+pub fn calibrate(priors: &HashMap<i32, IrtParams>, responses: &[Response]) -> Calibration {
+    let mut items = priors.clone();
+    let by_user = responses.iter().into_group_map_by(|r| r.user_id);
+    let by_item = responses.iter().into_group_map_by(|r| r.question_id);
+    let mut abilities = HashMap::new();
+    for _ in 0..CALIBRATE_ITERATIONS {
+        for (user_id, rs) in &by_user {
+            let rs = rs
+                .iter()
+                .map(|r| {
+                    let item = items.get(&r.question_id).copied().unwrap_or_default();
+                    (item, r.correct)
+                })
+                .collect_vec();
+            abilities.insert(*user_id, estimate_ability(&rs, 0.0));
+        }
+        for (question_id, rs) in &by_item {
+            let prior = priors.get(question_id).copied().unwrap_or_default();
+            let rs = rs
+                .iter()
+                .map(|r| (abilities[&r.user_id], r.correct))
+                .collect_vec();
+            items.insert(*question_id, calibrate_item(prior, &rs));
+        }
+    }
+    Calibration { items, abilities }
+}
+
+/// 按知识点估计能力：以整体能力为先验均值，作答少的知识点向整体能力收缩。
+/// keypoints为题目id -> 该题所有层级的知识点id
+pub fn estimate_keypoint_abilities(
+    calibration: &Calibration,
+    responses: &[Response],
+    keypoints: &HashMap<i32, Vec<i32>>,
+) -> Vec<KeypointAbility> {
+    let mut grouped: HashMap<(i32, i32), Vec<(IrtParams, bool)>> = HashMap::new();
+    for r in responses {
+        let item = calibration
+            .items
+            .get(&r.question_id)
+            .copied()
+            .unwrap_or_default();
+        grouped
+            .entry((r.user_id, OVERALL_KEYPOINT))
+            .or_default()
+            .push((item, r.correct));
+        for kp in keypoints.get(&r.question_id).into_iter().flatten() {
+            grouped
+                .entry((r.user_id, *kp))
+                .or_default()
+                .push((item, r.correct));
+        }
+    }
+    grouped
+        .into_iter()
+        .map(|((user_id, keypoint_id), rs)| {
+            let overall = calibration
+                .abilities
+                .get(&user_id)
+                .copied()
+                .unwrap_or_default();
+            KeypointAbility {
+                user_id,
+                keypoint_id,
+                ability: if keypoint_id == OVERALL_KEYPOINT {
+                    overall
+                } else {
+                    estimate_ability(&rs, overall)
+                },
+                answer_count: rs.len(),
+            }
+        })
+        .collect()
+}
+
+/// 最大后验估计能力值
+pub fn estimate_ability(responses: &[(IrtParams, bool)], prior_mean: f32) -> f32 {
+    let prior_var = ABILITY_PRIOR_SD * ABILITY_PRIOR_SD;
+    let mut ability = prior_mean;
+    for _ in 0..NEWTON_ITERATIONS {
+        let mut grad = -(ability - prior_mean) / prior_var;
+        let mut hess = -1.0 / prior_var;
+        for (item, correct) in responses {
+            let p = item.probability(ability);
+            let a = item.discrimination;
+            grad += a * (outcome(*correct) - p);
+            hess -= a * a * p * (1.0 - p);
+        }
+        let step = grad / hess;
+        ability = (ability - step).clamp(-MAX_LOGIT, MAX_LOGIT);
+        if step.abs() < 1e-4 {
+            break;
+        }
+    }
+    ability
+}
+
+/// 最大后验估计题目参数，responses为(作答用户的能力, 是否答对)
+pub fn calibrate_item(prior: IrtParams, responses: &[(f32, bool)]) -> IrtParams {
+    let two_pl = responses.len() >= MIN_2PL_ANSWERS;
+    let b_var = DIFFICULTY_PRIOR_SD * DIFFICULTY_PRIOR_SD;
+    let a_var = DISCRIMINATION_PRIOR_SD * DISCRIMINATION_PRIOR_SD;
+    let mut item = IrtParams {
+        difficulty: prior.difficulty,
+        discrimination: 1.0,
+    };
+    for _ in 0..NEWTON_ITERATIONS {
+        let mut grad = -(item.difficulty - prior.difficulty) / b_var;
+        let mut hess = -1.0 / b_var;
+        for (ability, correct) in responses {
+            let p = item.probability(*ability);
+            let a = item.discrimination;
+            grad -= a * (outcome(*correct) - p);
+            hess -= a * a * p * (1.0 - p);
+        }
+        let b_step = grad / hess;
+        item.difficulty = (item.difficulty - b_step).clamp(-MAX_LOGIT, MAX_LOGIT);
+
+        let mut a_step = 0.0;
+        if two_pl {
+            let mut grad = -(item.discrimination - 1.0) / a_var;
+            let mut hess = -1.0 / a_var;
+            for (ability, correct) in responses {
+                let p = item.probability(*ability);
+                let d = ability - item.difficulty;
+                grad += d * (outcome(*correct) - p);
+                hess -= d * d * p * (1.0 - p);
+            }
+            a_step = grad / hess;
+            item.discrimination =
+                (item.discrimination - a_step).clamp(MIN_DISCRIMINATION, MAX_DISCRIMINATION);
+        }
+        if b_step.abs() < 1e-4 && a_step.abs() < 1e-4 {
+            break;
+        }
+    }
+    item
+}
+
+/// 自适应练习答题后即时修正能力，已作答越多修正幅度越小，下次校准时会重新估计
+pub fn update_ability(ability: f32, answer_count: i32, item: &IrtParams, correct: bool) -> f32 {
+    let p = item.probability(ability);
+    let step = item.discrimination * (outcome(correct) - p) / (1.0 + 0.25 * answer_count as f32);
+    (ability + step).clamp(-MAX_LOGIT, MAX_LOGIT)
+}
+
+/// 知识点路径上的所有知识点id，第一个为整体能力
+pub fn ability_keypoint_ids(keypoint_path: Option<&str>) -> Vec<i32> {
+    let mut ids = vec![OVERALL_KEYPOINT];
+    if let Some(path) = keypoint_path {
+        ids.extend(path.split('.').filter_map(|id| id.parse::<i32>().ok()));
+    }
+    ids
+}
+
+fn outcome(correct: bool) -> f32 {
+    if correct {
+        1.0
+    } else {
+        0.0
+    }
+}
+
+fn sigmoid(x: f32) -> f32 {
+    1.0 / (1.0 + (-x).exp())
+}
+
+fn logit(p: f32) -> f32 {
+    let p = p.clamp(0.01, 0.99);
+    (p / (1.0 - p)).ln()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        ability_keypoint_ids, calibrate, calibrate_item, estimate_ability, IrtParams, Response,
+    };
+    use std::collections::HashMap;
+
+    #[test]
+    fn test_probability_from_correct_ratio() {
+        let easy = IrtParams::from_correct_ratio(Some(80.0));
+        assert!(easy.difficulty < 0.0);
+        assert!((easy.probability(0.0) - 0.8).abs() < 1e-4);
+        assert!((IrtParams::default().probability(0.0) - 0.5).abs() < 1e-6);
+        assert!(easy.probability(1.0) > easy.probability(0.0));
+    }
+
+    #[test]
+    fn test_estimate_ability_follows_answers() {
+        let items = [-1.0, 0.0, 1.0].map(|difficulty| IrtParams {
+            difficulty,
+            discrimination: 1.0,
+        });
+        let strong = items.iter().map(|i| (*i, true)).collect::<Vec<_>>();
+        let weak = items.iter().map(|i| (*i, false)).collect::<Vec<_>>();
+        assert!(estimate_ability(&strong, 0.0) > 0.5);
+        assert!(estimate_ability(&weak, 0.0) < -0.5);
+        assert_eq!(estimate_ability(&[], 0.3), 0.3);
+    }
+
+    #[test]
+    fn test_calibrate_item_moves_from_prior() {
+        let prior = IrtParams::from_correct_ratio(Some(50.0));
+        let mostly_wrong = (0..20).map(|i| (0.0, i % 5 == 0)).collect::<Vec<_>>();
+        let item = calibrate_item(prior, &mostly_wrong);
+        assert!(item.difficulty > prior.difficulty);
+        assert_eq!(item.discrimination, 1.0);
+    }
+
+    #[test]
+    fn test_calibrate_separates_users_and_items() {
+        let priors = HashMap::from([(1, IrtParams::default()), (2, IrtParams::default())]);
+        let mut responses = vec![];
+        for user_id in 0..10 {
+            // 题目1所有人都答对，题目2只有前三个用户答对
+            responses.push(Response {
+                user_id,
+                question_id: 1,
+                correct: true,
+            });
+            responses.push(Response {
+                user_id,
+                question_id: 2,
+                correct: user_id < 3,
+            });
+        }
+        let c = calibrate(&priors, &responses);
+        assert!(c.items[&2].difficulty > c.items[&1].difficulty);
+        assert!(c.abilities[&0] > c.abilities[&9]);
+        assert_eq!(ability_keypoint_ids(Some("3.15")), vec![0, 3, 15]);
+    }
+}
@@ -0,0 +1,172 @@
+use super::question_duplicate::DuplicateQuestion;
+use crate::model::question_keypoint_tag;
+use itertools::Itertools;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+
+/// 参与投票的近邻题目数
+pub const NEIGHBOUR_COUNT: u64 = 15;
+/// 得票占比不低于该值时直接写入，否则进入人工审核
+pub const AUTO_ACCEPT_CONFIDENCE: f64 = 0.75;
+/// 子知识点得票占比超过该值才继续往下细分
+const MIN_CHILD_SUPPORT: f64 = 0.5;
+/// 审核时展示的候选知识点数
+const MAX_CANDIDATES: usize = 5;
+
+/// 已标注的近邻题目
+#[derive(Debug, Clone)]
+pub struct Neighbour {
+    /// embedding余弦距离
+    pub distance: f64,
+    /// 题目每个知识点从根节点开始的id路径
+    pub paths: Vec<Vec<i32>>,
+}
+
+/// 候选知识点的得票
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PathVote {
+    pub path: Vec<i32>,
+    pub share: f64,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct TagDecision {
+    pub path: Vec<i32>,
+    pub confidence: f64,
+    pub candidates: Vec<PathVote>,
+}
+
+impl TagDecision {
+    pub fn key_point_id(&self) -> i32 {
+        *self.path.last().expect("decision path is never empty")
+    }
+
+    pub fn ltree_path(&self) -> String {
+        self.path.iter().join(".")
+    }
+
+    pub fn auto_accept(&self) -> bool {
+        self.confidence >= AUTO_ACCEPT_CONFIDENCE
+    }
+}
+
+/// 审核列表中的一条标注，keypoints为标注和候选路径上的知识点名称
+#[derive(Debug, Clone, Serialize)]
+pub struct KeypointTagReview {
+    #[serde(flatten)]
+    pub tag: question_keypoint_tag::Model,
+    pub question: Option<DuplicateQuestion>,
+    pub keypoints: HashMap<i32, String>,
+}
+
+/// 近邻按相似度加权投票，每个近邻给自己知识点路径上的所有节点投一票；
+/// 从根节点开始逐级选得票最多的子节点，子节点票数不够时停在当前层级，
+/// 最终节点的得票占比作为置信度
+pub fn vote(neighbours: &[Neighbour]) -> Option<TagDecision> {
+    let mut scores: HashMap<Vec<i32>, f64> = HashMap::new();
+    let mut total = 0.0;
+    for n in neighbours {
+        let weight = (1.0 - n.distance).max(0.0);
+        let nodes: HashSet<&[i32]> = n
+            .paths
+            .iter()
+            .flat_map(|p| (1..=p.len()).map(move |i| &p[..i]))
+            .collect();
+        if weight <= 0.0 || nodes.is_empty() {
+            continue;
+        }
+        total += weight;
+        for node in nodes {
+            *scores.entry(node.to_vec()).or_default() += weight;
+        }
+    }
+    if total <= 0.0 {
+        return None;
+    }
+
+    let mut path: Vec<i32> = vec![];
+    let mut score = 0.0;
+    loop {
+        let best = scores
+            .iter()
+            .filter(|(p, _)| p.len() == path.len() + 1 && p.starts_with(&path))
+            .max_by(|(pa, a), (pb, b)| a.total_cmp(b).then_with(|| pb.cmp(pa)));
+        match best {
+            Some((p, s)) if path.is_empty() || s / total > MIN_CHILD_SUPPORT => {
+                path = p.clone();
+                score = *s;
+            }
+            _ => break,
+        }
+    }
+
+    let candidates = scores
+        .into_iter()
+        .sorted_by(|(pa, a), (pb, b)| {
+            b.total_cmp(a)
+                .then_with(|| pb.len().cmp(&pa.len()))
+                .then_with(|| pa.cmp(pb))
+        })
+        .take(MAX_CANDIDATES)
+        .map(|(path, s)| PathVote {
+            path,
+            share: s / total,
+        })
+        .collect();
+    Some(TagDecision {
+        path,
+        confidence: score / total,
+        candidates,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn n(distance: f64, paths: &[&[i32]]) -> Neighbour {
+        Neighbour {
+            distance,
+            paths: paths.iter().map(|p| p.to_vec()).collect(),
+        }
+    }
+
+    #[test]
+    fn descends_while_children_agree() {
+        let d = vote(&[
+            n(0.1, &[&[1, 2, 3]]),
+            n(0.1, &[&[1, 2, 3]]),
+            n(0.2, &[&[1, 2, 4]]),
+        ])
+        .unwrap();
+        assert_eq!(d.path, vec![1, 2, 3]);
+        assert_eq!(d.ltree_path(), "1.2.3");
+        assert_eq!(d.key_point_id(), 3);
+        assert!((d.confidence - 1.8 / 2.6).abs() < 1e-9);
+        assert!(!d.auto_accept());
+    }
+
+    #[test]
+    fn stops_at_parent_when_children_split() {
+        let d = vote(&[n(0.1, &[&[1, 2]]), n(0.1, &[&[1, 3]]), n(0.1, &[&[1, 4]])]).unwrap();
+        assert_eq!(d.path, vec![1]);
+        assert!((d.confidence - 1.0).abs() < 1e-9);
+        assert!(d.auto_accept());
+    }
+
+    #[test]
+    fn counts_shared_ancestors_once() {
+        // 同一题目的两个知识点共享父节点，父节点只计一票
+        let d = vote(&[n(0.0, &[&[1, 2], &[1, 3]]), n(0.0, &[&[5]])]).unwrap();
+        assert_eq!(d.path, vec![1]);
+        assert!((d.confidence - 0.5).abs() < 1e-9);
+        let paths = d.candidates.iter().map(|c| c.path.clone()).collect_vec();
+        assert_eq!(paths, vec![vec![1, 2], vec![1, 3], vec![1], vec![5]]);
+    }
+
+    #[test]
+    fn ignores_untagged_or_distant_neighbours() {
+        assert_eq!(vote(&[]), None);
+        assert_eq!(vote(&[n(0.1, &[]), n(1.2, &[&[1]])]), None);
+    }
+}
@@ -0,0 +1,201 @@
+use crate::model::key_point;
+use std::collections::HashMap;
+
+/// 合并知识点时，源知识点的子节点如何处理
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ChildPlan {
+    /// 目标下已有同名子节点，递归合并(源子节点id, 目标子节点id)
+    Merge(i32, i32),
+    /// 目标下没有同名子节点，直接挂到目标下
+    Move(i32),
+}
+
+/// source合并到target，target_path为target从根节点开始的id路径
+pub fn check_merge(
+    source: &key_point::Model,
+    target: &key_point::Model,
+    target_path: &[i32],
+) -> Result<(), &'static str> {
+    if source.paper_type != target.paper_type {
+        return Err("只能合并同一试卷类型的知识点");
+    }
+    if source.id == target.id {
+        return Err("不能合并到自身");
+    }
+    if target_path.contains(&source.id) {
+        return Err("不能合并到自己的子知识点");
+    }
+    Ok(())
+}
+
+/// node移动到parent下，parent为None时移动到根节点；
+/// siblings为新位置下已有的知识点
+pub fn check_move(
+    node: &key_point::Model,
+    parent: Option<(&key_point::Model, &[i32])>,
+    siblings: &[key_point::Model],
+) -> Result<(), &'static str> {
+    if let Some((parent, parent_path)) = parent {
+        if node.paper_type != parent.paper_type {
+            return Err("只能移动到同一试卷类型的知识点下");
+        }
+        if parent_path.contains(&node.id) {
+            return Err("不能移动到自己的子知识点下");
+        }
+    }
+    if siblings
+        .iter()
+        .any(|s| s.id != node.id && s.name == node.name)
+    {
+        return Err("目标位置已有同名知识点，请使用合并");
+    }
+    Ok(())
+}
+
+/// siblings为同一父节点下的知识点
+pub fn check_rename(
+    node: &key_point::Model,
+    name: &str,
+    siblings: &[key_point::Model],
+) -> Result<(), &'static str> {
+    if name.trim().is_empty() {
+        return Err("知识点名称不能为空");
+    }
+    if name.chars().count() > 64 {
+        return Err("知识点名称不能超过64个字符");
+    }
+    if siblings.iter().any(|s| s.id != node.id && s.name == name) {
+        return Err("同级已有同名知识点，请使用合并");
+    }
+    Ok(())
+}
+
+/// 只能删除叶子知识点，有子知识点时需要先移动或合并
+pub fn check_delete(children: &[key_point::Model]) -> Result<(), &'static str> {
+    if !children.is_empty() {
+        return Err("请先移动或合并子知识点");
+    }
+    Ok(())
+}
+
+/// 按名称配对源和目标的子节点
+pub fn plan_merge(
+    source_children: &[key_point::Model],
+    target_children: &[key_point::Model],
+) -> Vec<ChildPlan> {
+    let targets: HashMap<&str, i32> = target_children
+        .iter()
+        .map(|c| (c.name.as_str(), c.id))
+        .collect();
+    source_children
+        .iter()
+        .map(|c| match targets.get(c.name.as_str()) {
+            Some(tid) => ChildPlan::Merge(c.id, *tid),
+            None => ChildPlan::Move(c.id),
+        })
+        .collect()
+}
+
+/// 多个知识点的公共祖先路径，按id逐段比较
+pub fn common_path(paths: &[Vec<i32>]) -> Vec<i32> {
+    let Some((first, rest)) = paths.split_first() else {
+        return vec![];
+    };
+    let len = rest.iter().fold(first.len(), |len, path| {
+        first
+            .iter()
+            .zip(path)
+            .take(len)
+            .take_while(|(a, b)| a == b)
+            .count()
+    });
+    first[..len].to_vec()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn kp(id: i32, pid: i32, name: &str, paper_type: i16) -> key_point::Model {
+        key_point::Model {
+            id,
+            name: name.to_string(),
+            pid,
+            exam_id: 1,
+            paper_type,
+        }
+    }
+
+    #[test]
+    fn rejects_invalid_merge() {
+        let source = kp(1, 0, "言语理解", 1);
+        assert_eq!(
+            check_merge(&source, &kp(2, 0, "言语理解", 2), &[2]),
+            Err("只能合并同一试卷类型的知识点")
+        );
+        assert_eq!(check_merge(&source, &source, &[1]), Err("不能合并到自身"));
+        assert_eq!(
+            check_merge(&source, &kp(3, 1, "逻辑填空", 1), &[1, 3]),
+            Err("不能合并到自己的子知识点")
+        );
+        assert_eq!(
+            check_merge(&source, &kp(4, 0, "言语理解与表达", 1), &[4]),
+            Ok(())
+        );
+    }
+
+    #[test]
+    fn rejects_invalid_move() {
+        let node = kp(3, 1, "逻辑填空", 1);
+        let parent = kp(5, 4, "言语理解与表达", 1);
+        assert_eq!(
+            check_move(&node, Some((&parent, &[4, 5])), &[kp(6, 5, "逻辑填空", 1)]),
+            Err("目标位置已有同名知识点，请使用合并")
+        );
+        assert_eq!(
+            check_move(&kp(4, 0, "言语", 1), Some((&parent, &[4, 5])), &[]),
+            Err("不能移动到自己的子知识点下")
+        );
+        assert_eq!(check_move(&node, Some((&parent, &[4, 5])), &[]), Ok(()));
+        assert_eq!(check_move(&node, None, &[kp(1, 0, "言语理解", 1)]), Ok(()));
+    }
+
+    #[test]
+    fn rejects_invalid_rename_and_delete() {
+        let node = kp(3, 1, "逻辑填空", 1);
+        let siblings = [node.clone(), kp(4, 1, "片段阅读", 1)];
+        assert_eq!(
+            check_rename(&node, " ", &siblings),
+            Err("知识点名称不能为空")
+        );
+        assert_eq!(
+            check_rename(&node, "片段阅读", &siblings),
+            Err("同级已有同名知识点，请使用合并")
+        );
+        assert_eq!(check_rename(&node, "逻辑填空", &siblings), Ok(()));
+        assert_eq!(check_rename(&node, "选词填空", &siblings), Ok(()));
+        assert_eq!(check_delete(&siblings), Err("请先移动或合并子知识点"));
+        assert_eq!(check_delete(&[]), Ok(()));
+    }
+
+    #[test]
+    fn pairs_children_by_name() {
+        let plan = plan_merge(
+            &[kp(11, 1, "逻辑填空", 1), kp(12, 1, "片段阅读", 1)],
+            &[kp(21, 2, "逻辑填空", 1), kp(22, 2, "语句表达", 1)],
+        );
+        assert_eq!(plan, vec![ChildPlan::Merge(11, 21), ChildPlan::Move(12)]);
+    }
+
+    #[test]
+    fn common_path_compares_whole_ids() {
+        assert_eq!(common_path(&[vec![1, 12], vec![1, 13]]), vec![1]);
+        assert_eq!(common_path(&[vec![12, 3], vec![1, 2]]), Vec::<i32>::new());
+        assert_eq!(
+            common_path(&[vec![1, 12, 30], vec![1, 12, 31], vec![1, 12]]),
+            vec![1, 12]
+        );
+        assert_eq!(common_path(&[vec![5, 6]]), vec![5, 6]);
+        assert_eq!(common_path(&[]), Vec::<i32>::new());
+    }
+}
@@ -0,0 +1,150 @@
+use crate::model::material_duplicate;
+use itertools::Itertools;
+use regex::Regex;
+use scraper::{Html, Selector};
+use serde::Serialize;
+use std::sync::LazyLock;
+
+static RE_NUMBER: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"\d+(?:\.\d+)?").unwrap());
+
+/// 两份材料的纯文本都超过该长度才做levenshtein对比
+const LEVENSHTEIN_MIN_TEXT_LENGTH: usize = 100;
+
+/// 材料html的纯文本
+pub fn material_text(content: &str) -> String {
+    Html::parse_fragment(content).root_element().text().join("")
+}
+
+/// html完全相同，或者纯文本足够长且相似度超过95%的材料视为同一份材料
+pub fn is_same_material(content: &str, text: &str, other_content: &str, other_text: &str) -> bool {
+    if content == other_content {
+        // 完全相同，包括图片等html内容
+        return true;
+    }
+    let text_length = text.chars().count();
+    let other_text_length = other_text.chars().count();
+    if text_length > LEVENSHTEIN_MIN_TEXT_LENGTH && other_text_length > LEVENSHTEIN_MIN_TEXT_LENGTH
+    {
+        let edit_distance = textdistance::str::levenshtein(other_text, text);
+        // 95%相似度: 100个字只有5个字不同
+        return edit_distance * 20 < text_length.max(other_text_length);
+    }
+    false
+}
+
+/// 文本相似的材料中数字或图片不同时不能合并，
+/// 如不同年份的资料分析材料，返回不能合并的原因
+pub fn merge_conflict(
+    content: &str,
+    text: &str,
+    other_content: &str,
+    other_text: &str,
+) -> Option<&'static str> {
+    let numbers = RE_NUMBER.find_iter(text).map(|m| m.as_str());
+    if !numbers.eq(RE_NUMBER.find_iter(other_text).map(|m| m.as_str())) {
+        return Some("材料中的数字不一致");
+    }
+    if image_srcs(content) != image_srcs(other_content) {
+        return Some("材料中的图片不一致");
+    }
+    None
+}
+
+fn image_srcs(content: &str) -> Vec<String> {
+    let selector = Selector::parse("img").unwrap();
+    Html::parse_fragment(content)
+        .select(&selector)
+        .filter_map(|img| img.value().attr("src").map(str::to_string))
+        .collect()
+}
+
+/// 核对队列中的一对相似材料，材料已被删除时为None
+#[derive(Debug, Clone, Serialize)]
+pub struct MaterialDuplicateReview {
+    #[serde(flatten)]
+    pub duplicate: material_duplicate::Model,
+    pub material: Option<String>,
+    pub candidate: Option<String>,
+}
+
+/// 被多份试卷共用的材料
+#[derive(Debug, Clone, Serialize)]
+pub struct SharedMaterial {
+    pub material_id: i32,
+    pub paper_count: i64,
+    pub content: String,
+    pub papers: Vec<SharedMaterialPaper>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct SharedMaterialPaper {
+    pub id: i32,
+    pub title: String,
+    pub year: i16,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{is_same_material, material_text, merge_conflict};
+
+    const LONG: &str = "近年来，随着数字技术的快速发展，越来越多的老年人开始尝试使用智能手机进行网上购物、移动支付和视频通话。但与此同时，也有不少老年人因为不熟悉操作流程而在就医挂号、出行购票等场景中遇到困难。有专家指出，推动适老化改造不仅需要企业优化产品设计，更需要社会各方共同参与。";
+
+    #[test]
+    fn test_is_same_material() {
+        let content = format!("<p>{LONG}</p>");
+        let text = material_text(&content);
+        assert_eq!(text, LONG);
+        assert!(is_same_material(&content, &text, &content, &text));
+
+        // 排版不同、个别字不同
+        let other_content = format!("<div><span>{}</span></div>", LONG.replace("因为", "由于"));
+        let other_text = material_text(&other_content);
+        assert!(is_same_material(
+            &content,
+            &text,
+            &other_content,
+            &other_text
+        ));
+
+        // 文本太短不做相似度对比
+        let short = "<p>材料一</p>";
+        let other_short = "<p>材料二</p>";
+        assert!(!is_same_material(
+            short,
+            &material_text(short),
+            other_short,
+            &material_text(other_short)
+        ));
+
+        let other_content = format!("<p>{}</p>", LONG.chars().take(80).collect::<String>());
+        let other_text = material_text(&other_content);
+        assert!(!is_same_material(
+            &content,
+            &text,
+            &other_content,
+            &other_text
+        ));
+    }
+
+    #[test]
+    fn test_merge_conflict() {
+        let content = format!("<p>{LONG}2023年增长5.2%</p><img src=\"a.png\">");
+        let text = material_text(&content);
+        let same = content.replace("因为", "由于");
+        assert_eq!(
+            merge_conflict(&content, &text, &same, &material_text(&same)),
+            None
+        );
+
+        let other = content.replace("5.2%", "5.3%");
+        assert_eq!(
+            merge_conflict(&content, &text, &other, &material_text(&other)),
+            Some("材料中的数字不一致")
+        );
+        let other = content.replace("a.png", "b.png");
+        assert_eq!(
+            merge_conflict(&content, &text, &other, &material_text(&other)),
+            Some("材料中的图片不一致")
+        );
+    }
+}
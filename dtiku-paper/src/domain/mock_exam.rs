@@ -0,0 +1,220 @@
+use crate::model::paper::{Chapters, PaperChapter};
+use std::collections::HashMap;
+
+/// 真题没有正确率时按该正确率抽题
+pub const DEFAULT_CORRECT_RATIO: f32 = 50.0;
+
+/// 真题中一道题的知识点和难度
+#[derive(Debug, Clone)]
+pub struct ReferenceQuestion {
+    pub num: usize,
+    pub keypoint_path: Option<String>,
+    pub correct_ratio: Option<f32>,
+    pub has_material: bool,
+}
+
+/// 用于分析题型分布的近年真题
+#[derive(Debug, Clone)]
+pub struct ReferencePaper {
+    pub chapters: Chapters,
+    pub questions: Vec<ReferenceQuestion>,
+}
+
+/// 模拟卷中一个章节的组卷要求
+#[derive(Debug, Clone)]
+pub struct ChapterPlan {
+    pub chapter: PaperChapter,
+    /// 资料分析等材料题章节按整组材料抽题
+    pub material: bool,
+    pub quotas: Vec<KeypointQuota>,
+}
+
+/// 某个知识点要抽取的题数和目标难度
+#[derive(Debug, Clone, PartialEq)]
+pub struct KeypointQuota {
+    pub keypoint_path: Option<String>,
+    pub count: usize,
+    pub correct_ratio: f32,
+}
+
+#[derive(Default)]
+struct KeypointStats {
+    count: usize,
+    ratio_sum: f32,
+    ratio_count: usize,
+}
+
+/// 以最近一张真题的章节结构为准，合并各真题同名章节的知识点分布
+pub fn plan_chapters(papers: &[ReferencePaper]) -> Vec<ChapterPlan> {
+    let Some(latest) = papers.first() else {
+        return vec![];
+    };
+    let mut chapter_questions: HashMap<String, Vec<&ReferenceQuestion>> = HashMap::new();
+    for p in papers {
+        let ranges = p.chapters.compute_paper_chapter_range();
+        for q in &p.questions {
+            if let Some((_, c)) = ranges.iter().find(|(range, _)| range.contains(&q.num)) {
+                chapter_questions.entry(c.name.clone()).or_default().push(q);
+            }
+        }
+    }
+
+    latest
+        .chapters
+        .chapters
+        .iter()
+        .map(|c| {
+            let questions = chapter_questions.remove(&c.name).unwrap_or_default();
+            plan_chapter(c, &questions)
+        })
+        .collect()
+}
+
+fn plan_chapter(chapter: &PaperChapter, questions: &[&ReferenceQuestion]) -> ChapterPlan {
+    let count = chapter.count.max(0) as usize;
+    let material = questions.iter().filter(|q| q.has_material).count() * 2 > questions.len();
+
+    let mut stats: Vec<(Option<String>, KeypointStats)> = vec![];
+    for q in questions {
+        let index = match stats.iter().position(|(kp, _)| *kp == q.keypoint_path) {
+            Some(index) => index,
+            None => {
+                stats.push((q.keypoint_path.clone(), KeypointStats::default()));
+                stats.len() - 1
+            }
+        };
+        let s = &mut stats[index].1;
+        s.count += 1;
+        if let Some(ratio) = q.correct_ratio {
+            s.ratio_sum += ratio;
+            s.ratio_count += 1;
+        }
+    }
+    let ratio_sum: f32 = stats.iter().map(|(_, s)| s.ratio_sum).sum();
+    let ratio_count: usize = stats.iter().map(|(_, s)| s.ratio_count).sum();
+    let chapter_ratio = if ratio_count == 0 {
+        DEFAULT_CORRECT_RATIO
+    } else {
+        ratio_sum / ratio_count as f32
+    };
+
+    let quotas = if stats.is_empty() {
+        vec![KeypointQuota {
+            keypoint_path: None,
+            count,
+            correct_ratio: chapter_ratio,
+        }]
+    } else {
+        let weights = stats.iter().map(|(_, s)| s.count).collect::<Vec<_>>();
+        stats
+            .into_iter()
+            .zip(allocate(&weights, count))
+            .filter(|(_, n)| *n > 0)
+            .map(|((keypoint_path, s), n)| KeypointQuota {
+                keypoint_path,
+                count: n,
+                correct_ratio: if s.ratio_count == 0 {
+                    chapter_ratio
+                } else {
+                    s.ratio_sum / s.ratio_count as f32
+                },
+            })
+            .collect()
+    };
+    ChapterPlan {
+        chapter: chapter.clone(),
+        material,
+        quotas,
+    }
+}
+
+/// 按权重把total道题分配给各项，使用最大余数法保证总数不变
+pub fn allocate(weights: &[usize], total: usize) -> Vec<usize> {
+    let sum: usize = weights.iter().sum();
+    if sum == 0 {
+        return vec![0; weights.len()];
+    }
+    let mut result: Vec<usize> = weights.iter().map(|w| w * total / sum).collect();
+    let mut remainders: Vec<(usize, usize)> = weights
+        .iter()
+        .enumerate()
+        .map(|(i, w)| (i, w * total % sum))
+        .collect();
+    remainders.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(&b.0)));
+    let assigned: usize = result.iter().sum();
+    for (i, _) in remainders.into_iter().take(total - assigned) {
+        result[i] += 1;
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{allocate, plan_chapters, ReferencePaper, ReferenceQuestion};
+    use crate::model::paper::{Chapters, PaperChapter};
+
+    fn chapter(name: &str, count: i16) -> PaperChapter {
+        PaperChapter {
+            name: name.to_string(),
+            desc: String::new(),
+            count,
+        }
+    }
+
+    fn question(num: usize, kp: &str, ratio: f32, has_material: bool) -> ReferenceQuestion {
+        ReferenceQuestion {
+            num,
+            keypoint_path: Some(kp.to_string()),
+            correct_ratio: Some(ratio),
+            has_material,
+        }
+    }
+
+    #[test]
+    fn test_allocate_keeps_total() {
+        assert_eq!(allocate(&[1, 1, 1], 10), vec![4, 3, 3]);
+        assert_eq!(allocate(&[3, 1], 8), vec![6, 2]);
+        assert_eq!(allocate(&[0, 0], 5), vec![0, 0]);
+        assert_eq!(allocate(&[2, 5, 3], 7).iter().sum::<usize>(), 7);
+    }
+
+    #[test]
+    fn test_plan_chapters_merges_by_chapter_name() {
+        let latest = ReferencePaper {
+            chapters: Chapters {
+                desc: None,
+                chapters: vec![chapter("常识判断", 2), chapter("资料分析", 2)],
+            },
+            questions: vec![
+                question(1, "1.2", 40.0, false),
+                question(2, "1.3", 60.0, false),
+                question(3, "5", 70.0, true),
+                question(4, "5", 50.0, true),
+            ],
+        };
+        let older = ReferencePaper {
+            chapters: Chapters {
+                desc: None,
+                chapters: vec![chapter("常识判断", 2)],
+            },
+            questions: vec![
+                question(1, "1.2", 20.0, false),
+                question(2, "1.2", 30.0, false),
+            ],
+        };
+        let plans = plan_chapters(&[latest, older]);
+        assert_eq!(plans.len(), 2);
+
+        let common = &plans[0];
+        assert!(!common.material);
+        assert_eq!(common.quotas.len(), 1);
+        assert_eq!(common.quotas[0].keypoint_path.as_deref(), Some("1.2"));
+        assert_eq!(common.quotas[0].count, 2);
+        assert!((common.quotas[0].correct_ratio - 30.0).abs() < 1e-4);
+
+        let data = &plans[1];
+        assert!(data.material);
+        assert_eq!(data.quotas[0].count, 2);
+        assert!((data.quotas[0].correct_ratio - 60.0).abs() < 1e-4);
+    }
+}
@@ -1,5 +1,23 @@
+pub mod dedup;
+pub mod essay;
 pub mod exam_category;
+pub mod exercise;
+pub mod export;
+pub mod ingest;
+pub mod irt;
 pub mod keypoint;
+pub mod keypoint_tag;
+pub mod keypoint_taxonomy;
 pub mod label;
+pub mod material;
+pub mod mock_exam;
 pub mod paper;
 pub mod question;
+pub mod question_duplicate;
+pub mod question_feedback;
+pub mod revision;
+pub mod scoring;
+pub mod search;
+pub mod shenlun_category;
+pub mod user_note;
+pub mod wrong_question;
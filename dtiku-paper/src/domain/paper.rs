@@ -1,5 +1,8 @@
 use crate::{
-    domain::question::FullQuestion,
+    domain::{
+        question::FullQuestion,
+        scoring::{is_full_credit, ScoringScheme},
+    },
     model::paper::{self, PaperChapter, PaperExtra},
 };
 use serde::Deserialize;
@@ -17,6 +20,8 @@ pub enum PaperMode {
     Exercise,
     #[strum(message = "展示答案")]
     ShowAnswer,
+    #[strum(message = "限时模考")]
+    Timed,
 }
 
 impl PaperMode {
@@ -40,6 +45,8 @@ pub struct ChapterReport {
     pub correct: u64,
     pub error: u64,
     pub time: u64,
+    /// 按计分方案累计的得分，每题满分为1
+    pub score: f32,
 }
 
 impl ChapterReport {
@@ -53,6 +60,10 @@ impl ChapterReport {
             )
         }
     }
+
+    pub fn score_text(&self) -> String {
+        format!("{:.1}", self.score)
+    }
 }
 
 impl FullPaper {
@@ -75,16 +86,16 @@ impl FullPaper {
     }
 }
 
+/// 按章节统计行测练习的对错和耗时，申论试卷没有章节，返回None
 pub fn compute_report(
     paper: &paper::Model,
     questions: &Vec<FullQuestion>,
     user_answers: &HashMap<i32, String>,
     id_time_map: &HashMap<i32, u64>,
-) -> Vec<ChapterReport> {
-    let chapters = if let PaperExtra::Chapters(chapters) = &paper.extra {
-        chapters
-    } else {
-        panic!("申论暂时不支持批改")
+    scheme: &ScoringScheme,
+) -> Option<Vec<ChapterReport>> {
+    let PaperExtra::Chapters(chapters) = &paper.extra else {
+        return None;
     };
     // compute_paper_chapter_range 返回 Vec<(start..=end, chapter)>
     let number_range = chapters.compute_paper_chapter_range();
@@ -92,37 +103,45 @@ pub fn compute_report(
     let mut correct: HashMap<PaperChapter, u64> = HashMap::new();
     let mut error: HashMap<PaperChapter, u64> = HashMap::new();
     let mut time: HashMap<PaperChapter, u64> = HashMap::new();
+    let mut score: HashMap<PaperChapter, f32> = HashMap::new();
 
     let mut total_correct = 0;
     let mut total_error = 0;
     let mut total_time = 0;
+    let mut total_score = 0.0;
 
     for q in questions {
-        let answer_in_db = q.get_raw_answer();
         let user_answer = user_answers.get(&q.id).map(|s| s.as_str());
 
+        // 题号不在任何章节内时只计入合计
         let chapter = number_range
             .iter()
             .find(|(range, _)| range.contains(&q.num))
-            .map(|(_, ch)| ch.clone())
-            .expect("Question number not in any chapter");
+            .map(|(_, ch)| ch.clone());
 
-        if let (Some(db), Some(user)) = (answer_in_db, user_answer) {
-            if db.eq_ignore_ascii_case(user) {
+        let t = *id_time_map.get(&q.id).unwrap_or(&0);
+        total_time += t;
+        let s = user_answer
+            .map(|user| q.score_answer(scheme, user))
+            .unwrap_or_default();
+        total_score += s;
+        if let Some(chapter) = &chapter {
+            *score.entry(chapter.clone()).or_default() += s;
+        }
+        if is_full_credit(s) {
+            total_correct += 1;
+            if let Some(chapter) = &chapter {
                 *correct.entry(chapter.clone()).or_default() += 1;
-                total_correct += 1;
-            } else {
-                *error.entry(chapter.clone()).or_default() += 1;
-                total_error += 1;
             }
         } else {
-            *error.entry(chapter.clone()).or_default() += 1;
             total_error += 1;
+            if let Some(chapter) = &chapter {
+                *error.entry(chapter.clone()).or_default() += 1;
+            }
+        }
+        if let Some(chapter) = chapter {
+            *time.entry(chapter).or_default() += t;
         }
-
-        let t = *id_time_map.get(&q.id).unwrap_or(&0);
-        *time.entry(chapter.clone()).or_default() += t;
-        total_time += t;
     }
 
     let mut stats: Vec<ChapterReport> = chapters
@@ -133,6 +152,7 @@ pub fn compute_report(
             correct: *correct.get(c).unwrap_or(&0),
             error: *error.get(c).unwrap_or(&0),
             time: *time.get(c).unwrap_or(&0),
+            score: *score.get(c).unwrap_or(&0.0),
         })
         .collect();
 
@@ -145,7 +165,8 @@ pub fn compute_report(
         correct: total_correct,
         error: total_error,
         time: total_time,
+        score: total_score,
     });
 
-    stats
+    Some(stats)
 }
@@ -1,32 +1,48 @@
-use crate::model::{
-    self,
-    question::{Column, QuestionExtra},
+use crate::{
+    domain::scoring::{self, ScoringScheme},
+    model::{
+        self,
+        question::{QuestionExtra, QuestionWithPaper},
+    },
 };
-use sea_orm::{sea_query::IntoCondition, ColumnTrait};
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 
-#[derive(Debug, Clone, Deserialize)]
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
 pub struct QuestionSearch {
     #[serde(default)]
     pub content: String,
+    #[serde(skip_serializing)]
     pub exam_id: Option<i16>,
-    #[serde(rename = "type")]
+    #[serde(rename = "type", skip_serializing_if = "Option::is_none")]
     pub paper_type: Option<i16>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub year: Option<i16>,
+    #[serde(rename = "lid", skip_serializing_if = "Option::is_none")]
+    pub label_id: Option<i32>,
+    /// 题型，即QuestionExtra的类型标识，如sc、mc
+    #[serde(rename = "qt", skip_serializing_if = "Option::is_none")]
+    pub question_type: Option<String>,
+    /// 混合检索：关键词检索和向量检索的结果融合排序
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub hybrid: bool,
 }
 
-impl IntoCondition for QuestionSearch {
-    fn into_condition(self) -> sea_orm::Condition {
-        let q = Column::ExamId
-            .eq(self.exam_id)
-            .and(Column::Content.contains(self.content));
-        if let Some(paper_type) = self.paper_type {
-            q.and(Column::PaperType.eq(paper_type)).into_condition()
-        } else {
-            q.into_condition()
-        }
+impl QuestionSearch {
+    pub fn is_question_type(&self, question_type: &str) -> bool {
+        self.question_type.as_deref() == Some(question_type)
+    }
+
+    pub fn is_year(&self, year: &i16) -> bool {
+        self.year.as_ref() == Some(year)
     }
 }
 
+/// 一条搜索结果，snippet为高亮命中词的摘要html
+pub struct QuestionSearchHit {
+    pub question: QuestionWithPaper,
+    pub snippet: String,
+}
+
 pub struct FullQuestion {
     pub id: i32,
     pub content: String,
@@ -66,6 +82,16 @@ impl FullQuestion {
         }
     }
 
+    /// 按标准计分方案判断作答是否正确
+    pub fn check_answer(&self, user_answer: &str) -> bool {
+        scoring::check_answer(&self.extra, self.solutions.as_deref(), user_answer)
+    }
+
+    /// 按计分方案给出用户作答的得分率
+    pub fn score_answer(&self, scheme: &ScoringScheme, user_answer: &str) -> f32 {
+        scheme.score_solutions(&self.extra, self.solutions.as_deref(), user_answer)
+    }
+
     pub fn get_answer(&self) -> Option<String> {
         match &self.solutions {
             None => None,
@@ -83,3 +109,85 @@ impl FullQuestion {
         }
     }
 }
+
+/// 用户作答数达到该值时，用户正确率与厂商正确率各占一半权重
+const USER_RATIO_CONFIDENCE: f32 = 50.0;
+/// 没有厂商正确率时，至少需要这么多次作答才采用用户正确率
+const MIN_USER_ANSWERS: i64 = 10;
+
+/// 题目正确率，均为百分制
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct QuestionCorrectRatio {
+    /// 题库厂商公布的正确率
+    pub vendor: Option<f32>,
+    /// 本站用户的正确率
+    pub user: Option<f32>,
+    pub user_answer_count: i32,
+    /// 按用户作答数加权后的正确率，难度筛选使用该值
+    pub blended: Option<f32>,
+}
+
+impl QuestionCorrectRatio {
+    /// 用户作答越多，用户正确率的权重越大：w = n / (n + K)
+    pub fn new(vendor: Option<f32>, total: i64, correct: i64) -> Self {
+        let user = (total > 0).then(|| correct as f32 * 100.0 / total as f32);
+        let blended = match (vendor, user) {
+            (Some(v), Some(u)) => {
+                let w = total as f32 / (total as f32 + USER_RATIO_CONFIDENCE);
+                Some(v * (1.0 - w) + u * w)
+            }
+            (Some(v), None) => Some(v),
+            (None, Some(u)) if total >= MIN_USER_ANSWERS => Some(u),
+            (None, _) => None,
+        };
+        Self {
+            vendor,
+            user,
+            user_answer_count: total.min(i32::MAX as i64) as i32,
+            blended,
+        }
+    }
+}
+
+impl From<&model::paper_question::Model> for QuestionCorrectRatio {
+    fn from(m: &model::paper_question::Model) -> Self {
+        Self {
+            vendor: m.correct_ratio,
+            user: m.user_correct_ratio,
+            user_answer_count: m.user_answer_count.unwrap_or_default(),
+            blended: m.blended_correct_ratio,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::QuestionCorrectRatio;
+
+    #[test]
+    fn test_blend_without_user_answers() {
+        let r = QuestionCorrectRatio::new(Some(62.0), 0, 0);
+        assert_eq!(r.user, None);
+        assert_eq!(r.blended, Some(62.0));
+    }
+
+    #[test]
+    fn test_blend_weight_grows_with_answers() {
+        let r = QuestionCorrectRatio::new(Some(80.0), 50, 10);
+        assert!((r.user.unwrap() - 20.0).abs() < 1e-4);
+        assert!((r.blended.unwrap() - 50.0).abs() < 1e-4);
+
+        let r = QuestionCorrectRatio::new(Some(80.0), 450, 90);
+        assert!((r.blended.unwrap() - 26.0).abs() < 1e-4);
+    }
+
+    #[test]
+    fn test_blend_without_vendor() {
+        let r = QuestionCorrectRatio::new(None, 5, 5);
+        assert_eq!(r.user, Some(100.0));
+        assert_eq!(r.blended, None);
+
+        let r = QuestionCorrectRatio::new(None, 10, 3);
+        assert!((r.blended.unwrap() - 30.0).abs() < 1e-4);
+    }
+}
@@ -0,0 +1,29 @@
+use crate::model::{question, question_duplicate};
+use serde::Serialize;
+
+/// 人工核对时对比的题目内容
+#[derive(Debug, Clone, Serialize)]
+pub struct DuplicateQuestion {
+    pub id: i32,
+    pub content: String,
+    pub extra: question::QuestionExtra,
+}
+
+impl From<question::Model> for DuplicateQuestion {
+    fn from(q: question::Model) -> Self {
+        Self {
+            id: q.id,
+            content: q.content,
+            extra: q.extra,
+        }
+    }
+}
+
+/// 核对队列中的一对相似题目，题目已被删除时为None
+#[derive(Debug, Clone, Serialize)]
+pub struct DuplicateReview {
+    #[serde(flatten)]
+    pub duplicate: question_duplicate::Model,
+    pub question: Option<DuplicateQuestion>,
+    pub candidate: Option<DuplicateQuestion>,
+}
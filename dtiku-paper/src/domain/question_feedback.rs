@@ -0,0 +1,76 @@
+use crate::{
+    domain::question_duplicate::DuplicateQuestion,
+    model::{question::QuestionExtra, question_feedback, solution, FeedbackCategory},
+};
+use serde::{Deserialize, Serialize};
+
+/// 纠错描述的最大长度
+pub const MAX_FEEDBACK_LENGTH: usize = 1000;
+
+/// 用户在题目详情页或移动端提交的纠错
+#[derive(Debug, Clone, Deserialize)]
+pub struct FeedbackSubmit {
+    pub question_id: i32,
+    pub category: FeedbackCategory,
+    #[serde(default)]
+    pub content: String,
+    pub suggested_answer: Option<String>,
+}
+
+impl FeedbackSubmit {
+    /// 校验失败时返回给用户的提示
+    pub fn validate(&self) -> Result<(), &'static str> {
+        let content = self.content.trim();
+        let suggested_answer = self.suggested_answer.as_deref().map(str::trim);
+        if content.is_empty() && suggested_answer.is_none_or(str::is_empty) {
+            return Err("请填写纠错描述或正确答案");
+        }
+        if content.chars().count() > MAX_FEEDBACK_LENGTH
+            || suggested_answer.is_some_and(|a| a.chars().count() > MAX_FEEDBACK_LENGTH)
+        {
+            return Err("纠错内容过长");
+        }
+        Ok(())
+    }
+}
+
+/// 采纳纠错时对题目和解答的修改，未填写的字段保持不变
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct FeedbackFix {
+    pub question_content: Option<String>,
+    pub question_extra: Option<QuestionExtra>,
+    pub solution_id: Option<i32>,
+    pub solution_extra: Option<solution::SolutionExtra>,
+}
+
+/// 后台处理纠错时展示的题目和现有解答，题目已被删除时为None
+#[derive(Debug, Clone, Serialize)]
+pub struct FeedbackReview {
+    #[serde(flatten)]
+    pub feedback: question_feedback::Model,
+    pub question: Option<DuplicateQuestion>,
+    pub solutions: Vec<solution::Model>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::FeedbackSubmit;
+    use crate::model::FeedbackCategory;
+
+    fn submit(content: &str, suggested_answer: Option<&str>) -> FeedbackSubmit {
+        FeedbackSubmit {
+            question_id: 1,
+            category: FeedbackCategory::WrongAnswer,
+            content: content.to_string(),
+            suggested_answer: suggested_answer.map(str::to_string),
+        }
+    }
+
+    #[test]
+    fn test_validate() {
+        assert!(submit("答案应该是B", None).validate().is_ok());
+        assert!(submit("", Some("B")).validate().is_ok());
+        assert!(submit("  ", Some(" ")).validate().is_err());
+        assert!(submit(&"错".repeat(1001), None).validate().is_err());
+    }
+}
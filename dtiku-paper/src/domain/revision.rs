@@ -0,0 +1,74 @@
+use serde_json::Value;
+use std::collections::BTreeSet;
+
+/// 不参与对比的字段：向量由内容生成，单独对比没有意义
+const IGNORED_FIELDS: [&str; 1] = ["embedding"];
+
+/// 逐字段对比两个JSON快照，每个变化的字段输出一行“路径: 旧值 -> 新值”
+pub fn json_diff(old: &Value, new: &Value) -> Vec<String> {
+    let mut lines = vec![];
+    diff_value("", old, new, &mut lines);
+    lines
+}
+
+fn diff_value(path: &str, old: &Value, new: &Value, lines: &mut Vec<String>) {
+    match (old, new) {
+        (Value::Object(o), Value::Object(n)) => {
+            let keys: BTreeSet<_> = o.keys().chain(n.keys()).collect();
+            for key in keys {
+                if path.is_empty() && IGNORED_FIELDS.contains(&key.as_str()) {
+                    continue;
+                }
+                let child = if path.is_empty() {
+                    key.clone()
+                } else {
+                    format!("{path}.{key}")
+                };
+                diff_value(
+                    &child,
+                    o.get(key).unwrap_or(&Value::Null),
+                    n.get(key).unwrap_or(&Value::Null),
+                    lines,
+                );
+            }
+        }
+        (Value::Array(o), Value::Array(n)) if o.len() == n.len() => {
+            for (i, (o, n)) in o.iter().zip(n).enumerate() {
+                diff_value(&format!("{path}[{i}]"), o, n, lines);
+            }
+        }
+        _ if old != new => lines.push(format!("{path}: {old} -> {new}")),
+        _ => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::json_diff;
+    use serde_json::json;
+
+    #[test]
+    fn test_json_diff() {
+        let old = json!({
+            "id": 1,
+            "content": "题干",
+            "embedding": [0.1, 0.2],
+            "extra": {"type": "sc", "answer": 1, "options": ["A", "B"]}
+        });
+        let new = json!({
+            "id": 1,
+            "content": "题干",
+            "embedding": [0.3, 0.4],
+            "extra": {"type": "sc", "answer": 2, "options": ["A", "C"], "analysis": "解析"}
+        });
+        assert_eq!(
+            json_diff(&old, &new),
+            vec![
+                "extra.analysis: null -> \"解析\"",
+                "extra.answer: 1 -> 2",
+                "extra.options[1]: \"B\" -> \"C\"",
+            ]
+        );
+        assert!(json_diff(&old, &old).is_empty());
+    }
+}
@@ -0,0 +1,273 @@
+use crate::model::{
+    exam_scoring::{RuleConfig, ScoringConfig},
+    question::QuestionExtra,
+    solution::{self, FillBlank, SolutionExtra},
+};
+use std::collections::{BTreeSet, HashMap};
+use std::sync::OnceLock;
+
+/// 一道题的满分，题目得分为得分率
+pub const FULL_CREDIT: f32 = 1.0;
+
+static STANDARD_SCHEME: OnceLock<ScoringScheme> = OnceLock::new();
+
+/// 计分规则：根据参考答案和用户作答给出0~1的得分率
+pub trait ScoringRule: Send + Sync {
+    fn score(&self, solution: &SolutionExtra, user_answer: &str) -> f32;
+}
+
+/// 规范化后完全一致才得分
+pub struct ExactMatch;
+
+impl ScoringRule for ExactMatch {
+    fn score(&self, solution: &SolutionExtra, user_answer: &str) -> f32 {
+        let matched = solution
+            .get_raw_answer()
+            .is_some_and(|answer| normalize_answer(&answer) == normalize_answer(user_answer));
+        if matched {
+            FULL_CREDIT
+        } else {
+            0.0
+        }
+    }
+}
+
+/// 选择题按选项集合判分，与作答顺序无关
+pub struct OptionSetMatch {
+    /// 少选且没有错选时的得分率，为空时少选不得分
+    pub partial_credit: Option<f32>,
+}
+
+impl ScoringRule for OptionSetMatch {
+    fn score(&self, solution: &SolutionExtra, user_answer: &str) -> f32 {
+        let Some(answer) = solution.get_raw_answer() else {
+            return 0.0;
+        };
+        let answer = parse_options(&answer);
+        let user = parse_options(user_answer);
+        if user.is_empty() || !user.is_subset(&answer) {
+            0.0
+        } else if user.len() == answer.len() {
+            FULL_CREDIT
+        } else {
+            self.partial_credit.unwrap_or_default()
+        }
+    }
+}
+
+/// 填空题逐空判分，得分率为答对的空数占比
+pub struct BlankMatch;
+
+impl ScoringRule for BlankMatch {
+    fn score(&self, solution: &SolutionExtra, user_answer: &str) -> f32 {
+        let SolutionExtra::FillBlank(FillBlank { blanks, .. }) = solution else {
+            return ExactMatch.score(solution, user_answer);
+        };
+        if blanks.is_empty() {
+            return 0.0;
+        }
+        let user = split_blanks(user_answer, blanks.len());
+        let correct = blanks
+            .iter()
+            .zip(user.iter())
+            .filter(|(blank, user)| normalize_answer(blank) == **user)
+            .count();
+        FULL_CREDIT * correct as f32 / blanks.len() as f32
+    }
+}
+
+/// 一场考试的计分方案：题型 -> 计分规则
+pub struct ScoringScheme {
+    rules: HashMap<String, Box<dyn ScoringRule>>,
+}
+
+impl ScoringScheme {
+    /// 默认方案：选择题不分顺序、少选不得分，填空题逐空判分
+    pub fn standard() -> &'static ScoringScheme {
+        STANDARD_SCHEME.get_or_init(|| Self::new(None))
+    }
+
+    /// 在默认方案上按考试配置覆盖部分题型的规则
+    pub fn new(config: Option<&ScoringConfig>) -> Self {
+        let mut scheme = Self {
+            rules: HashMap::new(),
+        };
+        for ty in ["sc", "mc", "ic", "bc", "tf"] {
+            scheme = scheme.with_rule(
+                ty,
+                OptionSetMatch {
+                    partial_credit: None,
+                },
+            );
+        }
+        scheme = scheme.with_rule("fb", BlankMatch);
+        if let Some(ScoringConfig(rules)) = config {
+            for (ty, rule) in rules {
+                scheme = match rule {
+                    RuleConfig::Exact => scheme.with_rule(ty, ExactMatch),
+                    RuleConfig::OptionSet { partial_credit } => scheme.with_rule(
+                        ty,
+                        OptionSetMatch {
+                            partial_credit: partial_credit.map(|c| c.clamp(0.0, FULL_CREDIT)),
+                        },
+                    ),
+                    RuleConfig::Blanks => scheme.with_rule(ty, BlankMatch),
+                };
+            }
+        }
+        scheme
+    }
+
+    pub fn with_rule<R: ScoringRule + 'static>(mut self, ty: impl Into<String>, rule: R) -> Self {
+        self.rules.insert(ty.into(), Box::new(rule));
+        self
+    }
+
+    /// 题目得分率，没有配置规则的题型按答案完全一致判分
+    pub fn score(&self, extra: &QuestionExtra, solution: &SolutionExtra, user_answer: &str) -> f32 {
+        match self.rules.get(&extra.to_string()) {
+            Some(rule) => rule.score(solution, user_answer),
+            None => ExactMatch.score(solution, user_answer),
+        }
+    }
+
+    /// 按第一个参考答案给出得分率，没有参考答案时为0
+    pub fn score_solutions(
+        &self,
+        extra: &QuestionExtra,
+        solutions: Option<&[solution::Model]>,
+        user_answer: &str,
+    ) -> f32 {
+        solutions
+            .and_then(|ss| ss.first())
+            .map(|s| self.score(extra, &s.extra, user_answer))
+            .unwrap_or_default()
+    }
+}
+
+/// 判断用户作答是否正确，部分得分不算正确
+pub fn check_answer(
+    extra: &QuestionExtra,
+    solutions: Option<&[solution::Model]>,
+    user_answer: &str,
+) -> bool {
+    is_full_credit(ScoringScheme::standard().score_solutions(extra, solutions, user_answer))
+}
+
+pub fn is_full_credit(score: f32) -> bool {
+    score >= FULL_CREDIT - f32::EPSILON
+}
+
+/// 全角转半角、去掉首尾空白、合并连续空白并统一小写
+pub fn normalize_answer(s: &str) -> String {
+    let half_width: String = s
+        .chars()
+        .map(|c| match c {
+            '\u{3000}' => ' ',
+            '\u{FF01}'..='\u{FF5E}' => char::from_u32(c as u32 - 0xFEE0).unwrap_or(c),
+            _ => c,
+        })
+        .collect();
+    half_width
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+        .to_lowercase()
+}
+
+/// 解析选项：数字为选项下标，字母按A=0依次对应，其余字符视为分隔符
+fn parse_options(s: &str) -> BTreeSet<u8> {
+    let s = normalize_answer(s);
+    let mut options = BTreeSet::new();
+    for token in s.split(|c: char| !c.is_ascii_alphanumeric()) {
+        if token.is_empty() {
+            continue;
+        }
+        if let Ok(index) = token.parse::<u8>() {
+            options.insert(index);
+        } else if token.chars().all(|c| c.is_ascii_lowercase()) {
+            options.extend(token.bytes().map(|b| b - b'a'));
+        }
+    }
+    options
+}
+
+/// 按`|`或`;`分隔各空的作答；都没有时只有一空则整体作为答案，多空按空白分隔
+fn split_blanks(s: &str, count: usize) -> Vec<String> {
+    let s = normalize_answer(s);
+    let blanks: Vec<&str> = if s.contains(['|', ';']) {
+        s.split(['|', ';']).collect()
+    } else if count <= 1 {
+        vec![s.as_str()]
+    } else {
+        s.split(' ').collect()
+    };
+    blanks.into_iter().map(|b| b.trim().to_string()).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{normalize_answer, BlankMatch, OptionSetMatch, ScoringRule};
+    use crate::model::solution::{FillBlank, MultiChoice, SolutionExtra};
+
+    fn multi_choice(answer: Vec<u8>) -> SolutionExtra {
+        SolutionExtra::IndefiniteChoice(MultiChoice {
+            answer,
+            analysis: String::new(),
+        })
+    }
+
+    #[test]
+    fn test_normalize_full_width() {
+        assert_eq!(normalize_answer("　ＡＢ，１２ "), "ab,12");
+        assert_eq!(normalize_answer("中国  人民"), "中国 人民");
+    }
+
+    #[test]
+    fn test_option_set_ignores_order() {
+        let rule = OptionSetMatch {
+            partial_credit: None,
+        };
+        let solution = multi_choice(vec![0, 2]);
+        assert_eq!(rule.score(&solution, "2,0"), 1.0);
+        assert_eq!(rule.score(&solution, "ＣＡ"), 1.0);
+        assert_eq!(rule.score(&solution, "0"), 0.0);
+        assert_eq!(rule.score(&solution, ""), 0.0);
+    }
+
+    #[test]
+    fn test_option_set_partial_credit() {
+        let rule = OptionSetMatch {
+            partial_credit: Some(0.5),
+        };
+        let solution = multi_choice(vec![0, 2, 3]);
+        assert_eq!(rule.score(&solution, "0,3"), 0.5);
+        assert_eq!(rule.score(&solution, "0,1"), 0.0);
+        assert_eq!(rule.score(&solution, "3,2,0"), 1.0);
+    }
+
+    #[test]
+    fn test_blank_match_per_blank() {
+        let solution = SolutionExtra::FillBlank(FillBlank {
+            blanks: vec!["长江".to_string(), "黄河".to_string()],
+            analysis: String::new(),
+        });
+        assert_eq!(BlankMatch.score(&solution, "长江 黄河"), 1.0);
+        assert_eq!(BlankMatch.score(&solution, "长江；珠江"), 0.5);
+        assert_eq!(BlankMatch.score(&solution, "黄河|长江"), 0.0);
+    }
+
+    #[test]
+    fn test_blank_match_keeps_spaces_in_blank() {
+        let solution = SolutionExtra::FillBlank(FillBlank {
+            blanks: vec!["New York".to_string()],
+            analysis: String::new(),
+        });
+        assert_eq!(BlankMatch.score(&solution, " new  york "), 1.0);
+        let solution = SolutionExtra::FillBlank(FillBlank {
+            blanks: vec!["New York".to_string(), "黄河".to_string()],
+            analysis: String::new(),
+        });
+        assert_eq!(BlankMatch.score(&solution, "New York|黄河"), 1.0);
+    }
+}
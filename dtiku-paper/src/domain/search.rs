@@ -0,0 +1,291 @@
+use crate::model::question::QuestionExtra;
+use itertools::Itertools;
+use jieba_rs::Jieba;
+use scraper::Html;
+use std::{collections::HashMap, sync::OnceLock};
+
+/// 每道题索引的材料文本上限，避免资料分析等长材料淹没题干
+const MAX_MATERIAL_CHARS: usize = 2000;
+/// 查询最多使用的词数
+const MAX_QUERY_TERMS: usize = 32;
+/// 拍照搜题时识别文本用作查询的字数上限
+const MAX_OCR_QUERY_CHARS: usize = 200;
+/// 搜索结果摘要的字数
+pub const SNIPPET_CHARS: usize = 120;
+/// 混合检索时关键词和向量各自召回的候选数
+pub const HYBRID_CANDIDATES: u64 = 100;
+/// 倒数排名融合的平滑常数，越大则排名靠后的结果权重衰减越慢
+const RRF_K: f32 = 60.0;
+/// 可供筛选的题型：QuestionExtra的类型标识和名称
+pub const QUESTION_TYPES: [(&str, &str); 8] = [
+    ("sc", "单选题"),
+    ("mc", "多选题"),
+    ("ic", "不定项选择题"),
+    ("tf", "判断题"),
+    ("fb", "填空题"),
+    ("sqa", "分步式解答题"),
+    ("cqa", "封闭式解答题"),
+    ("oqa", "开放式解答题"),
+];
+/// 几乎每道题都会出现的词，不参与索引和排序
+const STOP_WORDS: &[&str] = &[
+    "的", "了", "是", "在", "和", "与", "及", "或", "等", "为", "对", "中", "下列", "以下", "关于",
+    "说法", "正确", "错误", "选项", "哪", "项",
+];
+
+static JIEBA: OnceLock<Jieba> = OnceLock::new();
+
+fn jieba() -> &'static Jieba {
+    JIEBA.get_or_init(Jieba::new)
+}
+
+/// html转纯文本，连续空白合并为一个空格
+pub fn plain_text(html: &str) -> String {
+    let text = Html::parse_fragment(html).root_element().text().join(" ");
+    text.split_whitespace().join(" ")
+}
+
+/// 题干、选项和材料拼成索引文本
+pub fn question_text(content: &str, extra: &QuestionExtra, materials: &[&str]) -> String {
+    let mut parts = vec![plain_text(content), plain_text(&extra.options_html())];
+    for m in materials {
+        parts.push(plain_text(m).chars().take(MAX_MATERIAL_CHARS).collect());
+    }
+    parts.into_iter().filter(|p| !p.is_empty()).join(" ")
+}
+
+/// 索引用分词：搜索引擎模式切分，长词和其中的短词都会进入索引
+pub fn index_tokens(text: &str) -> Vec<String> {
+    normalize(jieba().cut_for_search(text, true))
+}
+
+/// 查询用分词：精确模式切分并去重
+pub fn query_terms(query: &str) -> Vec<String> {
+    normalize(jieba().cut(query, true))
+        .into_iter()
+        .unique()
+        .take(MAX_QUERY_TERMS)
+        .collect()
+}
+
+/// 查询词组成tsquery：命中任一词即可召回，命中越多排序越靠前
+pub fn to_tsquery(terms: &[String]) -> String {
+    terms.join(" | ")
+}
+
+/// 只保留字母数字并转小写，去掉标点和停用词。
+/// 处理后的词不含tsquery的运算符，可以直接拼接
+fn normalize(words: Vec<&str>) -> Vec<String> {
+    words
+        .into_iter()
+        .map(|w| {
+            w.chars()
+                .filter(|c| c.is_alphanumeric())
+                .map(fold_case)
+                .collect::<String>()
+        })
+        .filter(|w| !w.is_empty() && !STOP_WORDS.contains(&w.as_str()))
+        .collect()
+}
+
+/// 截取第一个命中词附近的文本作为摘要，命中词用`<mark>`标出，其余文本做html转义
+pub fn highlight_snippet(text: &str, terms: &[String], max_chars: usize) -> String {
+    let chars = text.chars().collect_vec();
+    let terms = terms
+        .iter()
+        .map(|t| t.chars().collect_vec())
+        .filter(|t| !t.is_empty())
+        .collect_vec();
+    let mut matches = vec![];
+    let mut i = 0;
+    while i < chars.len() {
+        let len = terms
+            .iter()
+            .filter(|t| {
+                chars.len() - i >= t.len()
+                    && t.iter().zip(&chars[i..]).all(|(a, b)| *a == fold_case(*b))
+            })
+            .map(|t| t.len())
+            .max();
+        match len {
+            Some(len) => {
+                matches.push(i..i + len);
+                i += len;
+            }
+            None => i += 1,
+        }
+    }
+
+    let start = matches
+        .first()
+        .map(|m| m.start.saturating_sub(max_chars / 4))
+        .unwrap_or_default();
+    let end = (start + max_chars).min(chars.len());
+    let mut snippet = String::new();
+    if start > 0 {
+        snippet.push('…');
+    }
+    let mut matches = matches.into_iter().peekable();
+    let mut pos = start;
+    while pos < end {
+        while matches.next_if(|m| m.end <= pos).is_some() {}
+        let (next, marked) = match matches.peek() {
+            Some(m) if m.start <= pos => (m.end.min(end), true),
+            Some(m) => (m.start.min(end), false),
+            None => (end, false),
+        };
+        let text = escape_html(&chars[pos..next].iter().collect::<String>());
+        if marked {
+            snippet.push_str(&format!("<mark>{text}</mark>"));
+        } else {
+            snippet.push_str(&text);
+        }
+        pos = next;
+    }
+    if end < chars.len() {
+        snippet.push('…');
+    }
+    snippet
+}
+
+/// 清理OCR识别出的文本：去掉页码行和开头的题号，合并断行，
+/// 去掉汉字两侧多余的空格，过长时截断
+pub fn clean_ocr_text(text: &str) -> String {
+    let joined = text
+        .lines()
+        .map(str::trim)
+        .filter(|l| !l.is_empty() && !l.chars().all(|c| c.is_ascii_digit()))
+        .join(" ");
+    let chars = joined.split_whitespace().join(" ").chars().collect_vec();
+    let mut cleaned = String::with_capacity(chars.len());
+    for (i, c) in chars.iter().enumerate() {
+        // 合并后首尾没有空格，空格两侧一定有字符
+        if *c == ' ' && (is_cjk(chars[i - 1]) || is_cjk(chars[i + 1])) {
+            continue;
+        }
+        cleaned.push(*c);
+    }
+    strip_question_number(&cleaned)
+        .chars()
+        .take(MAX_OCR_QUERY_CHARS)
+        .collect()
+}
+
+/// 去掉开头的"12."、"(3)"这类题号，年份等不带标点的数字保留
+fn strip_question_number(text: &str) -> &str {
+    let rest = text.trim_start_matches(['(', '（']);
+    let digits = rest.len() - rest.trim_start_matches(|c: char| c.is_ascii_digit()).len();
+    if digits == 0 || digits > 3 {
+        return text;
+    }
+    match rest[digits..].strip_prefix(['.', '、', '．', ')', '）']) {
+        Some(rest) => rest.trim_start(),
+        None => text,
+    }
+}
+
+/// 汉字和全角标点
+fn is_cjk(c: char) -> bool {
+    matches!(c, '\u{4e00}'..='\u{9fff}' | '\u{3000}'..='\u{303f}' | '\u{ff00}'..='\u{ffef}')
+}
+
+/// 倒数排名融合：每个列表中排第r位(从0开始)的结果得分1/(k+r+1)，
+/// 多个列表的得分相加后降序排列，同分时先出现的在前
+pub fn reciprocal_rank_fusion(lists: &[Vec<i32>]) -> Vec<i32> {
+    let mut scores: HashMap<i32, (f32, usize)> = HashMap::new();
+    let mut seen = 0;
+    for list in lists {
+        for (rank, id) in list.iter().enumerate() {
+            let entry = scores.entry(*id).or_insert_with(|| {
+                seen += 1;
+                (0.0, seen)
+            });
+            entry.0 += 1.0 / (RRF_K + rank as f32 + 1.0);
+        }
+    }
+    scores
+        .into_iter()
+        .sorted_by(|(_, (a, i)), (_, (b, j))| b.total_cmp(a).then(i.cmp(j)))
+        .map(|(id, _)| id)
+        .collect()
+}
+
+fn fold_case(c: char) -> char {
+    c.to_lowercase().next().unwrap_or(c)
+}
+
+fn escape_html(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        clean_ocr_text, highlight_snippet, plain_text, query_terms, reciprocal_rank_fusion,
+        to_tsquery,
+    };
+
+    #[test]
+    fn test_plain_text() {
+        assert_eq!(
+            plain_text("<p>我国<b>宪法</b></p>\n<p>规定  A&amp;B</p>"),
+            "我国 宪法 规定 A&B"
+        );
+    }
+
+    #[test]
+    fn test_query_terms_drop_stop_words() {
+        let terms = query_terms("下列关于公务员录用的说法，正确的是？");
+        assert!(terms.contains(&"公务员".to_string()));
+        assert!(terms
+            .iter()
+            .all(|t| t != "的" && t != "说法" && t != "正确"));
+        assert!(!to_tsquery(&terms).contains(['，', '？']));
+        assert!(query_terms("，。？").is_empty());
+    }
+
+    #[test]
+    fn test_highlight_snippet() {
+        let terms = vec!["宪法".to_string(), "abc".to_string()];
+        assert_eq!(
+            highlight_snippet("我国宪法规定<ABC>", &terms, 100),
+            "我国<mark>宪法</mark>规定&lt;<mark>ABC</mark>&gt;"
+        );
+        let text = format!("{}宪法{}", "甲".repeat(50), "乙".repeat(50));
+        let snippet = highlight_snippet(&text, &terms, 20);
+        assert!(snippet.starts_with('…') && snippet.ends_with('…'));
+        assert!(snippet.contains("<mark>宪法</mark>"));
+        assert_eq!(highlight_snippet("没有命中", &terms, 2), "没有…");
+    }
+
+    #[test]
+    fn test_reciprocal_rank_fusion() {
+        // 3在两个列表中都靠前，融合后排第一；1和4只出现在一个列表的首位，按出现顺序排列
+        let fused = reciprocal_rank_fusion(&[vec![1, 3, 2], vec![4, 3]]);
+        assert_eq!(fused, vec![3, 1, 4, 2]);
+        assert!(reciprocal_rank_fusion(&[vec![], vec![]]).is_empty());
+    }
+
+    #[test]
+    fn test_clean_ocr_text() {
+        let text = "12. 我 国 宪 法 规 定\n的 公 民 权 利 是（  ）\n\n  37  \nA. 选举权  B. 劳动权";
+        assert_eq!(
+            clean_ocr_text(text),
+            "我国宪法规定的公民权利是（）A.选举权B.劳动权"
+        );
+        assert_eq!(clean_ocr_text("(3) GDP 增长"), "GDP增长");
+        assert_eq!(clean_ocr_text("2023 年"), "2023年");
+        assert_eq!(clean_ocr_text("\n 5 \n"), "");
+    }
+}
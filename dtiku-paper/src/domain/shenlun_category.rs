@@ -0,0 +1,354 @@
+use anyhow::Context;
+use itertools::Itertools;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashSet};
+
+/// 内置的申论分类规则，按优先级排列，得分相同时排在前面的优先
+static DEFAULT_RULES: &[(&str, f64, &[&str])] = &[
+    ("作文题/议论文", 1.0, &["(议?论文)", "写一篇.*文章", "自拟题目.*写作?一?(?:篇|段)?(?:.*的)?文章"]),
+
+    ("公文写作题/评论类", 1.0, &["(时评|短评|社评)", "写一篇(评论)", "写点评", "反驳.*观点", "对.*(?:评析|评价|点评)"]),
+    ("公文写作题/总结类", 1.0, &["一份.*(总结|报告|综述)", "(调查报告|调研提纲|调研报告|考察报告|专题报告|简报|工作总结|汇报|导学材料)"]),
+    ("公文写作题/宣传类", 1.0, &["(公开信|导言|宣讲|编者按)", "一个?份?篇?.*(倡议|通知|通报|发言|讲话|讲解|宣传|介绍|推介|推荐|经验交流|主持词|新闻|报道)", "一封.*(信)"]),
+    ("公文写作题/方案类", 1.0, &["一份.*(方案|意见|建议|提案|备询要点)", "《.*(意见)》"]),
+
+    ("综合类/词句解释类", 1.0, &["(?:阐述|陈述|解释|分析|谈谈|谈一谈|谈一下|指出).*(看法|理解|见解|认识|含义)", "对.*(看法|理解|见解|认识|含义).*概括"]),
+    ("综合类/概括主要内容类", 1.0, &["(?:概括|提炼).*(看法|理解|见解|认识|含义)"]),
+
+    // 兜底的公文类型，权重较低，只在没有更具体的公文类型时胜出
+    ("公文写作题/其他类", 0.5, &["一份.*"]),
+
+    ("单一题/影响类", 1.0, &["(变化|影响|作用|功能|意义|成效|危害|效果)"]),
+    ("单一题/提出对策类", 1.0, &["(做法|启示|对策|建议|措施|举措|经验|方式|途径)", "如何.*", "怎么.*", "解决(?:办法|方式)"]),
+    ("单一题/原因类", 1.0, &["(原因|理由|因素)", "为什么.*", "为何.*"]),
+    ("单一题/问题类", 1.0, &["(问题|困难|挑战|不足|劣势|难题)", "(现象|背景|现状)"]),
+    ("单一题/特点类", 1.0, &["(特征|特点)", "具?体?(表现)"]),
+];
+
+/// 一条分类规则，category为"题型/分类"，分类为"_"时只归到题型
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ShenlunRule {
+    pub category: String,
+    #[serde(default = "default_weight")]
+    pub weight: f64,
+    pub patterns: Vec<String>,
+}
+
+fn default_weight() -> f64 {
+    1.0
+}
+
+/// 申论分类规则，保存在系统配置中，rules的顺序即优先级
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ShenlunCategoryConfig {
+    pub rules: Vec<ShenlunRule>,
+    /// 得分最高的分类总会输出，其余分类的置信度需要达到该值
+    pub min_confidence: f64,
+    /// 每道题最多输出的分类数
+    pub max_labels: usize,
+}
+
+impl Default for ShenlunCategoryConfig {
+    fn default() -> Self {
+        Self {
+            rules: DEFAULT_RULES
+                .iter()
+                .map(|(category, weight, patterns)| ShenlunRule {
+                    category: category.to_string(),
+                    weight: *weight,
+                    patterns: patterns.iter().map(|p| p.to_string()).collect(),
+                })
+                .collect(),
+            min_confidence: 0.3,
+            max_labels: 3,
+        }
+    }
+}
+
+/// 分类结果，confidence为该分类得分在所有命中分类中的占比
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct CategoryLabel {
+    pub category_type: String,
+    pub category_name: String,
+    pub confidence: f64,
+}
+
+impl CategoryLabel {
+    pub fn category(&self) -> String {
+        format!("{}/{}", self.category_type, self.category_name)
+    }
+}
+
+struct CompiledRule {
+    category_type: String,
+    category_name: String,
+    weight: f64,
+    regexes: Vec<Regex>,
+}
+
+pub struct ShenlunClassifier {
+    rules: Vec<CompiledRule>,
+    min_confidence: f64,
+    max_labels: usize,
+}
+
+impl ShenlunClassifier {
+    pub fn new(config: &ShenlunCategoryConfig) -> anyhow::Result<Self> {
+        let rules = config
+            .rules
+            .iter()
+            .map(|rule| {
+                let (ty, name) = rule
+                    .category
+                    .split_once('/')
+                    .with_context(|| format!("分类格式应为\"题型/分类\": {}", rule.category))?;
+                let regexes = rule
+                    .patterns
+                    .iter()
+                    .map(|p| Regex::new(p).with_context(|| format!("正则格式错误: {p}")))
+                    .collect::<anyhow::Result<_>>()?;
+                Ok(CompiledRule {
+                    category_type: ty.to_string(),
+                    category_name: name.to_string(),
+                    weight: rule.weight,
+                    regexes,
+                })
+            })
+            .collect::<anyhow::Result<_>>()?;
+        Ok(Self {
+            rules,
+            min_confidence: config.min_confidence,
+            max_labels: config.max_labels.max(1),
+        })
+    }
+
+    /// 每条规则的得分为命中的正则数乘以权重，按得分从高到低输出，
+    /// 得分相同的按规则顺序，同一分类有多条规则时得分累加
+    pub fn classify(&self, html: &str) -> Vec<CategoryLabel> {
+        let text = html.replace(|c: char| c.is_whitespace(), "");
+        let mut scores: Vec<((&str, &str), f64)> = vec![];
+        for rule in &self.rules {
+            let hits = rule.regexes.iter().filter(|re| re.is_match(&text)).count();
+            if hits == 0 || rule.weight <= 0.0 {
+                continue;
+            }
+            let key = (rule.category_type.as_str(), rule.category_name.as_str());
+            let score = rule.weight * hits as f64;
+            match scores.iter_mut().find(|(k, _)| *k == key) {
+                Some((_, s)) => *s += score,
+                None => scores.push((key, score)),
+            }
+        }
+        let total: f64 = scores.iter().map(|(_, s)| s).sum();
+        scores
+            .into_iter()
+            // sorted_by是稳定排序，得分相同时保持规则顺序
+            .sorted_by(|(_, a), (_, b)| b.total_cmp(a))
+            .enumerate()
+            .take_while(|(i, (_, s))| *i == 0 || s / total >= self.min_confidence)
+            .take(self.max_labels)
+            .map(|(_, ((ty, name), s))| CategoryLabel {
+                category_type: ty.to_string(),
+                category_name: name.to_string(),
+                confidence: s / total,
+            })
+            .collect()
+    }
+}
+
+/// 人工标注的题目，labels为"题型/分类"
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LabelledSample {
+    pub text: String,
+    pub labels: Vec<String>,
+}
+
+impl LabelledSample {
+    /// 随代码维护的人工标注集，调整规则后用来评估效果
+    pub fn builtin() -> Vec<Self> {
+        serde_json::from_str(include_str!("../../tests/fixtures/shenlun_labels.json"))
+            .expect("invalid shenlun_labels.json")
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct CategoryMetrics {
+    pub category: String,
+    pub true_positive: usize,
+    pub false_positive: usize,
+    pub false_negative: usize,
+    pub precision: f64,
+    pub recall: f64,
+}
+
+impl CategoryMetrics {
+    fn new(category: String, tp: usize, fp: usize, fn_: usize) -> Self {
+        Self {
+            category,
+            true_positive: tp,
+            false_positive: fp,
+            false_negative: fn_,
+            precision: ratio(tp, tp + fp),
+            recall: ratio(tp, tp + fn_),
+        }
+    }
+}
+
+/// 分类结果和人工标注不一致的题目
+#[derive(Debug, Clone, Serialize)]
+pub struct Mismatch {
+    pub text: String,
+    pub expected: Vec<String>,
+    pub predicted: Vec<CategoryLabel>,
+}
+
+/// 对人工标注集的评估结果，overall为所有分类合计(micro)的准确率和召回率
+#[derive(Debug, Clone, Serialize)]
+pub struct Evaluation {
+    pub samples: usize,
+    pub overall: CategoryMetrics,
+    pub categories: Vec<CategoryMetrics>,
+    pub mismatches: Vec<Mismatch>,
+}
+
+pub fn evaluate(classifier: &ShenlunClassifier, samples: &[LabelledSample]) -> Evaluation {
+    // 分类 -> (tp, fp, fn)
+    let mut counts: BTreeMap<String, (usize, usize, usize)> = BTreeMap::new();
+    let mut mismatches = vec![];
+    for sample in samples {
+        let predicted = classifier.classify(&sample.text);
+        let expected: HashSet<&str> = sample.labels.iter().map(|l| l.as_str()).collect();
+        let actual: HashSet<String> = predicted.iter().map(|l| l.category()).collect();
+        for label in &actual {
+            let c = counts.entry(label.clone()).or_default();
+            if expected.contains(label.as_str()) {
+                c.0 += 1;
+            } else {
+                c.1 += 1;
+            }
+        }
+        for label in &expected {
+            if !actual.contains(*label) {
+                counts.entry(label.to_string()).or_default().2 += 1;
+            }
+        }
+        if expected.len() != actual.len() || actual.iter().any(|l| !expected.contains(l.as_str())) {
+            mismatches.push(Mismatch {
+                text: sample.text.clone(),
+                expected: sample.labels.clone(),
+                predicted,
+            });
+        }
+    }
+    let (tp, fp, fn_) = counts.values().fold((0, 0, 0), |(tp, fp, fn_), c| {
+        (tp + c.0, fp + c.1, fn_ + c.2)
+    });
+    Evaluation {
+        samples: samples.len(),
+        overall: CategoryMetrics::new("overall".to_string(), tp, fp, fn_),
+        categories: counts
+            .into_iter()
+            .map(|(category, (tp, fp, fn_))| CategoryMetrics::new(category, tp, fp, fn_))
+            .collect(),
+        mismatches,
+    }
+}
+
+fn ratio(n: usize, d: usize) -> f64 {
+    if d == 0 {
+        0.0
+    } else {
+        n as f64 / d as f64
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn categories(labels: &[CategoryLabel]) -> Vec<String> {
+        labels.iter().map(|l| l.category()).collect()
+    }
+
+    #[test]
+    fn test_classify_is_ordered_and_multi_label() {
+        let classifier = ShenlunClassifier::new(&ShenlunCategoryConfig::default()).unwrap();
+        // 总结类比兜底的其他类得分高
+        let labels = classifier.classify("请根据给定资料，写一份 调研报告。");
+        assert_eq!(categories(&labels)[0], "公文写作题/总结类");
+        assert!(labels.iter().all(|l| l.category() != "公文写作题/其他类"));
+        // 方案类和提出对策类得分相同，按规则顺序输出
+        let labels = classifier.classify("请针对材料反映的情况写一份建议。");
+        assert_eq!(
+            categories(&labels),
+            vec!["公文写作题/方案类", "单一题/提出对策类"]
+        );
+        assert!((labels.iter().map(|l| l.confidence).sum::<f64>() - 0.8).abs() < 1e-9);
+        assert!(classifier.classify("请阅读材料。").is_empty());
+        // 多次分类结果一致
+        for _ in 0..10 {
+            assert_eq!(
+                classifier.classify("请针对材料反映的情况写一份建议。"),
+                labels
+            );
+        }
+    }
+
+    #[test]
+    fn test_invalid_config() {
+        let mut config = ShenlunCategoryConfig::default();
+        config.rules[0].patterns.push("(".to_string());
+        assert!(ShenlunClassifier::new(&config).is_err());
+        config.rules[0] = ShenlunRule {
+            category: "作文题".to_string(),
+            weight: 1.0,
+            patterns: vec![],
+        };
+        assert!(ShenlunClassifier::new(&config).is_err());
+    }
+
+    #[test]
+    fn test_evaluate() {
+        let config: ShenlunCategoryConfig = serde_json::from_str(
+            r#"{"rules":[{"category":"单一题/原因类","patterns":["原因"]},
+                {"category":"单一题/问题类","patterns":["问题"]}]}"#,
+        )
+        .unwrap();
+        let classifier = ShenlunClassifier::new(&config).unwrap();
+        let samples: Vec<LabelledSample> = serde_json::from_str(
+            r#"[{"text":"分析问题产生的原因","labels":["单一题/原因类"]},
+                {"text":"概括存在的问题","labels":["单一题/问题类"]},
+                {"text":"概括主要困难","labels":["单一题/问题类"]}]"#,
+        )
+        .unwrap();
+        let e = evaluate(&classifier, &samples);
+        assert_eq!(e.samples, 3);
+        assert_eq!(
+            (
+                e.overall.true_positive,
+                e.overall.false_positive,
+                e.overall.false_negative
+            ),
+            (2, 1, 1)
+        );
+        let problem = e
+            .categories
+            .iter()
+            .find(|c| c.category == "单一题/问题类")
+            .unwrap();
+        assert_eq!(problem.precision, 0.5);
+        assert_eq!(problem.recall, 0.5);
+        assert_eq!(e.mismatches.len(), 2);
+    }
+
+    #[test]
+    fn test_labelled_fixtures() {
+        let samples = LabelledSample::builtin();
+        let classifier = ShenlunClassifier::new(&ShenlunCategoryConfig::default()).unwrap();
+        let e = evaluate(&classifier, &samples);
+        assert!(e.overall.precision >= 0.85, "{e:#?}");
+        assert!(e.overall.recall >= 0.95, "{e:#?}");
+    }
+}
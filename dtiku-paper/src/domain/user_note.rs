@@ -0,0 +1,234 @@
+use crate::{
+    domain::material::material_text,
+    model::{user_note, SrcType},
+};
+use itertools::Itertools;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// 笔记内容的最大长度
+pub const MAX_NOTE_LENGTH: usize = 5000;
+
+/// 新建笔记：题目笔记只有markdown，材料高亮需要高亮的位置，笔记可以为空
+#[derive(Debug, Clone, Deserialize)]
+pub struct NoteSave {
+    pub target_type: SrcType,
+    pub target_id: i32,
+    #[serde(default)]
+    pub markdown: String,
+    pub highlight: Option<Highlight>,
+}
+
+/// 材料中高亮的一段文字，start、end为材料纯文本中按utf16计数的位置，与浏览器中的文本偏移一致
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct Highlight {
+    pub start: i32,
+    pub end: i32,
+    pub text: String,
+}
+
+impl NoteSave {
+    /// 校验失败时返回给用户的提示
+    pub fn validate(&self) -> Result<(), &'static str> {
+        match (&self.target_type, &self.highlight) {
+            (SrcType::Question, None) if self.markdown.trim().is_empty() => {
+                return Err("笔记内容不能为空")
+            }
+            (SrcType::Question, None) => {}
+            (SrcType::Question, Some(_)) => return Err("只能高亮材料中的文字"),
+            (SrcType::Material, None) => return Err("请选择要高亮的文字"),
+            (SrcType::Material, Some(_)) => {}
+            (SrcType::Solution, _) => return Err("不支持给解析记笔记"),
+        }
+        if self.markdown.chars().count() > MAX_NOTE_LENGTH {
+            return Err("笔记内容过长");
+        }
+        Ok(())
+    }
+}
+
+impl Highlight {
+    /// 高亮的文字与材料中对应位置的文字一致
+    pub fn matches(&self, content: &str) -> bool {
+        if self.start < 0 || self.end <= self.start {
+            return false;
+        }
+        let text = material_text(content).encode_utf16().collect_vec();
+        text.get(self.start as usize..self.end as usize)
+            .is_some_and(|s| String::from_utf16_lossy(s) == self.text)
+    }
+}
+
+/// 当前用户在页面中题目、材料上的笔记
+#[derive(Debug, Clone, Default)]
+pub struct UserNotes {
+    questions: HashMap<i32, Vec<user_note::Model>>,
+    materials: HashMap<i32, Vec<user_note::Model>>,
+}
+
+impl UserNotes {
+    pub fn new(notes: Vec<user_note::Model>) -> Self {
+        let (questions, materials): (Vec<_>, Vec<_>) = notes
+            .into_iter()
+            .partition(|n| n.target_type == SrcType::Question);
+        Self {
+            questions: questions.into_iter().into_group_map_by(|n| n.target_id),
+            materials: materials.into_iter().into_group_map_by(|n| n.target_id),
+        }
+    }
+
+    pub fn question_notes(&self, question_id: &i32) -> &[user_note::Model] {
+        self.questions
+            .get(question_id)
+            .map(Vec::as_slice)
+            .unwrap_or_default()
+    }
+
+    pub fn highlights(&self, material_id: &i32) -> &[user_note::Model] {
+        self.materials
+            .get(material_id)
+            .map(Vec::as_slice)
+            .unwrap_or_default()
+    }
+}
+
+/// 笔记所在的题目或材料，url为能找回原题的页面
+#[derive(Debug, Clone, Serialize)]
+pub struct NoteTarget {
+    pub summary: String,
+    pub url: String,
+}
+
+/// 笔记列表和导出时展示的一条笔记，题目、材料已被删除时target为None
+#[derive(Debug, Clone, Serialize)]
+pub struct UserNoteView {
+    #[serde(flatten)]
+    pub note: user_note::Model,
+    pub target: Option<NoteTarget>,
+}
+
+/// 把笔记导出为Markdown，每条笔记都带上原题链接
+pub fn render_notes_markdown(notes: &[UserNoteView], site_url: &str) -> String {
+    let mut md = "# 我的笔记\n\n".to_string();
+    for view in notes {
+        let note = &view.note;
+        let title = match note.target_type {
+            SrcType::Material => "材料高亮",
+            _ => "题目笔记",
+        };
+        md.push_str(&format!(
+            "## {title}（{}）\n\n",
+            note.created.format("%Y-%m-%d %H:%M")
+        ));
+        if let Some(target) = &view.target {
+            md.push_str(&format!("[查看原题]({site_url}{})\n\n", target.url));
+            if note.highlight_text.is_none() {
+                md.push_str(&quote(&target.summary));
+            }
+        }
+        if let Some(text) = &note.highlight_text {
+            md.push_str(&quote(text));
+        }
+        if !note.markdown.trim().is_empty() {
+            md.push_str(note.markdown.trim());
+            md.push_str("\n\n");
+        }
+    }
+    md
+}
+
+fn quote(text: &str) -> String {
+    let mut quoted = text.lines().map(|l| format!("> {l}")).join("\n");
+    quoted.push_str("\n\n");
+    quoted
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{render_notes_markdown, Highlight, NoteSave, NoteTarget, UserNoteView};
+    use crate::model::{user_note, SrcType};
+    use chrono::NaiveDate;
+
+    #[test]
+    fn test_highlight_matches() {
+        let content = "<p>材料一：<b>数字经济</b>快速发展😀，带动就业。</p>";
+        let highlight = |start, end, text: &str| Highlight {
+            start,
+            end,
+            text: text.to_string(),
+        };
+        assert!(highlight(4, 8, "数字经济").matches(content));
+        // emoji占两个utf16位置
+        assert!(highlight(14, 19, "，带动就业").matches(content));
+        assert!(!highlight(4, 8, "数字经").matches(content));
+        assert!(!highlight(8, 4, "").matches(content));
+        assert!(!highlight(16, 100, "就业").matches(content));
+    }
+
+    #[test]
+    fn test_validate() {
+        let save = |target_type, markdown: &str, highlight: Option<Highlight>| NoteSave {
+            target_type,
+            target_id: 1,
+            markdown: markdown.to_string(),
+            highlight,
+        };
+        let highlight = Highlight {
+            start: 0,
+            end: 2,
+            text: "材料".to_string(),
+        };
+        assert!(save(SrcType::Question, "**易错**", None).validate().is_ok());
+        assert!(save(SrcType::Question, " ", None).validate().is_err());
+        assert!(save(SrcType::Question, "a", Some(highlight.clone()))
+            .validate()
+            .is_err());
+        assert!(save(SrcType::Material, "", Some(highlight))
+            .validate()
+            .is_ok());
+        assert!(save(SrcType::Material, "a", None).validate().is_err());
+    }
+
+    #[test]
+    fn test_render_notes_markdown() {
+        let created = NaiveDate::from_ymd_opt(2024, 3, 1)
+            .unwrap()
+            .and_hms_opt(8, 30, 0)
+            .unwrap();
+        let note = |target_type, markdown: &str, highlight_text: Option<&str>| user_note::Model {
+            id: 1,
+            user_id: 1,
+            target_type,
+            target_id: 10,
+            markdown: markdown.to_string(),
+            highlight_start: None,
+            highlight_end: None,
+            highlight_text: highlight_text.map(str::to_string),
+            created,
+            modified: created,
+        };
+        let notes = vec![
+            UserNoteView {
+                note: note(SrcType::Question, "选B，注意**偷换概念**\n", None),
+                target: Some(NoteTarget {
+                    summary: "下列说法正确的是".to_string(),
+                    url: "/question/detail/10".to_string(),
+                }),
+            },
+            UserNoteView {
+                note: note(SrcType::Material, "", Some("第一行\n第二行")),
+                target: None,
+            },
+        ];
+        assert_eq!(
+            render_notes_markdown(&notes, "https://www.dtiku.cn"),
+            "# 我的笔记\n\n\
+            ## 题目笔记（2024-03-01 08:30）\n\n\
+            [查看原题](https://www.dtiku.cn/question/detail/10)\n\n\
+            > 下列说法正确的是\n\n\
+            选B，注意**偷换概念**\n\n\
+            ## 材料高亮（2024-03-01 08:30）\n\n\
+            > 第一行\n> 第二行\n\n"
+        );
+    }
+}
@@ -0,0 +1,23 @@
+/// 错题本中的一个分组：按一级知识点或按试卷聚合
+#[derive(Debug, Clone)]
+pub struct WrongQuestionGroup {
+    pub id: i32,
+    pub name: String,
+    pub count: usize,
+}
+
+#[derive(Debug, Clone)]
+pub struct WrongQuestionNotebook {
+    pub total: usize,
+    pub keypoints: Vec<WrongQuestionGroup>,
+    pub papers: Vec<WrongQuestionGroup>,
+}
+
+/// 重做错题的结果
+#[derive(Debug, Clone, Default)]
+pub struct WrongQuestionRedo {
+    pub correct: Vec<i32>,
+    pub wrong: Vec<i32>,
+    /// 已达到连续答对次数而移出错题本的题目
+    pub removed: Vec<i32>,
+}
@@ -16,6 +16,7 @@ pub struct Model {
     pub label_id: i32,
     #[sea_orm(column_type = "JsonBinary")]
     pub extra: PaperExtra,
+    pub time_limit: Option<i16>,
 }
 
 #[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
@@ -16,6 +16,11 @@ pub struct Model {
     pub keypoint_path: Option<String>,
     #[sea_orm(column_type = "Float")]
     pub correct_ratio: Option<f32>,
+    #[sea_orm(column_type = "Float")]
+    pub user_correct_ratio: Option<f32>,
+    pub user_answer_count: Option<i32>,
+    #[sea_orm(column_type = "Float")]
+    pub blended_correct_ratio: Option<f32>,
 }
 
 #[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
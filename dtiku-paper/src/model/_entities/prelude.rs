@@ -1,16 +1,33 @@
 //! `SeaORM` Entity, @generated by sea-orm-codegen 1.1.8
 
+pub use super::adaptive_answer::Entity as AdaptiveAnswer;
 pub use super::assets::Entity as Assets;
+pub use super::essay_grade::Entity as EssayGrade;
 pub use super::exam_category::Entity as ExamCategory;
+pub use super::exam_scoring::Entity as ExamScoring;
+pub use super::exercise_answer::Entity as ExerciseAnswer;
+pub use super::exercise_record::Entity as ExerciseRecord;
 pub use super::key_point::Entity as KeyPoint;
+pub use super::key_point_mapping::Entity as KeyPointMapping;
 pub use super::label::Entity as Label;
 pub use super::material::Entity as Material;
+pub use super::material_duplicate::Entity as MaterialDuplicate;
+pub use super::material_paper_stats::Entity as MaterialPaperStats;
+pub use super::mock_paper::Entity as MockPaper;
 pub use super::paper::Entity as Paper;
 pub use super::paper_material::Entity as PaperMaterial;
 pub use super::paper_question::Entity as PaperQuestion;
 pub use super::question::Entity as Question;
+pub use super::question_duplicate::Entity as QuestionDuplicate;
+pub use super::question_feedback::Entity as QuestionFeedback;
+pub use super::question_irt::Entity as QuestionIrt;
 pub use super::question_key_point::Entity as QuestionKeyPoint;
 pub use super::question_key_point_stats::Entity as QuestionKeyPointStats;
+pub use super::question_keypoint_tag::Entity as QuestionKeypointTag;
 pub use super::question_material::Entity as QuestionMaterial;
+pub use super::question_search_index::Entity as QuestionSearchIndex;
 pub use super::scraper_solution::Entity as ScraperSolution;
 pub use super::solution::Entity as Solution;
+pub use super::user_ability::Entity as UserAbility;
+pub use super::user_note::Entity as UserNote;
+pub use super::wrong_question::Entity as WrongQuestion;
@@ -55,4 +55,129 @@ pub enum FromType {
     Chinagwy,
     #[sea_orm(string_value = "mock_exam")]
     MockExam,
+    #[sea_orm(string_value = "upload")]
+    Upload,
+}
+
+#[derive(
+    Debug,
+    Clone,
+    Copy,
+    PartialEq,
+    Eq,
+    EnumIter,
+    DeriveActiveEnum,
+    Serialize,
+    Deserialize,
+    strum :: EnumString,
+    strum :: Display,
+)]
+#[sea_orm(rs_type = "String", db_type = "Enum", enum_name = "duplicate_status")]
+#[serde(rename_all = "snake_case")]
+pub enum DuplicateStatus {
+    #[sea_orm(string_value = "pending")]
+    Pending,
+    #[sea_orm(string_value = "merged")]
+    Merged,
+    #[sea_orm(string_value = "ignored")]
+    Ignored,
+}
+
+#[derive(
+    Debug,
+    Clone,
+    Copy,
+    PartialEq,
+    Eq,
+    EnumIter,
+    DeriveActiveEnum,
+    Serialize,
+    Deserialize,
+    strum :: EnumString,
+    strum :: Display,
+    strum :: EnumMessage,
+)]
+#[sea_orm(rs_type = "String", db_type = "Enum", enum_name = "feedback_category")]
+#[serde(rename_all = "snake_case")]
+#[strum(serialize_all = "snake_case")]
+pub enum FeedbackCategory {
+    #[sea_orm(string_value = "wrong_answer")]
+    #[strum(message = "答案错误")]
+    WrongAnswer,
+    #[sea_orm(string_value = "wrong_analysis")]
+    #[strum(message = "解析有误")]
+    WrongAnalysis,
+    #[sea_orm(string_value = "broken_image")]
+    #[strum(message = "图片无法显示")]
+    BrokenImage,
+    #[sea_orm(string_value = "garbled_option")]
+    #[strum(message = "选项乱码")]
+    GarbledOption,
+    #[sea_orm(string_value = "wrong_content")]
+    #[strum(message = "题目内容有误")]
+    WrongContent,
+    #[sea_orm(string_value = "other")]
+    #[strum(message = "其他")]
+    Other,
+}
+
+#[derive(
+    Debug,
+    Clone,
+    Copy,
+    PartialEq,
+    Eq,
+    EnumIter,
+    DeriveActiveEnum,
+    Serialize,
+    Deserialize,
+    strum :: EnumString,
+    strum :: Display,
+    strum :: EnumMessage,
+)]
+#[sea_orm(rs_type = "String", db_type = "Enum", enum_name = "feedback_status")]
+#[serde(rename_all = "snake_case")]
+#[strum(serialize_all = "snake_case")]
+pub enum FeedbackStatus {
+    #[sea_orm(string_value = "open")]
+    #[strum(message = "待处理")]
+    Open,
+    #[sea_orm(string_value = "accepted")]
+    #[strum(message = "已采纳")]
+    Accepted,
+    #[sea_orm(string_value = "rejected")]
+    #[strum(message = "未采纳")]
+    Rejected,
+}
+
+#[derive(
+    Debug,
+    Clone,
+    Copy,
+    PartialEq,
+    Eq,
+    EnumIter,
+    DeriveActiveEnum,
+    Serialize,
+    Deserialize,
+    strum :: EnumString,
+    strum :: Display,
+    strum :: EnumMessage,
+)]
+#[sea_orm(rs_type = "String", db_type = "Enum", enum_name = "keypoint_tag_status")]
+#[serde(rename_all = "snake_case")]
+#[strum(serialize_all = "snake_case")]
+pub enum KeypointTagStatus {
+    #[sea_orm(string_value = "auto")]
+    #[strum(message = "自动标注")]
+    Auto,
+    #[sea_orm(string_value = "pending")]
+    #[strum(message = "待审核")]
+    Pending,
+    #[sea_orm(string_value = "accepted")]
+    #[strum(message = "已采纳")]
+    Accepted,
+    #[sea_orm(string_value = "rejected")]
+    #[strum(message = "未采纳")]
+    Rejected,
 }
@@ -0,0 +1,36 @@
+pub use super::_entities::adaptive_answer::*;
+use anyhow::Context;
+use sea_orm::{
+    sea_query::OnConflict, sqlx::types::chrono::Local, ActiveModelBehavior, ActiveValue::Set,
+    ConnectionTrait, EntityTrait,
+};
+
+impl ActiveModelBehavior for ActiveModel {}
+
+impl Entity {
+    /// 记录作答，重复作答时覆盖上一次的结果
+    pub async fn save<C: ConnectionTrait>(
+        db: &C,
+        user_id: i32,
+        question_id: i32,
+        answer: String,
+        correct: bool,
+    ) -> anyhow::Result<()> {
+        Entity::insert(ActiveModel {
+            user_id: Set(user_id),
+            question_id: Set(question_id),
+            answer: Set(answer),
+            correct: Set(correct),
+            created: Set(Local::now().naive_local()),
+        })
+        .on_conflict(
+            OnConflict::columns([Column::UserId, Column::QuestionId])
+                .update_columns([Column::Answer, Column::Correct, Column::Created])
+                .to_owned(),
+        )
+        .exec(db)
+        .await
+        .with_context(|| format!("adaptive_answer::save({user_id},{question_id}) failed"))?;
+        Ok(())
+    }
+}
@@ -0,0 +1,56 @@
+pub use super::_entities::essay_grade::*;
+use anyhow::Context;
+use sea_orm::{ColumnTrait, ConnectionTrait, EntityTrait, FromJsonQueryResult, QueryFilter};
+use serde::{Deserialize, Serialize};
+
+/// 一道申论题的批改结果
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, FromJsonQueryResult)]
+pub struct EssayFeedback {
+    /// 参考答案要点的覆盖率，没有参考答案时为空
+    pub coverage: Option<f32>,
+    /// 作答中没有覆盖到的要点
+    pub missed: Vec<String>,
+    pub blocks: Vec<EssayBlockFeedback>,
+}
+
+/// 申论题中每一问的批改意见
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct EssayBlockFeedback {
+    pub title: String,
+    pub word_count: usize,
+    pub word_limit: Option<i16>,
+    /// 这一问作答覆盖到的要点
+    pub covered: Vec<String>,
+    pub comments: Vec<String>,
+}
+
+impl EssayFeedback {
+    pub fn coverage_ratio(&self) -> Option<String> {
+        self.coverage.map(|c| format!("{:.0}%", 100.0 * c))
+    }
+}
+
+impl Entity {
+    pub async fn find_by_record_id<C: ConnectionTrait>(
+        db: &C,
+        record_id: i32,
+    ) -> anyhow::Result<Vec<Model>> {
+        Entity::find()
+            .filter(Column::RecordId.eq(record_id))
+            .all(db)
+            .await
+            .with_context(|| format!("essay_grade::find_by_record_id({record_id}) failed"))
+    }
+
+    pub async fn delete_by_record_id<C: ConnectionTrait>(
+        db: &C,
+        record_id: i32,
+    ) -> anyhow::Result<u64> {
+        Ok(Entity::delete_many()
+            .filter(Column::RecordId.eq(record_id))
+            .exec(db)
+            .await
+            .with_context(|| format!("essay_grade::delete_by_record_id({record_id}) failed"))?
+            .rows_affected)
+    }
+}
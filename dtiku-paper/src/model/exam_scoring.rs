@@ -0,0 +1,45 @@
+pub use super::_entities::exam_scoring::*;
+use anyhow::Context;
+use sea_orm::{
+    ColumnTrait, ConnectionTrait, EntityTrait, FromJsonQueryResult, QueryFilter, QueryOrder,
+};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// 题型(如`ic`) -> 计分方式，没有配置的题型使用默认规则
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize, FromJsonQueryResult)]
+pub struct ScoringConfig(pub HashMap<String, RuleConfig>);
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum RuleConfig {
+    /// 答案完全一致才得分
+    Exact,
+    /// 选项集合一致得满分，少选且没有错选时得partial_credit
+    OptionSet {
+        #[serde(default)]
+        partial_credit: Option<f32>,
+    },
+    /// 填空题逐空判分
+    Blanks,
+}
+
+impl Entity {
+    /// 优先取地区的计分规则，没有时取试卷类型的通用规则
+    pub async fn find_by_paper<C: ConnectionTrait>(
+        db: &C,
+        paper_type: i16,
+        label_id: i32,
+    ) -> anyhow::Result<Option<Model>> {
+        Entity::find()
+            .filter(
+                Column::PaperType
+                    .eq(paper_type)
+                    .and(Column::LabelId.is_in([label_id, 0])),
+            )
+            .order_by_desc(Column::LabelId)
+            .one(db)
+            .await
+            .with_context(|| format!("exam_scoring::find_by_paper({paper_type},{label_id}) failed"))
+    }
+}
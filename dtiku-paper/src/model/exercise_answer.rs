@@ -0,0 +1,139 @@
+pub use super::_entities::exercise_answer::*;
+use crate::model::exercise_record;
+use anyhow::Context;
+use sea_orm::{
+    sea_query::Query, ColumnTrait, ConnectionTrait, EntityTrait, FromQueryResult, QueryFilter,
+    QuerySelect, Statement,
+};
+
+/// 登录用户的一次作答
+#[derive(Debug, FromQueryResult)]
+pub struct UserResponse {
+    pub user_id: i32,
+    pub question_id: i32,
+    pub correct: bool,
+}
+
+/// 单道题的用户作答统计
+#[derive(Debug, FromQueryResult)]
+pub struct AnswerStats {
+    pub question_id: i32,
+    pub total: i64,
+    pub correct: i64,
+}
+
+impl Entity {
+    pub async fn find_by_record_id<C: ConnectionTrait>(
+        db: &C,
+        record_id: i32,
+    ) -> anyhow::Result<Vec<Model>> {
+        Entity::find()
+            .filter(Column::RecordId.eq(record_id))
+            .all(db)
+            .await
+            .with_context(|| format!("exercise_answer::find_by_record_id({record_id}) failed"))
+    }
+
+    pub async fn delete_by_record_id<C: ConnectionTrait>(
+        db: &C,
+        record_id: i32,
+    ) -> anyhow::Result<u64> {
+        Ok(Entity::delete_many()
+            .filter(Column::RecordId.eq(record_id))
+            .exec(db)
+            .await
+            .with_context(|| format!("exercise_answer::delete_by_record_id({record_id}) failed"))?
+            .rows_affected)
+    }
+
+    /// question_ids中用户做过的题目
+    pub async fn find_answered_question_ids<C: ConnectionTrait>(
+        db: &C,
+        user_id: i32,
+        question_ids: Vec<i32>,
+    ) -> anyhow::Result<Vec<i32>> {
+        let records = Query::select()
+            .column(exercise_record::Column::Id)
+            .from(exercise_record::Entity)
+            .and_where(exercise_record::Column::UserId.eq(user_id))
+            .to_owned();
+        Entity::find()
+            .select_only()
+            .column(Column::QuestionId)
+            .distinct()
+            .filter(Column::RecordId.in_subquery(records))
+            .filter(Column::QuestionId.is_in(question_ids))
+            .into_tuple()
+            .all(db)
+            .await
+            .with_context(|| {
+                format!("exercise_answer::find_answered_question_ids({user_id}) failed")
+            })
+    }
+
+    /// 按题目汇总已交卷记录的作答次数和答对次数，申论题按分数判定的结果不计入
+    pub async fn stats_by_question_id_gt<C: ConnectionTrait>(
+        db: &C,
+        question_id: i32,
+        limit: u64,
+    ) -> anyhow::Result<Vec<AnswerStats>> {
+        AnswerStats::find_by_statement(Statement::from_sql_and_values(
+            sea_orm::DatabaseBackend::Postgres,
+            r#"
+                SELECT a.question_id,
+                    count(*) AS total,
+                    count(*) FILTER (WHERE a.correct) AS correct
+                FROM exercise_answer a
+                JOIN exercise_record r ON r.id = a.record_id
+                WHERE r.finished
+                AND a.question_id > $1
+                AND NOT EXISTS (
+                    SELECT 1 FROM essay_grade g
+                    WHERE g.record_id = a.record_id AND g.question_id = a.question_id
+                )
+                GROUP BY a.question_id
+                ORDER BY a.question_id
+                LIMIT $2
+            "#,
+            vec![question_id.into(), (limit as i64).into()],
+        ))
+        .all(db)
+        .await
+        .with_context(|| format!("exercise_answer::stats_by_question_id_gt({question_id}) failed"))
+    }
+
+    /// 某个试卷类型下登录用户已交卷的作答和自适应练习的作答，申论题不计入
+    pub async fn find_user_responses_by_paper_type<C: ConnectionTrait>(
+        db: &C,
+        paper_type: i16,
+    ) -> anyhow::Result<Vec<UserResponse>> {
+        UserResponse::find_by_statement(Statement::from_sql_and_values(
+            sea_orm::DatabaseBackend::Postgres,
+            r#"
+                SELECT r.user_id, a.question_id, a.correct
+                FROM exercise_answer a
+                JOIN exercise_record r ON r.id = a.record_id
+                WHERE r.finished
+                AND r.user_id IS NOT NULL
+                AND r.paper_type = $1
+                AND NOT EXISTS (
+                    SELECT 1 FROM essay_grade g
+                    WHERE g.record_id = a.record_id AND g.question_id = a.question_id
+                )
+                UNION ALL
+                SELECT aa.user_id, aa.question_id, aa.correct
+                FROM adaptive_answer aa
+                WHERE EXISTS (
+                    SELECT 1 FROM paper_question pq
+                    WHERE pq.question_id = aa.question_id AND pq.paper_type = $1
+                )
+            "#,
+            vec![paper_type.into()],
+        ))
+        .all(db)
+        .await
+        .with_context(|| {
+            format!("exercise_answer::find_user_responses_by_paper_type({paper_type}) failed")
+        })
+    }
+}
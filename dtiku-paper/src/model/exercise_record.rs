@@ -0,0 +1,122 @@
+pub use super::_entities::exercise_record::*;
+use crate::domain::exercise::{ExerciseOwner, TIMED_GRACE_SECS};
+use anyhow::Context;
+use chrono::{NaiveDateTime, TimeDelta};
+use sea_orm::{
+    sqlx::types::chrono::Local, ActiveModelBehavior, ActiveValue::Set, ColumnTrait,
+    ConnectionTrait, DbErr, EntityTrait, QueryFilter, QueryOrder, QuerySelect,
+};
+use spring::async_trait;
+
+#[async_trait]
+impl ActiveModelBehavior for ActiveModel {
+    async fn before_save<C>(mut self, _db: &C, insert: bool) -> Result<Self, DbErr>
+    where
+        C: ConnectionTrait,
+    {
+        if insert {
+            self.created = Set(Local::now().naive_local());
+        }
+        self.modified = Set(Local::now().naive_local());
+        Ok(self)
+    }
+}
+
+impl Model {
+    pub fn total(&self) -> i16 {
+        self.correct + self.error
+    }
+
+    pub fn total_time_secs(&self) -> u64 {
+        self.total_time.max(0) as u64
+    }
+
+    pub fn correct_ratio(&self) -> String {
+        let total = self.total();
+        if total == 0 {
+            "0%".to_string()
+        } else {
+            format!("{:.1}%", 100.0 * (self.correct as f64) / (total as f64))
+        }
+    }
+
+    /// 未交卷的限时模考已超过截止时间(含宽限时间)
+    pub fn is_overdue(&self, now: NaiveDateTime) -> bool {
+        match self.deadline {
+            Some(deadline) => {
+                !self.finished && now > deadline + TimeDelta::seconds(TIMED_GRACE_SECS)
+            }
+            None => false,
+        }
+    }
+}
+
+impl Entity {
+    pub async fn find_by_owner_and_id<C: ConnectionTrait>(
+        db: &C,
+        owner: &ExerciseOwner,
+        id: i32,
+    ) -> anyhow::Result<Option<Model>> {
+        Entity::find()
+            .filter(owner.clone())
+            .filter(Column::Id.eq(id))
+            .one(db)
+            .await
+            .with_context(|| {
+                format!("exercise_record::find_by_owner_and_id({owner:?},{id}) failed")
+            })
+    }
+
+    pub async fn find_by_owner_and_paper<C: ConnectionTrait>(
+        db: &C,
+        owner: &ExerciseOwner,
+        paper_id: i32,
+    ) -> anyhow::Result<Vec<Model>> {
+        Entity::find()
+            .filter(owner.clone())
+            .filter(Column::PaperId.eq(paper_id))
+            .order_by_desc(Column::Id)
+            .limit(100)
+            .all(db)
+            .await
+            .with_context(|| {
+                format!("exercise_record::find_by_owner_and_paper({owner:?},{paper_id}) failed")
+            })
+    }
+
+    /// 未完成的普通练习
+    pub async fn find_unfinished<C: ConnectionTrait>(
+        db: &C,
+        owner: &ExerciseOwner,
+        paper_id: i32,
+    ) -> anyhow::Result<Option<Model>> {
+        Entity::find()
+            .filter(owner.clone())
+            .filter(Column::PaperId.eq(paper_id).and(Column::Finished.eq(false)))
+            .filter(Column::Deadline.is_null())
+            .order_by_desc(Column::Id)
+            .one(db)
+            .await
+            .with_context(|| {
+                format!("exercise_record::find_unfinished({owner:?},{paper_id}) failed")
+            })
+    }
+
+    /// 未交卷的限时模考
+    pub async fn find_unfinished_timed<C: ConnectionTrait>(
+        db: &C,
+        owner: &ExerciseOwner,
+        paper_id: i32,
+    ) -> anyhow::Result<Option<Model>> {
+        Entity::find()
+            .filter(owner.clone())
+            .filter(Column::PaperId.eq(paper_id).and(Column::Finished.eq(false)))
+            .filter(Column::Deadline.is_not_null())
+            .order_by_desc(Column::Id)
+            .one(db)
+            .await
+            .with_context(|| {
+                format!("exercise_record::find_unfinished_timed({owner:?},{paper_id}) failed")
+            })
+    }
+}
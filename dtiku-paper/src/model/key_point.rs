@@ -1,12 +1,16 @@
 pub use super::_entities::key_point::*;
-use crate::util;
-use anyhow::Context;
+use crate::domain::keypoint_taxonomy;
+use anyhow::{bail, Context};
+use itertools::Itertools;
 use sea_orm::{
-    sea_query::OnConflict, ColumnTrait, ConnectionTrait, DbErr, EntityTrait, QueryFilter,
-    QueryOrder,
+    sea_query::OnConflict, ColumnTrait, ConnectionTrait, DbBackend, DbErr, EntityTrait,
+    QueryFilter, QueryOrder, Statement,
 };
 use spring_redis::cache;
 
+/// 知识点树的最大层级，防止pid成环时无限递归
+const MAX_DEPTH: i32 = 16;
+
 impl Entity {
     #[cache("keypoint:{id}", expire = 86400)]
     pub async fn find_by_id_with_cache<C: ConnectionTrait>(
@@ -74,40 +78,67 @@ impl Entity {
             .await
     }
 
+    /// 多个知识点的公共祖先路径，不存在的知识点忽略
     pub async fn query_common_keypoint_path<C: ConnectionTrait>(
         db: &C,
         keypoint_ids: &[i32],
     ) -> anyhow::Result<Option<String>> {
         let mut paths = vec![];
         for kp_id in keypoint_ids {
-            let path = Self::query_keypoint_path(db, *kp_id).await?;
-            if let Some(path) = path {
+            let path = Self::find_path(db, *kp_id).await?;
+            if !path.is_empty() {
                 paths.push(path);
             }
         }
-        let paths: Vec<&str> = paths.iter().map(|s| s.as_str()).collect();
-        let common_prefix = util::str::common_prefix_all(&paths);
-        Ok(Some(common_prefix.trim_matches('.').to_string()))
+        let common = keypoint_taxonomy::common_path(&paths);
+        Ok(Some(common.iter().join(".")))
     }
 
+    /// 从根节点到当前节点的id路径，一次递归查询得到，不走缓存；
+    /// 最上层节点的pid不为0说明pid成环或层级超过MAX_DEPTH，返回错误
+    pub async fn find_path<C: ConnectionTrait>(db: &C, id: i32) -> anyhow::Result<Vec<i32>> {
+        let rows = db
+            .query_all(Statement::from_sql_and_values(
+                DbBackend::Postgres,
+                r#"
+                    WITH RECURSIVE p AS (
+                        SELECT id, pid, 1 AS depth FROM key_point WHERE id = $1
+                        UNION ALL
+                        SELECT k.id, k.pid, p.depth + 1
+                        FROM key_point k JOIN p ON k.id = p.pid
+                        WHERE p.depth < $2
+                    )
+                    SELECT id, pid FROM p ORDER BY depth DESC
+                "#,
+                [id.into(), MAX_DEPTH.into()],
+            ))
+            .await
+            .with_context(|| format!("KeyPoint::find_path({id}) failed"))?;
+        let path = rows
+            .into_iter()
+            .map(|row| {
+                Ok((
+                    row.try_get::<i32>("", "id")?,
+                    row.try_get::<i32>("", "pid")?,
+                ))
+            })
+            .collect::<Result<Vec<(i32, i32)>, DbErr>>()
+            .with_context(|| format!("parse KeyPoint::find_path({id}) failed"))?;
+        if let Some((top, pid)) = path.first() {
+            if *pid != 0 {
+                bail!("key_point#{id} path broken at key_point#{top}(pid={pid})");
+            }
+        }
+        Ok(path.into_iter().map(|(id, _)| id).collect())
+    }
+
+    /// ltree格式的知识点路径，知识点不存在时返回None
     pub async fn query_keypoint_path<C: ConnectionTrait>(
         db: &C,
-        mut keypoint_id: i32,
+        keypoint_id: i32,
     ) -> anyhow::Result<Option<String>> {
-        let mut path = keypoint_id.to_string();
-        loop {
-            let kp = Self::find_by_id_with_cache(db, keypoint_id).await?;
-            if let Some(kp) = kp {
-                if kp.pid == 0 {
-                    return Ok(Some(path));
-                } else {
-                    keypoint_id = kp.pid;
-                    path.insert_str(0, &format!("{keypoint_id}."));
-                }
-            } else {
-                return Ok(None);
-            }
-        }
+        let path = Self::find_path(db, keypoint_id).await?;
+        Ok((!path.is_empty()).then(|| path.iter().join(".")))
     }
 }
 
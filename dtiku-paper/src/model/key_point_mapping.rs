@@ -0,0 +1,206 @@
+pub use super::_entities::key_point_mapping::*;
+use super::{key_point, FromType, KeyPoint};
+use anyhow::Context;
+use sea_orm::{
+    sea_query::OnConflict, sqlx::types::chrono::Local, ActiveModelBehavior, ActiveValue::Set,
+    ColumnTrait, ConnectionTrait, DbErr, EntityTrait, QueryFilter, QueryOrder, QuerySelect,
+};
+
+impl ActiveModelBehavior for ActiveModel {}
+
+/// 按知识点树同步的厂商，映射的parent_path为厂商父知识点名称路径
+const TREE_VENDORS: [FromType; 1] = [FromType::Fenbi];
+
+/// 同步题目时按知识点名称或章节名称查找知识点的厂商，映射的parent_path为空
+const NAME_VENDORS: [FromType; 4] = [
+    FromType::Fenbi,
+    FromType::Huatu,
+    FromType::Offcn,
+    FromType::Chinagwy,
+];
+
+impl Entity {
+    pub async fn find_key_point_id<C: ConnectionTrait>(
+        db: &C,
+        from_ty: FromType,
+        paper_type: i16,
+        parent_path: &str,
+        name: &str,
+    ) -> Result<Option<i32>, DbErr> {
+        Entity::find()
+            .select_only()
+            .column(Column::KeyPointId)
+            .filter(
+                Column::FromTy
+                    .eq(from_ty)
+                    .and(Column::PaperType.eq(paper_type))
+                    .and(Column::ParentPath.eq(parent_path))
+                    .and(Column::Name.eq(name)),
+            )
+            .into_tuple()
+            .one(db)
+            .await
+    }
+
+    /// 厂商知识点树中的知识点被合并或移动后，返回映射的规范知识点
+    pub async fn find_key_point<C: ConnectionTrait>(
+        db: &C,
+        from_ty: FromType,
+        paper_type: i16,
+        parent_path: &str,
+        name: &str,
+    ) -> anyhow::Result<Option<key_point::Model>> {
+        let id = Self::find_key_point_id(db, from_ty, paper_type, parent_path, name)
+            .await
+            .with_context(|| {
+                format!(
+                    "find key_point_mapping({from_ty},{paper_type},{parent_path},{name}) failed"
+                )
+            })?;
+        match id {
+            Some(id) => KeyPoint::find_by_id(id)
+                .one(db)
+                .await
+                .with_context(|| format!("find mapped key_point#{id} failed")),
+            None => Ok(None),
+        }
+    }
+
+    /// 厂商只提供知识点名称时，优先按映射查找，没有映射时按名称查找
+    pub async fn find_key_point_by_name<C: ConnectionTrait>(
+        db: &C,
+        from_ty: FromType,
+        paper_type: i16,
+        name: &str,
+    ) -> anyhow::Result<Option<key_point::Model>> {
+        if let Some(kp) = Self::find_key_point(db, from_ty, paper_type, "", name).await? {
+            return Ok(Some(kp));
+        }
+        KeyPoint::find_by_paper_type_and_name(db, paper_type, name)
+            .await
+            .with_context(|| format!("find paper_type#{paper_type} keypoint({name}) failed"))
+    }
+
+    pub async fn find_by_paper_type<C: ConnectionTrait>(
+        db: &C,
+        paper_type: i16,
+    ) -> anyhow::Result<Vec<Model>> {
+        Entity::find()
+            .filter(Column::PaperType.eq(paper_type))
+            .order_by_asc(Column::FromTy)
+            .order_by_asc(Column::ParentPath)
+            .order_by_asc(Column::Name)
+            .all(db)
+            .await
+            .with_context(|| format!("key_point_mapping::find_by_paper_type({paper_type}) failed"))
+    }
+
+    pub async fn save<C: ConnectionTrait>(
+        db: &C,
+        from_ty: FromType,
+        paper_type: i16,
+        parent_path: &str,
+        name: &str,
+        key_point_id: i32,
+    ) -> anyhow::Result<()> {
+        Entity::insert(ActiveModel {
+            from_ty: Set(from_ty),
+            paper_type: Set(paper_type),
+            parent_path: Set(parent_path.to_string()),
+            name: Set(name.to_string()),
+            key_point_id: Set(key_point_id),
+            created: Set(Local::now().naive_local()),
+        })
+        .on_conflict(
+            OnConflict::columns([
+                Column::FromTy,
+                Column::PaperType,
+                Column::ParentPath,
+                Column::Name,
+            ])
+            .update_columns([Column::KeyPointId, Column::Created])
+            .to_owned(),
+        )
+        .exec_without_returning(db)
+        .await
+        .with_context(|| {
+            format!("save key_point_mapping({from_ty},{paper_type},{parent_path},{name}) failed")
+        })?;
+        Ok(())
+    }
+
+    /// 知识点原来的位置(parent_path下的name)映射到key_point_id，
+    /// by_name为true时，只按名称查找的厂商也映射过去
+    pub async fn save_for_vendors<C: ConnectionTrait>(
+        db: &C,
+        paper_type: i16,
+        parent_path: &str,
+        name: &str,
+        key_point_id: i32,
+        by_name: bool,
+    ) -> anyhow::Result<()> {
+        for from_ty in TREE_VENDORS {
+            Self::save(db, from_ty, paper_type, parent_path, name, key_point_id).await?;
+        }
+        if by_name {
+            for from_ty in NAME_VENDORS {
+                Self::save(db, from_ty, paper_type, "", name, key_point_id).await?;
+            }
+        }
+        Ok(())
+    }
+
+    /// 知识点被合并后，指向它的映射改为指向合并后的知识点
+    pub async fn retarget<C: ConnectionTrait>(
+        db: &C,
+        from_id: i32,
+        to_id: i32,
+    ) -> anyhow::Result<()> {
+        Entity::update_many()
+            .col_expr(Column::KeyPointId, to_id.into())
+            .filter(Column::KeyPointId.eq(from_id))
+            .exec(db)
+            .await
+            .with_context(|| format!("retarget key_point_mapping {from_id}->{to_id} failed"))?;
+        Ok(())
+    }
+
+    pub async fn delete<C: ConnectionTrait>(
+        db: &C,
+        from_ty: FromType,
+        paper_type: i16,
+        parent_path: &str,
+        name: &str,
+    ) -> anyhow::Result<bool> {
+        let result = Entity::delete_many()
+            .filter(
+                Column::FromTy
+                    .eq(from_ty)
+                    .and(Column::PaperType.eq(paper_type))
+                    .and(Column::ParentPath.eq(parent_path))
+                    .and(Column::Name.eq(name)),
+            )
+            .exec(db)
+            .await
+            .with_context(|| {
+                format!(
+                    "delete key_point_mapping({from_ty},{paper_type},{parent_path},{name}) failed"
+                )
+            })?;
+        Ok(result.rows_affected > 0)
+    }
+
+    pub async fn delete_by_key_point_id<C: ConnectionTrait>(
+        db: &C,
+        key_point_id: i32,
+    ) -> anyhow::Result<()> {
+        Entity::delete_many()
+            .filter(Column::KeyPointId.eq(key_point_id))
+            .exec(db)
+            .await
+            .with_context(|| {
+                format!("delete key_point_mapping of key_point#{key_point_id} failed")
+            })?;
+        Ok(())
+    }
+}
@@ -1,21 +1,35 @@
 pub use super::_entities::material::*;
 use super::{PaperMaterial, _entities::paper_material};
 use crate::{
-    model::{assets, QuestionMaterial, SrcType},
+    domain::material::{is_same_material, material_text},
+    model::{
+        assets,
+        merge_table::MergeTable,
+        revision::{RevisionKind, RevisionSource},
+        QuestionMaterial, SrcType, UserNote,
+    },
     util::html,
 };
-use anyhow::{anyhow, Context};
+use anyhow::{anyhow, bail, Context};
 use gaoya::simhash::{SimHash, SimSipHasher128};
 use itertools::Itertools;
-use scraper::Html;
 use sea_orm::{
     ActiveModelTrait, ActiveValue::Set, ColumnTrait, ConnectionTrait, DbBackend, EntityTrait,
-    FromJsonQueryResult, FromQueryResult, QueryFilter, Statement,
+    FromJsonQueryResult, FromQueryResult, QueryFilter, QueryOrder, QuerySelect, Statement,
 };
 use serde::{Deserialize, Serialize};
 use spring::tracing;
 use std::collections::HashMap;
 
+/// 合并材料时需要改写的关联表
+const MERGE_TABLES: [MergeTable; 4] = [
+    MergeTable::new("paper_material", "material_id", &["paper_id"]),
+    MergeTable::new("question_material", "material_id", &["question_id"]),
+    MergeTable::new("assets_ref", "src_id", &["src_type", "assets_id"])
+        .filter("src_type = 'material'"),
+    MergeTable::plain("user_note", "target_id").filter("target_type = 'material'"),
+];
+
 #[derive(Debug, Clone)]
 pub struct Material {
     pub id: i32,
@@ -113,54 +127,139 @@ impl Entity {
         .await
         .context("Material::find_by_sim_hash() failed")
     }
+
+    /// 按id分批查询材料
+    pub async fn find_merge_batch<C>(db: &C, last_id: i32, limit: u64) -> anyhow::Result<Vec<Model>>
+    where
+        C: ConnectionTrait,
+    {
+        Entity::find()
+            .filter(Column::Id.gt(last_id))
+            .order_by_asc(Column::Id)
+            .limit(limit)
+            .all(db)
+            .await
+            .with_context(|| format!("material::find_merge_batch({last_id}) failed"))
+    }
+
+    /// id更小且content_sim_hash海明距离不超过max_distance的材料，按距离排序
+    pub async fn find_similar_before<C>(
+        db: &C,
+        id: i32,
+        max_distance: i32,
+    ) -> anyhow::Result<Vec<Model>>
+    where
+        C: ConnectionTrait,
+    {
+        Model::find_by_statement(Statement::from_sql_and_values(
+            DbBackend::Postgres,
+            r#"
+                SELECT m.id, m.content, m.extra
+                FROM material m, (SELECT content_sim_hash FROM material WHERE id = $1) s
+                WHERE m.id < $1 AND (m.content_sim_hash <~> s.content_sim_hash) <= $2
+                ORDER BY m.content_sim_hash <~> s.content_sim_hash
+                LIMIT 10
+            "#,
+            vec![id.into(), max_distance.into()],
+        ))
+        .all(db)
+        .await
+        .with_context(|| format!("material::find_similar_before({id}) failed"))
+    }
+
+    /// 把remove_id的材料合并到keep_id：试卷、题目、图片、笔记的关联都改写到keep_id，
+    /// 两边都有的关联保留keep_id的，最后删除remove_id的材料
+    pub async fn merge<C>(db: &C, keep_id: i32, remove_id: i32) -> anyhow::Result<()>
+    where
+        C: ConnectionTrait,
+    {
+        if keep_id == remove_id {
+            bail!("can't merge material#{keep_id} into itself");
+        }
+        let texts: HashMap<i32, String> = Entity::find_by_ids(db, vec![keep_id, remove_id])
+            .await?
+            .into_iter()
+            .map(|m| (m.id, material_text(&m.content)))
+            .collect();
+        if texts.get(&keep_id) != texts.get(&remove_id) {
+            UserNote::clear_highlights(db, SrcType::Material, remove_id).await?;
+        }
+        for table in MERGE_TABLES {
+            table.rewrite(db, keep_id, remove_id).await?;
+        }
+        Entity::delete_by_id(remove_id)
+            .exec(db)
+            .await
+            .with_context(|| format!("delete material#{remove_id} failed"))?;
+        Ok(())
+    }
+
+    /// 按快照覆盖材料，同时重新计算content_sim_hash
+    pub async fn restore<C>(db: &C, model: &Model) -> anyhow::Result<()>
+    where
+        C: ConnectionTrait,
+    {
+        let sim_hash = text_sim_hash(&material_text(&model.content));
+        let extra = serde_json::to_value(&model.extra).context("serialize extra failed")?;
+        db.execute(Statement::from_sql_and_values(
+            DbBackend::Postgres,
+            r#"
+                UPDATE material
+                SET content = $2, content_sim_hash = $3::bit(128), extra = $4
+                WHERE id = $1
+            "#,
+            vec![
+                model.id.into(),
+                model.content.clone().into(),
+                format!("{sim_hash:0128b}").into(),
+                extra.into(),
+            ],
+        ))
+        .await
+        .with_context(|| format!("restore material#{} failed", model.id))?;
+        Ok(())
+    }
+}
+
+fn text_sim_hash(text_content: &str) -> u128 {
+    let sim_hash = SimHash::<SimSipHasher128, u128, 128>::new(SimSipHasher128::new(1, 2));
+    sim_hash.create_signature(text_content.chars())
 }
 
 impl ActiveModel {
-    pub async fn insert_on_conflict<C>(mut self, db: &C) -> anyhow::Result<Model>
+    /// 覆盖已有的材料时保存修改前的版本
+    pub async fn insert_on_conflict<C>(
+        mut self,
+        db: &C,
+        source: &RevisionSource,
+    ) -> anyhow::Result<Model>
     where
         C: ConnectionTrait,
     {
         if let Some(content) = self.content.take() {
             // simhash算法去重
-            let text_content = {
-                Html::parse_fragment(&content)
-                    .root_element()
-                    .text()
-                    .join("")
-            };
-            let sim_hash = SimHash::<SimSipHasher128, u128, 128>::new(SimSipHasher128::new(1, 2));
-            let sim_hash = sim_hash.create_signature(text_content.chars());
+            let text_content = material_text(&content);
+            let sim_hash = text_sim_hash(&text_content);
             let ms = Entity::find_by_sim_hash(db, sim_hash).await?;
             for m in ms {
-                if m.content == content {
-                    // 完全相同，包括图片等html内容
+                let m_text_content = material_text(&m.content);
+                if is_same_material(&content, &text_content, &m.content, &m_text_content) {
                     return Ok(m);
                 }
-                let m_text_content = {
-                    Html::parse_fragment(&m.content)
-                        .root_element()
-                        .text()
-                        .join("")
-                };
-                let m_text_content_length = m_text_content.chars().count();
-                let text_content_length = text_content.chars().count();
-                if m_text_content_length > 100 && text_content_length > 100 {
-                    let edit_distance =
-                        textdistance::str::levenshtein(&m_text_content, &text_content);
-                    // 95%相似度: 100个字只有5个字不同
-                    if edit_distance * 20 < text_content_length.max(m_text_content_length) {
-                        return Ok(m);
-                    } else {
-                        tracing::warn!(
-                            "material text对比匹配失败==>{text_content}--->{m_text_content}"
-                        );
-                    }
-                }
+                tracing::warn!("material text对比匹配失败==>{text_content}--->{m_text_content}");
             }
 
             let extra = serde_json::to_value(&self.extra.take().unwrap_or_default())
                 .context("serialize extra failed")?;
-            let return_model = if let Some(id) = self.id.take() {
+            let id = self.id.take();
+            let previous = match id {
+                Some(id) => Entity::find_by_id(id)
+                    .one(db)
+                    .await
+                    .with_context(|| format!("find material#{id} failed"))?,
+                None => None,
+            };
+            let return_model = if let Some(id) = id {
                 let sql = r#"
 INSERT INTO material (id, content, content_sim_hash, extra)
 VALUES ($1, $2, $3::bit(128), $4)
@@ -231,6 +330,11 @@ SET
             .update(db)
             .await
             .context("update content failed")?;
+            if let Some(previous) = previous {
+                RevisionKind::Material
+                    .record(db, model.id, &previous, &model, source)
+                    .await?;
+            }
             Ok(model)
         } else {
             Err(anyhow!("content is required for material insertion"))
@@ -0,0 +1,98 @@
+pub use super::_entities::material_duplicate::*;
+use super::DuplicateStatus;
+use anyhow::Context;
+use sea_orm::{
+    sea_query::OnConflict, sqlx::types::chrono::Local, ActiveModelBehavior, ActiveValue::Set,
+    ColumnTrait, ConnectionTrait, EntityTrait, QueryFilter, QueryOrder,
+};
+use spring_sea_orm::pagination::{Page, Pagination, PaginationExt};
+
+impl ActiveModelBehavior for ActiveModel {}
+
+impl Entity {
+    /// 保存待人工核对的相似材料，已经在队列中的不重复写入
+    pub async fn save_candidate<C: ConnectionTrait>(
+        db: &C,
+        material_id: i32,
+        candidate_id: i32,
+        reason: &str,
+    ) -> anyhow::Result<()> {
+        let now = Local::now().naive_local();
+        Entity::insert(ActiveModel {
+            material_id: Set(material_id),
+            candidate_id: Set(candidate_id),
+            reason: Set(reason.to_string()),
+            status: Set(DuplicateStatus::Pending),
+            created: Set(now),
+            modified: Set(now),
+            ..Default::default()
+        })
+        .on_conflict(
+            OnConflict::columns([Column::MaterialId, Column::CandidateId])
+                .do_nothing()
+                .to_owned(),
+        )
+        .exec_without_returning(db)
+        .await
+        .with_context(|| {
+            format!("material_duplicate::save_candidate({material_id}, {candidate_id}) failed")
+        })?;
+        Ok(())
+    }
+
+    pub async fn find_page_by_status<C: ConnectionTrait>(
+        db: &C,
+        status: DuplicateStatus,
+        pagination: &Pagination,
+    ) -> anyhow::Result<Page<Model>> {
+        Entity::find()
+            .filter(Column::Status.eq(status))
+            .order_by_asc(Column::Id)
+            .page(db, pagination)
+            .await
+            .context("material_duplicate::find_page_by_status() failed")
+    }
+
+    pub async fn update_status<C: ConnectionTrait>(
+        db: &C,
+        id: i32,
+        status: DuplicateStatus,
+    ) -> anyhow::Result<()> {
+        Entity::update_many()
+            .set(ActiveModel {
+                status: Set(status),
+                modified: Set(Local::now().naive_local()),
+                ..Default::default()
+            })
+            .filter(Column::Id.eq(id))
+            .exec(db)
+            .await
+            .with_context(|| format!("update material_duplicate#{id} status failed"))?;
+        Ok(())
+    }
+
+    /// 材料被合并删除后，仍在等待核对的相关记录没有意义了，直接忽略
+    pub async fn ignore_pending_by_material_id<C: ConnectionTrait>(
+        db: &C,
+        material_id: i32,
+    ) -> anyhow::Result<()> {
+        Entity::update_many()
+            .set(ActiveModel {
+                status: Set(DuplicateStatus::Ignored),
+                modified: Set(Local::now().naive_local()),
+                ..Default::default()
+            })
+            .filter(Column::Status.eq(DuplicateStatus::Pending))
+            .filter(
+                Column::MaterialId
+                    .eq(material_id)
+                    .or(Column::CandidateId.eq(material_id)),
+            )
+            .exec(db)
+            .await
+            .with_context(|| {
+                format!("ignore material_duplicate of material#{material_id} failed")
+            })?;
+        Ok(())
+    }
+}
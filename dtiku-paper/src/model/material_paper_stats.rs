@@ -0,0 +1,37 @@
+pub use super::_entities::material_paper_stats::*;
+use anyhow::Context;
+use sea_orm::{
+    ColumnTrait, ConnectionTrait, DbBackend, EntityTrait, QueryFilter, QueryOrder, Statement,
+};
+use spring_sea_orm::pagination::{Page, Pagination, PaginationExt};
+
+impl Entity {
+    /// 至少被min_paper_count份试卷共用的材料，共用最多的排在前面
+    pub async fn find_page_by_min_paper_count<C: ConnectionTrait>(
+        db: &C,
+        min_paper_count: i64,
+        pagination: &Pagination,
+    ) -> anyhow::Result<Page<Model>> {
+        Entity::find()
+            .filter(Column::PaperCount.gte(min_paper_count))
+            .order_by_desc(Column::PaperCount)
+            .order_by_asc(Column::MaterialId)
+            .page(db, pagination)
+            .await
+            .with_context(|| {
+                format!(
+                    "material_paper_stats::find_page_by_min_paper_count({min_paper_count}) failed"
+                )
+            })
+    }
+
+    pub async fn refresh<C: ConnectionTrait>(db: &C) -> anyhow::Result<()> {
+        db.execute(Statement::from_string(
+            DbBackend::Postgres,
+            "refresh materialized view concurrently material_paper_stats",
+        ))
+        .await
+        .context("refresh material_paper_stats failed")?;
+        Ok(())
+    }
+}
@@ -0,0 +1,95 @@
+use anyhow::Context;
+use itertools::Itertools;
+use sea_orm::{ConnectionTrait, DbBackend, Statement};
+
+/// 合并题目、材料时需要改写的关联表
+pub(crate) struct MergeTable {
+    table: &'static str,
+    /// 被合并的id列
+    column: &'static str,
+    /// 与id组成唯一键的其他列，为空时id列本身唯一，None表示没有唯一键
+    unique_columns: Option<&'static [&'static str]>,
+    filter: Option<&'static str>,
+}
+
+impl MergeTable {
+    pub(crate) const fn new(
+        table: &'static str,
+        column: &'static str,
+        unique_columns: &'static [&'static str],
+    ) -> Self {
+        Self {
+            table,
+            column,
+            unique_columns: Some(unique_columns),
+            filter: None,
+        }
+    }
+
+    /// 没有唯一键的表，关联数据全部改写
+    pub(crate) const fn plain(table: &'static str, column: &'static str) -> Self {
+        Self {
+            table,
+            column,
+            unique_columns: None,
+            filter: None,
+        }
+    }
+
+    pub(crate) const fn filter(self, filter: &'static str) -> Self {
+        Self {
+            filter: Some(filter),
+            ..self
+        }
+    }
+
+    /// 没有冲突的关联数据改写为keep_id，剩下和keep_id冲突的直接删除
+    pub(crate) async fn rewrite<C: ConnectionTrait>(
+        &self,
+        db: &C,
+        keep_id: i32,
+        remove_id: i32,
+    ) -> anyhow::Result<()> {
+        let Self {
+            table,
+            column,
+            unique_columns,
+            filter,
+        } = self;
+        let filter = filter.map(|f| format!("and {f}")).unwrap_or_default();
+        let conflict = match unique_columns {
+            Some(columns) => {
+                let same_key = std::iter::once(format!("k.{column} = $1"))
+                    .chain(columns.iter().map(|c| format!("k.{c} = t.{c}")))
+                    .join(" and ");
+                format!("and not exists (select 1 from {table} k where {same_key})")
+            }
+            None => String::new(),
+        };
+        let update = format!(
+            r#"
+            update {table} t set {column} = $1
+            where t.{column} = $2 {filter}
+            {conflict}
+            "#
+        );
+        db.execute(Statement::from_sql_and_values(
+            DbBackend::Postgres,
+            update,
+            [keep_id.into(), remove_id.into()],
+        ))
+        .await
+        .with_context(|| {
+            format!("rewrite {table}.{column} from {remove_id} to {keep_id} failed")
+        })?;
+        let delete = format!("delete from {table} where {column} = $1 {filter}");
+        db.execute(Statement::from_sql_and_values(
+            DbBackend::Postgres,
+            delete,
+            [remove_id.into()],
+        ))
+        .await
+        .with_context(|| format!("delete {table}.{column}={remove_id} failed"))?;
+        Ok(())
+    }
+}
@@ -0,0 +1,72 @@
+pub use super::_entities::mock_paper::*;
+use anyhow::Context;
+use sea_orm::{
+    prelude::DateTime, sqlx::types::chrono::Local, ActiveModelBehavior, ActiveValue::Set,
+    ColumnTrait, ConnectionTrait, DbBackend, DbErr, EntityTrait, PaginatorTrait, QueryFilter,
+    Statement,
+};
+use spring::async_trait;
+
+#[async_trait]
+impl ActiveModelBehavior for ActiveModel {
+    async fn before_save<C>(mut self, _db: &C, insert: bool) -> Result<Self, DbErr>
+    where
+        C: ConnectionTrait,
+    {
+        if insert {
+            self.created = Set(Local::now().naive_local());
+        }
+        Ok(self)
+    }
+}
+
+impl Entity {
+    /// 用户在该地区最近生成、还没有交卷的个人模拟卷
+    pub async fn find_unfinished_by_user<C: ConnectionTrait>(
+        db: &C,
+        user_id: i32,
+        paper_type: i16,
+        label_id: i32,
+    ) -> anyhow::Result<Option<i32>> {
+        let row = db
+            .query_one(Statement::from_sql_and_values(
+                DbBackend::Postgres,
+                r#"
+                    SELECT mp.paper_id
+                    FROM mock_paper mp
+                    JOIN paper p ON p.id = mp.paper_id
+                    WHERE mp.user_id = $1
+                    AND p.paper_type = $2
+                    AND mp.label_id = $3
+                    AND NOT EXISTS (
+                        SELECT 1 FROM exercise_record r
+                        WHERE r.paper_id = mp.paper_id AND r.user_id = $1 AND r.finished
+                    )
+                    ORDER BY mp.created DESC
+                    LIMIT 1
+                "#,
+                [user_id.into(), paper_type.into(), label_id.into()],
+            ))
+            .await
+            .with_context(|| {
+                format!(
+                    "mock_paper::find_unfinished_by_user({user_id},{paper_type},{label_id}) failed"
+                )
+            })?;
+        row.map(|r| r.try_get("", "paper_id"))
+            .transpose()
+            .context("parse mock_paper.paper_id failed")
+    }
+
+    pub async fn count_by_user_since<C: ConnectionTrait>(
+        db: &C,
+        user_id: i32,
+        since: DateTime,
+    ) -> anyhow::Result<u64> {
+        Entity::find()
+            .filter(Column::UserId.eq(user_id).and(Column::Created.gte(since)))
+            .count(db)
+            .await
+            .with_context(|| format!("mock_paper::count_by_user_since({user_id}) failed"))
+    }
+}
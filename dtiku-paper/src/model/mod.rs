@@ -1,19 +1,38 @@
 mod _entities;
+pub mod adaptive_answer;
 pub mod assets;
+pub mod essay_grade;
 pub mod exam_category;
+pub mod exam_scoring;
+pub mod exercise_answer;
+pub mod exercise_record;
 pub mod key_point;
+pub mod key_point_mapping;
 pub mod label;
 pub mod material;
+pub mod material_duplicate;
+pub mod material_paper_stats;
+pub(crate) mod merge_table;
+pub mod mock_paper;
 pub mod paper;
 pub mod paper_material;
 pub mod paper_question;
 pub mod query;
 pub mod question;
+pub mod question_duplicate;
+pub mod question_feedback;
+pub mod question_irt;
 pub mod question_keypoint;
 pub mod question_keypoint_stats;
+pub mod question_keypoint_tag;
 pub mod question_material;
+pub mod question_search_index;
+pub mod revision;
 pub mod scraper_solution;
 pub mod solution;
+pub mod user_ability;
+pub mod user_note;
+pub mod wrong_question;
 
 pub use _entities::prelude::*;
 pub use _entities::sea_orm_active_enums::*;
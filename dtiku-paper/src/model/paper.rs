@@ -1,15 +1,21 @@
 use std::ops::RangeInclusive;
 
 pub use super::_entities::paper::*;
-use crate::query::paper::ListPaperQuery;
+use crate::{
+    model::revision::{RevisionKind, RevisionSource},
+    query::paper::ListPaperQuery,
+};
 use anyhow::Context;
 use sea_orm::{
-    sea_query::OnConflict, ColumnTrait, ConnectionTrait, DbErr, EntityTrait, FromJsonQueryResult,
-    QueryFilter, QueryOrder, QuerySelect,
+    sea_query::OnConflict, ActiveValue, ColumnTrait, ConnectionTrait, EntityTrait,
+    FromJsonQueryResult, QueryFilter, QueryOrder, QuerySelect,
 };
 use serde::{Deserialize, Serialize};
 use spring_sea_orm::pagination::{Page, PaginationExt};
 
+/// 试卷没有配置考试时长时每道题按1分钟估算
+const DEFAULT_SECONDS_PER_QUESTION: i64 = 60;
+
 #[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize, FromJsonQueryResult)]
 #[serde(tag = "type")]
 pub enum PaperExtra {
@@ -77,6 +83,22 @@ impl PaperExtra {
     }
 }
 
+impl Model {
+    /// 限时模考的时长(秒)，申论试卷不支持限时模考
+    pub fn time_limit_secs(&self) -> Option<i64> {
+        let PaperExtra::Chapters(cs) = &self.extra else {
+            return None;
+        };
+        Some(match self.time_limit {
+            Some(minutes) if minutes > 0 => minutes as i64 * 60,
+            _ => {
+                let count: i64 = cs.chapters.iter().map(|c| c.count.max(0) as i64).sum();
+                count * DEFAULT_SECONDS_PER_QUESTION
+            }
+        })
+    }
+}
+
 #[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize, FromJsonQueryResult)]
 pub struct Chapters {
     pub desc: Option<String>,
@@ -179,14 +201,49 @@ impl Entity {
             .await
             .with_context(|| format!("find_by_paper_type_and_id_gt({paper_id},{last_id}) failed"))
     }
+
+    /// 某个地区最近几年的真题
+    pub async fn find_recent_by_label<C: ConnectionTrait>(
+        db: &C,
+        paper_type: i16,
+        label_id: i32,
+        limit: u64,
+    ) -> anyhow::Result<Vec<Model>> {
+        Entity::find()
+            .filter(
+                Column::PaperType
+                    .eq(paper_type)
+                    .and(Column::LabelId.eq(label_id)),
+            )
+            .order_by_desc(Column::Year)
+            .order_by_desc(Column::Id)
+            .limit(limit)
+            .all(db)
+            .await
+            .with_context(|| format!("find_recent_by_label({paper_type},{label_id}) failed"))
+    }
 }
 
 impl ActiveModel {
-    pub async fn insert_on_conflict<C>(self, db: &C) -> Result<Model, DbErr>
+    /// 覆盖同一标签下的同名试卷时保存修改前的版本
+    pub async fn insert_on_conflict<C>(
+        self,
+        db: &C,
+        source: &RevisionSource,
+    ) -> anyhow::Result<Model>
     where
         C: ConnectionTrait,
     {
-        Entity::insert(self)
+        let previous = match (&self.label_id, &self.title) {
+            (ActiveValue::Set(label_id), ActiveValue::Set(title)) => Entity::find()
+                .filter(Column::LabelId.eq(*label_id))
+                .filter(Column::Title.eq(title))
+                .one(db)
+                .await
+                .with_context(|| format!("find paper({label_id},{title}) failed"))?,
+            _ => None,
+        };
+        let model = Entity::insert(self)
             .on_conflict(
                 OnConflict::columns([Column::LabelId, Column::Title])
                     .update_column(Column::Extra)
@@ -194,5 +251,12 @@ impl ActiveModel {
             )
             .exec_with_returning(db)
             .await
+            .context("insert paper failed")?;
+        if let Some(previous) = previous {
+            RevisionKind::Paper
+                .record(db, model.id, &previous, &model, source)
+                .await?;
+        }
+        Ok(model)
     }
 }
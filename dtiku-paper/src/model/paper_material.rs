@@ -1,5 +1,62 @@
 pub use super::_entities::paper_material::*;
-use sea_orm::{sea_query::OnConflict, ConnectionTrait, DbErr, EntityTrait};
+use crate::model::paper;
+use anyhow::Context;
+use itertools::Itertools;
+use sea_orm::{
+    prelude::Expr,
+    sea_query::{OnConflict, Query},
+    ColumnTrait, ConnectionTrait, DbErr, EntityTrait, Order, QueryFilter, QueryOrder, QuerySelect,
+};
+use std::collections::HashMap;
+
+impl Entity {
+    /// 材料 -> 使用该材料的试卷
+    pub async fn find_paper_ids_by_material_ids<C: ConnectionTrait>(
+        db: &C,
+        material_ids: Vec<i32>,
+    ) -> anyhow::Result<HashMap<i32, Vec<i32>>> {
+        let rows = Entity::find()
+            .filter(Column::MaterialId.is_in(material_ids))
+            .order_by_asc(Column::PaperId)
+            .all(db)
+            .await
+            .context("paper_material::find_paper_ids_by_material_ids() failed")?;
+        Ok(rows
+            .into_iter()
+            .map(|r| (r.material_id, r.paper_id))
+            .into_group_map())
+    }
+
+    /// 从同类型的其他试卷中随机抽取材料
+    pub async fn sample_material_ids<C: ConnectionTrait>(
+        db: &C,
+        paper_type: i16,
+        excluded_paper_ids: Vec<i32>,
+        limit: u64,
+    ) -> anyhow::Result<Vec<i32>> {
+        let mut papers = Query::select()
+            .column(paper::Column::Id)
+            .from(paper::Entity)
+            .and_where(paper::Column::PaperType.eq(paper_type))
+            .to_owned();
+        if !excluded_paper_ids.is_empty() {
+            papers.and_where(paper::Column::Id.is_not_in(excluded_paper_ids));
+        }
+        Entity::find()
+            .select_only()
+            .column(Column::MaterialId)
+            .filter(Column::PaperId.in_subquery(papers))
+            .filter(Expr::cust(
+                "not exists (select 1 from mock_paper mp where mp.paper_id = paper_material.paper_id)",
+            ))
+            .order_by(Expr::cust("random()"), Order::Asc)
+            .limit(limit)
+            .into_tuple()
+            .all(db)
+            .await
+            .with_context(|| format!("paper_material::sample_material_ids({paper_type}) failed"))
+    }
+}
 
 impl ActiveModel {
     pub async fn insert_on_conflict<C>(self, db: &C) -> Result<Model, DbErr>
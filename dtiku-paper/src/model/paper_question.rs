@@ -1,12 +1,36 @@
 pub use super::_entities::paper_question::*;
-use crate::query::question::PaperQuestionQuery;
+use crate::{domain::question::QuestionCorrectRatio, query::question::PaperQuestionQuery};
 use anyhow::Context;
 use sea_orm::{
+    prelude::Expr,
     sea_query::{IntoCondition, OnConflict},
-    ColumnTrait, ConnectionTrait, DbErr, EntityTrait, QueryFilter, QuerySelect,
+    ColumnTrait, ConnectionTrait, DbErr, EntityTrait, FromQueryResult, Order, QueryFilter,
+    QueryOrder, QuerySelect,
 };
 use std::collections::HashMap;
 
+/// 题目在试卷中的知识点和难度，用于分析真题的题型分布
+#[derive(Debug, FromQueryResult)]
+pub struct QuestionProfile {
+    pub paper_id: i32,
+    pub question_id: i32,
+    pub sort: i16,
+    pub keypoint_path: Option<String>,
+    pub correct_ratio: Option<f32>,
+}
+
+/// 题目在某个试卷类型下的厂商正确率和知识点，用于IRT校准
+#[derive(Debug, FromQueryResult)]
+pub struct ItemProfile {
+    pub question_id: i32,
+    pub correct_ratio: Option<f32>,
+    pub keypoint_path: Option<String>,
+}
+
+/// 排除生成的模拟卷，只保留真题
+const NOT_MOCK_PAPER: &str =
+    "not exists (select 1 from mock_paper mp where mp.paper_id = paper_question.paper_id)";
+
 impl Entity {
     pub async fn find_by_question_id<C>(db: &C, question_id: i32) -> anyhow::Result<Vec<Model>>
     where
@@ -26,6 +50,68 @@ impl Entity {
             .with_context(|| format!("paper_question::find_by_question_id({question_id}) failed"))
     }
 
+    /// 题目出处，不包含模拟卷
+    pub async fn find_source_by_question_id_in<C>(
+        db: &C,
+        question_ids: Vec<i32>,
+    ) -> anyhow::Result<Vec<Model>>
+    where
+        C: ConnectionTrait,
+    {
+        Entity::find()
+            .select_only()
+            .columns([
+                Column::PaperId,
+                Column::QuestionId,
+                Column::Sort,
+                Column::PaperType,
+            ])
+            .filter(Column::QuestionId.is_in(question_ids))
+            .filter(Expr::cust(NOT_MOCK_PAPER))
+            .all(db)
+            .await
+            .context("paper_question::find_source_by_question_id_in() failed")
+    }
+
+    /// 题目在该试卷类型下的一张出处试卷，不包含模拟卷，优先选择有知识点的试卷
+    pub async fn find_source_by_question_id<C>(
+        db: &C,
+        question_id: i32,
+        paper_type: i16,
+    ) -> anyhow::Result<Option<Model>>
+    where
+        C: ConnectionTrait,
+    {
+        Entity::find()
+            .select_only()
+            .columns([
+                Column::PaperId,
+                Column::QuestionId,
+                Column::Sort,
+                Column::PaperType,
+                Column::CorrectRatio,
+                Column::UserCorrectRatio,
+                Column::UserAnswerCount,
+                Column::BlendedCorrectRatio,
+            ])
+            .column_as(Expr::cust("keypoint_path::text"), "keypoint_path")
+            .filter(
+                Column::QuestionId
+                    .eq(question_id)
+                    .and(Column::PaperType.eq(paper_type)),
+            )
+            .filter(Expr::cust(NOT_MOCK_PAPER))
+            .order_by(Expr::cust("keypoint_path is null"), Order::Asc)
+            .order_by_asc(Column::PaperId)
+            .one(db)
+            .await
+            .with_context(|| {
+                format!(
+                    "paper_question::find_source_by_question_id({question_id},{paper_type}) failed"
+                )
+            })
+    }
+
     pub async fn find_by_question_id_in<C>(
         db: &C,
         question_ids: Vec<i32>,
@@ -47,6 +133,34 @@ impl Entity {
             .with_context(|| format!("paper_question::find_by_question_id_in() failed"))
     }
 
+    /// 查询题目所属的知识点路径，同一道题在多张试卷中时任取其一
+    pub async fn find_keypoint_path_by_question_id_in<C>(
+        db: &C,
+        question_ids: Vec<i32>,
+    ) -> anyhow::Result<HashMap<i32, String>>
+    where
+        C: ConnectionTrait,
+    {
+        if question_ids.is_empty() {
+            return Ok(HashMap::new());
+        }
+        Ok(Entity::find()
+            .select_only()
+            .column(Column::QuestionId)
+            .column_as(Expr::cust("keypoint_path::text"), "keypoint_path")
+            .filter(
+                Column::QuestionId
+                    .is_in(question_ids)
+                    .and(Column::KeypointPath.is_not_null()),
+            )
+            .into_tuple::<(i32, String)>()
+            .all(db)
+            .await
+            .context("paper_question::find_keypoint_path_by_question_id_in() failed")?
+            .into_iter()
+            .collect())
+    }
+
     pub async fn find_by_paper_id<C>(db: &C, paper_id: i32) -> anyhow::Result<Vec<Model>>
     where
         C: ConnectionTrait,
@@ -128,6 +242,285 @@ impl Entity {
                 format!("paper_question::find_by_paper_type_and_qid_gt({paper_type},{qid}) failed")
             })
     }
+
+    pub async fn find_profile_by_paper_ids<C: ConnectionTrait>(
+        db: &C,
+        paper_ids: Vec<i32>,
+    ) -> anyhow::Result<Vec<QuestionProfile>> {
+        Entity::find()
+            .select_only()
+            .columns([Column::PaperId, Column::QuestionId, Column::Sort])
+            .column_as(Expr::cust("keypoint_path::text"), "keypoint_path")
+            .column_as(
+                Expr::cust("coalesce(blended_correct_ratio, correct_ratio)"),
+                "correct_ratio",
+            )
+            .filter(Column::PaperId.is_in(paper_ids))
+            .into_model::<QuestionProfile>()
+            .all(db)
+            .await
+            .context("paper_question::find_profile_by_paper_ids() failed")
+    }
+
+    /// 随机抽取知识点及其子知识点下没有材料的题目，正确率越接近目标越优先；
+    /// user_id不为空时跳过该用户做过的题
+    pub async fn sample_question_ids<C: ConnectionTrait>(
+        db: &C,
+        paper_type: i16,
+        keypoint_path: Option<&str>,
+        correct_ratio: f32,
+        excluded: Vec<i32>,
+        user_id: Option<i32>,
+        limit: u64,
+    ) -> anyhow::Result<Vec<i32>> {
+        let mut cond = Column::PaperType.eq(paper_type).into_condition();
+        cond = match keypoint_path {
+            Some(path) => cond.add(Expr::cust_with_values("keypoint_path <@ $1::ltree", [path])),
+            None => cond.add(Column::KeypointPath.is_null()),
+        };
+        if !excluded.is_empty() {
+            cond = cond.add(Column::QuestionId.is_not_in(excluded));
+        }
+        cond = cond.add(Expr::cust(
+            "not exists (select 1 from question_material qm where qm.question_id = paper_question.question_id)",
+        ));
+        // 没有知识点时也不能从生成的模拟卷里抽题
+        cond = cond.add(Expr::cust(NOT_MOCK_PAPER));
+        if let Some(user_id) = user_id {
+            cond = cond.add(Expr::cust_with_values(
+                r#"not exists (
+                    select 1 from exercise_answer a join exercise_record r on r.id = a.record_id
+                    where r.user_id = $1 and a.question_id = paper_question.question_id
+                ) and not exists (
+                    select 1 from wrong_question w
+                    where w.user_id = $2 and w.question_id = paper_question.question_id
+                )"#,
+                [user_id, user_id],
+            ));
+        }
+        Entity::find()
+            .select_only()
+            .column(Column::QuestionId)
+            .filter(cond)
+            .group_by(Column::QuestionId)
+            .order_by(
+                Expr::cust_with_values(
+                    "min(abs(coalesce(blended_correct_ratio, correct_ratio, $1) - $2)) + random() * 5",
+                    [correct_ratio, correct_ratio],
+                ),
+                Order::Asc,
+            )
+            .limit(limit)
+            .into_tuple()
+            .all(db)
+            .await
+            .with_context(|| {
+                format!("paper_question::sample_question_ids({paper_type},{keypoint_path:?}) failed")
+            })
+    }
+
+    pub async fn find_paper_types<C: ConnectionTrait>(db: &C) -> anyhow::Result<Vec<i16>> {
+        Entity::find()
+            .select_only()
+            .column(Column::PaperType)
+            .distinct()
+            .order_by_asc(Column::PaperType)
+            .into_tuple()
+            .all(db)
+            .await
+            .context("paper_question::find_paper_types() failed")
+    }
+
+    /// 题目出现在多张试卷中时取厂商正确率的平均值
+    pub async fn find_item_profiles_by_paper_type<C: ConnectionTrait>(
+        db: &C,
+        paper_type: i16,
+    ) -> anyhow::Result<Vec<ItemProfile>> {
+        Entity::find()
+            .select_only()
+            .column(Column::QuestionId)
+            .column_as(Expr::cust("avg(correct_ratio)::float4"), "correct_ratio")
+            .column_as(Expr::cust("max(keypoint_path::text)"), "keypoint_path")
+            .filter(Column::PaperType.eq(paper_type))
+            .group_by(Column::QuestionId)
+            .into_model::<ItemProfile>()
+            .all(db)
+            .await
+            .with_context(|| {
+                format!("paper_question::find_item_profiles_by_paper_type({paper_type}) failed")
+            })
+    }
+
+    /// 查询题目在各试卷中的正确率统计
+    pub async fn find_correct_ratio_by_question_id<C: ConnectionTrait>(
+        db: &C,
+        question_id: i32,
+    ) -> anyhow::Result<Vec<Model>> {
+        Entity::find()
+            .select_only()
+            .columns([
+                Column::PaperId,
+                Column::QuestionId,
+                Column::Sort,
+                Column::PaperType,
+                Column::CorrectRatio,
+                Column::UserCorrectRatio,
+                Column::UserAnswerCount,
+                Column::BlendedCorrectRatio,
+            ])
+            .filter(Column::QuestionId.eq(question_id))
+            .order_by_asc(Column::PaperId)
+            .all(db)
+            .await
+            .with_context(|| {
+                format!("paper_question::find_correct_ratio_by_question_id({question_id}) failed")
+            })
+    }
+
+    pub async fn find_vendor_correct_ratio_by_question_id_in<C: ConnectionTrait>(
+        db: &C,
+        question_ids: Vec<i32>,
+    ) -> anyhow::Result<Vec<(i32, i32, Option<f32>)>> {
+        Entity::find()
+            .select_only()
+            .columns([Column::PaperId, Column::QuestionId, Column::CorrectRatio])
+            .filter(Column::QuestionId.is_in(question_ids))
+            .into_tuple()
+            .all(db)
+            .await
+            .context("paper_question::find_vendor_correct_ratio_by_question_id_in() failed")
+    }
+
+    /// 已有知识点但试卷中还没有keypoint_path的题目
+    pub async fn find_question_ids_missing_keypoint_path<C: ConnectionTrait>(
+        db: &C,
+        paper_type: i16,
+        last_qid: i32,
+        limit: u64,
+    ) -> anyhow::Result<Vec<i32>> {
+        Entity::find()
+            .select_only()
+            .column(Column::QuestionId)
+            .distinct()
+            .filter(
+                Column::PaperType
+                    .eq(paper_type)
+                    .and(Column::QuestionId.gt(last_qid))
+                    .and(Column::KeypointPath.is_null())
+                    .and(Expr::cust(
+                        "EXISTS (SELECT 1 FROM question_key_point qkp WHERE qkp.question_id = paper_question.question_id)",
+                    )),
+            )
+            .order_by_asc(Column::QuestionId)
+            .limit(limit)
+            .into_tuple()
+            .all(db)
+            .await
+            .with_context(|| {
+                format!("paper_question::find_question_ids_missing_keypoint_path({paper_type},{last_qid}) failed")
+            })
+    }
+
+    /// 只补全还没有keypoint_path的试卷，厂商同步的知识点保持不变
+    pub async fn fill_keypoint_path<C: ConnectionTrait>(
+        db: &C,
+        question_id: i32,
+        keypoint_path: &str,
+    ) -> anyhow::Result<u64> {
+        Ok(Entity::update_many()
+            .col_expr(
+                Column::KeypointPath,
+                Expr::cust_with_values("CAST($1 AS ltree)", [keypoint_path]),
+            )
+            .filter(
+                Column::QuestionId
+                    .eq(question_id)
+                    .and(Column::KeypointPath.is_null()),
+            )
+            .exec(db)
+            .await
+            .with_context(|| {
+                format!("paper_question::fill_keypoint_path({question_id},{keypoint_path}) failed")
+            })?
+            .rows_affected)
+    }
+
+    /// 知识点在树中移动或合并后，把old_path及其子路径改写到new_path下
+    pub async fn rebase_keypoint_path<C: ConnectionTrait>(
+        db: &C,
+        old_path: &str,
+        new_path: &str,
+    ) -> anyhow::Result<u64> {
+        Ok(Entity::update_many()
+            .col_expr(
+                Column::KeypointPath,
+                Expr::cust_with_values(
+                    r#"CASE WHEN nlevel(keypoint_path) = nlevel(CAST($1 AS ltree))
+                    THEN CAST($2 AS ltree)
+                    ELSE CAST($3 AS ltree) || subpath(keypoint_path, nlevel(CAST($4 AS ltree)))
+                    END"#,
+                    [old_path, new_path, new_path, old_path],
+                ),
+            )
+            .filter(Expr::cust_with_values(
+                "keypoint_path <@ CAST($1 AS ltree)",
+                [old_path],
+            ))
+            .exec(db)
+            .await
+            .with_context(|| {
+                format!("paper_question::rebase_keypoint_path({old_path},{new_path}) failed")
+            })?
+            .rows_affected)
+    }
+
+    /// 根知识点被删除后，清空该知识点下题目的keypoint_path
+    pub async fn clear_keypoint_path<C: ConnectionTrait>(
+        db: &C,
+        keypoint_path: &str,
+    ) -> anyhow::Result<u64> {
+        Ok(Entity::update_many()
+            .col_expr(Column::KeypointPath, Expr::value(Option::<String>::None))
+            .filter(Expr::cust_with_values(
+                "keypoint_path <@ CAST($1 AS ltree)",
+                [keypoint_path],
+            ))
+            .exec(db)
+            .await
+            .with_context(|| {
+                format!("paper_question::clear_keypoint_path({keypoint_path}) failed")
+            })?
+            .rows_affected)
+    }
+
+    /// 更新本站用户的正确率统计，厂商正确率保持不变
+    pub async fn update_user_correct_ratio<C: ConnectionTrait>(
+        db: &C,
+        paper_id: i32,
+        question_id: i32,
+        ratio: &QuestionCorrectRatio,
+    ) -> anyhow::Result<u64> {
+        Ok(Entity::update_many()
+            .col_expr(Column::UserCorrectRatio, Expr::value(ratio.user))
+            .col_expr(
+                Column::UserAnswerCount,
+                Expr::value(ratio.user_answer_count),
+            )
+            .col_expr(Column::BlendedCorrectRatio, Expr::value(ratio.blended))
+            .filter(
+                Column::PaperId
+                    .eq(paper_id)
+                    .and(Column::QuestionId.eq(question_id)),
+            )
+            .exec(db)
+            .await
+            .with_context(|| {
+                format!(
+                    "paper_question::update_user_correct_ratio({paper_id},{question_id}) failed"
+                )
+            })?
+            .rows_affected)
+    }
 }
 
 impl ActiveModel {
@@ -1,18 +1,24 @@
 pub use super::_entities::question::*;
 use super::{paper, Paper, PaperQuestion, _entities::solution, material, SrcType};
 use crate::{
-    domain::question::QuestionSearch,
-    model::{assets, paper_question, Solution},
+    domain::dedup::{DedupDecision, DedupText, QuestionDeduplicator},
+    model::{
+        assets, paper_question,
+        revision::{RevisionKind, RevisionSource},
+        QuestionDuplicate, Solution,
+    },
     util::html,
 };
 use anyhow::Context;
 use itertools::Itertools;
 use regex::Regex;
-use scraper::Html;
 use sea_orm::{
-    prelude::PgVector, sea_query::OnConflict, ActiveModelTrait, ActiveValue::Set, ColumnTrait,
-    ConnectionTrait, DerivePartialModel, EntityTrait, FromJsonQueryResult, FromQueryResult,
-    QueryFilter, QuerySelect, Statement,
+    prelude::{Expr, PgVector},
+    sea_query::OnConflict,
+    ActiveModelTrait,
+    ActiveValue::{self, Set},
+    ColumnTrait, ConnectionTrait, DerivePartialModel, EntityTrait, FromJsonQueryResult,
+    FromQueryResult, QueryFilter, QueryOrder, QuerySelect, Statement,
 };
 use serde::{Deserialize, Serialize};
 use spring::tracing;
@@ -20,6 +26,20 @@ use std::collections::HashMap;
 use std::sync::OnceLock;
 use strum::Display;
 
+/// 索引行写入之后题目或其材料有新的修订记录
+const STALE_SEARCH_INDEX: &str = r#"exists (
+    select 1 from question_search_index s
+    where s.question_id = question.id
+    and (
+        exists (select 1 from question_revision r where r.target_id = question.id and r.created > s.modified)
+        or exists (
+            select 1 from question_material qm
+            join material_revision mr on mr.target_id = qm.material_id
+            where qm.question_id = question.id and mr.created > s.modified
+        )
+    )
+)"#;
+
 macro_rules! question_methods {
     () => {
         pub fn option_len(&self) -> usize {
@@ -43,6 +63,24 @@ macro_rules! question_methods {
             }
         }
 
+        /// 按标准计分方案判断作答是否正确
+        pub fn check_answer(&self, user_answer: &str) -> bool {
+            crate::domain::scoring::check_answer(
+                &self.extra,
+                self.solutions.as_deref(),
+                user_answer,
+            )
+        }
+
+        /// 按计分方案给出用户作答的得分率
+        pub fn score_answer(
+            &self,
+            scheme: &crate::domain::scoring::ScoringScheme,
+            user_answer: &str,
+        ) -> f32 {
+            scheme.score_solutions(&self.extra, self.solutions.as_deref(), user_answer)
+        }
+
         pub fn abbr(&self, size: usize) -> String {
             let text = {
                 let html = scraper::Html::parse_fragment(&self.content);
@@ -175,6 +213,21 @@ pub struct QuestionSelect {
     pub extra: QuestionExtra,
 }
 
+#[derive(Clone, Debug, DerivePartialModel, FromQueryResult)]
+#[sea_orm(entity = "Entity")]
+pub struct QuestionIndexSelect {
+    #[sea_orm(from_col = "id")]
+    pub id: i32,
+    #[sea_orm(from_col = "exam_id")]
+    pub exam_id: i16,
+    #[sea_orm(from_col = "paper_type")]
+    pub paper_type: i16,
+    #[sea_orm(from_col = "content")]
+    pub content: String,
+    #[sea_orm(from_col = "extra")]
+    pub extra: QuestionExtra,
+}
+
 impl QuestionSelect {
     fn with_pid_num(self, num_map: &HashMap<i32, (i32, i16)>) -> Question {
         Question {
@@ -344,6 +397,14 @@ impl QuestionExtra {
         }
     }
 
+    /// 申论问答题的各问，其他题型为空
+    pub fn qa(&self) -> &[QA] {
+        match &self {
+            Self::StepByStepQA { qa } | Self::ClosedEndedQA { qa } | Self::OpenEndedQA { qa } => qa,
+            _ => &[],
+        }
+    }
+
     async fn replace_img_src<C: ConnectionTrait>(&self, qid: i32, db: &C) -> anyhow::Result<Self> {
         Ok(match self {
             Self::SingleChoice { options } => Self::SingleChoice {
@@ -401,7 +462,20 @@ impl QuestionExtra {
         Ok(r)
     }
 
-    fn options_html(&self) -> String {
+    /// 选择题的各个选项，其他题型为空
+    pub fn options(&self) -> &[QuestionChoice] {
+        match &self {
+            Self::SingleChoice { options }
+            | Self::MultiChoice { options }
+            | Self::IndefiniteChoice { options }
+            | Self::BlankChoice { options }
+            | Self::WordSelection { options }
+            | Self::Compose { options } => options,
+            _ => &[],
+        }
+    }
+
+    pub(crate) fn options_html(&self) -> String {
         match &self {
             Self::SingleChoice { options }
             | Self::MultiChoice { options }
@@ -512,35 +586,43 @@ impl Entity {
             .collect())
     }
 
-    pub async fn search_question<C>(
+    /// 按id顺序分批读取题目，用于建全文索引
+    pub async fn find_index_batch<C>(
         db: &C,
-        search: &QuestionSearch,
-    ) -> anyhow::Result<Vec<QuestionWithPaper>>
+        last_id: i32,
+        limit: u64,
+    ) -> anyhow::Result<Vec<QuestionIndexSelect>>
     where
         C: ConnectionTrait,
     {
-        let qs = Entity::find()
-            .filter(search.clone())
-            .limit(100)
-            .into_partial_model::<QuestionSelect>()
+        Entity::find()
+            .filter(Column::Id.gt(last_id))
+            .order_by_asc(Column::Id)
+            .limit(limit)
+            .into_partial_model::<QuestionIndexSelect>()
             .all(db)
             .await
-            .context("question search failed")?;
-
-        let qids = qs.iter().map(|q| q.id).collect_vec();
-        let pqs = PaperQuestion::find_by_question_id_in(db, qids).await?;
-        let pids = pqs.iter().map(|pq| pq.paper_id).collect_vec();
-        let qid_map = pqs
-            .into_iter()
-            .map(|pq| (pq.question_id, pq))
-            .into_group_map();
-        let papers = Paper::find_by_ids(db, pids).await?;
-        let id_paper: HashMap<i32, paper::Model> = papers.into_iter().map(|p| (p.id, p)).collect();
+            .with_context(|| format!("question::find_index_batch({last_id}) failed"))
+    }
 
-        Ok(qs
-            .into_iter()
-            .map(|q| q.with_paper(&qid_map, &id_paper))
-            .collect())
+    /// 建索引后题目或材料又有修订(回滚、纠错采纳、重新同步)的题目，需要重建索引
+    pub async fn find_stale_index_batch<C>(
+        db: &C,
+        last_id: i32,
+        limit: u64,
+    ) -> anyhow::Result<Vec<QuestionIndexSelect>>
+    where
+        C: ConnectionTrait,
+    {
+        Entity::find()
+            .filter(Column::Id.gt(last_id))
+            .filter(Expr::cust(STALE_SEARCH_INDEX))
+            .order_by_asc(Column::Id)
+            .limit(limit)
+            .into_partial_model::<QuestionIndexSelect>()
+            .all(db)
+            .await
+            .with_context(|| format!("question::find_stale_index_batch({last_id}) failed"))
     }
 
     pub async fn find_by_paper_id<C>(db: &C, paper_id: i32) -> anyhow::Result<Vec<Question>>
@@ -654,106 +736,62 @@ pub fn get_re_whitespace() -> &'static Regex {
 }
 
 impl ActiveModel {
-    pub async fn insert_on_conflict<C>(mut self, db: &C) -> anyhow::Result<Model>
+    /// 按dedup判定重复的题目直接返回已有题目，覆盖已有的题目时保存修改前的版本
+    pub async fn insert_on_conflict<C>(
+        mut self,
+        db: &C,
+        source: &RevisionSource,
+        dedup: &dyn QuestionDeduplicator,
+    ) -> anyhow::Result<Model>
     where
         C: ConnectionTrait,
     {
+        let mut near_duplicates = vec![];
         if let Some(embedding) = self.embedding.take() {
             // embedding算法去重
             let embedding_vec = embedding.to_vec();
             let content = self.content.take().unwrap();
-            let content_has_media = html::contains_media(&content);
             let extra = self.extra.take().unwrap();
-            let text_content = {
-                Html::parse_fragment(&content)
-                    .root_element()
-                    .text()
-                    .join("")
-            };
-            let origin_text_content = {
-                let q_extra_content = Html::parse_fragment(&extra.options_html())
-                    .root_element()
-                    .text()
-                    .join("");
-                let s1 = get_re_punct()
-                    .replace_all(&format!("{text_content}\n{q_extra_content}"), "")
-                    .into_owned();
-                get_re_whitespace().replace_all(&s1, " ").into_owned()
-            };
+            let paper_type = self.paper_type.try_as_ref().copied().unwrap_or_default();
+            let text = DedupText::new(paper_type, &content, &extra);
             let qs_and_distance = Entity::find_by_embedding(db, embedding_vec).await?;
             for (q, semantic_distance) in qs_and_distance {
-                let q_content_has_media = html::contains_media(&content);
-                let text_content_length = text_content.chars().count();
-                let mut compare_stpes = vec![];
-                if content == q.content {
-                    // 完全相同，包括图片等html内容
-                    if text_content_length > 20 {
+                let q_text = DedupText::new(q.paper_type, &q.content, &q.extra);
+                match dedup.compare(&text, &q_text, semantic_distance) {
+                    DedupDecision::Duplicate(reason) => {
+                        tracing::info!("question#{} duplicated: {reason:?}", q.id);
                         return Ok(q);
                     }
-                    if extra == q.extra {
-                        // 如果内容和extra都相同，直接返回
-                        return Ok(q);
+                    DedupDecision::NearDuplicate(similarity) => {
+                        tracing::info!(
+                            "{:?}>>>>\n{}\n----\n{}",
+                            similarity.steps,
+                            q_text.normalized,
+                            text.normalized
+                        );
+                        near_duplicates.push((q.id, similarity));
                     }
-                    compare_stpes.push("question.extra对比失败".to_string());
-                }
-                compare_stpes.push("question.content对比失败".to_string());
-
-                let q_text_content = {
-                    let content = Html::parse_fragment(&q.content)
-                        .root_element()
-                        .text()
-                        .join("");
-                    let extra_content = Html::parse_fragment(&q.extra.options_html())
-                        .root_element()
-                        .text()
-                        .join("");
-                    let s1 = get_re_punct()
-                        .replace_all(&format!("{content}\n{extra_content}"), "")
-                        .into_owned();
-                    get_re_whitespace().replace_all(&s1, " ").into_owned()
-                };
-                let q_text_content_length = q_text_content.chars().count();
-                let origin_text_content_length = origin_text_content.chars().count();
-                if q_text_content_length > 100 && origin_text_content_length > 100 {
-                    let edit_distance =
-                        textdistance::str::levenshtein(&q_text_content, &origin_text_content);
-                    // 95%相似度: 100个字只有5个字不同
-                    if edit_distance * 20 < q_text_content_length.max(origin_text_content_length) {
-                        return Ok(q);
-                    } else {
-                        compare_stpes.push(format!(
-                            "levenshtein距离对比失败: levenshtein={edit_distance}"
-                        ));
-                    }
-                } else {
-                    compare_stpes.push(format!("不满足levenshtein对比条件; q_text_content_length={q_text_content_length} origin_text_content_length={origin_text_content_length}"));
-                }
-                let jaro_winkler =
-                    textdistance::str::jaro_winkler(&q_text_content, &origin_text_content);
-                let jaccard = textdistance::str::jaccard(&q_text_content, &origin_text_content);
-                // 90%相似度: 20个字只能有2个字不同(针对纯文本，没有图片的)
-                if jaro_winkler > 0.90
-                    || jaccard > 0.90
-                    || (jaro_winkler > 0.85 || jaccard > 0.85) && semantic_distance < 0.01
-                {
-                    if !content_has_media && !q_content_has_media {
-                        return Ok(q);
-                    } else if content_has_media && q_content_has_media {
+                    DedupDecision::Distinct(similarity) => {
+                        tracing::info!(
+                            "{:?}>>>>\n{}\n----\n{}",
+                            similarity.steps,
+                            q_text.normalized,
+                            text.normalized
+                        );
                     }
-                    compare_stpes.push(format!(
-                        "jaro_winkler距离对比失败: jaro_winkler={jaro_winkler} jaccard={jaccard} distance={semantic_distance}"
-                    ));
-                } else {
-                    compare_stpes.push(format!("不满足jaro_winkler|jaccard对比条件; q_content_has_media={q_content_has_media} content_has_media={content_has_media}"));
                 }
-                tracing::info!(
-                    "{compare_stpes:?}>>>>\n{q_text_content}\n----\n{origin_text_content}"
-                )
             }
             self.embedding = Set(embedding);
             self.content = Set(content);
             self.extra = Set(extra);
         }
+        let previous = match &self.id {
+            ActiveValue::Set(id) => Entity::find_by_id(*id)
+                .one(db)
+                .await
+                .with_context(|| format!("find question#{id} failed"))?,
+            _ => None,
+        };
         let model = Entity::insert(self)
             .on_conflict(
                 OnConflict::columns([Column::Id])
@@ -788,6 +826,12 @@ impl ActiveModel {
         .update(db)
         .await
         .context("update content failed")?;
+        if let Some(previous) = previous {
+            RevisionKind::Question
+                .record(db, model.id, &previous, &model, source)
+                .await?;
+        }
+        QuestionDuplicate::save_candidates(db, model.id, near_duplicates).await?;
         Ok(model)
     }
 }
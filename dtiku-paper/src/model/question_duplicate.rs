@@ -0,0 +1,110 @@
+pub use super::_entities::question_duplicate::*;
+use super::DuplicateStatus;
+use crate::domain::dedup::Similarity;
+use anyhow::Context;
+use sea_orm::{
+    sea_query::OnConflict, sqlx::types::chrono::Local, ActiveModelBehavior, ActiveValue::Set,
+    ColumnTrait, ConnectionTrait, EntityTrait, QueryFilter, QueryOrder,
+};
+use spring_sea_orm::pagination::{Page, Pagination, PaginationExt};
+
+impl ActiveModelBehavior for ActiveModel {}
+
+impl Entity {
+    /// 保存待人工核对的相似题目，已经在队列中的不重复写入
+    pub async fn save_candidates<C: ConnectionTrait>(
+        db: &C,
+        question_id: i32,
+        candidates: Vec<(i32, Similarity)>,
+    ) -> anyhow::Result<()> {
+        let now = Local::now().naive_local();
+        let models = candidates
+            .into_iter()
+            .filter(|(candidate_id, _)| *candidate_id != question_id)
+            .map(|(candidate_id, s)| ActiveModel {
+                question_id: Set(question_id),
+                candidate_id: Set(candidate_id),
+                semantic_distance: Set(s.semantic_distance),
+                levenshtein: Set(s.levenshtein.map(|l| l as i32)),
+                jaro_winkler: Set(s.jaro_winkler),
+                jaccard: Set(s.jaccard),
+                content_has_media: Set(s.content_has_media),
+                candidate_has_media: Set(s.candidate_has_media),
+                reason: Set(s.steps.join("\n")),
+                status: Set(DuplicateStatus::Pending),
+                created: Set(now),
+                modified: Set(now),
+                ..Default::default()
+            })
+            .collect::<Vec<_>>();
+        if models.is_empty() {
+            return Ok(());
+        }
+        Entity::insert_many(models)
+            .on_conflict(
+                OnConflict::columns([Column::QuestionId, Column::CandidateId])
+                    .do_nothing()
+                    .to_owned(),
+            )
+            .exec_without_returning(db)
+            .await
+            .context("question_duplicate::save_candidates() failed")?;
+        Ok(())
+    }
+
+    pub async fn find_page_by_status<C: ConnectionTrait>(
+        db: &C,
+        status: DuplicateStatus,
+        pagination: &Pagination,
+    ) -> anyhow::Result<Page<Model>> {
+        Entity::find()
+            .filter(Column::Status.eq(status))
+            .order_by_asc(Column::Id)
+            .page(db, pagination)
+            .await
+            .context("question_duplicate::find_page_by_status() failed")
+    }
+
+    pub async fn update_status<C: ConnectionTrait>(
+        db: &C,
+        id: i32,
+        status: DuplicateStatus,
+    ) -> anyhow::Result<()> {
+        Entity::update_many()
+            .set(ActiveModel {
+                status: Set(status),
+                modified: Set(Local::now().naive_local()),
+                ..Default::default()
+            })
+            .filter(Column::Id.eq(id))
+            .exec(db)
+            .await
+            .with_context(|| format!("update question_duplicate#{id} status failed"))?;
+        Ok(())
+    }
+
+    /// 题目被合并删除后，仍在等待核对的相关记录没有意义了，直接忽略
+    pub async fn ignore_pending_by_question_id<C: ConnectionTrait>(
+        db: &C,
+        question_id: i32,
+    ) -> anyhow::Result<()> {
+        Entity::update_many()
+            .set(ActiveModel {
+                status: Set(DuplicateStatus::Ignored),
+                modified: Set(Local::now().naive_local()),
+                ..Default::default()
+            })
+            .filter(Column::Status.eq(DuplicateStatus::Pending))
+            .filter(
+                Column::QuestionId
+                    .eq(question_id)
+                    .or(Column::CandidateId.eq(question_id)),
+            )
+            .exec(db)
+            .await
+            .with_context(|| {
+                format!("ignore question_duplicate of question#{question_id} failed")
+            })?;
+        Ok(())
+    }
+}
@@ -0,0 +1,104 @@
+pub use super::_entities::question_feedback::*;
+use super::{FeedbackCategory, FeedbackStatus};
+use anyhow::Context;
+use sea_orm::{
+    sqlx::types::chrono::Local, ActiveModelBehavior, ActiveValue::Set, ColumnTrait,
+    ConnectionTrait, EntityTrait, PaginatorTrait, QueryFilter, QueryOrder,
+};
+use spring_sea_orm::pagination::{Page, Pagination, PaginationExt};
+use strum::EnumMessage;
+
+impl ActiveModelBehavior for ActiveModel {}
+
+impl FeedbackCategory {
+    pub fn text(&self) -> &'static str {
+        self.get_message().unwrap_or_default()
+    }
+}
+
+impl FeedbackStatus {
+    pub fn text(&self) -> &'static str {
+        self.get_message().unwrap_or_default()
+    }
+}
+
+impl Entity {
+    pub async fn find_page_by_status<C: ConnectionTrait>(
+        db: &C,
+        status: FeedbackStatus,
+        pagination: &Pagination,
+    ) -> anyhow::Result<Page<Model>> {
+        Entity::find()
+            .filter(Column::Status.eq(status))
+            .order_by_asc(Column::Id)
+            .page(db, pagination)
+            .await
+            .context("question_feedback::find_page_by_status() failed")
+    }
+
+    /// 用户提交的纠错，最新的排在前面
+    pub async fn find_page_by_user<C: ConnectionTrait>(
+        db: &C,
+        user_id: i32,
+        pagination: &Pagination,
+    ) -> anyhow::Result<Page<Model>> {
+        Entity::find()
+            .filter(Column::UserId.eq(user_id))
+            .order_by_desc(Column::Id)
+            .page(db, pagination)
+            .await
+            .with_context(|| format!("question_feedback::find_page_by_user({user_id}) failed"))
+    }
+
+    /// 已处理但用户还没查看结果的纠错数
+    pub async fn count_unread_by_user<C: ConnectionTrait>(
+        db: &C,
+        user_id: i32,
+    ) -> anyhow::Result<u64> {
+        Entity::find()
+            .filter(Column::UserId.eq(user_id))
+            .filter(Column::UserRead.eq(false))
+            .count(db)
+            .await
+            .with_context(|| format!("question_feedback::count_unread_by_user({user_id}) failed"))
+    }
+
+    pub async fn mark_read_by_user<C: ConnectionTrait>(db: &C, user_id: i32) -> anyhow::Result<()> {
+        Entity::update_many()
+            .set(ActiveModel {
+                user_read: Set(true),
+                ..Default::default()
+            })
+            .filter(Column::UserId.eq(user_id))
+            .filter(Column::UserRead.eq(false))
+            .exec(db)
+            .await
+            .with_context(|| format!("question_feedback::mark_read_by_user({user_id}) failed"))?;
+        Ok(())
+    }
+
+    /// 处理待处理的纠错，处理结果等待用户查看。纠错已被处理过时返回false
+    pub async fn resolve<C: ConnectionTrait>(
+        db: &C,
+        id: i32,
+        status: FeedbackStatus,
+        reply: Option<String>,
+        handler_id: Option<i32>,
+    ) -> anyhow::Result<bool> {
+        let result = Entity::update_many()
+            .set(ActiveModel {
+                status: Set(status),
+                reply: Set(reply),
+                handler_id: Set(handler_id),
+                user_read: Set(false),
+                modified: Set(Local::now().naive_local()),
+                ..Default::default()
+            })
+            .filter(Column::Id.eq(id))
+            .filter(Column::Status.eq(FeedbackStatus::Open))
+            .exec(db)
+            .await
+            .with_context(|| format!("resolve question_feedback#{id} failed"))?;
+        Ok(result.rows_affected > 0)
+    }
+}
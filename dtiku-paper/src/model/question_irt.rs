@@ -0,0 +1,110 @@
+pub use super::_entities::question_irt::*;
+use crate::domain::irt::IrtParams;
+use anyhow::Context;
+use sea_orm::{
+    sea_query::OnConflict, sqlx::types::chrono::Local, ActiveModelBehavior, ActiveValue::Set,
+    ConnectionTrait, EntityTrait, FromQueryResult, Statement,
+};
+
+impl ActiveModelBehavior for ActiveModel {}
+
+impl Model {
+    pub fn params(&self) -> IrtParams {
+        IrtParams {
+            difficulty: self.difficulty,
+            discrimination: self.discrimination,
+        }
+    }
+}
+
+#[derive(Debug, FromQueryResult)]
+struct AdaptiveCandidate {
+    question_id: i32,
+}
+
+impl Entity {
+    /// 批量保存校准结果：(题目id, 参数, 作答数)
+    pub async fn save_batch<C: ConnectionTrait>(
+        db: &C,
+        items: Vec<(i32, IrtParams, i32)>,
+    ) -> anyhow::Result<()> {
+        if items.is_empty() {
+            return Ok(());
+        }
+        let now = Local::now().naive_local();
+        let models = items
+            .into_iter()
+            .map(|(question_id, params, answer_count)| ActiveModel {
+                question_id: Set(question_id),
+                difficulty: Set(params.difficulty),
+                discrimination: Set(params.discrimination),
+                answer_count: Set(answer_count),
+                modified: Set(now),
+            });
+        Entity::insert_many(models)
+            .on_conflict(
+                OnConflict::column(Column::QuestionId)
+                    .update_columns([
+                        Column::Difficulty,
+                        Column::Discrimination,
+                        Column::AnswerCount,
+                        Column::Modified,
+                    ])
+                    .to_owned(),
+            )
+            .exec(db)
+            .await
+            .context("question_irt::save_batch() failed")?;
+        Ok(())
+    }
+
+    /// 在用户没做过的题目中选出预测答对概率最接近target的一道，
+    /// 加一点随机扰动避免每次都出同一道题
+    pub async fn find_adaptive_question_id<C: ConnectionTrait>(
+        db: &C,
+        user_id: i32,
+        paper_type: i16,
+        keypoint_path: Option<&str>,
+        ability: f32,
+        target: f32,
+    ) -> anyhow::Result<Option<i32>> {
+        let candidate = AdaptiveCandidate::find_by_statement(Statement::from_sql_and_values(
+            sea_orm::DatabaseBackend::Postgres,
+            r#"
+                SELECT i.question_id
+                FROM question_irt i
+                WHERE EXISTS (
+                    SELECT 1 FROM paper_question pq
+                    WHERE pq.question_id = i.question_id
+                    AND pq.paper_type = $1
+                    AND ($2::text IS NULL OR pq.keypoint_path <@ $2::ltree)
+                )
+                AND NOT EXISTS (
+                    SELECT 1 FROM adaptive_answer aa
+                    WHERE aa.user_id = $3 AND aa.question_id = i.question_id
+                )
+                AND NOT EXISTS (
+                    SELECT 1 FROM exercise_answer a
+                    JOIN exercise_record r ON r.id = a.record_id
+                    WHERE r.user_id = $3 AND a.question_id = i.question_id
+                )
+                ORDER BY abs(1 / (1 + exp(-i.discrimination * ($4 - i.difficulty))) - $5)
+                    + random() * 0.05
+                LIMIT 1
+            "#,
+            [
+                paper_type.into(),
+                keypoint_path.map(|p| p.to_string()).into(),
+                user_id.into(),
+                ability.into(),
+                target.into(),
+            ],
+        ))
+        .one(db)
+        .await
+        .with_context(|| {
+            format!("question_irt::find_adaptive_question_id({user_id},{paper_type}) failed")
+        })?;
+        Ok(candidate.map(|c| c.question_id))
+    }
+}
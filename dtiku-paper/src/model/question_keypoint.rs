@@ -1,8 +1,13 @@
-use sea_orm::{sea_query::OnConflict, ConnectionTrait, DbErr, EntityTrait};
+use anyhow::Context;
+use sea_orm::{
+    sea_query::OnConflict, ColumnTrait, ConnectionTrait, DbErr, EntityTrait, QueryFilter,
+    QuerySelect,
+};
 
 pub use super::_entities::question_key_point::*;
 
 impl ActiveModel {
+    /// 厂商同步的知识点，覆盖分类任务写入的同一知识点
     pub async fn insert_on_conflict<C>(self, db: &C) -> Result<Model, DbErr>
     where
         C: ConnectionTrait,
@@ -10,10 +15,73 @@ impl ActiveModel {
         Entity::insert(self)
             .on_conflict(
                 OnConflict::columns([Column::QuestionId, Column::KeyPointId])
-                    .update_column(Column::Year)
+                    .update_columns([Column::Year, Column::Confidence])
                     .to_owned(),
             )
             .exec_with_returning(db)
             .await
     }
+
+    /// 分类任务写入的知识点，已有厂商知识点时保留厂商的数据
+    pub async fn insert_classified<C>(self, db: &C) -> Result<(), DbErr>
+    where
+        C: ConnectionTrait,
+    {
+        Entity::insert(self)
+            .on_conflict(
+                OnConflict::columns([Column::QuestionId, Column::KeyPointId])
+                    .do_nothing()
+                    .to_owned(),
+            )
+            .exec_without_returning(db)
+            .await?;
+        Ok(())
+    }
+}
+
+impl Entity {
+    /// 题目id和知识点id
+    pub async fn find_by_question_ids<C: ConnectionTrait>(
+        db: &C,
+        question_ids: Vec<i32>,
+    ) -> anyhow::Result<Vec<(i32, i32)>> {
+        Entity::find()
+            .select_only()
+            .columns([Column::QuestionId, Column::KeyPointId])
+            .filter(Column::QuestionId.is_in(question_ids))
+            .into_tuple()
+            .all(db)
+            .await
+            .context("question_key_point::find_by_question_ids() failed")
+    }
+
+    /// 删除分类任务之前写入的知识点，厂商同步的知识点没有置信度
+    pub async fn delete_classified<C: ConnectionTrait>(
+        db: &C,
+        question_id: i32,
+    ) -> anyhow::Result<u64> {
+        Ok(Entity::delete_many()
+            .filter(Column::QuestionId.eq(question_id))
+            .filter(Column::Confidence.is_not_null())
+            .exec(db)
+            .await
+            .with_context(|| {
+                format!("question_key_point::delete_classified({question_id}) failed")
+            })?
+            .rows_affected)
+    }
+
+    pub async fn delete_by_key_point_id<C: ConnectionTrait>(
+        db: &C,
+        key_point_id: i32,
+    ) -> anyhow::Result<u64> {
+        Ok(Entity::delete_many()
+            .filter(Column::KeyPointId.eq(key_point_id))
+            .exec(db)
+            .await
+            .with_context(|| {
+                format!("question_key_point::delete_by_key_point_id({key_point_id}) failed")
+            })?
+            .rows_affected)
+    }
 }
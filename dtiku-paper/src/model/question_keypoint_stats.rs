@@ -1,8 +1,8 @@
 pub use super::_entities::question_key_point_stats::*;
 use anyhow::Context;
 use sea_orm::{
-    prelude::Expr, ColumnTrait, ConnectionTrait, EntityTrait, FromQueryResult, QueryFilter,
-    QuerySelect,
+    prelude::Expr, ColumnTrait, ConnectionTrait, DbBackend, EntityTrait, FromQueryResult,
+    QueryFilter, QuerySelect, Statement,
 };
 
 #[derive(Debug, FromQueryResult)]
@@ -30,4 +30,15 @@ impl Entity {
             .await
             .context("Failed to find question key point stats by key point IDs")
     }
+
+    /// 物化视图没有唯一索引，不能concurrently刷新
+    pub async fn refresh<C: ConnectionTrait>(db: &C) -> anyhow::Result<()> {
+        db.execute(Statement::from_string(
+            DbBackend::Postgres,
+            "refresh materialized view question_key_point_stats",
+        ))
+        .await
+        .context("refresh question_key_point_stats failed")?;
+        Ok(())
+    }
 }
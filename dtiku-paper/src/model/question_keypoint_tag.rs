@@ -0,0 +1,247 @@
+pub use super::_entities::question_keypoint_tag::*;
+use super::{question, KeypointTagStatus, PaperQuestion};
+use crate::domain::keypoint_tag::{PathVote, TagDecision};
+use anyhow::Context;
+use sea_orm::{
+    sea_query::OnConflict, sqlx::types::chrono::Local, ActiveModelBehavior, ActiveValue::Set,
+    ColumnTrait, ConnectionTrait, DbBackend, EntityTrait, FromJsonQueryResult, FromQueryResult,
+    QueryFilter, QueryOrder, Statement,
+};
+use serde::{Deserialize, Serialize};
+use spring_sea_orm::pagination::{Page, Pagination, PaginationExt};
+
+/// 投票时得票最多的几个候选知识点
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize, FromJsonQueryResult)]
+#[serde(transparent)]
+pub struct TagVotes(pub Vec<PathVote>);
+
+impl ActiveModelBehavior for ActiveModel {}
+
+impl Entity {
+    /// 没有知识点、也没有自动标注过的题目
+    pub async fn find_untagged_questions<C: ConnectionTrait>(
+        db: &C,
+        paper_type: i16,
+        last_id: i32,
+        limit: u64,
+    ) -> anyhow::Result<Vec<question::Model>> {
+        question::Model::find_by_statement(Statement::from_sql_and_values(
+            DbBackend::Postgres,
+            r#"
+                SELECT q.*
+                FROM question q
+                WHERE q.paper_type = $1
+                AND q.id > $2
+                AND NOT EXISTS (SELECT 1 FROM question_key_point qkp WHERE qkp.question_id = q.id)
+                AND NOT EXISTS (SELECT 1 FROM question_keypoint_tag t WHERE t.question_id = q.id)
+                ORDER BY q.id
+                LIMIT $3
+            "#,
+            [paper_type.into(), last_id.into(), (limit as i64).into()],
+        ))
+        .all(db)
+        .await
+        .with_context(|| format!("find_untagged_questions({paper_type},{last_id}) failed"))
+    }
+
+    /// 同类型题目中embedding最接近的已标注题目，返回题目id和余弦距离；
+    /// 自动标注且未经审核的题目不参与投票，避免误差累积
+    pub async fn find_tagged_neighbours<C: ConnectionTrait>(
+        db: &C,
+        question: &question::Model,
+        limit: u64,
+    ) -> anyhow::Result<Vec<(i32, f64)>> {
+        let rows = db
+            .query_all(Statement::from_sql_and_values(
+                DbBackend::Postgres,
+                r#"
+                    SELECT q.id, q.embedding <=> $1 AS distance
+                    FROM question q
+                    WHERE q.paper_type = $2
+                    AND q.id <> $3
+                    AND EXISTS (SELECT 1 FROM question_key_point qkp WHERE qkp.question_id = q.id)
+                    AND NOT EXISTS (
+                        SELECT 1 FROM question_keypoint_tag t
+                        WHERE t.question_id = q.id AND t.status = 'auto'
+                    )
+                    ORDER BY distance
+                    LIMIT $4
+                "#,
+                [
+                    question.embedding.clone().into(),
+                    question.paper_type.into(),
+                    question.id.into(),
+                    (limit as i64).into(),
+                ],
+            ))
+            .await
+            .with_context(|| format!("find_tagged_neighbours(question#{}) failed", question.id))?;
+        rows.into_iter()
+            .map(|row| Ok((row.try_get("", "id")?, row.try_get("", "distance")?)))
+            .collect::<Result<_, sea_orm::DbErr>>()
+            .context("parse tagged neighbours failed")
+    }
+
+    pub async fn save_decision<C: ConnectionTrait>(
+        db: &C,
+        question_id: i32,
+        decision: TagDecision,
+        status: KeypointTagStatus,
+    ) -> anyhow::Result<Model> {
+        let now = Local::now().naive_local();
+        Entity::insert(ActiveModel {
+            question_id: Set(question_id),
+            key_point_id: Set(decision.key_point_id()),
+            keypoint_path: Set(decision.ltree_path()),
+            confidence: Set(decision.confidence),
+            votes: Set(TagVotes(decision.candidates)),
+            status: Set(status),
+            created: Set(now),
+            modified: Set(now),
+            ..Default::default()
+        })
+        .on_conflict(
+            OnConflict::column(Column::QuestionId)
+                .update_columns([
+                    Column::KeyPointId,
+                    Column::KeypointPath,
+                    Column::Confidence,
+                    Column::Votes,
+                    Column::Status,
+                    Column::Modified,
+                ])
+                .to_owned(),
+        )
+        .exec_with_returning(db)
+        .await
+        .with_context(|| format!("save keypoint tag of question#{question_id} failed"))
+    }
+
+    pub async fn find_page_by_status<C: ConnectionTrait>(
+        db: &C,
+        status: KeypointTagStatus,
+        pagination: &Pagination,
+    ) -> anyhow::Result<Page<Model>> {
+        Entity::find()
+            .filter(Column::Status.eq(status))
+            .order_by_asc(Column::Confidence)
+            .order_by_asc(Column::Id)
+            .page(db, pagination)
+            .await
+            .context("question_keypoint_tag::find_page_by_status() failed")
+    }
+
+    pub async fn update_status<C: ConnectionTrait>(
+        db: &C,
+        id: i32,
+        status: KeypointTagStatus,
+        key_point: Option<(i32, String)>,
+    ) -> anyhow::Result<()> {
+        let mut model = ActiveModel {
+            status: Set(status),
+            modified: Set(Local::now().naive_local()),
+            ..Default::default()
+        };
+        if let Some((key_point_id, keypoint_path)) = key_point {
+            model.key_point_id = Set(key_point_id);
+            model.keypoint_path = Set(keypoint_path);
+        }
+        Entity::update_many()
+            .set(model)
+            .filter(Column::Id.eq(id))
+            .exec(db)
+            .await
+            .with_context(|| format!("update question_keypoint_tag#{id} status failed"))?;
+        Ok(())
+    }
+
+    /// 知识点被删除后清掉对应的标注，这些题目会被重新标注
+    pub async fn delete_by_key_point_id<C: ConnectionTrait>(
+        db: &C,
+        key_point_id: i32,
+    ) -> anyhow::Result<()> {
+        Entity::delete_many()
+            .filter(Column::KeyPointId.eq(key_point_id))
+            .exec(db)
+            .await
+            .with_context(|| {
+                format!("delete question_keypoint_tag of key_point#{key_point_id} failed")
+            })?;
+        Ok(())
+    }
+
+    /// 知识点移动或合并后，改写标注的知识点和路径
+    pub async fn rebase<C: ConnectionTrait>(
+        db: &C,
+        old_path: &str,
+        new_path: &str,
+        old_key_point_id: i32,
+        new_key_point_id: i32,
+    ) -> anyhow::Result<()> {
+        db.execute(Statement::from_sql_and_values(
+            DbBackend::Postgres,
+            r#"
+                UPDATE question_keypoint_tag
+                SET keypoint_path = $2 || substr(keypoint_path, length($1) + 1),
+                    key_point_id = CASE WHEN key_point_id = $3 THEN $4 ELSE key_point_id END
+                WHERE keypoint_path = $1 OR keypoint_path LIKE $1 || '.%'
+            "#,
+            [
+                old_path.into(),
+                new_path.into(),
+                old_key_point_id.into(),
+                new_key_point_id.into(),
+            ],
+        ))
+        .await
+        .with_context(|| format!("rebase question_keypoint_tag {old_path}->{new_path} failed"))?;
+        Ok(())
+    }
+}
+
+impl Model {
+    /// 写入题目知识点，并补上试卷中缺失的keypoint_path
+    pub async fn apply<C: ConnectionTrait>(&self, db: &C) -> anyhow::Result<()> {
+        let qid = self.question_id;
+        db.execute(Statement::from_sql_and_values(
+            DbBackend::Postgres,
+            r#"
+                INSERT INTO question_key_point (question_id, key_point_id, year)
+                SELECT $1, $2, COALESCE(MIN(p.year), 1970)
+                FROM paper_question pq
+                JOIN paper p ON p.id = pq.paper_id
+                WHERE pq.question_id = $1
+                ON CONFLICT (question_id, key_point_id) DO NOTHING
+            "#,
+            [qid.into(), self.key_point_id.into()],
+        ))
+        .await
+        .with_context(|| format!("insert question_key_point for question#{qid} failed"))?;
+        PaperQuestion::fill_keypoint_path(db, qid, &self.keypoint_path).await?;
+        Ok(())
+    }
+
+    /// 撤销apply写入的数据
+    pub async fn revert<C: ConnectionTrait>(&self, db: &C) -> anyhow::Result<()> {
+        let qid = self.question_id;
+        db.execute(Statement::from_sql_and_values(
+            DbBackend::Postgres,
+            "DELETE FROM question_key_point WHERE question_id = $1 AND key_point_id = $2",
+            [qid.into(), self.key_point_id.into()],
+        ))
+        .await
+        .with_context(|| format!("delete question_key_point for question#{qid} failed"))?;
+        db.execute(Statement::from_sql_and_values(
+            DbBackend::Postgres,
+            r#"
+                UPDATE paper_question
+                SET keypoint_path = NULL
+                WHERE question_id = $1 AND keypoint_path = CAST($2 AS ltree)
+            "#,
+            [qid.into(), self.keypoint_path.clone().into()],
+        ))
+        .await
+        .with_context(|| format!("reset keypoint_path for question#{qid} failed"))?;
+        Ok(())
+    }
+}
@@ -4,6 +4,7 @@ use anyhow::Context;
 use itertools::Itertools;
 use sea_orm::{
     sea_query::OnConflict, ColumnTrait, ConnectionTrait, DbErr, EntityTrait, QueryFilter,
+    QueryOrder,
 };
 
 pub use super::_entities::question_material::*;
@@ -37,6 +38,26 @@ impl Entity {
             .map(|r| (r.question_id, r.material_id))
             .into_group_map())
     }
+
+    /// 材料 -> 材料下的题目，题目按id排序
+    pub async fn find_by_material_ids<C>(
+        db: &C,
+        material_ids: Vec<i32>,
+    ) -> anyhow::Result<HashMap<i32, Vec<i32>>>
+    where
+        C: ConnectionTrait,
+    {
+        let rows = Entity::find()
+            .filter(Column::MaterialId.is_in(material_ids))
+            .order_by_asc(Column::QuestionId)
+            .all(db)
+            .await
+            .context("question_material::find_by_material_ids() failed")?;
+        Ok(rows
+            .into_iter()
+            .map(|r| (r.material_id, r.question_id))
+            .into_group_map())
+    }
 }
 
 impl ActiveModel {
@@ -0,0 +1,267 @@
+pub use super::_entities::question_search_index::*;
+use crate::domain::{question::QuestionSearch, search::to_tsquery};
+use anyhow::Context;
+use chrono::Local;
+use sea_orm::{
+    prelude::PgVector, ActiveModelBehavior, ConnectionTrait, FromQueryResult, Statement, Value,
+};
+use spring_sea_orm::pagination::{Page, Pagination};
+
+impl ActiveModelBehavior for ActiveModel {}
+
+/// 待写入索引的题目，tokens为分词结果
+#[derive(Debug, Clone)]
+pub struct IndexDocument {
+    pub question_id: i32,
+    pub exam_id: i16,
+    pub paper_type: i16,
+    pub question_type: String,
+    pub text: String,
+    pub tokens: Vec<String>,
+}
+
+#[derive(Debug, FromQueryResult)]
+pub struct SearchRow {
+    pub question_id: i32,
+    pub text: String,
+}
+
+#[derive(Debug, FromQueryResult)]
+struct SearchTotal {
+    total: i64,
+}
+
+impl Entity {
+    /// 批量写入索引，tokens列不在实体中，只能用原生sql写入。
+    /// modified和修订记录的created用同一个时钟，用于判断索引是否过期
+    pub async fn save_batch<C: ConnectionTrait>(
+        db: &C,
+        docs: Vec<IndexDocument>,
+    ) -> anyhow::Result<()> {
+        if docs.is_empty() {
+            return Ok(());
+        }
+        let mut rows = Vec::with_capacity(docs.len());
+        let mut values: Vec<Value> = Vec::with_capacity(docs.len() * 6 + 1);
+        values.push(Local::now().naive_local().into());
+        for doc in docs {
+            let n = values.len();
+            rows.push(format!(
+                "(${},${},${},${},${},to_tsvector('simple',${}),$1)",
+                n + 1,
+                n + 2,
+                n + 3,
+                n + 4,
+                n + 5,
+                n + 6
+            ));
+            values.extend([
+                doc.question_id.into(),
+                doc.exam_id.into(),
+                doc.paper_type.into(),
+                doc.question_type.into(),
+                doc.text.into(),
+                doc.tokens.join(" ").into(),
+            ]);
+        }
+        let sql = format!(
+            r#"insert into question_search_index(question_id,exam_id,paper_type,question_type,text,tokens,modified)
+            values {}
+            on conflict(question_id) do update set
+                exam_id = excluded.exam_id,
+                paper_type = excluded.paper_type,
+                question_type = excluded.question_type,
+                text = excluded.text,
+                tokens = excluded.tokens,
+                modified = excluded.modified"#,
+            rows.join(",")
+        );
+        db.execute(Statement::from_sql_and_values(
+            sea_orm::DatabaseBackend::Postgres,
+            sql,
+            values,
+        ))
+        .await
+        .context("question_search_index::save_batch() failed")?;
+        Ok(())
+    }
+
+    /// 删除题目已被合并或删除的索引
+    pub async fn delete_orphans<C: ConnectionTrait>(db: &C) -> anyhow::Result<u64> {
+        let result = db
+            .execute_unprepared(
+                r#"delete from question_search_index s
+                where not exists (select 1 from question q where q.id = s.question_id)"#,
+            )
+            .await
+            .context("question_search_index::delete_orphans() failed")?;
+        Ok(result.rows_affected())
+    }
+
+    /// 分词命中或原文包含查询内容的题目，按分词覆盖度和原文相似度排序
+    pub async fn search<C: ConnectionTrait>(
+        db: &C,
+        search: &QuestionSearch,
+        terms: &[String],
+        pagination: &Pagination,
+    ) -> anyhow::Result<Page<SearchRow>> {
+        let total = Self::count(db, search, terms).await?;
+        if total == 0 {
+            return Ok(Page::new(vec![], pagination, 0));
+        }
+
+        let (where_clause, values) = keyword_conditions(search, terms);
+        let offset = pagination.page * pagination.size;
+        let sql = format!(
+            r#"select s.question_id, s.text
+            from question_search_index s
+            where {where_clause}
+            order by ts_rank_cd(s.tokens, to_tsquery('simple',$1)) + word_similarity($3, s.text) desc, s.question_id desc
+            offset {offset} limit {}"#,
+            pagination.size
+        );
+        let rows = SearchRow::find_by_statement(Statement::from_sql_and_values(
+            sea_orm::DatabaseBackend::Postgres,
+            sql,
+            values,
+        ))
+        .all(db)
+        .await
+        .with_context(|| format!("question_search_index::search({search:?}) failed"))?;
+        Ok(Page::new(rows, pagination, total))
+    }
+
+    /// 分词命中或原文包含查询内容的题目数
+    pub async fn count<C: ConnectionTrait>(
+        db: &C,
+        search: &QuestionSearch,
+        terms: &[String],
+    ) -> anyhow::Result<u64> {
+        let (where_clause, values) = keyword_conditions(search, terms);
+        Ok(
+            SearchTotal::find_by_statement(Statement::from_sql_and_values(
+                sea_orm::DatabaseBackend::Postgres,
+                format!(
+                    "select count(*) as total from question_search_index s where {where_clause}"
+                ),
+                values,
+            ))
+            .one(db)
+            .await
+            .with_context(|| format!("question_search_index::count({search:?}) failed"))?
+            .map(|t| t.total.max(0) as u64)
+            .unwrap_or_default(),
+        )
+    }
+
+    /// 混合检索的关键词候选，按相关度跳过offset条后取limit条
+    pub async fn search_candidates<C: ConnectionTrait>(
+        db: &C,
+        search: &QuestionSearch,
+        terms: &[String],
+        offset: u64,
+        limit: u64,
+    ) -> anyhow::Result<Vec<SearchRow>> {
+        let (where_clause, values) = keyword_conditions(search, terms);
+        let sql = format!(
+            r#"select s.question_id, s.text
+            from question_search_index s
+            where {where_clause}
+            order by ts_rank_cd(s.tokens, to_tsquery('simple',$1)) + word_similarity($3, s.text) desc, s.question_id desc
+            offset {offset} limit {limit}"#
+        );
+        SearchRow::find_by_statement(Statement::from_sql_and_values(
+            sea_orm::DatabaseBackend::Postgres,
+            sql,
+            values,
+        ))
+        .all(db)
+        .await
+        .with_context(|| format!("question_search_index::search_candidates({search:?}) failed"))
+    }
+
+    /// 混合检索的语义候选：按题目向量与查询向量的余弦距离取前limit条，筛选条件与关键词检索一致
+    pub async fn search_by_embedding<C: ConnectionTrait>(
+        db: &C,
+        search: &QuestionSearch,
+        embedding: Vec<f32>,
+        limit: u64,
+    ) -> anyhow::Result<Vec<SearchRow>> {
+        let mut values: Vec<Value> = vec![PgVector::from(embedding).into()];
+        let conds = filter_conditions(search, &mut values);
+        let where_clause = if conds.is_empty() {
+            String::new()
+        } else {
+            format!("where {}", conds.join(" and "))
+        };
+        let sql = format!(
+            r#"select s.question_id, s.text
+            from question_search_index s
+            join question q on q.id = s.question_id
+            {where_clause}
+            order by q.embedding <=> $1
+            limit {limit}"#
+        );
+        SearchRow::find_by_statement(Statement::from_sql_and_values(
+            sea_orm::DatabaseBackend::Postgres,
+            sql,
+            values,
+        ))
+        .all(db)
+        .await
+        .with_context(|| format!("question_search_index::search_by_embedding({search:?}) failed"))
+    }
+}
+
+/// 关键词检索的where条件，$1为tsquery，$2为like模式，$3为原始查询
+fn keyword_conditions(search: &QuestionSearch, terms: &[String]) -> (String, Vec<Value>) {
+    let content = search.content.trim();
+    let mut values: Vec<Value> = vec![
+        to_tsquery(terms).into(),
+        format!("%{}%", escape_like(content)).into(),
+        content.into(),
+    ];
+    let mut conds = vec!["(s.tokens @@ to_tsquery('simple',$1) or s.text ilike $2)".to_string()];
+    conds.extend(filter_conditions(search, &mut values));
+    (conds.join(" and "), values)
+}
+
+/// 考试、试卷类型、题型、年份、标签等筛选条件，参数追加到values中
+fn filter_conditions(search: &QuestionSearch, values: &mut Vec<Value>) -> Vec<String> {
+    let mut conds = vec![];
+    if let Some(exam_id) = search.exam_id {
+        values.push(exam_id.into());
+        conds.push(format!("s.exam_id = ${}", values.len()));
+    }
+    if let Some(paper_type) = search.paper_type {
+        values.push(paper_type.into());
+        conds.push(format!("s.paper_type = ${}", values.len()));
+    }
+    if let Some(question_type) = &search.question_type {
+        values.push(question_type.as_str().into());
+        conds.push(format!("s.question_type = ${}", values.len()));
+    }
+    let mut paper_conds = vec![];
+    if let Some(year) = search.year {
+        values.push(year.into());
+        paper_conds.push(format!("p.year = ${}", values.len()));
+    }
+    if let Some(label_id) = search.label_id {
+        values.push(label_id.into());
+        paper_conds.push(format!("p.label_id = ${}", values.len()));
+    }
+    if !paper_conds.is_empty() {
+        conds.push(format!(
+            "exists(select 1 from paper_question pq join paper p on p.id = pq.paper_id where pq.question_id = s.question_id and {})",
+            paper_conds.join(" and ")
+        ));
+    }
+    conds
+}
+
+/// 转义like的通配符
+fn escape_like(s: &str) -> String {
+    s.replace('\\', "\\\\")
+        .replace('%', "\\%")
+        .replace('_', "\\_")
+}
@@ -0,0 +1,153 @@
+use crate::{domain::revision::json_diff, model::FromType};
+use anyhow::Context;
+use sea_orm::{
+    prelude::DateTime, sqlx::types::chrono::Local, ActiveEnum, ConnectionTrait, DbBackend,
+    FromQueryResult, Statement,
+};
+use serde::{Deserialize, Serialize};
+use strum::{Display, EnumString};
+
+/// 有修订记录的实体，每种实体的修订记录存放在`{实体}_revision`表中
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Display, EnumString)]
+#[serde(rename_all = "snake_case")]
+#[strum(serialize_all = "snake_case")]
+pub enum RevisionKind {
+    Question,
+    Material,
+    Solution,
+    Paper,
+}
+
+/// 修改的来源：题库同步任务或后台管理员
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RevisionSource {
+    pub from_ty: Option<FromType>,
+    pub user_id: Option<i32>,
+}
+
+impl RevisionSource {
+    pub fn vendor(from_ty: FromType) -> Self {
+        Self {
+            from_ty: Some(from_ty),
+            user_id: None,
+        }
+    }
+
+    pub fn admin(user_id: Option<i32>) -> Self {
+        Self {
+            from_ty: None,
+            user_id,
+        }
+    }
+}
+
+/// 一次修改之前的快照，`diff`为修改前后的字段差异
+#[derive(Debug, Clone, Serialize, FromQueryResult)]
+pub struct Revision {
+    pub id: i32,
+    pub target_id: i32,
+    pub snapshot: serde_json::Value,
+    pub from_ty: Option<String>,
+    pub user_id: Option<i32>,
+    pub diff: String,
+    pub created: DateTime,
+}
+
+impl RevisionKind {
+    fn table(&self) -> &'static str {
+        match self {
+            Self::Question => "question_revision",
+            Self::Material => "material_revision",
+            Self::Solution => "solution_revision",
+            Self::Paper => "paper_revision",
+        }
+    }
+
+    /// 内容有变化时保存修改前的快照，没有变化则不记录
+    pub async fn record<C, T>(
+        &self,
+        db: &C,
+        target_id: i32,
+        previous: &T,
+        current: &T,
+        source: &RevisionSource,
+    ) -> anyhow::Result<()>
+    where
+        C: ConnectionTrait,
+        T: Serialize,
+    {
+        let snapshot = serde_json::to_value(previous).context("serialize revision failed")?;
+        let current = serde_json::to_value(current).context("serialize revision failed")?;
+        let diff = json_diff(&snapshot, &current);
+        if diff.is_empty() {
+            return Ok(());
+        }
+        let sql = format!(
+            r#"
+            insert into {}(target_id, snapshot, from_ty, user_id, diff, created)
+            values ($1, $2, $3::from_type, $4, $5, $6)
+            "#,
+            self.table()
+        );
+        db.execute(Statement::from_sql_and_values(
+            DbBackend::Postgres,
+            sql,
+            vec![
+                target_id.into(),
+                snapshot.into(),
+                source.from_ty.as_ref().map(|f| f.to_value()).into(),
+                source.user_id.into(),
+                diff.join("\n").into(),
+                Local::now().naive_local().into(),
+            ],
+        ))
+        .await
+        .with_context(|| format!("insert {}({target_id}) failed", self.table()))?;
+        Ok(())
+    }
+
+    pub async fn find_by_target<C>(&self, db: &C, target_id: i32) -> anyhow::Result<Vec<Revision>>
+    where
+        C: ConnectionTrait,
+    {
+        let sql = format!(
+            r#"
+            select id, target_id, snapshot, from_ty::text as from_ty, user_id, diff, created
+            from {}
+            where target_id = $1
+            order by id desc
+            "#,
+            self.table()
+        );
+        Revision::find_by_statement(Statement::from_sql_and_values(
+            DbBackend::Postgres,
+            sql,
+            vec![target_id.into()],
+        ))
+        .all(db)
+        .await
+        .with_context(|| format!("find {}({target_id}) failed", self.table()))
+    }
+
+    pub async fn find_by_id<C>(&self, db: &C, id: i32) -> anyhow::Result<Option<Revision>>
+    where
+        C: ConnectionTrait,
+    {
+        let sql = format!(
+            r#"
+            select id, target_id, snapshot, from_ty::text as from_ty, user_id, diff, created
+            from {}
+            where id = $1
+            "#,
+            self.table()
+        );
+        Revision::find_by_statement(Statement::from_sql_and_values(
+            DbBackend::Postgres,
+            sql,
+            vec![id.into()],
+        ))
+        .one(db)
+        .await
+        .with_context(|| format!("find {}#{id} failed", self.table()))
+    }
+}
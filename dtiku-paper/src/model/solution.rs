@@ -1,14 +1,20 @@
 pub use super::_entities::solution::*;
 use crate::{
-    model::{assets, SrcType},
+    model::{
+        assets,
+        revision::{RevisionKind, RevisionSource},
+        SrcType,
+    },
     util::html,
 };
 use anyhow::Context;
 use itertools::Itertools;
 use phf::phf_map;
 use sea_orm::{
-    sea_query::OnConflict, ActiveModelTrait as _, ActiveValue::Set, ColumnTrait, ConnectionTrait,
-    EntityTrait, FromJsonQueryResult, QueryFilter,
+    sea_query::OnConflict,
+    ActiveModelTrait as _,
+    ActiveValue::{self, Set},
+    ColumnTrait, ConnectionTrait, EntityTrait, FromJsonQueryResult, QueryFilter,
 };
 use serde::{Deserialize, Serialize};
 use serde_with::{formats::CommaSeparator, serde_as, StringWithSeparator};
@@ -291,7 +297,17 @@ impl Entity {
 }
 
 impl ActiveModel {
+    /// 覆盖已有的解答时保存修改前的版本，来源即解答的来源
     pub async fn insert_on_conflict<C: ConnectionTrait>(self, db: &C) -> anyhow::Result<Model> {
+        let previous = match (&self.question_id, &self.from_ty) {
+            (ActiveValue::Set(question_id), ActiveValue::Set(from_ty)) => Entity::find()
+                .filter(Column::QuestionId.eq(*question_id))
+                .filter(Column::FromTy.eq(*from_ty))
+                .one(db)
+                .await
+                .with_context(|| format!("find solution({question_id},{from_ty}) failed"))?,
+            _ => None,
+        };
         let model = Entity::insert(self)
             .on_conflict(
                 OnConflict::columns([Column::QuestionId, Column::FromTy])
@@ -430,14 +446,21 @@ impl ActiveModel {
                 SolutionExtra::OtherQA(oqa)
             }
         };
-        ActiveModel {
+        let model = ActiveModel {
             id: Set(model.id),
             extra: Set(replaced_extra),
             ..Default::default()
         }
         .update(db)
         .await
-        .context("update solution extra failed")
+        .context("update solution extra failed")?;
+        if let Some(previous) = previous {
+            let source = RevisionSource::vendor(model.from_ty);
+            RevisionKind::Solution
+                .record(db, model.id, &previous, &model, &source)
+                .await?;
+        }
+        Ok(model)
     }
 }
 
@@ -0,0 +1,63 @@
+pub use super::_entities::user_ability::*;
+use crate::domain::irt::KeypointAbility;
+use anyhow::Context;
+use sea_orm::{
+    sea_query::OnConflict, sqlx::types::chrono::Local, ActiveModelBehavior, ActiveValue::Set,
+    ColumnTrait, ConnectionTrait, EntityTrait, QueryFilter,
+};
+
+impl ActiveModelBehavior for ActiveModel {}
+
+impl Entity {
+    pub async fn find_by_user_and_keypoints<C: ConnectionTrait>(
+        db: &C,
+        user_id: i32,
+        paper_type: i16,
+        keypoint_ids: Vec<i32>,
+    ) -> anyhow::Result<Vec<Model>> {
+        if keypoint_ids.is_empty() {
+            return Ok(vec![]);
+        }
+        Entity::find()
+            .filter(
+                Column::UserId
+                    .eq(user_id)
+                    .and(Column::PaperType.eq(paper_type))
+                    .and(Column::KeypointId.is_in(keypoint_ids)),
+            )
+            .all(db)
+            .await
+            .with_context(|| {
+                format!("user_ability::find_by_user_and_keypoints({user_id},{paper_type}) failed")
+            })
+    }
+
+    pub async fn save_batch<C: ConnectionTrait>(
+        db: &C,
+        paper_type: i16,
+        abilities: Vec<KeypointAbility>,
+    ) -> anyhow::Result<()> {
+        if abilities.is_empty() {
+            return Ok(());
+        }
+        let now = Local::now().naive_local();
+        let models = abilities.into_iter().map(|a| ActiveModel {
+            user_id: Set(a.user_id),
+            paper_type: Set(paper_type),
+            keypoint_id: Set(a.keypoint_id),
+            ability: Set(a.ability),
+            answer_count: Set(a.answer_count as i32),
+            modified: Set(now),
+        });
+        Entity::insert_many(models)
+            .on_conflict(
+                OnConflict::columns([Column::UserId, Column::PaperType, Column::KeypointId])
+                    .update_columns([Column::Ability, Column::AnswerCount, Column::Modified])
+                    .to_owned(),
+            )
+            .exec(db)
+            .await
+            .context("user_ability::save_batch() failed")?;
+        Ok(())
+    }
+}
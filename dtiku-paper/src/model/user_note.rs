@@ -0,0 +1,124 @@
+pub use super::_entities::user_note::*;
+use super::SrcType;
+use anyhow::Context;
+use sea_orm::{
+    sqlx::types::chrono::Local, ActiveModelBehavior, ActiveModelTrait, ActiveValue::Set,
+    ColumnTrait, Condition, ConnectionTrait, EntityTrait, QueryFilter, QueryOrder,
+};
+use spring_sea_orm::pagination::{Page, Pagination, PaginationExt};
+
+impl ActiveModelBehavior for ActiveModel {}
+
+impl Entity {
+    pub async fn find_by_targets<C: ConnectionTrait>(
+        db: &C,
+        user_id: i32,
+        target_type: SrcType,
+        target_ids: Vec<i32>,
+    ) -> anyhow::Result<Vec<Model>> {
+        Entity::find()
+            .filter(Column::UserId.eq(user_id))
+            .filter(Column::TargetType.eq(target_type))
+            .filter(Column::TargetId.is_in(target_ids))
+            .order_by_asc(Column::Id)
+            .all(db)
+            .await
+            .with_context(|| format!("user_note::find_by_targets({user_id}) failed"))
+    }
+
+    /// 按笔记内容或高亮文字搜索，最新的排在前面
+    pub async fn search_page<C: ConnectionTrait>(
+        db: &C,
+        user_id: i32,
+        keyword: Option<&str>,
+        pagination: &Pagination,
+    ) -> anyhow::Result<Page<Model>> {
+        let mut query = Entity::find().filter(Column::UserId.eq(user_id));
+        if let Some(keyword) = keyword {
+            query = query.filter(
+                Condition::any()
+                    .add(Column::Markdown.contains(keyword))
+                    .add(Column::HighlightText.contains(keyword)),
+            );
+        }
+        query
+            .order_by_desc(Column::Id)
+            .page(db, pagination)
+            .await
+            .with_context(|| format!("user_note::search_page({user_id}, {keyword:?}) failed"))
+    }
+
+    pub async fn find_all_by_user<C: ConnectionTrait>(
+        db: &C,
+        user_id: i32,
+    ) -> anyhow::Result<Vec<Model>> {
+        Entity::find()
+            .filter(Column::UserId.eq(user_id))
+            .order_by_asc(Column::Id)
+            .all(db)
+            .await
+            .with_context(|| format!("user_note::find_all_by_user({user_id}) failed"))
+    }
+
+    /// 修改用户自己的笔记，笔记不存在时返回None
+    pub async fn update_markdown<C: ConnectionTrait>(
+        db: &C,
+        user_id: i32,
+        id: i32,
+        markdown: String,
+    ) -> anyhow::Result<Option<Model>> {
+        let note = Entity::find_by_id(id)
+            .filter(Column::UserId.eq(user_id))
+            .one(db)
+            .await
+            .with_context(|| format!("find user_note#{id} failed"))?;
+        let Some(note) = note else {
+            return Ok(None);
+        };
+        let note = ActiveModel {
+            id: Set(note.id),
+            markdown: Set(markdown),
+            modified: Set(Local::now().naive_local()),
+            ..Default::default()
+        }
+        .update(db)
+        .await
+        .with_context(|| format!("update user_note#{id} failed"))?;
+        Ok(Some(note))
+    }
+
+    /// 删除用户自己的笔记，返回是否删除成功
+    pub async fn delete_by_user<C: ConnectionTrait>(
+        db: &C,
+        user_id: i32,
+        id: i32,
+    ) -> anyhow::Result<bool> {
+        let result = Entity::delete_many()
+            .filter(Column::Id.eq(id))
+            .filter(Column::UserId.eq(user_id))
+            .exec(db)
+            .await
+            .with_context(|| format!("delete user_note#{id} failed"))?;
+        Ok(result.rows_affected > 0)
+    }
+
+    /// 内容变化后高亮位置已经对不上，只保留高亮的文字
+    pub async fn clear_highlights<C: ConnectionTrait>(
+        db: &C,
+        target_type: SrcType,
+        target_id: i32,
+    ) -> anyhow::Result<()> {
+        Entity::update_many()
+            .set(ActiveModel {
+                highlight_start: Set(None),
+                highlight_end: Set(None),
+                ..Default::default()
+            })
+            .filter(Column::TargetType.eq(target_type.clone()))
+            .filter(Column::TargetId.eq(target_id))
+            .exec(db)
+            .await
+            .with_context(|| format!("clear highlights of {target_type:?}#{target_id} failed"))?;
+        Ok(())
+    }
+}
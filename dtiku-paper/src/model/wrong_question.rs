@@ -0,0 +1,115 @@
+pub use super::_entities::wrong_question::*;
+use anyhow::Context;
+use sea_orm::{
+    prelude::Expr, sea_query::OnConflict, sqlx::types::chrono::Local, ActiveModelBehavior,
+    ActiveValue::Set, ColumnTrait, ConnectionTrait, DbErr, EntityTrait, QueryFilter, QueryOrder,
+};
+use spring::async_trait;
+
+#[async_trait]
+impl ActiveModelBehavior for ActiveModel {
+    async fn before_save<C>(mut self, _db: &C, insert: bool) -> Result<Self, DbErr>
+    where
+        C: ConnectionTrait,
+    {
+        if insert {
+            self.created = Set(Local::now().naive_local());
+        }
+        self.modified = Set(Local::now().naive_local());
+        Ok(self)
+    }
+}
+
+impl Entity {
+    pub async fn find_by_user_id<C: ConnectionTrait>(
+        db: &C,
+        user_id: i32,
+    ) -> anyhow::Result<Vec<Model>> {
+        Entity::find()
+            .filter(Column::UserId.eq(user_id))
+            .order_by_desc(Column::Modified)
+            .all(db)
+            .await
+            .with_context(|| format!("wrong_question::find_by_user_id({user_id}) failed"))
+    }
+
+    pub async fn find_by_user_and_qids<C: ConnectionTrait>(
+        db: &C,
+        user_id: i32,
+        question_ids: Vec<i32>,
+    ) -> anyhow::Result<Vec<Model>> {
+        if question_ids.is_empty() {
+            return Ok(vec![]);
+        }
+        Entity::find()
+            .filter(
+                Column::UserId
+                    .eq(user_id)
+                    .and(Column::QuestionId.is_in(question_ids)),
+            )
+            .all(db)
+            .await
+            .with_context(|| format!("wrong_question::find_by_user_and_qids({user_id}) failed"))
+    }
+
+    /// 记录答错的题目：已在错题本中的累加错误次数，并清零连续答对次数
+    pub async fn record_wrong<C: ConnectionTrait>(
+        db: &C,
+        user_id: i32,
+        paper_id: i32,
+        wrong_answers: Vec<(i32, String)>,
+    ) -> anyhow::Result<()> {
+        if wrong_answers.is_empty() {
+            return Ok(());
+        }
+        let now = Local::now().naive_local();
+        let models = wrong_answers
+            .into_iter()
+            .map(|(question_id, answer)| ActiveModel {
+                user_id: Set(user_id),
+                question_id: Set(question_id),
+                paper_id: Set(paper_id),
+                wrong_count: Set(1),
+                redo_correct: Set(0),
+                last_answer: Set(answer),
+                created: Set(now),
+                modified: Set(now),
+            });
+        Entity::insert_many(models)
+            .on_conflict(
+                OnConflict::columns([Column::UserId, Column::QuestionId])
+                    .value(
+                        Column::WrongCount,
+                        Expr::col((Entity, Column::WrongCount)).add(1),
+                    )
+                    .update_columns([Column::RedoCorrect, Column::LastAnswer, Column::Modified])
+                    .to_owned(),
+            )
+            .exec(db)
+            .await
+            .with_context(|| {
+                format!("wrong_question::record_wrong({user_id},{paper_id}) failed")
+            })?;
+        Ok(())
+    }
+
+    pub async fn delete_by_user_and_qids<C: ConnectionTrait>(
+        db: &C,
+        user_id: i32,
+        question_ids: Vec<i32>,
+    ) -> anyhow::Result<u64> {
+        if question_ids.is_empty() {
+            return Ok(0);
+        }
+        Ok(Entity::delete_many()
+            .filter(
+                Column::UserId
+                    .eq(user_id)
+                    .and(Column::QuestionId.is_in(question_ids)),
+            )
+            .exec(db)
+            .await
+            .with_context(|| format!("wrong_question::delete_by_user_and_qids({user_id}) failed"))?
+            .rows_affected)
+    }
+}
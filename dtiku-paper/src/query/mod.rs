@@ -1,2 +1,3 @@
 pub mod paper;
 pub mod question;
+pub mod wrong_question;
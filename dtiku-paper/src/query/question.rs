@@ -11,7 +11,7 @@ static KEY_POINT_PATH: OnceLock<Regex> = OnceLock::new();
 
 /// 获取关键点路径正则
 fn get_key_point_path() -> &'static Regex {
-    KEY_POINT_PATH.get_or_init(|| Regex::new(r"\d+(.\d+)?").unwrap())
+    KEY_POINT_PATH.get_or_init(|| Regex::new(r"^\d+(\.\d+)*$").unwrap())
 }
 
 #[derive(
@@ -71,14 +71,18 @@ impl IntoCondition for PaperQuestionQuery {
             cond = cond.add(paper_question::Column::PaperId.is_in(self.paper_ids));
         }
         if !self.keypoint_path.is_empty() {
-            cond = cond.add(Expr::cust(format!(
-                "keypoint_path <@ '{}'::ltree",
-                self.keypoint_path
-            )));
+            cond = cond.add(Expr::cust_with_values(
+                "keypoint_path <@ $1::ltree",
+                [self.keypoint_path],
+            ));
         }
         if self.correct_ratio.0 != 0.0 || self.correct_ratio.1 != 100.0 {
             let ratio = self.correct_ratio;
-            cond = cond.add(paper_question::Column::CorrectRatio.between(ratio.0, ratio.1));
+            // 优先使用结合了本站用户作答的正确率，没有时回退到厂商正确率
+            cond = cond.add(Expr::cust_with_values(
+                "coalesce(blended_correct_ratio, correct_ratio) between $1 and $2",
+                [ratio.0, ratio.1],
+            ));
         }
         cond
     }
@@ -0,0 +1,10 @@
+use serde::{Deserialize, Serialize};
+
+/// 错题重做的筛选条件：kp为一级知识点id(0表示未分类)，pid为试卷id
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+pub struct WrongQuestionQuery {
+    #[serde(default)]
+    pub kp: Option<i32>,
+    #[serde(default)]
+    pub pid: Option<i32>,
+}
@@ -0,0 +1,471 @@
+use crate::{
+    domain::{
+        essay::{encode_essay_answer, EssayScore},
+        exercise::{cap_question_times, ExerciseDetail, ExerciseOwner, TimedExercise},
+        question::FullQuestion,
+        scoring::{is_full_credit, ScoringScheme},
+    },
+    model::{
+        essay_grade, exercise_answer, exercise_record, paper, EssayGrade, ExamScoring,
+        ExerciseAnswer, ExerciseRecord, WrongQuestion,
+    },
+};
+use anyhow::Context;
+use chrono::{Local, TimeDelta};
+use sea_orm::{
+    ActiveModelTrait, ActiveValue::Set, ConnectionTrait, DbConn, EntityTrait, TransactionTrait,
+};
+use spring::plugin::service::Service;
+use std::collections::HashMap;
+
+/// 申论得分达到该分数计为答对
+pub const ESSAY_PASS_SCORE: f32 = 60.0;
+
+#[derive(Clone, Service)]
+pub struct ExerciseService {
+    #[inject(component)]
+    db: DbConn,
+}
+
+impl ExerciseService {
+    /// 保存一次练习的作答。
+    /// 同一试卷未完成的练习只保留一份，再次暂存或交卷时会覆盖它。
+    /// 登录用户交卷时，答错的题目会计入错题本。
+    pub async fn save_exercise(
+        &self,
+        owner: &ExerciseOwner,
+        paper: &paper::Model,
+        questions: &[FullQuestion],
+        user_answer: &HashMap<i32, String>,
+        user_time: &HashMap<i32, u64>,
+        finished: bool,
+    ) -> anyhow::Result<exercise_record::Model> {
+        let unfinished = ExerciseRecord::find_unfinished(&self.db, owner, paper.id).await?;
+        self.save_answers(
+            owner,
+            unfinished,
+            paper,
+            questions,
+            user_answer,
+            user_time,
+            finished,
+        )
+        .await
+    }
+
+    /// 进行中的限时模考，已超时的按暂存的作答自动交卷；没有进行中的考试时返回None
+    pub async fn find_timed_exercise(
+        &self,
+        owner: &ExerciseOwner,
+        paper: &paper::Model,
+        questions: &[FullQuestion],
+    ) -> anyhow::Result<Option<TimedExercise>> {
+        match ExerciseRecord::find_unfinished_timed(&self.db, owner, paper.id).await? {
+            Some(record) => Ok(Some(
+                self.resume_timed(owner, paper, questions, record).await?,
+            )),
+            None => Ok(None),
+        }
+    }
+
+    /// 开始限时模考，从此刻起计时；已有进行中的考试时继续该场考试。
+    /// 试卷不支持限时模考时返回None
+    pub async fn start_timed_exercise(
+        &self,
+        owner: &ExerciseOwner,
+        paper: &paper::Model,
+        questions: &[FullQuestion],
+    ) -> anyhow::Result<Option<TimedExercise>> {
+        let Some(limit) = paper.time_limit_secs() else {
+            return Ok(None);
+        };
+        if let Some(record) =
+            ExerciseRecord::find_unfinished_timed(&self.db, owner, paper.id).await?
+        {
+            return Ok(Some(
+                self.resume_timed(owner, paper, questions, record).await?,
+            ));
+        }
+        let now = Local::now().naive_local();
+        let record = exercise_record::ActiveModel {
+            user_id: Set(owner.user_id()),
+            fingerprint: Set(owner.fingerprint()),
+            paper_id: Set(paper.id),
+            paper_type: Set(paper.paper_type),
+            finished: Set(false),
+            correct: Set(0),
+            error: Set(questions.len() as i16),
+            score: Set(0.0),
+            total_time: Set(0),
+            deadline: Set(Some(now + TimeDelta::seconds(limit))),
+            ..Default::default()
+        }
+        .insert(&self.db)
+        .await
+        .context("insert timed exercise_record failed")?;
+        Ok(Some(TimedExercise::Running {
+            detail: ExerciseDetail {
+                record,
+                user_answer: HashMap::new(),
+                user_time: HashMap::new(),
+            },
+            remaining_secs: limit,
+        }))
+    }
+
+    async fn resume_timed(
+        &self,
+        owner: &ExerciseOwner,
+        paper: &paper::Model,
+        questions: &[FullQuestion],
+        record: exercise_record::Model,
+    ) -> anyhow::Result<TimedExercise> {
+        let now = Local::now().naive_local();
+        if record.is_overdue(now) {
+            return Ok(TimedExercise::Expired(
+                self.expire(owner, paper, questions, record).await?,
+            ));
+        }
+        let deadline = record.deadline.unwrap_or(now);
+        Ok(TimedExercise::Running {
+            detail: self.with_answers(record).await?,
+            remaining_secs: (deadline - now).num_seconds().max(0),
+        })
+    }
+
+    /// 保存限时模考的作答，每题用时以服务端记录的考试时长为上限。
+    /// 超过截止时间提交的作答不予采纳，按截止前暂存的作答自动交卷；没有进行中的考试时返回None
+    pub async fn save_timed_exercise(
+        &self,
+        owner: &ExerciseOwner,
+        paper: &paper::Model,
+        questions: &[FullQuestion],
+        user_answer: &HashMap<i32, String>,
+        user_time: &HashMap<i32, u64>,
+        finished: bool,
+    ) -> anyhow::Result<Option<TimedExercise>> {
+        let Some(record) = ExerciseRecord::find_unfinished_timed(&self.db, owner, paper.id).await?
+        else {
+            return Ok(None);
+        };
+        let now = Local::now().naive_local();
+        let deadline = record.deadline.unwrap_or(now);
+        if record.is_overdue(now) {
+            let detail = self.expire(owner, paper, questions, record).await?;
+            return Ok(Some(TimedExercise::Expired(detail)));
+        }
+        let elapsed = (now.min(deadline) - record.created).num_seconds().max(0) as u64;
+        let user_time = cap_question_times(user_time, elapsed);
+        let record = self
+            .save_answers(
+                owner,
+                Some(record),
+                paper,
+                questions,
+                user_answer,
+                &user_time,
+                finished,
+            )
+            .await?;
+        let detail = ExerciseDetail {
+            record,
+            user_answer: user_answer.clone(),
+            user_time,
+        };
+        Ok(Some(if finished {
+            TimedExercise::Submitted(detail)
+        } else {
+            TimedExercise::Running {
+                detail,
+                remaining_secs: (deadline - now).num_seconds().max(0),
+            }
+        }))
+    }
+
+    /// 超时的限时模考按最后一次暂存的作答交卷
+    async fn expire(
+        &self,
+        owner: &ExerciseOwner,
+        paper: &paper::Model,
+        questions: &[FullQuestion],
+        record: exercise_record::Model,
+    ) -> anyhow::Result<ExerciseDetail> {
+        let limit = record
+            .deadline
+            .map(|d| (d - record.created).num_seconds().max(0) as u64)
+            .unwrap_or_default();
+        let draft = self.with_answers(record).await?;
+        let user_time = cap_question_times(&draft.user_time, limit);
+        let record = self
+            .save_answers(
+                owner,
+                Some(draft.record),
+                paper,
+                questions,
+                &draft.user_answer,
+                &user_time,
+                true,
+            )
+            .await?;
+        Ok(ExerciseDetail {
+            record,
+            user_answer: draft.user_answer,
+            user_time,
+        })
+    }
+
+    /// 按试卷的计分方案批改客观题作答，并覆盖未完成的练习记录
+    #[allow(clippy::too_many_arguments)]
+    async fn save_answers(
+        &self,
+        owner: &ExerciseOwner,
+        unfinished: Option<exercise_record::Model>,
+        paper: &paper::Model,
+        questions: &[FullQuestion],
+        user_answer: &HashMap<i32, String>,
+        user_time: &HashMap<i32, u64>,
+        finished: bool,
+    ) -> anyhow::Result<exercise_record::Model> {
+        let scheme = self.find_scoring_scheme(paper).await?;
+        let mut answers = Vec::with_capacity(user_answer.len());
+        let mut wrong_answers = vec![];
+        let mut correct = 0;
+        let mut total_time = 0;
+        let mut total_score = 0.0;
+        for q in questions {
+            let time = user_time.get(&q.id).cloned().unwrap_or_default();
+            total_time += time;
+            if let Some(answer) = user_answer.get(&q.id) {
+                let score = q.score_answer(&scheme, answer);
+                total_score += score;
+                let is_correct = is_full_credit(score);
+                if is_correct {
+                    correct += 1;
+                } else {
+                    wrong_answers.push((q.id, answer.clone()));
+                }
+                answers.push(exercise_answer::ActiveModel {
+                    question_id: Set(q.id),
+                    answer: Set(answer.clone()),
+                    correct: Set(is_correct),
+                    time: Set(time.min(i32::MAX as u64) as i32),
+                    score: Set(score),
+                    ..Default::default()
+                });
+            }
+        }
+        let error = questions.len() as i16 - correct;
+        let total_time = total_time.min(i32::MAX as u64) as i32;
+
+        let tx = self.db.begin().await.context("begin transaction failed")?;
+        let record = Self::save_record(
+            &tx,
+            owner,
+            unfinished,
+            paper,
+            finished,
+            (correct, error, total_score),
+            total_time,
+        )
+        .await?;
+
+        ExerciseAnswer::delete_by_record_id(&tx, record.id).await?;
+        if !answers.is_empty() {
+            let answers = answers.into_iter().map(|mut a| {
+                a.record_id = Set(record.id);
+                a
+            });
+            ExerciseAnswer::insert_many(answers)
+                .exec(&tx)
+                .await
+                .context("insert exercise_answer failed")?;
+        }
+        if let Some(user_id) = owner.user_id().filter(|_| finished) {
+            WrongQuestion::record_wrong(&tx, user_id, paper.id, wrong_answers).await?;
+        }
+        tx.commit().await.context("commit exercise failed")?;
+        Ok(record)
+    }
+
+    /// 保存申论练习的作答和批改结果，申论只在交卷时批改保存
+    pub async fn save_essay_exercise(
+        &self,
+        owner: &ExerciseOwner,
+        paper: &paper::Model,
+        questions: &[FullQuestion],
+        user_answer: &HashMap<i32, Vec<String>>,
+        user_time: &HashMap<i32, u64>,
+        scores: Vec<EssayScore>,
+    ) -> anyhow::Result<(exercise_record::Model, Vec<essay_grade::Model>)> {
+        let passed: HashMap<i32, bool> = scores
+            .iter()
+            .map(|s| (s.question_id, s.score >= ESSAY_PASS_SCORE))
+            .collect();
+        let correct = passed.values().filter(|p| **p).count() as i16;
+        // 没有作答的题目也计为答错
+        let error = questions.len() as i16 - correct;
+        let total_score = scores.iter().map(|s| s.score).sum::<f32>();
+        let essay_scores: HashMap<i32, f32> =
+            scores.iter().map(|s| (s.question_id, s.score)).collect();
+        let total_time = user_time.values().sum::<u64>().min(i32::MAX as u64) as i32;
+
+        let unfinished = ExerciseRecord::find_unfinished(&self.db, owner, paper.id).await?;
+        let tx = self.db.begin().await.context("begin transaction failed")?;
+        let record = Self::save_record(
+            &tx,
+            owner,
+            unfinished,
+            paper,
+            true,
+            (correct, error, total_score),
+            total_time,
+        )
+        .await?;
+
+        ExerciseAnswer::delete_by_record_id(&tx, record.id).await?;
+        EssayGrade::delete_by_record_id(&tx, record.id).await?;
+        if !user_answer.is_empty() {
+            let answers = user_answer.iter().map(|(qid, answers)| {
+                let time = user_time.get(qid).cloned().unwrap_or_default();
+                exercise_answer::ActiveModel {
+                    record_id: Set(record.id),
+                    question_id: Set(*qid),
+                    answer: Set(encode_essay_answer(answers)),
+                    correct: Set(passed.get(qid).cloned().unwrap_or_default()),
+                    time: Set(time.min(i32::MAX as u64) as i32),
+                    score: Set(essay_scores.get(qid).cloned().unwrap_or_default()),
+                }
+            });
+            ExerciseAnswer::insert_many(answers)
+                .exec(&tx)
+                .await
+                .context("insert exercise_answer failed")?;
+        }
+        let grades = scores
+            .into_iter()
+            .map(|s| essay_grade::Model {
+                record_id: record.id,
+                question_id: s.question_id,
+                score: s.score,
+                feedback: s.feedback,
+            })
+            .collect::<Vec<_>>();
+        if !grades.is_empty() {
+            let models = grades
+                .iter()
+                .map(|g| essay_grade::ActiveModel::from(g.clone()));
+            EssayGrade::insert_many(models)
+                .exec(&tx)
+                .await
+                .context("insert essay_grade failed")?;
+        }
+        tx.commit().await.context("commit essay exercise failed")?;
+        Ok((record, grades))
+    }
+
+    /// 同一试卷未完成的练习只保留一份，有则覆盖；result为答对数、答错数和总得分
+    async fn save_record<C: ConnectionTrait>(
+        db: &C,
+        owner: &ExerciseOwner,
+        unfinished: Option<exercise_record::Model>,
+        paper: &paper::Model,
+        finished: bool,
+        (correct, error, score): (i16, i16, f32),
+        total_time: i32,
+    ) -> anyhow::Result<exercise_record::Model> {
+        Ok(match unfinished {
+            Some(r) => exercise_record::ActiveModel {
+                id: Set(r.id),
+                finished: Set(finished),
+                correct: Set(correct),
+                error: Set(error),
+                score: Set(score),
+                total_time: Set(total_time),
+                ..Default::default()
+            }
+            .update(db)
+            .await
+            .with_context(|| format!("update exercise_record#{} failed", r.id))?,
+            None => exercise_record::ActiveModel {
+                user_id: Set(owner.user_id()),
+                fingerprint: Set(owner.fingerprint()),
+                paper_id: Set(paper.id),
+                paper_type: Set(paper.paper_type),
+                finished: Set(finished),
+                correct: Set(correct),
+                error: Set(error),
+                score: Set(score),
+                total_time: Set(total_time),
+                ..Default::default()
+            }
+            .insert(db)
+            .await
+            .context("insert exercise_record failed")?,
+        })
+    }
+
+    pub async fn find_exercise(
+        &self,
+        owner: &ExerciseOwner,
+        record_id: i32,
+    ) -> anyhow::Result<Option<ExerciseDetail>> {
+        let record = ExerciseRecord::find_by_owner_and_id(&self.db, owner, record_id).await?;
+        match record {
+            Some(record) => Ok(Some(self.with_answers(record).await?)),
+            None => Ok(None),
+        }
+    }
+
+    pub async fn find_unfinished_exercise(
+        &self,
+        owner: &ExerciseOwner,
+        paper_id: i32,
+    ) -> anyhow::Result<Option<ExerciseDetail>> {
+        let record = ExerciseRecord::find_unfinished(&self.db, owner, paper_id).await?;
+        match record {
+            Some(record) => Ok(Some(self.with_answers(record).await?)),
+            None => Ok(None),
+        }
+    }
+
+    pub async fn list_exercise_by_paper(
+        &self,
+        owner: &ExerciseOwner,
+        paper_id: i32,
+    ) -> anyhow::Result<Vec<exercise_record::Model>> {
+        ExerciseRecord::find_by_owner_and_paper(&self.db, owner, paper_id).await
+    }
+
+    pub async fn find_essay_grades(
+        &self,
+        record_id: i32,
+    ) -> anyhow::Result<HashMap<i32, essay_grade::Model>> {
+        Ok(EssayGrade::find_by_record_id(&self.db, record_id)
+            .await?
+            .into_iter()
+            .map(|g| (g.question_id, g))
+            .collect())
+    }
+
+    /// 试卷所在考试的计分方案
+    pub async fn find_scoring_scheme(&self, paper: &paper::Model) -> anyhow::Result<ScoringScheme> {
+        let scoring =
+            ExamScoring::find_by_paper(&self.db, paper.paper_type, paper.label_id).await?;
+        Ok(ScoringScheme::new(scoring.as_ref().map(|s| &s.rules)))
+    }
+
+    async fn with_answers(&self, record: exercise_record::Model) -> anyhow::Result<ExerciseDetail> {
+        let answers = ExerciseAnswer::find_by_record_id(&self.db, record.id).await?;
+        let mut user_answer = HashMap::with_capacity(answers.len());
+        let mut user_time = HashMap::with_capacity(answers.len());
+        for a in answers {
+            user_time.insert(a.question_id, a.time as u64);
+            user_answer.insert(a.question_id, a.answer);
+        }
+        Ok(ExerciseDetail {
+            record,
+            user_answer,
+            user_time,
+        })
+    }
+}
@@ -0,0 +1,153 @@
+use crate::{
+    domain::{
+        export::{ExportDocument, ExportFormat, ExportOptions},
+        paper::FullPaper,
+    },
+    model::{question::QuestionSinglePaper, Assets},
+    util::export::{absolute_url, docx, markdown, pdf, ExportImage, ExportImages},
+};
+use anyhow::Context;
+use futures::{stream, StreamExt};
+use sea_orm::{DbConn, EntityTrait};
+use serde::Deserialize;
+use spring::{config::Configurable, plugin::service::Service, tracing};
+use std::time::Duration;
+
+/// 同时下载的图片数
+const IMAGE_CONCURRENCY: usize = 8;
+const IMAGE_TIMEOUT: Duration = Duration::from_secs(10);
+
+#[derive(Debug, Clone, Configurable, Deserialize)]
+#[config_prefix = "export"]
+pub struct ExportConfig {
+    /// 生成pdf使用的中文ttf字体
+    #[serde(default = "default_pdf_font")]
+    pub pdf_font: String,
+}
+
+fn default_pdf_font() -> String {
+    "/usr/share/fonts/truetype/droid/DroidSansFallbackFull.ttf".to_string()
+}
+
+/// 导出生成的文件
+pub struct ExportFile {
+    pub file_name: String,
+    pub content_type: &'static str,
+    pub data: Vec<u8>,
+}
+
+#[derive(Clone, Service)]
+pub struct ExportService {
+    #[inject(component)]
+    db: DbConn,
+    #[inject(config)]
+    config: ExportConfig,
+}
+
+impl ExportService {
+    pub async fn export_paper(
+        &self,
+        paper: &FullPaper,
+        options: &ExportOptions,
+    ) -> anyhow::Result<ExportFile> {
+        let doc = ExportDocument::from_paper(paper, options.solution);
+        self.export(doc, options.format).await
+    }
+
+    pub async fn export_questions(
+        &self,
+        title: &str,
+        questions: &[QuestionSinglePaper],
+        options: &ExportOptions,
+    ) -> anyhow::Result<ExportFile> {
+        let doc = ExportDocument::from_questions(title, questions, options.solution);
+        self.export(doc, options.format).await
+    }
+
+    async fn export(
+        &self,
+        doc: ExportDocument,
+        format: ExportFormat,
+    ) -> anyhow::Result<ExportFile> {
+        let images = self.download_images(doc.image_srcs()).await;
+        let data = match format {
+            ExportFormat::Pdf => {
+                let font = tokio::fs::read(&self.config.pdf_font)
+                    .await
+                    .with_context(|| format!("read pdf font {} failed", self.config.pdf_font))?;
+                pdf::render(&doc, &images, &font)?
+            }
+            ExportFormat::Docx => docx::render(&doc, &images)?,
+            ExportFormat::Markdown => markdown::render(&doc, &images).into_bytes(),
+        };
+        Ok(ExportFile {
+            file_name: format!("{}.{}", doc.title, format.extension()),
+            content_type: format.content_type(),
+            data,
+        })
+    }
+
+    /// 下载失败的图片不嵌入文档，由渲染时输出占位文字
+    async fn download_images(&self, srcs: Vec<String>) -> ExportImages {
+        let client = reqwest::Client::new();
+        stream::iter(srcs)
+            .map(|src| {
+                let client = client.clone();
+                async move {
+                    match self.download_image(&client, &src).await {
+                        Ok(image) => Some((src, image)),
+                        Err(e) => {
+                            tracing::warn!("download export image {src} failed: {e:?}");
+                            None
+                        }
+                    }
+                }
+            })
+            .buffer_unordered(IMAGE_CONCURRENCY)
+            .filter_map(|r| async { r })
+            .collect()
+            .await
+    }
+
+    /// 自有存储还没同步的图片，回退到素材的原始地址下载
+    async fn download_image(
+        &self,
+        client: &reqwest::Client,
+        src: &str,
+    ) -> anyhow::Result<ExportImage> {
+        match fetch(client, &absolute_url(src)).await {
+            Ok(data) => ExportImage::new(data),
+            Err(e) => {
+                let assets_id = src
+                    .rsplit_once("/assets/")
+                    .and_then(|(_, path)| path.rsplit('/').next())
+                    .and_then(|id| id.parse::<i32>().ok());
+                let Some(assets_id) = assets_id else {
+                    return Err(e);
+                };
+                let assets = Assets::find_by_id(assets_id)
+                    .one(&self.db)
+                    .await
+                    .with_context(|| format!("find assets#{assets_id} failed"))?
+                    .ok_or(e)?;
+                let data = fetch(client, &assets.compute_src_url()).await?;
+                ExportImage::new(data)
+            }
+        }
+    }
+}
+
+async fn fetch(client: &reqwest::Client, url: &str) -> anyhow::Result<Vec<u8>> {
+    let bytes = client
+        .get(url)
+        .timeout(IMAGE_TIMEOUT)
+        .send()
+        .await
+        .with_context(|| format!("request {url} failed"))?
+        .error_for_status()
+        .with_context(|| format!("request {url} failed"))?
+        .bytes()
+        .await
+        .with_context(|| format!("read {url} failed"))?;
+    Ok(bytes.to_vec())
+}
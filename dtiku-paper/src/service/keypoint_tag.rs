@@ -0,0 +1,111 @@
+use crate::{
+    domain::{keypoint_tag::KeypointTagReview, question_duplicate::DuplicateQuestion},
+    model::{question_keypoint_tag, KeyPoint, KeypointTagStatus, Question, QuestionKeypointTag},
+};
+use anyhow::Context;
+use itertools::Itertools;
+use sea_orm::{DbConn, EntityTrait, TransactionTrait};
+use spring::plugin::service::Service;
+use spring_sea_orm::pagination::{Page, Pagination};
+use std::collections::HashMap;
+
+#[derive(Clone, Service)]
+pub struct KeypointTagService {
+    #[inject(component)]
+    db: DbConn,
+}
+
+impl KeypointTagService {
+    pub async fn find_page(
+        &self,
+        status: KeypointTagStatus,
+        pagination: &Pagination,
+    ) -> anyhow::Result<Page<KeypointTagReview>> {
+        let page = QuestionKeypointTag::find_page_by_status(&self.db, status, pagination).await?;
+        let qids = page.content.iter().map(|t| t.question_id).collect();
+        let mut questions: HashMap<i32, DuplicateQuestion> = Question::find_by_ids(&self.db, qids)
+            .await?
+            .into_iter()
+            .map(|q| (q.id, q.into()))
+            .collect();
+        let kp_ids = page
+            .content
+            .iter()
+            .flat_map(keypoint_ids)
+            .unique()
+            .collect_vec();
+        let mut names = HashMap::new();
+        for id in kp_ids {
+            if let Some(kp) = KeyPoint::find_by_id_with_cache(&self.db, id).await? {
+                names.insert(id, kp.name);
+            }
+        }
+        Ok(page.map(|tag| KeypointTagReview {
+            question: questions.remove(&tag.question_id),
+            keypoints: keypoint_ids(&tag)
+                .filter_map(|id| Some((id, names.get(&id)?.clone())))
+                .collect(),
+            tag,
+        }))
+    }
+
+    /// 采纳标注，key_point_id不为空时改用审核人员选择的知识点；
+    /// 标注或知识点不存在时返回false
+    pub async fn accept(&self, id: i32, key_point_id: Option<i32>) -> anyhow::Result<bool> {
+        let Some(tag) = QuestionKeypointTag::find_by_id(id)
+            .one(&self.db)
+            .await
+            .with_context(|| format!("find question_keypoint_tag#{id} failed"))?
+        else {
+            return Ok(false);
+        };
+        let mut accepted = tag.clone();
+        if let Some(kp_id) = key_point_id.filter(|kp_id| *kp_id != tag.key_point_id) {
+            let Some(path) = KeyPoint::query_keypoint_path(&self.db, kp_id).await? else {
+                return Ok(false);
+            };
+            accepted.key_point_id = kp_id;
+            accepted.keypoint_path = path;
+        }
+        let tx = self.db.begin().await.context("begin transaction failed")?;
+        if tag.status == KeypointTagStatus::Auto || tag.status == KeypointTagStatus::Accepted {
+            tag.revert(&tx).await?;
+        }
+        accepted.apply(&tx).await?;
+        QuestionKeypointTag::update_status(
+            &tx,
+            id,
+            KeypointTagStatus::Accepted,
+            Some((accepted.key_point_id, accepted.keypoint_path)),
+        )
+        .await?;
+        tx.commit().await.context("commit transaction failed")?;
+        Ok(true)
+    }
+
+    /// 驳回标注，已经写入的知识点一并撤销
+    pub async fn reject(&self, id: i32) -> anyhow::Result<bool> {
+        let Some(tag) = QuestionKeypointTag::find_by_id(id)
+            .one(&self.db)
+            .await
+            .with_context(|| format!("find question_keypoint_tag#{id} failed"))?
+        else {
+            return Ok(false);
+        };
+        let tx = self.db.begin().await.context("begin transaction failed")?;
+        if tag.status == KeypointTagStatus::Auto || tag.status == KeypointTagStatus::Accepted {
+            tag.revert(&tx).await?;
+        }
+        QuestionKeypointTag::update_status(&tx, id, KeypointTagStatus::Rejected, None).await?;
+        tx.commit().await.context("commit transaction failed")?;
+        Ok(true)
+    }
+}
+
+/// 标注路径和候选路径上的所有知识点
+fn keypoint_ids(tag: &question_keypoint_tag::Model) -> impl Iterator<Item = i32> + '_ {
+    tag.keypoint_path
+        .split('.')
+        .filter_map(|id| id.parse().ok())
+        .chain(tag.votes.0.iter().flat_map(|v| v.path.iter().copied()))
+}
@@ -0,0 +1,341 @@
+use crate::{
+    domain::keypoint_taxonomy::{
+        check_delete, check_merge, check_move, check_rename, plan_merge, ChildPlan,
+    },
+    model::{
+        key_point, key_point_mapping, merge_table::MergeTable, FromType, KeyPoint, KeyPointMapping,
+        PaperQuestion, QuestionKeyPoint, QuestionKeyPointStats, QuestionKeypointTag,
+    },
+};
+use anyhow::Context;
+use itertools::Itertools;
+use sea_orm::{
+    ActiveValue::Set, ColumnTrait, ConnectionTrait, DbConn, EntityTrait, QueryFilter,
+    TransactionTrait,
+};
+use spring::{plugin::service::Service, tracing};
+use spring_redis::{redis::AsyncCommands as _, Redis};
+use std::collections::HashMap;
+
+const QUESTION_KEY_POINT: MergeTable =
+    MergeTable::new("question_key_point", "key_point_id", &["question_id"]);
+
+/// 调整知识点树的结果
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TaxonomyOutcome {
+    Done,
+    NotFound,
+    Invalid(&'static str),
+}
+
+#[derive(Clone, Service)]
+pub struct KeyPointTaxonomyService {
+    #[inject(component)]
+    db: DbConn,
+    #[inject(component)]
+    redis: Redis,
+}
+
+impl KeyPointTaxonomyService {
+    /// 把source合并到target：同名子知识点递归合并，其他子知识点挂到target下，
+    /// 题目知识点、试卷中的keypoint_path和厂商映射都改写到target
+    pub async fn merge(&self, source_id: i32, target_id: i32) -> anyhow::Result<TaxonomyOutcome> {
+        let (Some(source), Some(target)) = (
+            self.find_key_point(source_id).await?,
+            self.find_key_point(target_id).await?,
+        ) else {
+            return Ok(TaxonomyOutcome::NotFound);
+        };
+        let target_path = KeyPoint::find_path(&self.db, target_id).await?;
+        if let Err(msg) = check_merge(&source, &target, &target_path) {
+            return Ok(TaxonomyOutcome::Invalid(msg));
+        }
+
+        let mut evict = vec![];
+        let tx = self.db.begin().await.context("begin transaction failed")?;
+        Self::merge_into(&tx, source, target, &mut evict).await?;
+        tx.commit().await.context("commit transaction failed")?;
+
+        self.after_change(evict).await;
+        Ok(TaxonomyOutcome::Done)
+    }
+
+    async fn merge_into<C: ConnectionTrait>(
+        db: &C,
+        source: key_point::Model,
+        target: key_point::Model,
+        evict: &mut Vec<String>,
+    ) -> anyhow::Result<()> {
+        let paper_type = source.paper_type;
+        let source_children = KeyPoint::find_by_pid(db, paper_type, source.id).await?;
+        let target_children = KeyPoint::find_by_pid(db, paper_type, target.id).await?;
+        for plan in plan_merge(&source_children, &target_children) {
+            match plan {
+                ChildPlan::Merge(child_id, target_child_id) => {
+                    let child = source_children.iter().find(|c| c.id == child_id);
+                    let target_child = target_children.iter().find(|c| c.id == target_child_id);
+                    if let (Some(child), Some(target_child)) = (child, target_child) {
+                        let (child, target_child) = (child.clone(), target_child.clone());
+                        Box::pin(Self::merge_into(db, child, target_child, evict)).await?;
+                    }
+                }
+                ChildPlan::Move(child_id) => {
+                    KeyPoint::update(key_point::ActiveModel {
+                        id: Set(child_id),
+                        pid: Set(target.id),
+                        ..Default::default()
+                    })
+                    .exec(db)
+                    .await
+                    .with_context(|| format!("move key_point#{child_id} failed"))?;
+                    evict.push(format!("keypoint:{child_id}"));
+                }
+            }
+        }
+
+        let source_path = KeyPoint::find_path(db, source.id).await?.iter().join(".");
+        let target_path = KeyPoint::find_path(db, target.id).await?.iter().join(".");
+        let rebased = PaperQuestion::rebase_keypoint_path(db, &source_path, &target_path).await?;
+        QUESTION_KEY_POINT.rewrite(db, target.id, source.id).await?;
+        QuestionKeypointTag::rebase(db, &source_path, &target_path, source.id, target.id).await?;
+        let parent_path = Self::vendor_parent_path(db, &source).await?;
+        KeyPointMapping::retarget(db, source.id, target.id).await?;
+        KeyPointMapping::save_for_vendors(
+            db,
+            paper_type,
+            &parent_path,
+            &source.name,
+            target.id,
+            true,
+        )
+        .await?;
+        KeyPoint::delete_by_id(source.id)
+            .exec(db)
+            .await
+            .with_context(|| format!("delete key_point#{} failed", source.id))?;
+        tracing::warn!(
+            "key_point#{}({}) merged into key_point#{}({}), {rebased} paper_question rebased",
+            source.id,
+            source.name,
+            target.id,
+            target.name
+        );
+
+        evict.push(format!("keypoint:{}", source.id));
+        evict.push(format!("key_point:by_pid:{paper_type}:{}", source.id));
+        evict.push(format!("key_point:by_pid:{paper_type}:{}", source.pid));
+        evict.push(format!("key_point:by_pid:{paper_type}:{}", target.id));
+        evict.push(format!("key_point:tree:{paper_type}"));
+        Ok(())
+    }
+
+    /// 把知识点移动到pid下，pid为0时移动到根节点，原来的位置映射到该知识点
+    pub async fn move_node(&self, id: i32, pid: i32) -> anyhow::Result<TaxonomyOutcome> {
+        let Some(node) = self.find_key_point(id).await? else {
+            return Ok(TaxonomyOutcome::NotFound);
+        };
+        let parent = match pid {
+            0 => None,
+            pid => match self.find_key_point(pid).await? {
+                Some(parent) => Some((parent, KeyPoint::find_path(&self.db, pid).await?)),
+                None => return Ok(TaxonomyOutcome::NotFound),
+            },
+        };
+        let siblings = KeyPoint::find_by_pid(&self.db, node.paper_type, pid).await?;
+        let check = check_move(
+            &node,
+            parent.as_ref().map(|(p, path)| (p, path.as_slice())),
+            &siblings,
+        );
+        if let Err(msg) = check {
+            return Ok(TaxonomyOutcome::Invalid(msg));
+        }
+        if node.pid == pid {
+            return Ok(TaxonomyOutcome::Done);
+        }
+
+        let old_path = KeyPoint::find_path(&self.db, id).await?.iter().join(".");
+        let new_path = match &parent {
+            Some((_, path)) => path.iter().chain([&id]).join("."),
+            None => id.to_string(),
+        };
+        let parent_path = Self::vendor_parent_path(&self.db, &node).await?;
+        let tx = self.db.begin().await.context("begin transaction failed")?;
+        KeyPoint::update(key_point::ActiveModel {
+            id: Set(id),
+            pid: Set(pid),
+            ..Default::default()
+        })
+        .exec(&tx)
+        .await
+        .with_context(|| format!("move key_point#{id} failed"))?;
+        PaperQuestion::rebase_keypoint_path(&tx, &old_path, &new_path).await?;
+        QuestionKeypointTag::rebase(&tx, &old_path, &new_path, id, id).await?;
+        let paper_type = node.paper_type;
+        KeyPointMapping::save_for_vendors(&tx, paper_type, &parent_path, &node.name, id, false)
+            .await?;
+        tx.commit().await.context("commit transaction failed")?;
+
+        let evict = vec![
+            format!("keypoint:{id}"),
+            format!("key_point:by_pid:{paper_type}:{}", node.pid),
+            format!("key_point:by_pid:{paper_type}:{pid}"),
+            format!("key_point:tree:{paper_type}"),
+        ];
+        self.after_change(evict).await;
+        Ok(TaxonomyOutcome::Done)
+    }
+
+    /// 重命名知识点，旧名称映射到该知识点，避免厂商同步时重新创建
+    pub async fn rename(&self, id: i32, name: &str) -> anyhow::Result<TaxonomyOutcome> {
+        let Some(node) = self.find_key_point(id).await? else {
+            return Ok(TaxonomyOutcome::NotFound);
+        };
+        let name = name.trim();
+        let siblings = KeyPoint::find_by_pid(&self.db, node.paper_type, node.pid).await?;
+        if let Err(msg) = check_rename(&node, name, &siblings) {
+            return Ok(TaxonomyOutcome::Invalid(msg));
+        }
+        if node.name == name {
+            return Ok(TaxonomyOutcome::Done);
+        }
+
+        let parent_path = Self::vendor_parent_path(&self.db, &node).await?;
+        let tx = self.db.begin().await.context("begin transaction failed")?;
+        KeyPoint::update(key_point::ActiveModel {
+            id: Set(id),
+            name: Set(name.to_string()),
+            ..Default::default()
+        })
+        .exec(&tx)
+        .await
+        .with_context(|| format!("rename key_point#{id} failed"))?;
+        let paper_type = node.paper_type;
+        KeyPointMapping::save_for_vendors(&tx, paper_type, &parent_path, &node.name, id, true)
+            .await?;
+        tx.commit().await.context("commit transaction failed")?;
+
+        let evict = vec![
+            format!("keypoint:{id}"),
+            format!("key_point:by_pid:{paper_type}:{}", node.pid),
+            format!("key_point:tree:{paper_type}"),
+        ];
+        self.after_change(evict).await;
+        Ok(TaxonomyOutcome::Done)
+    }
+
+    /// 删除叶子知识点，题目的keypoint_path退回到父知识点
+    pub async fn delete(&self, id: i32) -> anyhow::Result<TaxonomyOutcome> {
+        let Some(node) = self.find_key_point(id).await? else {
+            return Ok(TaxonomyOutcome::NotFound);
+        };
+        let children = KeyPoint::find_by_pid(&self.db, node.paper_type, id).await?;
+        if let Err(msg) = check_delete(&children) {
+            return Ok(TaxonomyOutcome::Invalid(msg));
+        }
+
+        let path = KeyPoint::find_path(&self.db, id).await?;
+        let parent_path = path[..path.len().saturating_sub(1)].iter().join(".");
+        let path = path.iter().join(".");
+        let tx = self.db.begin().await.context("begin transaction failed")?;
+        if parent_path.is_empty() {
+            PaperQuestion::clear_keypoint_path(&tx, &path).await?;
+        } else {
+            PaperQuestion::rebase_keypoint_path(&tx, &path, &parent_path).await?;
+        }
+        QuestionKeyPoint::delete_by_key_point_id(&tx, id).await?;
+        QuestionKeypointTag::delete_by_key_point_id(&tx, id).await?;
+        KeyPointMapping::delete_by_key_point_id(&tx, id).await?;
+        KeyPoint::delete_by_id(id)
+            .exec(&tx)
+            .await
+            .with_context(|| format!("delete key_point#{id} failed"))?;
+        tx.commit().await.context("commit transaction failed")?;
+
+        let paper_type = node.paper_type;
+        let evict = vec![
+            format!("keypoint:{id}"),
+            format!("key_point:by_pid:{paper_type}:{id}"),
+            format!("key_point:by_pid:{paper_type}:{}", node.pid),
+            format!("key_point:tree:{paper_type}"),
+        ];
+        self.after_change(evict).await;
+        Ok(TaxonomyOutcome::Done)
+    }
+
+    pub async fn find_mappings(
+        &self,
+        paper_type: i16,
+    ) -> anyhow::Result<Vec<key_point_mapping::Model>> {
+        KeyPointMapping::find_by_paper_type(&self.db, paper_type).await
+    }
+
+    /// 把厂商知识点映射到规范知识点，知识点不存在或试卷类型不一致时返回false
+    pub async fn save_mapping(
+        &self,
+        from_ty: FromType,
+        paper_type: i16,
+        parent_path: &str,
+        name: &str,
+        key_point_id: i32,
+    ) -> anyhow::Result<bool> {
+        match self.find_key_point(key_point_id).await? {
+            Some(kp) if kp.paper_type == paper_type => {
+                let parent_path = parent_path.trim().trim_matches('/');
+                KeyPointMapping::save(&self.db, from_ty, paper_type, parent_path, name, kp.id)
+                    .await?;
+                Ok(true)
+            }
+            _ => Ok(false),
+        }
+    }
+
+    pub async fn delete_mapping(
+        &self,
+        from_ty: FromType,
+        paper_type: i16,
+        parent_path: &str,
+        name: &str,
+    ) -> anyhow::Result<bool> {
+        KeyPointMapping::delete(&self.db, from_ty, paper_type, parent_path, name).await
+    }
+
+    /// 父知识点名称用/连接的路径，厂商按知识点树同步时用这个路径查找映射
+    async fn vendor_parent_path<C: ConnectionTrait>(
+        db: &C,
+        node: &key_point::Model,
+    ) -> anyhow::Result<String> {
+        if node.pid == 0 {
+            return Ok(String::new());
+        }
+        let path = KeyPoint::find_path(db, node.pid).await?;
+        let names: HashMap<i32, String> = KeyPoint::find()
+            .filter(key_point::Column::Id.is_in(path.clone()))
+            .all(db)
+            .await
+            .with_context(|| format!("find parents of key_point#{} failed", node.id))?
+            .into_iter()
+            .map(|kp| (kp.id, kp.name))
+            .collect();
+        Ok(path.iter().filter_map(|id| names.get(id)).join("/"))
+    }
+
+    async fn find_key_point(&self, id: i32) -> anyhow::Result<Option<key_point::Model>> {
+        KeyPoint::find_by_id(id)
+            .one(&self.db)
+            .await
+            .with_context(|| format!("find key_point#{id} failed"))
+    }
+
+    /// 数据已经提交，统计和缓存刷新失败只记录日志
+    async fn after_change(&self, evict: Vec<String>) {
+        if let Err(e) = QuestionKeyPointStats::refresh(&self.db).await {
+            tracing::error!("refresh question_key_point_stats failed: {e:?}");
+        }
+        let keys = evict.into_iter().unique().collect_vec();
+        let result: Result<(), _> = self.redis.clone().del(&keys).await;
+        if let Err(e) = result {
+            tracing::error!("evict key_point cache {keys:?} failed: {e:?}");
+        }
+    }
+}
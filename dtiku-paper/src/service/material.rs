@@ -0,0 +1,131 @@
+use crate::{
+    domain::material::{
+        material_text, merge_conflict, MaterialDuplicateReview, SharedMaterial, SharedMaterialPaper,
+    },
+    model::{
+        DuplicateStatus, Material, MaterialDuplicate, MaterialPaperStats, Paper, PaperMaterial,
+    },
+};
+use anyhow::{bail, Context};
+use itertools::Itertools;
+use sea_orm::{DbConn, TransactionTrait};
+use spring::plugin::service::Service;
+use spring_sea_orm::pagination::{Page, Pagination};
+use std::collections::HashMap;
+
+#[derive(Clone, Service)]
+pub struct MaterialService {
+    #[inject(component)]
+    db: DbConn,
+}
+
+impl MaterialService {
+    /// 至少被min_paper_count份试卷共用的材料
+    pub async fn find_shared_page(
+        &self,
+        min_paper_count: i64,
+        pagination: &Pagination,
+    ) -> anyhow::Result<Page<SharedMaterial>> {
+        let page =
+            MaterialPaperStats::find_page_by_min_paper_count(&self.db, min_paper_count, pagination)
+                .await?;
+        let mids = page.content.iter().map(|s| s.material_id).collect_vec();
+        let mut contents: HashMap<i32, String> = Material::find_by_ids(&self.db, mids.clone())
+            .await?
+            .into_iter()
+            .map(|m| (m.id, m.content))
+            .collect();
+        let mut mid_pids = PaperMaterial::find_paper_ids_by_material_ids(&self.db, mids).await?;
+        let pids = mid_pids.values().flatten().copied().unique().collect_vec();
+        let papers: HashMap<i32, SharedMaterialPaper> = Paper::find_by_ids(&self.db, pids)
+            .await?
+            .into_iter()
+            .map(|p| {
+                (
+                    p.id,
+                    SharedMaterialPaper {
+                        id: p.id,
+                        title: p.title,
+                        year: p.year,
+                    },
+                )
+            })
+            .collect();
+        Ok(page.map(|s| SharedMaterial {
+            material_id: s.material_id,
+            paper_count: s.paper_count,
+            content: contents.remove(&s.material_id).unwrap_or_default(),
+            papers: mid_pids
+                .remove(&s.material_id)
+                .unwrap_or_default()
+                .iter()
+                .filter_map(|pid| papers.get(pid).cloned())
+                .collect(),
+        }))
+    }
+
+    pub async fn find_duplicate_page(
+        &self,
+        status: DuplicateStatus,
+        pagination: &Pagination,
+    ) -> anyhow::Result<Page<MaterialDuplicateReview>> {
+        let page = MaterialDuplicate::find_page_by_status(&self.db, status, pagination).await?;
+        let mids = page
+            .content
+            .iter()
+            .flat_map(|d| [d.material_id, d.candidate_id])
+            .unique()
+            .collect();
+        let mut contents: HashMap<i32, String> = Material::find_by_ids(&self.db, mids)
+            .await?
+            .into_iter()
+            .map(|m| (m.id, m.content))
+            .collect();
+        Ok(page.map(|duplicate| MaterialDuplicateReview {
+            material: contents.get(&duplicate.material_id).cloned(),
+            candidate: contents.remove(&duplicate.candidate_id),
+            duplicate,
+        }))
+    }
+
+    pub async fn ignore_duplicate(&self, id: i32) -> anyhow::Result<()> {
+        MaterialDuplicate::update_status(&self.db, id, DuplicateStatus::Ignored).await
+    }
+
+    /// 把remove_id的材料合并到keep_id，数字或图片不一致时拒绝合并并返回原因
+    pub async fn merge(
+        &self,
+        keep_id: i32,
+        remove_id: i32,
+        duplicate_id: Option<i32>,
+    ) -> anyhow::Result<Option<&'static str>> {
+        let mut contents: HashMap<i32, String> =
+            Material::find_by_ids(&self.db, vec![keep_id, remove_id])
+                .await?
+                .into_iter()
+                .map(|m| (m.id, m.content))
+                .collect();
+        let (Some(keep), Some(remove)) = (contents.remove(&keep_id), contents.remove(&remove_id))
+        else {
+            bail!("material#{keep_id} or material#{remove_id} not found");
+        };
+        let conflict = merge_conflict(
+            &keep,
+            &material_text(&keep),
+            &remove,
+            &material_text(&remove),
+        );
+        if conflict.is_some() {
+            return Ok(conflict);
+        }
+
+        let tx = self.db.begin().await.context("begin transaction failed")?;
+        Material::merge(&tx, keep_id, remove_id).await?;
+        if let Some(duplicate_id) = duplicate_id {
+            MaterialDuplicate::update_status(&tx, duplicate_id, DuplicateStatus::Merged).await?;
+        }
+        MaterialDuplicate::ignore_pending_by_material_id(&tx, remove_id).await?;
+        tx.commit().await.context("commit transaction failed")?;
+        Ok(None)
+    }
+}
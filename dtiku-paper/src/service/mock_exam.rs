@@ -0,0 +1,353 @@
+use crate::{
+    domain::mock_exam::{plan_chapters, ChapterPlan, ReferencePaper, ReferenceQuestion},
+    model::{
+        label, mock_paper,
+        paper::{self, Chapters, PaperChapter, PaperExtra},
+        paper_material, paper_question, ExerciseAnswer, Label, MockPaper, Paper, PaperMaterial,
+        PaperQuestion, QuestionMaterial,
+    },
+};
+use anyhow::Context;
+use chrono::{Datelike, Local, TimeDelta};
+use itertools::Itertools;
+use sea_orm::{
+    ActiveModelTrait, ActiveValue::Set, ConnectionTrait, DbConn, EntityTrait, TransactionTrait,
+};
+use spring::plugin::service::Service;
+use std::collections::HashSet;
+
+/// 参考最近几张真题的题型分布
+const REFERENCE_PAPER_COUNT: u64 = 5;
+/// 材料题章节每次随机候选的材料数
+const MATERIAL_CANDIDATES: u64 = 50;
+/// 模拟卷所在的隐藏标签
+const MOCK_LABEL_NAME: &str = "模拟卷";
+/// 每个用户24小时内最多生成的个人模拟卷数
+const DAILY_PERSONAL_MOCK_LIMIT: u64 = 5;
+
+/// 用户申请个人模拟卷的结果
+#[derive(Debug)]
+pub enum PersonalMock {
+    Ready(paper::Model),
+    NoReference,
+    RateLimited,
+}
+
+#[derive(Clone, Service)]
+pub struct MockExamService {
+    #[inject(component)]
+    db: DbConn,
+}
+
+impl MockExamService {
+    /// 个人模拟卷：有未交卷的直接复用，否则在限额内生成一份新的
+    pub async fn personal(
+        &self,
+        paper_type: i16,
+        label_id: i32,
+        user_id: i32,
+    ) -> anyhow::Result<PersonalMock> {
+        let unfinished =
+            MockPaper::find_unfinished_by_user(&self.db, user_id, paper_type, label_id).await?;
+        if let Some(paper_id) = unfinished {
+            let paper = Paper::find_by_id(paper_id)
+                .one(&self.db)
+                .await
+                .with_context(|| format!("Paper::find_by_id({paper_id}) failed"))?;
+            if let Some(paper) = paper {
+                return Ok(PersonalMock::Ready(paper));
+            }
+        }
+        let since = Local::now().naive_local() - TimeDelta::days(1);
+        let count = MockPaper::count_by_user_since(&self.db, user_id, since).await?;
+        if count >= DAILY_PERSONAL_MOCK_LIMIT {
+            return Ok(PersonalMock::RateLimited);
+        }
+        let paper = self.generate(paper_type, label_id, Some(user_id)).await?;
+        Ok(paper.map_or(PersonalMock::NoReference, PersonalMock::Ready))
+    }
+
+    /// 按地区近年真题的章节结构、知识点分布和难度抽题生成模拟卷。
+    /// user_id不为空时生成个人模拟卷，避开该用户做过的题；没有可参考的真题时返回None
+    pub async fn generate(
+        &self,
+        paper_type: i16,
+        label_id: i32,
+        user_id: Option<i32>,
+    ) -> anyhow::Result<Option<paper::Model>> {
+        let refs =
+            Paper::find_recent_by_label(&self.db, paper_type, label_id, REFERENCE_PAPER_COUNT)
+                .await?
+                .into_iter()
+                .filter(|p| matches!(p.extra, PaperExtra::Chapters(_)))
+                .collect_vec();
+        let Some(latest) = refs.first() else {
+            return Ok(None);
+        };
+        let ref_ids = refs.iter().map(|p| p.id).collect_vec();
+        let reference_papers = self.reference_papers(&refs).await?;
+
+        let mut excluded: HashSet<i32> = reference_papers
+            .iter()
+            .flat_map(|(qids, _)| qids.iter().copied())
+            .collect();
+        let reference_papers = reference_papers.into_iter().map(|(_, p)| p).collect_vec();
+
+        let mut chapters = vec![];
+        let mut question_ids = vec![];
+        let mut material_ids = vec![];
+        for plan in plan_chapters(&reference_papers) {
+            let qids = if plan.material {
+                let (mids, qids) = self
+                    .sample_material_groups(paper_type, &ref_ids, &plan, user_id, &mut excluded)
+                    .await?;
+                material_ids.extend(mids);
+                qids
+            } else {
+                self.sample_questions(paper_type, &plan, user_id, &mut excluded)
+                    .await?
+            };
+            if qids.is_empty() {
+                continue;
+            }
+            chapters.push(PaperChapter {
+                count: qids.len() as i16,
+                ..plan.chapter
+            });
+            question_ids.extend(qids);
+        }
+        if question_ids.is_empty() {
+            return Ok(None);
+        }
+
+        let label_name = Label::find_by_id(label_id)
+            .one(&self.db)
+            .await
+            .with_context(|| format!("Label::find_by_id({label_id}) failed"))?
+            .map(|l| l.name)
+            .unwrap_or_default();
+        let now = Local::now();
+        let extra = PaperExtra::Chapters(Chapters {
+            desc: Some(format!(
+                "根据{label_name}近{}套真题的题型和知识点分布生成",
+                refs.len()
+            )),
+            chapters,
+        });
+
+        let tx = self.db.begin().await.context("begin transaction failed")?;
+        let mock_label = Self::mock_label(&tx, latest.exam_id, paper_type).await?;
+        let paper = paper::ActiveModel {
+            title: Set(format!(
+                "{label_name}{MOCK_LABEL_NAME}{}",
+                now.format("%Y%m%d%H%M%S%3f")
+            )),
+            year: Set(now.year() as i16),
+            exam_id: Set(latest.exam_id),
+            paper_type: Set(paper_type),
+            label_id: Set(mock_label.id),
+            extra: Set(extra),
+            time_limit: Set(latest.time_limit),
+            ..Default::default()
+        }
+        .insert(&tx)
+        .await
+        .context("insert mock paper failed")?;
+
+        let pqs =
+            question_ids
+                .into_iter()
+                .enumerate()
+                .map(|(i, qid)| paper_question::ActiveModel {
+                    paper_id: Set(paper.id),
+                    question_id: Set(qid),
+                    sort: Set(i as i16 + 1),
+                    paper_type: Set(paper_type),
+                    ..Default::default()
+                });
+        PaperQuestion::insert_many(pqs)
+            .exec(&tx)
+            .await
+            .context("insert mock paper_question failed")?;
+        if !material_ids.is_empty() {
+            let pms =
+                material_ids
+                    .into_iter()
+                    .enumerate()
+                    .map(|(i, mid)| paper_material::ActiveModel {
+                        paper_id: Set(paper.id),
+                        material_id: Set(mid),
+                        sort: Set(i as i16 + 1),
+                    });
+            PaperMaterial::insert_many(pms)
+                .exec(&tx)
+                .await
+                .context("insert mock paper_material failed")?;
+        }
+        MockPaper::insert(mock_paper::ActiveModel {
+            paper_id: Set(paper.id),
+            user_id: Set(user_id),
+            label_id: Set(label_id),
+            ..Default::default()
+        })
+        .exec(&tx)
+        .await
+        .context("insert mock_paper failed")?;
+        tx.commit().await.context("commit mock paper failed")?;
+        Ok(Some(paper))
+    }
+
+    /// 真题中的题目id以及题目的知识点、难度画像
+    async fn reference_papers(
+        &self,
+        refs: &[paper::Model],
+    ) -> anyhow::Result<Vec<(Vec<i32>, ReferencePaper)>> {
+        let profiles =
+            PaperQuestion::find_profile_by_paper_ids(&self.db, refs.iter().map(|p| p.id).collect())
+                .await?;
+        let qids = profiles.iter().map(|p| p.question_id).collect_vec();
+        let material_qids: HashSet<i32> = QuestionMaterial::find_by_qids(&self.db, qids)
+            .await?
+            .into_keys()
+            .collect();
+        let mut profile_map = profiles.into_iter().into_group_map_by(|p| p.paper_id);
+        Ok(refs
+            .iter()
+            .filter_map(|p| {
+                let PaperExtra::Chapters(chapters) = &p.extra else {
+                    return None;
+                };
+                let profiles = profile_map.remove(&p.id).unwrap_or_default();
+                let qids = profiles.iter().map(|q| q.question_id).collect();
+                let questions = profiles
+                    .into_iter()
+                    .map(|q| ReferenceQuestion {
+                        num: q.sort as usize,
+                        has_material: material_qids.contains(&q.question_id),
+                        keypoint_path: q.keypoint_path,
+                        correct_ratio: q.correct_ratio,
+                    })
+                    .collect();
+                Some((
+                    qids,
+                    ReferencePaper {
+                        chapters: chapters.clone(),
+                        questions,
+                    },
+                ))
+            })
+            .collect())
+    }
+
+    /// 按知识点配额抽题，某个知识点题量不够时从上一级知识点补足
+    async fn sample_questions(
+        &self,
+        paper_type: i16,
+        plan: &ChapterPlan,
+        user_id: Option<i32>,
+        excluded: &mut HashSet<i32>,
+    ) -> anyhow::Result<Vec<i32>> {
+        let mut result = vec![];
+        for quota in &plan.quotas {
+            let mut paths = vec![quota.keypoint_path.as_deref()];
+            if let Some((parent, _)) = quota
+                .keypoint_path
+                .as_deref()
+                .and_then(|p| p.rsplit_once('.'))
+            {
+                paths.push(Some(parent));
+            }
+            let mut remain = quota.count;
+            for path in paths {
+                if remain == 0 {
+                    break;
+                }
+                let qids = PaperQuestion::sample_question_ids(
+                    &self.db,
+                    paper_type,
+                    path,
+                    quota.correct_ratio,
+                    excluded.iter().copied().collect(),
+                    user_id,
+                    remain as u64,
+                )
+                .await?;
+                remain -= qids.len();
+                excluded.extend(&qids);
+                result.extend(qids);
+            }
+        }
+        Ok(result)
+    }
+
+    /// 材料题章节整组抽取材料和材料下的题目，返回(材料id, 题目id)
+    async fn sample_material_groups(
+        &self,
+        paper_type: i16,
+        ref_ids: &[i32],
+        plan: &ChapterPlan,
+        user_id: Option<i32>,
+        excluded: &mut HashSet<i32>,
+    ) -> anyhow::Result<(Vec<i32>, Vec<i32>)> {
+        let mids = PaperMaterial::sample_material_ids(
+            &self.db,
+            paper_type,
+            ref_ids.to_vec(),
+            MATERIAL_CANDIDATES,
+        )
+        .await?
+        .into_iter()
+        .unique()
+        .collect_vec();
+        let mut groups = QuestionMaterial::find_by_material_ids(&self.db, mids.clone()).await?;
+        if let Some(user_id) = user_id {
+            let qids = groups.values().flatten().copied().collect();
+            let answered = ExerciseAnswer::find_answered_question_ids(&self.db, user_id, qids);
+            excluded.extend(answered.await?);
+        }
+
+        let mut remain = plan.chapter.count.max(0) as usize;
+        let mut material_ids = vec![];
+        let mut question_ids = vec![];
+        for mid in mids {
+            if remain == 0 {
+                break;
+            }
+            let Some(qids) = groups.remove(&mid) else {
+                continue;
+            };
+            if qids.len() > remain || qids.iter().any(|qid| excluded.contains(qid)) {
+                continue;
+            }
+            remain -= qids.len();
+            excluded.extend(&qids);
+            material_ids.push(mid);
+            question_ids.extend(qids);
+        }
+        Ok((material_ids, question_ids))
+    }
+
+    async fn mock_label<C: ConnectionTrait>(
+        db: &C,
+        exam_id: i16,
+        paper_type: i16,
+    ) -> anyhow::Result<label::Model> {
+        if let Some(l) =
+            Label::find_by_exam_id_and_paper_type_and_name(db, exam_id, paper_type, MOCK_LABEL_NAME)
+                .await?
+        {
+            return Ok(l);
+        }
+        label::ActiveModel {
+            name: Set(MOCK_LABEL_NAME.to_string()),
+            pid: Set(0),
+            exam_id: Set(exam_id),
+            paper_type: Set(paper_type),
+            hidden: Set(true),
+            ..Default::default()
+        }
+        .insert_on_conflict(db)
+        .await
+        .context("insert mock label failed")
+    }
+}
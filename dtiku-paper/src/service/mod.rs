@@ -1,5 +1,16 @@
 pub mod exam_category;
+pub mod exercise;
+pub mod export;
 pub mod keypoint;
+pub mod keypoint_tag;
+pub mod keypoint_taxonomy;
 pub mod label;
+pub mod material;
+pub mod mock_exam;
 pub mod paper;
 pub mod question;
+pub mod question_duplicate;
+pub mod question_feedback;
+pub mod revision;
+pub mod user_note;
+pub mod wrong_question;
@@ -1,17 +1,31 @@
 use crate::{
-    domain::question::QuestionSearch,
+    domain::{
+        irt::{ability_keypoint_ids, update_ability, IrtParams, KeypointAbility},
+        question::{QuestionCorrectRatio, QuestionSearch, QuestionSearchHit},
+        search::{
+            highlight_snippet, query_terms, reciprocal_rank_fusion, HYBRID_CANDIDATES,
+            SNIPPET_CHARS,
+        },
+    },
     model::{
         self, paper_question,
-        question::{self, PaperWithNum, QuestionSinglePaper, QuestionWithPaper},
-        Material, Paper, PaperQuestion, Question, QuestionMaterial, Solution,
+        question::{PaperWithNum, QuestionSinglePaper, QuestionWithPaper},
+        question_search_index::SearchRow,
+        user_ability, AdaptiveAnswer, Material, Paper, PaperQuestion, Question, QuestionIrt,
+        QuestionMaterial, QuestionSearchIndex, Solution, UserAbility, WrongQuestion,
     },
     query::question::{PaperQuestionQuery, SectionType},
 };
 use anyhow::Context;
 use itertools::Itertools;
-use sea_orm::{DbConn, EntityTrait};
+use sea_orm::{DbConn, EntityTrait, TransactionTrait};
 use spring::plugin::service::Service;
-use std::collections::HashMap;
+use spring_sea_orm::pagination::{Page, Pagination};
+use std::collections::{HashMap, HashSet};
+
+/// 自适应练习的目标答对概率范围
+const MIN_TARGET_PROBABILITY: f32 = 0.3;
+const MAX_TARGET_PROBABILITY: f32 = 0.95;
 
 #[derive(Clone, Service)]
 pub struct QuestionService {
@@ -20,11 +34,106 @@ pub struct QuestionService {
 }
 
 impl QuestionService {
+    /// 全文检索题目，结果按相关度排序并带有高亮摘要
     pub async fn search_question(
         &self,
         query: &QuestionSearch,
-    ) -> anyhow::Result<Vec<question::QuestionWithPaper>> {
-        Question::search_question(&self.db, query).await
+        pagination: &Pagination,
+    ) -> anyhow::Result<Page<QuestionSearchHit>> {
+        let terms = query_terms(&query.content);
+        let rows = QuestionSearchIndex::search(&self.db, query, &terms, pagination).await?;
+        let hits = self.search_hits(query, terms, rows.content).await?;
+        Ok(Page::new(hits, pagination, rows.total_elements))
+    }
+
+    /// 混合检索：关键词和查询向量各召回一批候选，用倒数排名融合后分页，
+    /// 换一种说法的查询也能找到对应的真题。
+    /// 融合结果翻完后，按关键词相关度接着列出其余命中的题目
+    pub async fn hybrid_search_question(
+        &self,
+        query: &QuestionSearch,
+        embedding: Vec<f32>,
+        pagination: &Pagination,
+    ) -> anyhow::Result<Page<QuestionSearchHit>> {
+        let terms = query_terms(&query.content);
+        let keyword_total = QuestionSearchIndex::count(&self.db, query, &terms).await?;
+        let keyword_rows =
+            QuestionSearchIndex::search_candidates(&self.db, query, &terms, 0, HYBRID_CANDIDATES)
+                .await?;
+        let vector_rows =
+            QuestionSearchIndex::search_by_embedding(&self.db, query, embedding, HYBRID_CANDIDATES)
+                .await?;
+        let fused = reciprocal_rank_fusion(&[
+            keyword_rows.iter().map(|r| r.question_id).collect(),
+            vector_rows.iter().map(|r| r.question_id).collect(),
+        ]);
+        let fused_len = fused.len() as u64;
+        // 向量候选可能也出现在后面的关键词结果中，总数按不重复估算
+        let total = fused_len + keyword_total.saturating_sub(HYBRID_CANDIDATES);
+
+        let offset = pagination.page * pagination.size;
+        let fused_ids: HashSet<i32> = fused.iter().copied().collect();
+        let mut texts: HashMap<i32, SearchRow> = keyword_rows
+            .into_iter()
+            .chain(vector_rows)
+            .map(|r| (r.question_id, r))
+            .collect();
+        let mut rows: Vec<SearchRow> = fused
+            .into_iter()
+            .skip(offset as usize)
+            .take(pagination.size as usize)
+            .filter_map(|id| texts.remove(&id))
+            .collect();
+        let remain = pagination.size.saturating_sub(rows.len() as u64);
+        if remain > 0 && keyword_total > HYBRID_CANDIDATES {
+            let keyword_offset = HYBRID_CANDIDATES + offset.saturating_sub(fused_len);
+            let more = QuestionSearchIndex::search_candidates(
+                &self.db,
+                query,
+                &terms,
+                keyword_offset,
+                remain,
+            )
+            .await?;
+            rows.extend(
+                more.into_iter()
+                    .filter(|r| !fused_ids.contains(&r.question_id)),
+            );
+        }
+        let hits = self.search_hits(query, terms, rows).await?;
+        Ok(Page::new(hits, pagination, total))
+    }
+
+    /// 按检索结果的顺序加载题目，并生成高亮摘要
+    async fn search_hits(
+        &self,
+        query: &QuestionSearch,
+        terms: Vec<String>,
+        rows: Vec<SearchRow>,
+    ) -> anyhow::Result<Vec<QuestionSearchHit>> {
+        let qids = rows.iter().map(|r| r.question_id).collect_vec();
+        let mut questions: HashMap<i32, QuestionWithPaper> =
+            Question::find_by_ids_with_papers(&self.db, qids)
+                .await?
+                .into_iter()
+                .map(|q| (q.id, q))
+                .collect();
+        let highlight = if terms.is_empty() {
+            vec![query.content.trim().to_lowercase()]
+        } else {
+            terms
+        };
+        Ok(rows
+            .into_iter()
+            .filter_map(|r| {
+                questions
+                    .remove(&r.question_id)
+                    .map(|question| QuestionSearchHit {
+                        question,
+                        snippet: highlight_snippet(&r.text, &highlight, SNIPPET_CHARS),
+                    })
+            })
+            .collect())
     }
 
     pub async fn search_question_by_section(
@@ -75,6 +184,71 @@ impl QuestionService {
         Ok((qsp, papers))
     }
 
+    /// 按给定顺序查出题目及解析，题目出现在多张试卷时取第一张，用于收藏夹练习
+    pub async fn find_single_paper_by_ids(
+        &self,
+        qids: Vec<i32>,
+    ) -> anyhow::Result<(Vec<QuestionSinglePaper>, Vec<model::paper::Model>)> {
+        if qids.is_empty() {
+            return Ok((vec![], vec![]));
+        }
+        let pqs = PaperQuestion::find_source_by_question_id_in(&self.db, qids.clone())
+            .await
+            .context("find question papers failed")?;
+        let mut question_id_map: HashMap<i32, model::paper_question::Model> = HashMap::new();
+        for pq in pqs {
+            question_id_map.entry(pq.question_id).or_insert(pq);
+        }
+        let pids = question_id_map
+            .values()
+            .map(|pq| pq.paper_id)
+            .unique()
+            .collect_vec();
+        let papers = Paper::find_by_ids(&self.db, pids).await?;
+        let paper_id_map: HashMap<i32, &model::paper::Model> =
+            papers.iter().map(|p| (p.id, p)).collect();
+        // 没有所属试卷的题目无法展示来源，直接跳过
+        let qids = qids
+            .into_iter()
+            .filter(|qid| {
+                question_id_map
+                    .get(qid)
+                    .is_some_and(|pq| paper_id_map.contains_key(&pq.paper_id))
+            })
+            .collect_vec();
+
+        let mut questions: HashMap<i32, _> = Question::find_by_ids(&self.db, qids.clone())
+            .await?
+            .into_iter()
+            .map(|q| (q.id, q))
+            .collect();
+        let mut qm_map = QuestionMaterial::find_by_qids(&self.db, qids.clone()).await?;
+        let mids = qm_map.values().flatten().cloned().collect_vec();
+        let materials = Material::find_by_ids(&self.db, mids)
+            .await
+            .context("find materials by ids failed")?;
+        let mut id_material_map: HashMap<i32, _> =
+            materials.into_iter().map(|m| (m.id, m)).collect();
+        let ss = Solution::find_by_question_ids(&self.db, qids.clone()).await?;
+        let mut solution_map = ss.into_iter().into_group_map_by(|s| s.question_id);
+
+        let qsp = qids
+            .into_iter()
+            .filter_map(|qid| questions.remove(&qid))
+            .map(|q| {
+                QuestionSinglePaper::new(
+                    q,
+                    &paper_id_map,
+                    &mut question_id_map,
+                    &mut id_material_map,
+                    &mut qm_map,
+                    &mut solution_map,
+                )
+            })
+            .collect_vec();
+        Ok((qsp, papers))
+    }
+
     pub async fn full_question_by_id(&self, id: i32) -> anyhow::Result<Option<QuestionWithPaper>> {
         let q = Question::find_by_id(id).one(&self.db).await?;
         Ok(match q {
@@ -87,7 +261,7 @@ impl QuestionService {
                 let ms = Material::find_by_ids(&self.db, mids).await?;
                 let ss = Solution::find_by_qid(&self.db, q.id).await?;
 
-                let pq = PaperQuestion::find_by_question_id(&self.db, q.id)
+                let pq = PaperQuestion::find_source_by_question_id_in(&self.db, vec![q.id])
                     .await
                     .context("find question paper failed")?;
 
@@ -119,7 +293,7 @@ impl QuestionService {
 
         let all_solutions = Solution::find_by_question_ids(&self.db, ids.clone()).await?;
 
-        let all_pq = PaperQuestion::find_by_question_id_in(&self.db, ids.clone())
+        let all_pq = PaperQuestion::find_source_by_question_id_in(&self.db, ids.clone())
             .await
             .context("find question papers failed")?;
 
@@ -171,4 +345,172 @@ impl QuestionService {
             Ok(vec![])
         }
     }
+
+    /// 题目的厂商正确率和本站用户正确率，题目出现在多张试卷中时优先取有统计数据的一条
+    pub async fn find_correct_ratio(
+        &self,
+        id: i32,
+    ) -> anyhow::Result<Option<QuestionCorrectRatio>> {
+        let pqs = PaperQuestion::find_correct_ratio_by_question_id(&self.db, id).await?;
+        Ok(pqs
+            .iter()
+            .find(|pq| pq.blended_correct_ratio.is_some())
+            .or(pqs.first())
+            .map(QuestionCorrectRatio::from))
+    }
+
+    /// 自适应练习：按用户在该知识点上的能力，选出预测答对概率最接近target的下一道题
+    pub async fn next_adaptive_question(
+        &self,
+        user_id: i32,
+        paper_type: i16,
+        keypoint_path: Option<&str>,
+        target: f32,
+    ) -> anyhow::Result<Option<QuestionSinglePaper>> {
+        let ability = self
+            .find_ability(user_id, paper_type, keypoint_path)
+            .await?;
+        let target = target.clamp(MIN_TARGET_PROBABILITY, MAX_TARGET_PROBABILITY);
+        let qid = QuestionIrt::find_adaptive_question_id(
+            &self.db,
+            user_id,
+            paper_type,
+            keypoint_path,
+            ability,
+            target,
+        )
+        .await?;
+        Ok(match qid {
+            Some(qid) => self
+                .single_paper_question(qid, paper_type)
+                .await?
+                .map(|(q, _)| q),
+            None => None,
+        })
+    }
+
+    /// 提交自适应练习的作答：记录作答、即时修正各级知识点上的能力，答错的题目计入错题本
+    pub async fn answer_adaptive(
+        &self,
+        user_id: i32,
+        paper_type: i16,
+        question_id: i32,
+        answer: String,
+    ) -> anyhow::Result<Option<(QuestionSinglePaper, bool)>> {
+        let Some((q, pq)) = self.single_paper_question(question_id, paper_type).await? else {
+            return Ok(None);
+        };
+        let correct = q.check_answer(&answer);
+        let item = QuestionIrt::find_by_id(question_id)
+            .one(&self.db)
+            .await
+            .with_context(|| format!("QuestionIrt::find_by_id({question_id}) failed"))?
+            .map(|m| m.params())
+            .unwrap_or_else(|| IrtParams::from_correct_ratio(pq.correct_ratio));
+
+        let kp_ids = ability_keypoint_ids(pq.keypoint_path.as_deref());
+        let abilities: HashMap<i32, user_ability::Model> =
+            UserAbility::find_by_user_and_keypoints(&self.db, user_id, paper_type, kp_ids.clone())
+                .await?
+                .into_iter()
+                .map(|a| (a.keypoint_id, a))
+                .collect();
+        let overall = abilities
+            .get(&kp_ids[0])
+            .map(|a| a.ability)
+            .unwrap_or_default();
+        let updated = kp_ids
+            .into_iter()
+            .map(|keypoint_id| {
+                let (ability, answer_count) = abilities
+                    .get(&keypoint_id)
+                    .map(|a| (a.ability, a.answer_count))
+                    .unwrap_or((overall, 0));
+                KeypointAbility {
+                    user_id,
+                    keypoint_id,
+                    ability: update_ability(ability, answer_count, &item, correct),
+                    answer_count: answer_count as usize + 1,
+                }
+            })
+            .collect_vec();
+
+        let tx = self.db.begin().await.context("begin transaction failed")?;
+        AdaptiveAnswer::save(&tx, user_id, question_id, answer.clone(), correct).await?;
+        UserAbility::save_batch(&tx, paper_type, updated).await?;
+        if !correct {
+            WrongQuestion::record_wrong(&tx, user_id, pq.paper_id, vec![(question_id, answer)])
+                .await?;
+        }
+        tx.commit().await.context("commit adaptive answer failed")?;
+        Ok(Some((q, correct)))
+    }
+
+    /// 从最细的知识点往上找已有的能力估计，都没有时按平均水平0处理
+    async fn find_ability(
+        &self,
+        user_id: i32,
+        paper_type: i16,
+        keypoint_path: Option<&str>,
+    ) -> anyhow::Result<f32> {
+        let kp_ids = ability_keypoint_ids(keypoint_path);
+        let abilities: HashMap<i32, f32> =
+            UserAbility::find_by_user_and_keypoints(&self.db, user_id, paper_type, kp_ids.clone())
+                .await?
+                .into_iter()
+                .map(|a| (a.keypoint_id, a.ability))
+                .collect();
+        Ok(kp_ids
+            .iter()
+            .rev()
+            .find_map(|id| abilities.get(id).copied())
+            .unwrap_or_default())
+    }
+
+    /// 加载题目及其在该试卷类型下的一张出处试卷、材料和解答
+    async fn single_paper_question(
+        &self,
+        question_id: i32,
+        paper_type: i16,
+    ) -> anyhow::Result<Option<(QuestionSinglePaper, paper_question::Model)>> {
+        let Some(q) = Question::find_by_id(question_id)
+            .one(&self.db)
+            .await
+            .with_context(|| format!("Question::find_by_id({question_id}) failed"))?
+        else {
+            return Ok(None);
+        };
+        let pq = PaperQuestion::find_source_by_question_id(&self.db, question_id, paper_type);
+        let Some(pq) = pq.await? else {
+            return Ok(None);
+        };
+        let papers = Paper::find_by_ids(&self.db, vec![pq.paper_id]).await?;
+        let paper_id_map: HashMap<i32, &model::paper::Model> =
+            papers.iter().map(|p| (p.id, p)).collect();
+        if paper_id_map.is_empty() {
+            return Ok(None);
+        }
+        let mut question_id_map = HashMap::from([(question_id, pq.clone())]);
+        let mut qm_map = QuestionMaterial::find_by_qids(&self.db, vec![question_id]).await?;
+        let mids = qm_map.values().flatten().cloned().collect_vec();
+        let mut id_material_map: HashMap<i32, _> = Material::find_by_ids(&self.db, mids)
+            .await
+            .context("find materials by ids failed")?
+            .into_iter()
+            .map(|m| (m.id, m))
+            .collect();
+        let mut solution_map = Solution::find_by_question_ids(&self.db, vec![question_id])
+            .await?
+            .into_iter()
+            .into_group_map_by(|s| s.question_id);
+        let q = QuestionSinglePaper::new(
+            q,
+            &paper_id_map,
+            &mut question_id_map,
+            &mut id_material_map,
+            &mut qm_map,
+            &mut solution_map,
+        );
+        Ok(Some((q, pq)))
+    }
 }
@@ -0,0 +1,112 @@
+use crate::{
+    domain::question_duplicate::{DuplicateQuestion, DuplicateReview},
+    model::{merge_table::MergeTable, DuplicateStatus, Question, QuestionDuplicate},
+};
+use anyhow::{bail, Context};
+use itertools::Itertools;
+use sea_orm::{ConnectionTrait, DbBackend, DbConn, Statement, TransactionTrait};
+use spring::plugin::service::Service;
+use spring_sea_orm::pagination::{Page, Pagination};
+use std::collections::HashMap;
+
+const MERGE_TABLES: [MergeTable; 17] = [
+    MergeTable::new("paper_question", "question_id", &["paper_id"]),
+    MergeTable::new("question_material", "question_id", &["material_id"]),
+    MergeTable::new("question_key_point", "question_id", &["key_point_id"]),
+    MergeTable::new("solution", "question_id", &["from_ty"]),
+    MergeTable::new("scraper_solution", "question_id", &["src_url_hash"]),
+    MergeTable::new("assets_ref", "src_id", &["src_type", "assets_id"])
+        .filter("src_type = 'question'"),
+    MergeTable::new(
+        "idiom_ref",
+        "question_id",
+        &["ty", "label_id", "idiom_id", "paper_id"],
+    ),
+    MergeTable::new("wrong_question", "question_id", &["user_id"]),
+    MergeTable::new("exercise_answer", "question_id", &["record_id"]),
+    MergeTable::new("essay_grade", "question_id", &["record_id"]),
+    MergeTable::new("adaptive_answer", "question_id", &["user_id"]),
+    MergeTable::new("question_irt", "question_id", &[]),
+    MergeTable::new("review_card", "item_id", &["user_id", "item_type"])
+        .filter("item_type = 'question'"),
+    MergeTable::plain("question_feedback", "question_id"),
+    MergeTable::plain("user_note", "target_id").filter("target_type = 'question'"),
+    MergeTable::new(
+        "collection_item",
+        "item_id",
+        &["collection_id", "item_type"],
+    )
+    .filter("item_type = 'question'"),
+    MergeTable::new("question_keypoint_tag", "question_id", &[]),
+];
+
+#[derive(Clone, Service)]
+pub struct QuestionDuplicateService {
+    #[inject(component)]
+    db: DbConn,
+}
+
+impl QuestionDuplicateService {
+    pub async fn find_page(
+        &self,
+        status: DuplicateStatus,
+        pagination: &Pagination,
+    ) -> anyhow::Result<Page<DuplicateReview>> {
+        let page = QuestionDuplicate::find_page_by_status(&self.db, status, pagination).await?;
+        let qids = page
+            .content
+            .iter()
+            .flat_map(|d| [d.question_id, d.candidate_id])
+            .unique()
+            .collect();
+        let mut questions: HashMap<i32, DuplicateQuestion> = Question::find_by_ids(&self.db, qids)
+            .await?
+            .into_iter()
+            .map(|q| (q.id, q.into()))
+            .collect();
+        Ok(page.map(|duplicate| DuplicateReview {
+            question: questions.get(&duplicate.question_id).cloned(),
+            candidate: questions.remove(&duplicate.candidate_id),
+            duplicate,
+        }))
+    }
+
+    pub async fn ignore(&self, id: i32) -> anyhow::Result<()> {
+        QuestionDuplicate::update_status(&self.db, id, DuplicateStatus::Ignored).await
+    }
+
+    /// 把remove_id的题目合并到keep_id：关联数据都改写到keep_id，
+    /// 两边都有的关联数据保留keep_id的，最后删除remove_id的题目
+    pub async fn merge(
+        &self,
+        keep_id: i32,
+        remove_id: i32,
+        duplicate_id: Option<i32>,
+    ) -> anyhow::Result<()> {
+        if keep_id == remove_id {
+            bail!("can't merge question#{keep_id} into itself");
+        }
+        let tx = self.db.begin().await.context("begin transaction failed")?;
+        for table in MERGE_TABLES {
+            table.rewrite(&tx, keep_id, remove_id).await?;
+        }
+        for sql in [
+            "delete from question_search_index where question_id = $1",
+            "delete from question where id = $1",
+        ] {
+            tx.execute(Statement::from_sql_and_values(
+                DbBackend::Postgres,
+                sql,
+                [remove_id.into()],
+            ))
+            .await
+            .with_context(|| format!("delete question#{remove_id} failed"))?;
+        }
+        if let Some(duplicate_id) = duplicate_id {
+            QuestionDuplicate::update_status(&tx, duplicate_id, DuplicateStatus::Merged).await?;
+        }
+        QuestionDuplicate::ignore_pending_by_question_id(&tx, remove_id).await?;
+        tx.commit().await.context("commit transaction failed")?;
+        Ok(())
+    }
+}
@@ -0,0 +1,240 @@
+use crate::{
+    domain::question_feedback::{FeedbackFix, FeedbackReview, FeedbackSubmit},
+    model::{
+        question, question_feedback,
+        revision::{RevisionKind, RevisionSource},
+        solution, FeedbackStatus, Question, QuestionFeedback, Solution,
+    },
+};
+use anyhow::{bail, Context};
+use itertools::Itertools;
+use sea_orm::{
+    sqlx::types::chrono::Local, ActiveModelTrait, ActiveValue::Set, ConnectionTrait, DbConn,
+    EntityTrait, TransactionTrait,
+};
+use spring::plugin::service::Service;
+use spring_sea_orm::pagination::{Page, Pagination};
+use std::collections::HashMap;
+
+/// 采纳或驳回纠错的结果
+#[derive(Debug)]
+pub enum FeedbackResolution {
+    Resolved,
+    NotFound,
+    /// 纠错已被处理过，不能重复处理
+    AlreadyResolved,
+}
+
+#[derive(Clone, Service)]
+pub struct QuestionFeedbackService {
+    #[inject(component)]
+    db: DbConn,
+}
+
+impl QuestionFeedbackService {
+    /// 提交纠错，题目不存在时返回None
+    pub async fn submit(
+        &self,
+        user_id: i32,
+        submit: FeedbackSubmit,
+    ) -> anyhow::Result<Option<question_feedback::Model>> {
+        let question = Question::find_by_id(submit.question_id)
+            .one(&self.db)
+            .await
+            .with_context(|| format!("find question#{} failed", submit.question_id))?;
+        if question.is_none() {
+            return Ok(None);
+        }
+        let now = Local::now().naive_local();
+        let feedback = question_feedback::ActiveModel {
+            question_id: Set(submit.question_id),
+            user_id: Set(user_id),
+            category: Set(submit.category),
+            content: Set(submit.content.trim().to_string()),
+            suggested_answer: Set(submit
+                .suggested_answer
+                .map(|a| a.trim().to_string())
+                .filter(|a| !a.is_empty())),
+            status: Set(FeedbackStatus::Open),
+            user_read: Set(true),
+            created: Set(now),
+            modified: Set(now),
+            ..Default::default()
+        }
+        .insert(&self.db)
+        .await
+        .context("insert question_feedback failed")?;
+        Ok(Some(feedback))
+    }
+
+    pub async fn find_page(
+        &self,
+        status: FeedbackStatus,
+        pagination: &Pagination,
+    ) -> anyhow::Result<Page<FeedbackReview>> {
+        let page = QuestionFeedback::find_page_by_status(&self.db, status, pagination).await?;
+        let qids = page
+            .content
+            .iter()
+            .map(|f| f.question_id)
+            .unique()
+            .collect_vec();
+        let mut solutions = Solution::find_by_question_ids(&self.db, qids.clone())
+            .await?
+            .into_iter()
+            .into_group_map_by(|s| s.question_id);
+        let questions: HashMap<i32, question::Model> = Question::find_by_ids(&self.db, qids)
+            .await?
+            .into_iter()
+            .map(|q| (q.id, q))
+            .collect();
+        Ok(page.map(|feedback| FeedbackReview {
+            question: questions
+                .get(&feedback.question_id)
+                .cloned()
+                .map(Into::into),
+            solutions: solutions.remove(&feedback.question_id).unwrap_or_default(),
+            feedback,
+        }))
+    }
+
+    /// 采纳纠错并修改题目、解答，修改前的内容保存为修订记录
+    pub async fn accept(
+        &self,
+        id: i32,
+        fix: FeedbackFix,
+        reply: Option<String>,
+        handler_id: Option<i32>,
+    ) -> anyhow::Result<FeedbackResolution> {
+        let tx = self.db.begin().await.context("begin transaction failed")?;
+        let feedback = match find_open(&tx, id).await? {
+            Ok(feedback) => feedback,
+            Err(resolution) => return Ok(resolution),
+        };
+        let source = RevisionSource::admin(handler_id);
+        apply_question_fix(&tx, feedback.question_id, &fix, &source).await?;
+        apply_solution_fix(&tx, feedback.question_id, fix, &source).await?;
+        // 并发处理同一条纠错时只有一个能成功，其余的回滚对题目的修改
+        if !QuestionFeedback::resolve(&tx, id, FeedbackStatus::Accepted, reply, handler_id).await? {
+            return Ok(FeedbackResolution::AlreadyResolved);
+        }
+        tx.commit().await.context("commit transaction failed")?;
+        Ok(FeedbackResolution::Resolved)
+    }
+
+    /// 驳回纠错
+    pub async fn reject(
+        &self,
+        id: i32,
+        reply: Option<String>,
+        handler_id: Option<i32>,
+    ) -> anyhow::Result<FeedbackResolution> {
+        if let Err(resolution) = find_open(&self.db, id).await? {
+            return Ok(resolution);
+        }
+        if !QuestionFeedback::resolve(&self.db, id, FeedbackStatus::Rejected, reply, handler_id)
+            .await?
+        {
+            return Ok(FeedbackResolution::AlreadyResolved);
+        }
+        Ok(FeedbackResolution::Resolved)
+    }
+
+    /// 用户查看自己的纠错，同时把处理结果标记为已读
+    pub async fn find_user_page(
+        &self,
+        user_id: i32,
+        pagination: &Pagination,
+    ) -> anyhow::Result<Page<question_feedback::Model>> {
+        let page = QuestionFeedback::find_page_by_user(&self.db, user_id, pagination).await?;
+        if page.content.iter().any(|f| !f.user_read) {
+            QuestionFeedback::mark_read_by_user(&self.db, user_id).await?;
+        }
+        Ok(page)
+    }
+
+    pub async fn count_unread(&self, user_id: i32) -> anyhow::Result<u64> {
+        QuestionFeedback::count_unread_by_user(&self.db, user_id).await
+    }
+}
+
+/// 待处理的纠错，不存在或已处理过时返回对应的结果
+async fn find_open<C: ConnectionTrait>(
+    db: &C,
+    id: i32,
+) -> anyhow::Result<Result<question_feedback::Model, FeedbackResolution>> {
+    let feedback = QuestionFeedback::find_by_id(id)
+        .one(db)
+        .await
+        .with_context(|| format!("find question_feedback#{id} failed"))?;
+    Ok(match feedback {
+        None => Err(FeedbackResolution::NotFound),
+        Some(feedback) if feedback.status != FeedbackStatus::Open => {
+            Err(FeedbackResolution::AlreadyResolved)
+        }
+        Some(feedback) => Ok(feedback),
+    })
+}
+
+async fn apply_question_fix<C: ConnectionTrait>(
+    db: &C,
+    question_id: i32,
+    fix: &FeedbackFix,
+    source: &RevisionSource,
+) -> anyhow::Result<()> {
+    if fix.question_content.is_none() && fix.question_extra.is_none() {
+        return Ok(());
+    }
+    let previous = Question::find_by_id(question_id)
+        .one(db)
+        .await
+        .with_context(|| format!("find question#{question_id} failed"))?
+        .with_context(|| format!("question#{question_id} was deleted"))?;
+    let mut model = question::ActiveModel {
+        id: Set(question_id),
+        ..Default::default()
+    };
+    if let Some(content) = &fix.question_content {
+        model.content = Set(content.clone());
+    }
+    if let Some(extra) = &fix.question_extra {
+        model.extra = Set(extra.clone());
+    }
+    let current = model
+        .update(db)
+        .await
+        .with_context(|| format!("update question#{question_id} failed"))?;
+    RevisionKind::Question
+        .record(db, question_id, &previous, &current, source)
+        .await
+}
+
+async fn apply_solution_fix<C: ConnectionTrait>(
+    db: &C,
+    question_id: i32,
+    fix: FeedbackFix,
+    source: &RevisionSource,
+) -> anyhow::Result<()> {
+    let (Some(solution_id), Some(extra)) = (fix.solution_id, fix.solution_extra) else {
+        return Ok(());
+    };
+    let previous = Solution::find_by_id(solution_id)
+        .one(db)
+        .await
+        .with_context(|| format!("find solution#{solution_id} failed"))?
+        .with_context(|| format!("solution#{solution_id} was deleted"))?;
+    if previous.question_id != question_id {
+        bail!("solution#{solution_id} doesn't belong to question#{question_id}");
+    }
+    let current = solution::ActiveModel {
+        id: Set(solution_id),
+        extra: Set(extra),
+        ..Default::default()
+    }
+    .update(db)
+    .await
+    .with_context(|| format!("update solution#{solution_id} failed"))?;
+    RevisionKind::Solution
+        .record(db, solution_id, &previous, &current, source)
+        .await
+}
@@ -0,0 +1,121 @@
+use crate::model::{
+    material, paper, question,
+    revision::{Revision, RevisionKind, RevisionSource},
+    solution,
+};
+use anyhow::Context;
+use sea_orm::{
+    ActiveModelTrait, ConnectionTrait, DbConn, EntityTrait, IntoActiveModel, TransactionTrait,
+};
+use serde::{de::DeserializeOwned, Serialize};
+use spring::plugin::service::Service;
+
+#[derive(Clone, Service)]
+pub struct RevisionService {
+    #[inject(component)]
+    db: DbConn,
+}
+
+impl RevisionService {
+    pub async fn list(&self, kind: RevisionKind, target_id: i32) -> anyhow::Result<Vec<Revision>> {
+        kind.find_by_target(&self.db, target_id).await
+    }
+
+    /// 回滚到指定修订版本，回滚前的数据也会保存为一条修订记录。
+    /// 修订记录不存在时返回None
+    pub async fn rollback(
+        &self,
+        kind: RevisionKind,
+        revision_id: i32,
+        user_id: Option<i32>,
+    ) -> anyhow::Result<Option<serde_json::Value>> {
+        let tx = self.db.begin().await.context("begin transaction failed")?;
+        let revision = match kind.find_by_id(&tx, revision_id).await? {
+            Some(revision) => revision,
+            None => return Ok(None),
+        };
+        let source = RevisionSource::admin(user_id);
+        let restored = match kind {
+            RevisionKind::Question => {
+                let current = question::Entity::find_by_id(revision.target_id)
+                    .one(&tx)
+                    .await
+                    .with_context(|| format!("find question#{} failed", revision.target_id))?;
+                let snapshot: question::Model = parse_snapshot(&revision)?;
+                record_current(&tx, kind, &revision, current, &snapshot, &source).await?;
+                snapshot
+                    .into_active_model()
+                    .reset_all()
+                    .update(&tx)
+                    .await
+                    .context("restore question failed")?;
+                revision.snapshot
+            }
+            RevisionKind::Material => {
+                let current = material::Entity::find_by_id(revision.target_id)
+                    .one(&tx)
+                    .await
+                    .with_context(|| format!("find material#{} failed", revision.target_id))?;
+                let snapshot: material::Model = parse_snapshot(&revision)?;
+                record_current(&tx, kind, &revision, current, &snapshot, &source).await?;
+                material::Entity::restore(&tx, &snapshot).await?;
+                revision.snapshot
+            }
+            RevisionKind::Solution => {
+                let current = solution::Entity::find_by_id(revision.target_id)
+                    .one(&tx)
+                    .await
+                    .with_context(|| format!("find solution#{} failed", revision.target_id))?;
+                let snapshot: solution::Model = parse_snapshot(&revision)?;
+                record_current(&tx, kind, &revision, current, &snapshot, &source).await?;
+                snapshot
+                    .into_active_model()
+                    .reset_all()
+                    .update(&tx)
+                    .await
+                    .context("restore solution failed")?;
+                revision.snapshot
+            }
+            RevisionKind::Paper => {
+                let current = paper::Entity::find_by_id(revision.target_id)
+                    .one(&tx)
+                    .await
+                    .with_context(|| format!("find paper#{} failed", revision.target_id))?;
+                let snapshot: paper::Model = parse_snapshot(&revision)?;
+                record_current(&tx, kind, &revision, current, &snapshot, &source).await?;
+                snapshot
+                    .into_active_model()
+                    .reset_all()
+                    .update(&tx)
+                    .await
+                    .context("restore paper failed")?;
+                revision.snapshot
+            }
+        };
+        tx.commit().await.context("commit transaction failed")?;
+        Ok(Some(restored))
+    }
+}
+
+fn parse_snapshot<T: DeserializeOwned>(revision: &Revision) -> anyhow::Result<T> {
+    serde_json::from_value(revision.snapshot.clone())
+        .with_context(|| format!("parse revision#{} snapshot failed", revision.id))
+}
+
+/// 保存被回滚覆盖的当前数据，数据已被删除时无法回滚
+async fn record_current<C, T>(
+    db: &C,
+    kind: RevisionKind,
+    revision: &Revision,
+    current: Option<T>,
+    snapshot: &T,
+    source: &RevisionSource,
+) -> anyhow::Result<()>
+where
+    C: ConnectionTrait,
+    T: Serialize,
+{
+    let target_id = revision.target_id;
+    let current = current.with_context(|| format!("{kind}#{target_id} was deleted"))?;
+    kind.record(db, target_id, &current, snapshot, source).await
+}
@@ -0,0 +1,184 @@
+use crate::{
+    domain::{
+        material::material_text,
+        user_note::{render_notes_markdown, NoteSave, NoteTarget, UserNoteView, UserNotes},
+    },
+    model::{user_note, Material, PaperMaterial, Question, SrcType, UserNote},
+    service::export::ExportFile,
+};
+use anyhow::Context;
+use itertools::Itertools;
+use sea_orm::{
+    sqlx::types::chrono::Local, ActiveModelTrait, ActiveValue::Set, DbConn, EntityTrait,
+};
+use spring::plugin::service::Service;
+use spring_sea_orm::pagination::{Page, Pagination};
+use std::collections::HashMap;
+
+/// 列表和导出中题目、材料摘要的长度
+const SUMMARY_LENGTH: usize = 60;
+
+#[derive(Clone, Service)]
+pub struct UserNoteService {
+    #[inject(component)]
+    db: DbConn,
+}
+
+impl UserNoteService {
+    /// 新建笔记，题目或材料不存在、高亮的文字与材料不一致时返回None
+    pub async fn create(
+        &self,
+        user_id: i32,
+        save: NoteSave,
+    ) -> anyhow::Result<Option<user_note::Model>> {
+        let target_id = save.target_id;
+        let exists = match &save.highlight {
+            Some(highlight) => Material::find_by_id(target_id)
+                .one(&self.db)
+                .await
+                .with_context(|| format!("find material#{target_id} failed"))?
+                .is_some_and(|m| highlight.matches(&m.content)),
+            None => Question::find_by_id(target_id)
+                .one(&self.db)
+                .await
+                .with_context(|| format!("find question#{target_id} failed"))?
+                .is_some(),
+        };
+        if !exists {
+            return Ok(None);
+        }
+        let now = Local::now().naive_local();
+        let note = user_note::ActiveModel {
+            user_id: Set(user_id),
+            target_type: Set(save.target_type),
+            target_id: Set(target_id),
+            markdown: Set(save.markdown.trim().to_string()),
+            highlight_start: Set(save.highlight.as_ref().map(|h| h.start)),
+            highlight_end: Set(save.highlight.as_ref().map(|h| h.end)),
+            highlight_text: Set(save.highlight.map(|h| h.text)),
+            created: Set(now),
+            modified: Set(now),
+            ..Default::default()
+        }
+        .insert(&self.db)
+        .await
+        .context("insert user_note failed")?;
+        Ok(Some(note))
+    }
+
+    pub async fn update(
+        &self,
+        user_id: i32,
+        id: i32,
+        markdown: String,
+    ) -> anyhow::Result<Option<user_note::Model>> {
+        UserNote::update_markdown(&self.db, user_id, id, markdown.trim().to_string()).await
+    }
+
+    pub async fn delete(&self, user_id: i32, id: i32) -> anyhow::Result<bool> {
+        UserNote::delete_by_user(&self.db, user_id, id).await
+    }
+
+    /// 页面中题目和材料上的笔记
+    pub async fn find_notes(
+        &self,
+        user_id: i32,
+        question_ids: Vec<i32>,
+        material_ids: Vec<i32>,
+    ) -> anyhow::Result<UserNotes> {
+        let mut notes =
+            UserNote::find_by_targets(&self.db, user_id, SrcType::Question, question_ids).await?;
+        if !material_ids.is_empty() {
+            notes.extend(
+                UserNote::find_by_targets(&self.db, user_id, SrcType::Material, material_ids)
+                    .await?,
+            );
+        }
+        Ok(UserNotes::new(notes))
+    }
+
+    pub async fn search(
+        &self,
+        user_id: i32,
+        keyword: Option<&str>,
+        pagination: &Pagination,
+    ) -> anyhow::Result<Page<UserNoteView>> {
+        let page = UserNote::search_page(&self.db, user_id, keyword, pagination).await?;
+        let total = page.total_elements;
+        let views = self.to_views(page.content).await?;
+        Ok(Page::new(views, pagination, total))
+    }
+
+    /// 把用户的所有笔记导出为Markdown文件，site_url用于生成原题链接
+    pub async fn export_markdown(
+        &self,
+        user_id: i32,
+        site_url: &str,
+    ) -> anyhow::Result<ExportFile> {
+        let notes = UserNote::find_all_by_user(&self.db, user_id).await?;
+        let views = self.to_views(notes).await?;
+        Ok(ExportFile {
+            file_name: "我的笔记.md".to_string(),
+            content_type: "text/markdown; charset=utf-8",
+            data: render_notes_markdown(&views, site_url).into_bytes(),
+        })
+    }
+
+    async fn to_views(&self, notes: Vec<user_note::Model>) -> anyhow::Result<Vec<UserNoteView>> {
+        let (qids, mids): (Vec<_>, Vec<_>) = notes
+            .iter()
+            .map(|n| (n.target_type == SrcType::Question, n.target_id))
+            .unique()
+            .partition(|(is_question, _)| *is_question);
+        let qids = qids.into_iter().map(|(_, id)| id).collect_vec();
+        let mids = mids.into_iter().map(|(_, id)| id).collect_vec();
+
+        let questions: HashMap<i32, NoteTarget> = Question::find_by_ids(&self.db, qids)
+            .await?
+            .into_iter()
+            .map(|q| {
+                let target = NoteTarget {
+                    summary: summary(&q.content),
+                    url: format!("/question/detail/{}", q.id),
+                };
+                (q.id, target)
+            })
+            .collect();
+        // 材料没有单独的页面，链接到最早收录这份材料的试卷
+        let paper_ids =
+            PaperMaterial::find_paper_ids_by_material_ids(&self.db, mids.clone()).await?;
+        let materials: HashMap<i32, NoteTarget> = Material::find_by_ids(&self.db, mids)
+            .await?
+            .into_iter()
+            .filter_map(|m| {
+                let paper_id = paper_ids.get(&m.id)?.first()?;
+                let target = NoteTarget {
+                    summary: summary(&m.content),
+                    url: format!("/paper/{paper_id}"),
+                };
+                Some((m.id, target))
+            })
+            .collect();
+
+        Ok(notes
+            .into_iter()
+            .map(|note| {
+                let target = match note.target_type {
+                    SrcType::Material => materials.get(&note.target_id),
+                    _ => questions.get(&note.target_id),
+                };
+                UserNoteView {
+                    target: target.cloned(),
+                    note,
+                }
+            })
+            .collect())
+    }
+}
+
+fn summary(content: &str) -> String {
+    material_text(content)
+        .chars()
+        .take(SUMMARY_LENGTH)
+        .collect()
+}
@@ -0,0 +1,242 @@
+use crate::{
+    domain::wrong_question::{WrongQuestionGroup, WrongQuestionNotebook, WrongQuestionRedo},
+    model::{
+        paper, question::QuestionSinglePaper, wrong_question, KeyPoint, Material, Paper,
+        PaperQuestion, Question, QuestionMaterial, Solution, WrongQuestion,
+    },
+    query::wrong_question::WrongQuestionQuery,
+};
+use anyhow::Context;
+use itertools::Itertools;
+use sea_orm::{ActiveModelTrait, ActiveValue::Set, DbConn, TransactionTrait};
+use spring::plugin::service::Service;
+use std::collections::HashMap;
+
+/// 重做时连续答对几次后移出错题本
+pub const REMOVE_AFTER_REDO_CORRECT: i16 = 3;
+/// 单次重做最多加载的题目数
+const REDO_LIMIT: usize = 100;
+/// 没有知识点路径的题目归到这个分组
+const UNCATEGORIZED_KP: i32 = 0;
+
+#[derive(Clone, Service)]
+pub struct WrongQuestionService {
+    #[inject(component)]
+    db: DbConn,
+}
+
+impl WrongQuestionService {
+    /// 错题本概览：按一级知识点和试卷分组统计
+    pub async fn notebook(&self, user_id: i32) -> anyhow::Result<WrongQuestionNotebook> {
+        let wqs = WrongQuestion::find_by_user_id(&self.db, user_id).await?;
+        let root_kp_map = self.root_keypoint_map(&wqs).await?;
+
+        let mut keypoints = vec![];
+        for (kp_id, count) in wqs
+            .iter()
+            .map(|wq| Self::root_kp_of(&root_kp_map, wq.question_id))
+            .counts()
+        {
+            let name = if kp_id == UNCATEGORIZED_KP {
+                None
+            } else {
+                KeyPoint::find_by_id_with_cache(&self.db, kp_id)
+                    .await?
+                    .map(|kp| kp.name)
+            };
+            keypoints.push(WrongQuestionGroup {
+                id: kp_id,
+                name: name.unwrap_or_else(|| "未分类".to_string()),
+                count,
+            });
+        }
+
+        let paper_counts = wqs.iter().map(|wq| wq.paper_id).counts();
+        let papers = Paper::find_by_ids(&self.db, paper_counts.keys().cloned().collect()).await?;
+        let papers = papers
+            .into_iter()
+            .map(|p| WrongQuestionGroup {
+                count: paper_counts.get(&p.id).cloned().unwrap_or_default(),
+                id: p.id,
+                name: p.title,
+            })
+            .collect_vec();
+
+        Ok(WrongQuestionNotebook {
+            total: wqs.len(),
+            keypoints: Self::sort_groups(keypoints),
+            papers: Self::sort_groups(papers),
+        })
+    }
+
+    /// 按条件加载错题用于重做
+    pub async fn find_redo_questions(
+        &self,
+        user_id: i32,
+        query: &WrongQuestionQuery,
+    ) -> anyhow::Result<(Vec<QuestionSinglePaper>, Vec<paper::Model>)> {
+        let wqs = WrongQuestion::find_by_user_id(&self.db, user_id).await?;
+        let wqs = match query.pid {
+            Some(pid) => wqs.into_iter().filter(|wq| wq.paper_id == pid).collect(),
+            None => wqs,
+        };
+        let wqs = match query.kp {
+            Some(kp) => {
+                let root_kp_map = self.root_keypoint_map(&wqs).await?;
+                wqs.into_iter()
+                    .filter(|wq| Self::root_kp_of(&root_kp_map, wq.question_id) == kp)
+                    .collect()
+            }
+            None => wqs,
+        };
+        let wqs = wqs.into_iter().take(REDO_LIMIT).collect_vec();
+        self.build_questions(&wqs).await
+    }
+
+    /// 提交重做结果：答错的重新计入错题本，连续答对达到次数的移出错题本
+    pub async fn redo(
+        &self,
+        user_id: i32,
+        user_answer: &HashMap<i32, String>,
+    ) -> anyhow::Result<(
+        Vec<QuestionSinglePaper>,
+        Vec<paper::Model>,
+        WrongQuestionRedo,
+    )> {
+        let qids = user_answer.keys().cloned().collect_vec();
+        let wqs = WrongQuestion::find_by_user_and_qids(&self.db, user_id, qids).await?;
+        let (questions, papers) = self.build_questions(&wqs).await?;
+        let wq_map: HashMap<i32, &wrong_question::Model> =
+            wqs.iter().map(|wq| (wq.question_id, wq)).collect();
+
+        let mut result = WrongQuestionRedo::default();
+        let mut wrong_answers: HashMap<i32, Vec<(i32, String)>> = HashMap::new();
+        let tx = self.db.begin().await.context("begin transaction failed")?;
+        for q in &questions {
+            let (Some(answer), Some(wq)) = (user_answer.get(&q.id), wq_map.get(&q.id)) else {
+                continue;
+            };
+            if !q.check_answer(answer) {
+                result.wrong.push(q.id);
+                wrong_answers
+                    .entry(wq.paper_id)
+                    .or_default()
+                    .push((q.id, answer.clone()));
+            } else if wq.redo_correct + 1 >= REMOVE_AFTER_REDO_CORRECT {
+                result.correct.push(q.id);
+                result.removed.push(q.id);
+            } else {
+                result.correct.push(q.id);
+                wrong_question::ActiveModel {
+                    user_id: Set(user_id),
+                    question_id: Set(q.id),
+                    redo_correct: Set(wq.redo_correct + 1),
+                    last_answer: Set(answer.clone()),
+                    ..Default::default()
+                }
+                .update(&tx)
+                .await
+                .with_context(|| format!("update wrong_question({user_id},{}) failed", q.id))?;
+            }
+        }
+        for (paper_id, answers) in wrong_answers {
+            WrongQuestion::record_wrong(&tx, user_id, paper_id, answers).await?;
+        }
+        WrongQuestion::delete_by_user_and_qids(&tx, user_id, result.removed.clone()).await?;
+        tx.commit()
+            .await
+            .context("commit wrong_question redo failed")?;
+
+        Ok((questions, papers, result))
+    }
+
+    async fn build_questions(
+        &self,
+        wqs: &[wrong_question::Model],
+    ) -> anyhow::Result<(Vec<QuestionSinglePaper>, Vec<paper::Model>)> {
+        if wqs.is_empty() {
+            return Ok((vec![], vec![]));
+        }
+        let qid_pid: HashMap<i32, i32> =
+            wqs.iter().map(|wq| (wq.question_id, wq.paper_id)).collect();
+        let qids = qid_pid.keys().cloned().collect_vec();
+        let pids = qid_pid.values().cloned().unique().collect_vec();
+
+        let papers = Paper::find_by_ids(&self.db, pids).await?;
+        let paper_id_map: HashMap<i32, &paper::Model> = papers.iter().map(|p| (p.id, p)).collect();
+        let mut question_id_map: HashMap<i32, _> =
+            PaperQuestion::find_by_question_id_in(&self.db, qids.clone())
+                .await?
+                .into_iter()
+                .filter(|pq| qid_pid.get(&pq.question_id) == Some(&pq.paper_id))
+                .map(|pq| (pq.question_id, pq))
+                .collect();
+
+        let questions = Question::find_by_ids(&self.db, qids.clone()).await?;
+        let mut qm_map = QuestionMaterial::find_by_qids(&self.db, qids.clone()).await?;
+        let mids = qm_map.values().flatten().cloned().collect_vec();
+        let materials = Material::find_by_ids(&self.db, mids)
+            .await
+            .context("find materials by ids failed")?;
+        let mut id_material_map: HashMap<i32, _> =
+            materials.into_iter().map(|m| (m.id, m)).collect();
+        let mut solution_map = Solution::find_by_question_ids(&self.db, qids)
+            .await?
+            .into_iter()
+            .into_group_map_by(|s| s.question_id);
+
+        // 试卷已被删除的题目无法展示出处，直接跳过
+        question_id_map.retain(|_, pq| paper_id_map.contains_key(&pq.paper_id));
+        let questions = questions
+            .into_iter()
+            .filter(|q| question_id_map.contains_key(&q.id))
+            .collect_vec();
+        let questions = questions
+            .into_iter()
+            .map(|q| {
+                QuestionSinglePaper::new(
+                    q,
+                    &paper_id_map,
+                    &mut question_id_map,
+                    &mut id_material_map,
+                    &mut qm_map,
+                    &mut solution_map,
+                )
+            })
+            .sorted_by_key(|q| (q.paper.paper.id, q.paper.num))
+            .collect_vec();
+        Ok((questions, papers))
+    }
+
+    /// question_id => 一级知识点id
+    async fn root_keypoint_map(
+        &self,
+        wqs: &[wrong_question::Model],
+    ) -> anyhow::Result<HashMap<i32, i32>> {
+        let qids = wqs.iter().map(|wq| wq.question_id).collect_vec();
+        let kp_paths = PaperQuestion::find_keypoint_path_by_question_id_in(&self.db, qids).await?;
+        Ok(kp_paths
+            .into_iter()
+            .filter_map(|(qid, path)| {
+                path.split('.')
+                    .next()
+                    .and_then(|root| root.parse().ok())
+                    .map(|root| (qid, root))
+            })
+            .collect())
+    }
+
+    fn root_kp_of(root_kp_map: &HashMap<i32, i32>, question_id: i32) -> i32 {
+        root_kp_map
+            .get(&question_id)
+            .cloned()
+            .unwrap_or(UNCATEGORIZED_KP)
+    }
+
+    fn sort_groups(groups: Vec<WrongQuestionGroup>) -> Vec<WrongQuestionGroup> {
+        groups
+            .into_iter()
+            .sorted_by(|a, b| b.count.cmp(&a.count).then(a.id.cmp(&b.id)))
+            .collect()
+    }
+}
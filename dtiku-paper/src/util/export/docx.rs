@@ -0,0 +1,159 @@
+use super::{ExportImage, ExportImages};
+use crate::domain::export::{Block, ExportDocument, Run};
+use anyhow::Context;
+use std::{
+    collections::HashMap,
+    io::{Cursor, Write},
+};
+use zip::{write::SimpleFileOptions, ZipWriter};
+
+/// 每英寸的EMU数，docx中图片尺寸的单位
+const EMU_PER_INCH: u64 = 914400;
+/// 图片按96dpi换算
+const IMAGE_DPI: u64 = 96;
+/// A4纸去掉默认页边距后的可用宽度：6英寸
+const MAX_IMAGE_WIDTH_EMU: u64 = 6 * EMU_PER_INCH;
+/// 字号，单位为半磅
+const TITLE_SIZE: u32 = 36;
+const HEADING_SIZE: u32 = 30;
+const BODY_SIZE: u32 = 22;
+
+const CONTENT_TYPES: &str = r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>
+<Types xmlns="http://schemas.openxmlformats.org/package/2006/content-types"><Default Extension="rels" ContentType="application/vnd.openxmlformats-package.relationships+xml"/><Default Extension="xml" ContentType="application/xml"/><Default Extension="png" ContentType="image/png"/><Default Extension="jpg" ContentType="image/jpeg"/><Default Extension="gif" ContentType="image/gif"/><Default Extension="bmp" ContentType="image/bmp"/><Default Extension="tif" ContentType="image/tiff"/><Default Extension="webp" ContentType="image/webp"/><Override PartName="/word/document.xml" ContentType="application/vnd.openxmlformats-officedocument.wordprocessingml.document.main+xml"/></Types>"#;
+
+const ROOT_RELS: &str = r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>
+<Relationships xmlns="http://schemas.openxmlformats.org/package/2006/relationships"><Relationship Id="rId1" Type="http://schemas.openxmlformats.org/officeDocument/2006/relationships/officeDocument" Target="word/document.xml"/></Relationships>"#;
+
+/// 输出docx：只写最少的几个部件，不依赖样式表，格式直接写在文字上
+pub fn render(doc: &ExportDocument, images: &ExportImages) -> anyhow::Result<Vec<u8>> {
+    let mut body = String::new();
+    body.push_str(&paragraph(
+        &[Run {
+            text: doc.title.clone(),
+            bold: true,
+        }],
+        TITLE_SIZE,
+        true,
+    ));
+
+    // 同一张图片只打包一次
+    let mut media: HashMap<&str, (String, &ExportImage)> = HashMap::new();
+    let mut drawing_id = 0;
+    for block in &doc.blocks {
+        match block {
+            Block::Heading(text) => body.push_str(&paragraph(
+                &[Run {
+                    text: text.clone(),
+                    bold: true,
+                }],
+                HEADING_SIZE,
+                false,
+            )),
+            Block::Paragraph(runs) => body.push_str(&paragraph(runs, BODY_SIZE, false)),
+            Block::Image(src) => {
+                let Some(image) = images.get(src) else {
+                    body.push_str(&paragraph(
+                        &[Run {
+                            text: "[图片]".to_string(),
+                            bold: false,
+                        }],
+                        BODY_SIZE,
+                        false,
+                    ));
+                    continue;
+                };
+                let index = media.len() + 1;
+                let rid = media
+                    .entry(src.as_str())
+                    .or_insert_with(|| (format!("rIdImg{index}"), image))
+                    .0
+                    .clone();
+                drawing_id += 1;
+                body.push_str(&drawing(&rid, image, drawing_id));
+            }
+        }
+    }
+
+    let document = format!(
+        r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>
+<w:document xmlns:w="http://schemas.openxmlformats.org/wordprocessingml/2006/main" xmlns:r="http://schemas.openxmlformats.org/officeDocument/2006/relationships" xmlns:wp="http://schemas.openxmlformats.org/drawingml/2006/wordprocessingDrawing" xmlns:a="http://schemas.openxmlformats.org/drawingml/2006/main" xmlns:pic="http://schemas.openxmlformats.org/drawingml/2006/picture"><w:body>{body}<w:sectPr><w:pgSz w:w="11906" w:h="16838"/><w:pgMar w:top="1440" w:right="1080" w:bottom="1440" w:left="1080" w:header="851" w:footer="992" w:gutter="0"/></w:sectPr></w:body></w:document>"#
+    );
+    let mut rels = String::from(
+        r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>
+<Relationships xmlns="http://schemas.openxmlformats.org/package/2006/relationships">"#,
+    );
+    for (rid, image) in media.values() {
+        rels.push_str(&format!(
+            r#"<Relationship Id="{rid}" Type="http://schemas.openxmlformats.org/officeDocument/2006/relationships/image" Target="media/{rid}.{}"/>"#,
+            image.extension()
+        ));
+    }
+    rels.push_str("</Relationships>");
+
+    let mut zip = ZipWriter::new(Cursor::new(Vec::new()));
+    let options = SimpleFileOptions::default();
+    let mut write = |name: &str, data: &[u8]| -> anyhow::Result<()> {
+        zip.start_file(name, options)
+            .with_context(|| format!("docx start_file({name}) failed"))?;
+        zip.write_all(data)
+            .with_context(|| format!("docx write({name}) failed"))
+    };
+    write("[Content_Types].xml", CONTENT_TYPES.as_bytes())?;
+    write("_rels/.rels", ROOT_RELS.as_bytes())?;
+    write("word/document.xml", document.as_bytes())?;
+    write("word/_rels/document.xml.rels", rels.as_bytes())?;
+    for (rid, image) in media.values() {
+        write(
+            &format!("word/media/{rid}.{}", image.extension()),
+            &image.data,
+        )?;
+    }
+    let cursor = zip.finish().context("docx finish failed")?;
+    Ok(cursor.into_inner())
+}
+
+fn paragraph(runs: &[Run], size: u32, center: bool) -> String {
+    let mut p = String::from("<w:p>");
+    if center {
+        p.push_str(r#"<w:pPr><w:jc w:val="center"/></w:pPr>"#);
+    }
+    for run in runs {
+        let bold = if run.bold { "<w:b/>" } else { "" };
+        p.push_str(&format!(
+            r#"<w:r><w:rPr><w:rFonts w:eastAsia="宋体"/>{bold}<w:sz w:val="{size}"/></w:rPr><w:t xml:space="preserve">{}</w:t></w:r>"#,
+            escape_xml(&run.text)
+        ));
+    }
+    p.push_str("</w:p>");
+    p
+}
+
+/// 内嵌图片，超出版心宽度时等比缩小
+fn drawing(rid: &str, image: &ExportImage, index: usize) -> String {
+    let mut cx = image.width as u64 * EMU_PER_INCH / IMAGE_DPI;
+    let mut cy = image.height as u64 * EMU_PER_INCH / IMAGE_DPI;
+    if cx > MAX_IMAGE_WIDTH_EMU {
+        cy = cy * MAX_IMAGE_WIDTH_EMU / cx;
+        cx = MAX_IMAGE_WIDTH_EMU;
+    }
+    format!(
+        r#"<w:p><w:r><w:drawing><wp:inline distT="0" distB="0" distL="0" distR="0"><wp:extent cx="{cx}" cy="{cy}"/><wp:docPr id="{index}" name="图片{index}"/><a:graphic><a:graphicData uri="http://schemas.openxmlformats.org/drawingml/2006/picture"><pic:pic><pic:nvPicPr><pic:cNvPr id="{index}" name="图片{index}"/><pic:cNvPicPr/></pic:nvPicPr><pic:blipFill><a:blip r:embed="{rid}"/><a:stretch><a:fillRect/></a:stretch></pic:blipFill><pic:spPr><a:xfrm><a:off x="0" y="0"/><a:ext cx="{cx}" cy="{cy}"/></a:xfrm><a:prstGeom prst="rect"><a:avLst/></a:prstGeom></pic:spPr></pic:pic></a:graphicData></a:graphic></wp:inline></w:drawing></w:r></w:p>"#
+    )
+}
+
+/// 转义xml特殊字符，去掉xml中不允许出现的控制字符
+fn escape_xml(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\t' | '\n' | '\r' => escaped.push(c),
+            c if c.is_control() => {}
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
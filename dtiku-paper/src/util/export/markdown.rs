@@ -0,0 +1,87 @@
+use super::{absolute_url, ExportImages};
+use crate::domain::export::{Block, ExportDocument, Run};
+use base64::{engine::general_purpose::STANDARD, Engine as _};
+
+/// 输出Markdown，图片以data uri内嵌，下载失败的图片保留原地址
+pub fn render(doc: &ExportDocument, images: &ExportImages) -> String {
+    let mut md = format!("# {}\n\n", escape(&doc.title));
+    for block in &doc.blocks {
+        match block {
+            Block::Heading(text) => md.push_str(&format!("## {}\n\n", escape(text))),
+            Block::Paragraph(runs) => {
+                md.push_str(&runs.iter().map(render_run).collect::<String>());
+                md.push_str("\n\n");
+            }
+            Block::Image(src) => {
+                let url = match images.get(src) {
+                    Some(image) => format!(
+                        "data:{};base64,{}",
+                        image.mime(),
+                        STANDARD.encode(&image.data)
+                    ),
+                    None => absolute_url(src),
+                };
+                md.push_str(&format!("![]({url})\n\n"));
+            }
+        }
+    }
+    md
+}
+
+/// 加粗标记内侧不能有空格，把首尾空格移到标记外
+fn render_run(run: &Run) -> String {
+    let text = escape(&run.text);
+    let trimmed = text.trim();
+    if !run.bold || trimmed.is_empty() {
+        return text;
+    }
+    let leading = &text[..text.len() - text.trim_start().len()];
+    let trailing = &text[text.trim_end().len()..];
+    format!("{leading}**{trimmed}**{trailing}")
+}
+
+fn escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        if matches!(
+            c,
+            '\\' | '`' | '*' | '_' | '[' | ']' | '<' | '>' | '#' | '|' | '~'
+        ) {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::render;
+    use crate::domain::export::{Block, ExportDocument, Run};
+    use std::collections::HashMap;
+
+    #[test]
+    fn test_render_markdown() {
+        let doc = ExportDocument {
+            title: "2024年国考".to_string(),
+            blocks: vec![
+                Block::Heading("常识判断".to_string()),
+                Block::Paragraph(vec![
+                    Run {
+                        text: "1. ".to_string(),
+                        bold: true,
+                    },
+                    Run {
+                        text: "a*b".to_string(),
+                        bold: false,
+                    },
+                ]),
+                Block::Image("//s.dtiku.cn/a.png".to_string()),
+            ],
+        };
+        assert_eq!(
+            render(&doc, &HashMap::new()),
+            "# 2024年国考\n\n## 常识判断\n\n**1.** a\\*b\n\n![](https://s.dtiku.cn/a.png)\n\n"
+        );
+    }
+}
@@ -0,0 +1,59 @@
+pub mod docx;
+pub mod markdown;
+pub mod pdf;
+
+use anyhow::Context;
+use printpdf::image_crate::{io::Reader as ImageReader, ImageFormat};
+use std::{collections::HashMap, io::Cursor};
+
+/// 图片地址 -> 下载好的图片
+pub type ExportImages = HashMap<String, ExportImage>;
+
+/// 嵌入文档的图片
+#[derive(Debug, Clone)]
+pub struct ExportImage {
+    pub data: Vec<u8>,
+    pub format: ImageFormat,
+    pub width: u32,
+    pub height: u32,
+}
+
+impl ExportImage {
+    /// 识别图片格式和尺寸，不做完整解码
+    pub fn new(data: Vec<u8>) -> anyhow::Result<Self> {
+        let reader = ImageReader::new(Cursor::new(&data))
+            .with_guessed_format()
+            .context("guess image format failed")?;
+        let format = reader.format().context("unknown image format")?;
+        let (width, height) = reader
+            .into_dimensions()
+            .context("read image dimensions failed")?;
+        Ok(Self {
+            data,
+            format,
+            width,
+            height,
+        })
+    }
+
+    pub fn mime(&self) -> &'static str {
+        self.format.to_mime_type()
+    }
+
+    pub fn extension(&self) -> &'static str {
+        self.format
+            .extensions_str()
+            .first()
+            .copied()
+            .unwrap_or("png")
+    }
+}
+
+/// 题目中的图片地址多为省略协议的`//s.dtiku.cn/...`，下载时补全为https
+pub fn absolute_url(src: &str) -> String {
+    if src.starts_with("//") {
+        format!("https:{src}")
+    } else {
+        src.to_string()
+    }
+}
@@ -0,0 +1,239 @@
+use super::{ExportImage, ExportImages};
+use crate::domain::export::{Block, ExportDocument, Run};
+use anyhow::Context;
+use printpdf::{
+    image_crate::{self, DynamicImage, Rgb, RgbImage},
+    Image, ImageTransform, IndirectFontRef, Mm, PdfDocument, PdfDocumentReference,
+    PdfLayerReference,
+};
+use std::io::Cursor;
+
+/// A4纸，单位毫米
+const PAGE_WIDTH: f32 = 210.0;
+const PAGE_HEIGHT: f32 = 297.0;
+const MARGIN: f32 = 20.0;
+const CONTENT_WIDTH: f32 = PAGE_WIDTH - 2.0 * MARGIN;
+const PT_TO_MM: f32 = 25.4 / 72.0;
+/// 字号，单位为磅
+const TITLE_SIZE: f32 = 18.0;
+const HEADING_SIZE: f32 = 14.0;
+const BODY_SIZE: f32 = 11.0;
+const LINE_SPACING: f32 = 1.5;
+const PARAGRAPH_SPACING: f32 = 2.0;
+/// 图片按96dpi换算尺寸
+const IMAGE_DPI: f32 = 96.0;
+/// 只嵌入一种字体，粗体用错开重复描画模拟
+const BOLD_OFFSET: f32 = 0.15;
+const LAYER_NAME: &str = "正文";
+
+/// 用纯Rust的printpdf排版输出pdf，font为包含中文字形的ttf字体
+pub fn render(doc: &ExportDocument, images: &ExportImages, font: &[u8]) -> anyhow::Result<Vec<u8>> {
+    let (pdf, page, layer) =
+        PdfDocument::new(&doc.title, Mm(PAGE_WIDTH), Mm(PAGE_HEIGHT), LAYER_NAME);
+    let font = pdf
+        .add_external_font(Cursor::new(font))
+        .context("load pdf font failed")?;
+    let layer = pdf.get_page(page).get_layer(layer);
+    let mut writer = PdfWriter {
+        pdf: &pdf,
+        font,
+        layer,
+        y: PAGE_HEIGHT - MARGIN,
+    };
+    writer.paragraph(
+        &[Run {
+            text: doc.title.clone(),
+            bold: true,
+        }],
+        TITLE_SIZE,
+        true,
+    );
+    for block in &doc.blocks {
+        match block {
+            Block::Heading(text) => writer.paragraph(
+                &[Run {
+                    text: text.clone(),
+                    bold: true,
+                }],
+                HEADING_SIZE,
+                false,
+            ),
+            Block::Paragraph(runs) => writer.paragraph(runs, BODY_SIZE, false),
+            Block::Image(src) => match images.get(src).map(|image| writer.image(image)) {
+                Some(Ok(())) => {}
+                _ => writer.paragraph(
+                    &[Run {
+                        text: "[图片]".to_string(),
+                        bold: false,
+                    }],
+                    BODY_SIZE,
+                    false,
+                ),
+            },
+        }
+    }
+    drop(writer);
+    pdf.save_to_bytes().context("save pdf failed")
+}
+
+struct PdfWriter<'a> {
+    pdf: &'a PdfDocumentReference,
+    font: IndirectFontRef,
+    layer: PdfLayerReference,
+    /// 当前书写位置距页面底部的距离
+    y: f32,
+}
+
+impl PdfWriter<'_> {
+    fn paragraph(&mut self, runs: &[Run], size: f32, center: bool) {
+        let line_height = size * PT_TO_MM * LINE_SPACING;
+        for line in wrap(runs, size, CONTENT_WIDTH) {
+            self.ensure_space(line_height);
+            let baseline = self.y - size * PT_TO_MM;
+            let width: f32 = line.iter().map(|(text, _)| text_width(text, size)).sum();
+            let mut x = if center {
+                (PAGE_WIDTH - width) / 2.0
+            } else {
+                MARGIN
+            };
+            for (text, bold) in &line {
+                self.layer
+                    .use_text(text.as_str(), size, Mm(x), Mm(baseline), &self.font);
+                if *bold {
+                    self.layer.use_text(
+                        text.as_str(),
+                        size,
+                        Mm(x + BOLD_OFFSET),
+                        Mm(baseline),
+                        &self.font,
+                    );
+                }
+                x += text_width(text, size);
+            }
+            self.y -= line_height;
+        }
+        self.y -= PARAGRAPH_SPACING;
+    }
+
+    /// 等比缩放到版心以内
+    fn image(&mut self, image: &ExportImage) -> anyhow::Result<()> {
+        let decoded = image_crate::load_from_memory(&image.data).context("decode image failed")?;
+        let width = image.width as f32 * 25.4 / IMAGE_DPI;
+        let height = image.height as f32 * 25.4 / IMAGE_DPI;
+        let scale = (CONTENT_WIDTH / width)
+            .min((PAGE_HEIGHT - 2.0 * MARGIN) / height)
+            .min(1.0);
+        self.ensure_space(height * scale);
+        Image::from_dynamic_image(&flatten_alpha(&decoded)).add_to_layer(
+            self.layer.clone(),
+            ImageTransform {
+                translate_x: Some(Mm(MARGIN)),
+                translate_y: Some(Mm(self.y - height * scale)),
+                scale_x: Some(scale),
+                scale_y: Some(scale),
+                dpi: Some(IMAGE_DPI),
+                ..Default::default()
+            },
+        );
+        self.y -= height * scale + PARAGRAPH_SPACING;
+        Ok(())
+    }
+
+    /// 当前页放不下时换页，页首放不下的内容不再换页
+    fn ensure_space(&mut self, height: f32) {
+        if self.y - height < MARGIN && self.y < PAGE_HEIGHT - MARGIN {
+            let (page, layer) = self
+                .pdf
+                .add_page(Mm(PAGE_WIDTH), Mm(PAGE_HEIGHT), LAYER_NAME);
+            self.layer = self.pdf.get_page(page).get_layer(layer);
+            self.y = PAGE_HEIGHT - MARGIN;
+        }
+    }
+}
+
+/// 按字符折行，返回每行的(文字, 是否加粗)片段
+fn wrap(runs: &[Run], size: f32, max_width: f32) -> Vec<Vec<(String, bool)>> {
+    let mut lines = vec![];
+    let mut line = vec![];
+    let mut width = 0.0;
+    for run in runs {
+        let mut segment = String::new();
+        for c in run.text.chars() {
+            let w = char_width(c, size);
+            if width + w > max_width && width > 0.0 {
+                if !segment.is_empty() {
+                    line.push((std::mem::take(&mut segment), run.bold));
+                }
+                lines.push(std::mem::take(&mut line));
+                width = 0.0;
+                if c == ' ' {
+                    continue;
+                }
+            }
+            segment.push(c);
+            width += w;
+        }
+        if !segment.is_empty() {
+            line.push((segment, run.bold));
+        }
+    }
+    if !line.is_empty() {
+        lines.push(line);
+    }
+    lines
+}
+
+/// 没有读取字体度量，按半角字符半个字宽、其余字符一个字宽估算
+fn char_width(c: char, size: f32) -> f32 {
+    let em = size * PT_TO_MM;
+    if c.is_ascii() {
+        em * 0.5
+    } else {
+        em
+    }
+}
+
+fn text_width(text: &str, size: f32) -> f32 {
+    text.chars().map(|c| char_width(c, size)).sum()
+}
+
+/// 透明背景合成为白色，避免透明区域在pdf中显示为黑色
+fn flatten_alpha(image: &DynamicImage) -> DynamicImage {
+    let rgba = image.to_rgba8();
+    let rgb = RgbImage::from_fn(rgba.width(), rgba.height(), |x, y| {
+        let p = rgba.get_pixel(x, y);
+        let alpha = p[3] as u16;
+        Rgb([0, 1, 2].map(|i| ((p[i] as u16 * alpha + 255 * (255 - alpha)) / 255) as u8))
+    });
+    DynamicImage::ImageRgb8(rgb)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{wrap, BODY_SIZE, PT_TO_MM};
+    use crate::domain::export::Run;
+
+    #[test]
+    fn test_wrap() {
+        let em = BODY_SIZE * PT_TO_MM;
+        let runs = [
+            Run {
+                text: "1. ".to_string(),
+                bold: true,
+            },
+            Run {
+                text: "甲乙丙丁".to_string(),
+                bold: false,
+            },
+        ];
+        // 一行放得下"1. "和两个汉字
+        let lines = wrap(&runs, BODY_SIZE, em * 3.6);
+        assert_eq!(
+            lines,
+            vec![
+                vec![("1. ".to_string(), true), ("甲乙".to_string(), false)],
+                vec![("丙丁".to_string(), false)],
+            ]
+        );
+    }
+}
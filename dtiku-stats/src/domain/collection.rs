@@ -0,0 +1,204 @@
+use crate::model::{collection, collection_item, sea_orm_active_enums::CollectionItemType};
+use itertools::Itertools;
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+
+/// 收藏夹名称的最大长度
+pub const MAX_NAME_LENGTH: usize = 64;
+/// 每个条目最多的标签数
+pub const MAX_TAGS: usize = 10;
+/// 单个标签的最大长度
+pub const MAX_TAG_LENGTH: usize = 16;
+/// 每个收藏夹最多收藏的条目数
+pub const MAX_ITEMS: u64 = 2000;
+
+/// 收藏夹中条目的唯一标识
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct ItemKey {
+    pub item_type: CollectionItemType,
+    pub item_id: i32,
+}
+
+impl From<&collection_item::Model> for ItemKey {
+    fn from(item: &collection_item::Model) -> Self {
+        Self {
+            item_type: item.item_type,
+            item_id: item.item_id,
+        }
+    }
+}
+
+/// 校验失败时返回给用户的提示
+pub fn validate_name(name: &str) -> Result<String, &'static str> {
+    let name = name.trim();
+    if name.is_empty() {
+        return Err("收藏夹名称不能为空");
+    }
+    if name.chars().count() > MAX_NAME_LENGTH {
+        return Err("收藏夹名称过长");
+    }
+    Ok(name.to_string())
+}
+
+/// 去掉首尾空白、空标签和重复的标签
+pub fn normalize_tags<S: AsRef<str>>(tags: &[S]) -> Result<Vec<String>, &'static str> {
+    let tags = tags
+        .iter()
+        .map(|t| t.as_ref().trim())
+        .filter(|t| !t.is_empty())
+        .unique()
+        .map(str::to_string)
+        .collect_vec();
+    if tags.len() > MAX_TAGS {
+        return Err("标签太多了");
+    }
+    if tags.iter().any(|t| t.chars().count() > MAX_TAG_LENGTH) {
+        return Err("标签过长");
+    }
+    Ok(tags)
+}
+
+/// 按用户给出的顺序重排条目：order中不在收藏夹里的条目被忽略，
+/// 没有出现在order中的条目保持原来的相对顺序排在最后
+pub fn apply_order(current: &[ItemKey], order: &[ItemKey]) -> Vec<ItemKey> {
+    let existing: HashSet<&ItemKey> = current.iter().collect();
+    let ordered = order
+        .iter()
+        .filter(|k| existing.contains(k))
+        .unique()
+        .copied()
+        .collect_vec();
+    let placed: HashSet<&ItemKey> = ordered.iter().collect();
+    let rest = current.iter().filter(|k| !placed.contains(k)).copied();
+    let mut result = ordered.clone();
+    result.extend(rest);
+    result
+}
+
+/// 收藏条目的结果
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AddItemOutcome {
+    Added,
+    /// 收藏夹中已有该条目
+    Existed,
+    /// 收藏的题目、试卷等不存在
+    NotFound,
+    /// 收藏夹已满
+    Full,
+}
+
+/// 收藏夹列表，contains表示收藏夹中是否已有当前查看的条目
+#[derive(Debug, Clone, Serialize)]
+pub struct CollectionSummary {
+    #[serde(flatten)]
+    pub collection: collection::Model,
+    pub item_count: u64,
+    pub contains: bool,
+}
+
+/// 条目的标题和访问地址，条目已被删除时不展示
+#[derive(Debug, Clone, Serialize)]
+pub struct CollectionItemView {
+    #[serde(flatten)]
+    pub item: collection_item::Model,
+    pub title: String,
+    pub url: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct CollectionDetail {
+    pub collection: collection::Model,
+    pub items: Vec<CollectionItemView>,
+    /// 收藏夹中用到的所有标签，用于筛选
+    pub tags: Vec<String>,
+}
+
+impl CollectionDetail {
+    /// tags取自全部条目，items只保留带有筛选标签的条目
+    pub fn new(
+        collection: collection::Model,
+        items: Vec<CollectionItemView>,
+        tag: Option<&str>,
+    ) -> Self {
+        let tags = items
+            .iter()
+            .flat_map(|i| i.item.tags.0.iter())
+            .unique()
+            .cloned()
+            .collect_vec();
+        let items = match tag {
+            Some(tag) => items
+                .into_iter()
+                .filter(|i| i.item.tags.0.iter().any(|t| t == tag))
+                .collect(),
+            None => items,
+        };
+        Self {
+            collection,
+            items,
+            tags,
+        }
+    }
+
+    pub fn question_count(&self) -> usize {
+        self.items
+            .iter()
+            .filter(|i| i.item.item_type == CollectionItemType::Question)
+            .count()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{apply_order, normalize_tags, validate_name, ItemKey};
+    use crate::model::sea_orm_active_enums::CollectionItemType;
+
+    fn key(item_type: CollectionItemType, item_id: i32) -> ItemKey {
+        ItemKey { item_type, item_id }
+    }
+
+    #[test]
+    fn test_normalize_tags() {
+        assert_eq!(
+            normalize_tags(&[" 易错 ", "", "易错", "言语"]),
+            Ok(vec!["易错".to_string(), "言语".to_string()])
+        );
+        assert!(normalize_tags(&["一二三四五六七八九十一二三四五六七"]).is_err());
+        let many = (0..11).map(|i| i.to_string()).collect::<Vec<_>>();
+        assert!(normalize_tags(&many).is_err());
+    }
+
+    #[test]
+    fn test_validate_name() {
+        assert_eq!(validate_name(" 常识 "), Ok("常识".to_string()));
+        assert!(validate_name("  ").is_err());
+        assert!(validate_name(&"收".repeat(65)).is_err());
+    }
+
+    #[test]
+    fn test_apply_order() {
+        use CollectionItemType::*;
+        let current = vec![
+            key(Question, 1),
+            key(Paper, 1),
+            key(Idiom, 2),
+            key(Issue, 3),
+        ];
+        let order = vec![
+            key(Issue, 3),
+            key(Question, 9),
+            key(Question, 1),
+            key(Issue, 3),
+        ];
+        assert_eq!(
+            apply_order(&current, &order),
+            vec![
+                key(Issue, 3),
+                key(Question, 1),
+                key(Paper, 1),
+                key(Idiom, 2)
+            ]
+        );
+        assert_eq!(apply_order(&current, &[]), current);
+    }
+}
@@ -1,3 +1,6 @@
+pub mod collection;
+pub mod review;
+
 use crate::model::{
     idiom::{self, BriefIdiom},
     idiom_ref,
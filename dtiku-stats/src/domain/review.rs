@@ -0,0 +1,198 @@
+use crate::model::{idiom::BriefIdiom, review_card, sea_orm_active_enums::ReviewItemType};
+use chrono::Days;
+use dtiku_paper::model::question::QuestionWithSolutions;
+use sea_orm::prelude::Date;
+use serde::{Deserialize, Serialize};
+use strum::EnumMessage;
+
+/// SM-2中难度系数的下限
+const MIN_EASE: f32 = 1.3;
+const INIT_EASE: f32 = 2.5;
+/// 简单评分的额外间隔倍数
+const EASY_BONUS: f32 = 1.3;
+/// 困难评分的间隔倍数
+const HARD_FACTOR: f32 = 1.2;
+
+/// 复习评分，参考Anki的四档
+#[derive(
+    Debug,
+    Clone,
+    Copy,
+    PartialEq,
+    Eq,
+    Serialize,
+    Deserialize,
+    strum::EnumString,
+    strum::Display,
+    strum::EnumIter,
+    strum::EnumMessage,
+)]
+#[serde(rename_all = "snake_case")]
+#[strum(serialize_all = "snake_case")]
+pub enum ReviewGrade {
+    #[strum(message = "忘记")]
+    Again,
+    #[strum(message = "困难")]
+    Hard,
+    #[strum(message = "良好")]
+    Good,
+    #[strum(message = "简单")]
+    Easy,
+}
+
+impl ReviewGrade {
+    pub fn text(&self) -> &'static str {
+        self.get_message().unwrap_or_default()
+    }
+
+    /// 对应SM-2中0~5的回忆质量
+    fn quality(&self) -> f32 {
+        match self {
+            Self::Again => 1.0,
+            Self::Hard => 3.0,
+            Self::Good => 4.0,
+            Self::Easy => 5.0,
+        }
+    }
+}
+
+/// SM-2算法中一张卡片的调度状态
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Sm2State {
+    pub ease: f32,
+    pub interval_days: i32,
+    pub repetitions: i16,
+    pub lapses: i16,
+}
+
+impl Default for Sm2State {
+    fn default() -> Self {
+        Self {
+            ease: INIT_EASE,
+            interval_days: 0,
+            repetitions: 0,
+            lapses: 0,
+        }
+    }
+}
+
+impl From<&review_card::Model> for Sm2State {
+    fn from(card: &review_card::Model) -> Self {
+        Self {
+            ease: card.ease,
+            interval_days: card.interval_days,
+            repetitions: card.repetitions,
+            lapses: card.lapses,
+        }
+    }
+}
+
+impl Sm2State {
+    /// 根据评分计算下一次的调度状态
+    pub fn review(self, grade: ReviewGrade) -> Self {
+        if grade == ReviewGrade::Again {
+            return Self {
+                ease: (self.ease - 0.2).max(MIN_EASE),
+                interval_days: 1,
+                repetitions: 0,
+                lapses: self.lapses + 1,
+            };
+        }
+        let q = grade.quality();
+        let ease = (self.ease + 0.1 - (5.0 - q) * (0.08 + (5.0 - q) * 0.02)).max(MIN_EASE);
+        let interval = match self.repetitions {
+            0 => 1.0,
+            1 => 6.0,
+            _ => match grade {
+                ReviewGrade::Hard => self.interval_days as f32 * HARD_FACTOR,
+                _ => self.interval_days as f32 * ease,
+            },
+        };
+        let interval = match grade {
+            ReviewGrade::Easy => interval * EASY_BONUS,
+            _ => interval,
+        };
+        Self {
+            ease,
+            interval_days: (interval.round() as i32).max(self.interval_days + 1),
+            repetitions: self.repetitions + 1,
+            lapses: self.lapses,
+        }
+    }
+
+    pub fn due_from(&self, today: Date) -> Date {
+        today
+            .checked_add_days(Days::new(self.interval_days.max(0) as u64))
+            .unwrap_or(today)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub enum ReviewContent {
+    Question(QuestionWithSolutions),
+    Idiom(BriefIdiom),
+}
+
+/// 每日复习队列中的一项，card为空表示第一次复习
+#[derive(Debug, Clone)]
+pub struct ReviewItem {
+    pub item_type: ReviewItemType,
+    pub item_id: i32,
+    pub card: Option<review_card::Model>,
+    pub content: ReviewContent,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{ReviewGrade, Sm2State};
+
+    #[test]
+    fn test_sm2_good_intervals() {
+        let s = Sm2State::default().review(ReviewGrade::Good);
+        assert_eq!(s.interval_days, 1);
+        let s = s.review(ReviewGrade::Good);
+        assert_eq!(s.interval_days, 6);
+        let s = s.review(ReviewGrade::Good);
+        assert_eq!(s.interval_days, 15);
+        assert_eq!(s.repetitions, 3);
+        assert!((s.ease - 2.5).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_sm2_again_resets() {
+        let s = Sm2State::default()
+            .review(ReviewGrade::Good)
+            .review(ReviewGrade::Good)
+            .review(ReviewGrade::Again);
+        assert_eq!(s.interval_days, 1);
+        assert_eq!(s.repetitions, 0);
+        assert_eq!(s.lapses, 1);
+        assert!((s.ease - 2.3).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_sm2_ease_floor() {
+        let mut s = Sm2State::default();
+        for _ in 0..20 {
+            s = s.review(ReviewGrade::Again);
+        }
+        assert!((s.ease - 1.3).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_sm2_hard_and_easy() {
+        let base = Sm2State {
+            ease: 2.5,
+            interval_days: 10,
+            repetitions: 3,
+            lapses: 0,
+        };
+        let hard = base.review(ReviewGrade::Hard);
+        let good = base.review(ReviewGrade::Good);
+        let easy = base.review(ReviewGrade::Easy);
+        assert_eq!(hard.interval_days, 12);
+        assert!(hard.ease < base.ease);
+        assert!(hard.interval_days < good.interval_days);
+        assert!(good.interval_days < easy.interval_days);
+    }
+}
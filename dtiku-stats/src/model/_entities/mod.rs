@@ -2,7 +2,10 @@
 
 pub mod prelude;
 
+pub mod collection;
+pub mod collection_item;
 pub mod idiom;
 pub mod idiom_ref;
 pub mod idiom_ref_stats;
+pub mod review_card;
 pub mod sea_orm_active_enums;
@@ -1,5 +1,8 @@
 //! `SeaORM` Entity, @generated by sea-orm-codegen 1.1.13
 
+pub use super::collection::Entity as Collection;
+pub use super::collection_item::Entity as CollectionItem;
 pub use super::idiom::Entity as Idiom;
 pub use super::idiom_ref::Entity as IdiomRef;
 pub use super::idiom_ref_stats::Entity as IdiomRefStats;
+pub use super::review_card::Entity as ReviewCard;
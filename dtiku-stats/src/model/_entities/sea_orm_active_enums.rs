@@ -3,7 +3,7 @@
 use fancy_regex::{Regex, RegexBuilder};
 use sea_orm::entity::prelude::*;
 use serde::{Deserialize, Serialize};
-use strum::EnumProperty;
+use strum::{EnumMessage, EnumProperty};
 
 #[derive(
     Debug,
@@ -57,3 +57,81 @@ impl IdiomType {
         RegexBuilder::new(regex).multi_line(true).build().unwrap()
     }
 }
+
+#[derive(
+    Debug,
+    Clone,
+    Copy,
+    PartialEq,
+    Eq,
+    Hash,
+    DeriveActiveEnum,
+    Serialize,
+    Deserialize,
+    strum :: EnumString,
+    strum :: Display,
+    strum :: EnumIter,
+    strum :: AsRefStr,
+    strum :: EnumMessage,
+)]
+#[sea_orm(rs_type = "String", db_type = "Enum", enum_name = "review_item_type")]
+#[serde(rename_all = "snake_case")]
+#[strum(serialize_all = "snake_case")]
+pub enum ReviewItemType {
+    #[sea_orm(string_value = "question")]
+    #[strum(message = "错题")]
+    Question,
+    #[sea_orm(string_value = "idiom")]
+    #[strum(message = "成语")]
+    Idiom,
+}
+
+impl ReviewItemType {
+    pub fn text(&self) -> &'static str {
+        self.get_message().unwrap_or_default()
+    }
+}
+
+#[derive(
+    Debug,
+    Clone,
+    Copy,
+    PartialEq,
+    Eq,
+    Hash,
+    DeriveActiveEnum,
+    Serialize,
+    Deserialize,
+    strum :: EnumString,
+    strum :: Display,
+    strum :: EnumIter,
+    strum :: AsRefStr,
+    strum :: EnumMessage,
+)]
+#[sea_orm(
+    rs_type = "String",
+    db_type = "Enum",
+    enum_name = "collection_item_type"
+)]
+#[serde(rename_all = "snake_case")]
+#[strum(serialize_all = "snake_case")]
+pub enum CollectionItemType {
+    #[sea_orm(string_value = "question")]
+    #[strum(message = "题目")]
+    Question,
+    #[sea_orm(string_value = "paper")]
+    #[strum(message = "试卷")]
+    Paper,
+    #[sea_orm(string_value = "idiom")]
+    #[strum(message = "成语")]
+    Idiom,
+    #[sea_orm(string_value = "issue")]
+    #[strum(message = "帖子")]
+    Issue,
+}
+
+impl CollectionItemType {
+    pub fn text(&self) -> &'static str {
+        self.get_message().unwrap_or_default()
+    }
+}
@@ -0,0 +1,48 @@
+pub use super::_entities::collection::*;
+use anyhow::Context as _;
+use sea_orm::{
+    sqlx::types::chrono::Local, ActiveModelBehavior, ActiveValue::Set, ColumnTrait,
+    ConnectionTrait, DbErr, EntityTrait as _, QueryFilter, QueryOrder,
+};
+use spring::async_trait;
+
+#[async_trait]
+impl ActiveModelBehavior for ActiveModel {
+    async fn before_save<C>(mut self, _db: &C, insert: bool) -> Result<Self, DbErr>
+    where
+        C: ConnectionTrait,
+    {
+        if insert {
+            self.created = Set(Local::now().naive_local());
+        }
+        self.modified = Set(Local::now().naive_local());
+        Ok(self)
+    }
+}
+
+impl Entity {
+    pub async fn find_by_user<C: ConnectionTrait>(
+        db: &C,
+        user_id: i32,
+    ) -> anyhow::Result<Vec<Model>> {
+        Entity::find()
+            .filter(Column::UserId.eq(user_id))
+            .order_by_desc(Column::Modified)
+            .all(db)
+            .await
+            .with_context(|| format!("collection::find_by_user({user_id}) failed"))
+    }
+
+    /// 只能查到用户自己的收藏夹
+    pub async fn find_by_user_and_id<C: ConnectionTrait>(
+        db: &C,
+        user_id: i32,
+        id: i32,
+    ) -> anyhow::Result<Option<Model>> {
+        Entity::find_by_id(id)
+            .filter(Column::UserId.eq(user_id))
+            .one(db)
+            .await
+            .with_context(|| format!("collection::find_by_user_and_id({user_id},{id}) failed"))
+    }
+}
@@ -0,0 +1,217 @@
+pub use super::_entities::collection_item::*;
+use super::sea_orm_active_enums::CollectionItemType;
+use anyhow::Context as _;
+use sea_orm::{
+    prelude::Expr, sea_query::OnConflict, sqlx::types::chrono::Local, ActiveModelBehavior,
+    ActiveValue::Set, ColumnTrait, ConnectionTrait, DbErr, EntityTrait as _, FromJsonQueryResult,
+    PaginatorTrait as _, QueryFilter, QueryOrder, QuerySelect as _,
+};
+use serde::{Deserialize, Serialize};
+use spring::async_trait;
+use std::collections::HashMap;
+
+/// 用户给收藏条目打的标签
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize, FromJsonQueryResult)]
+#[serde(transparent)]
+pub struct ItemTags(pub Vec<String>);
+
+#[async_trait]
+impl ActiveModelBehavior for ActiveModel {
+    async fn before_save<C>(mut self, _db: &C, insert: bool) -> Result<Self, DbErr>
+    where
+        C: ConnectionTrait,
+    {
+        if insert {
+            self.created = Set(Local::now().naive_local());
+        }
+        Ok(self)
+    }
+}
+
+impl ActiveModel {
+    /// 已经收藏过的条目保持不变，返回None
+    pub async fn insert_on_conflict_do_nothing<C: ConnectionTrait>(
+        self,
+        db: &C,
+    ) -> Result<Option<Model>, DbErr> {
+        let am = ActiveModelBehavior::before_save(self, db, true).await?;
+        let result = Entity::insert(am)
+            .on_conflict(
+                OnConflict::columns([Column::CollectionId, Column::ItemType, Column::ItemId])
+                    .do_nothing()
+                    .to_owned(),
+            )
+            .exec_with_returning(db)
+            .await;
+        match result {
+            Ok(model) => Ok(Some(model)),
+            Err(DbErr::RecordNotFound(_)) => Ok(None),
+            Err(e) => Err(e),
+        }
+    }
+}
+
+impl Entity {
+    /// 按排序返回收藏夹中的条目
+    pub async fn find_by_collection<C: ConnectionTrait>(
+        db: &C,
+        collection_id: i32,
+    ) -> anyhow::Result<Vec<Model>> {
+        Entity::find()
+            .filter(Column::CollectionId.eq(collection_id))
+            .order_by_asc(Column::Sort)
+            .order_by_asc(Column::Created)
+            .all(db)
+            .await
+            .with_context(|| format!("collection_item::find_by_collection({collection_id}) failed"))
+    }
+
+    /// collection_id => 条目数
+    pub async fn count_by_collections<C: ConnectionTrait>(
+        db: &C,
+        collection_ids: Vec<i32>,
+    ) -> anyhow::Result<HashMap<i32, u64>> {
+        let rows: Vec<(i32, i64)> = Entity::find()
+            .select_only()
+            .column(Column::CollectionId)
+            .column_as(Column::ItemId.count(), "count")
+            .filter(Column::CollectionId.is_in(collection_ids))
+            .group_by(Column::CollectionId)
+            .into_tuple()
+            .all(db)
+            .await
+            .context("collection_item::count_by_collections() failed")?;
+        Ok(rows.into_iter().map(|(id, c)| (id, c as u64)).collect())
+    }
+
+    pub async fn count_by_collection<C: ConnectionTrait>(
+        db: &C,
+        collection_id: i32,
+    ) -> anyhow::Result<u64> {
+        Entity::find()
+            .filter(Column::CollectionId.eq(collection_id))
+            .count(db)
+            .await
+            .with_context(|| {
+                format!("collection_item::count_by_collection({collection_id}) failed")
+            })
+    }
+
+    /// 给定的收藏夹中，已收藏了某个条目的收藏夹id
+    pub async fn find_collection_ids_containing<C: ConnectionTrait>(
+        db: &C,
+        collection_ids: Vec<i32>,
+        item_type: CollectionItemType,
+        item_id: i32,
+    ) -> anyhow::Result<Vec<i32>> {
+        Entity::find()
+            .select_only()
+            .column(Column::CollectionId)
+            .filter(Column::CollectionId.is_in(collection_ids))
+            .filter(Column::ItemType.eq(item_type))
+            .filter(Column::ItemId.eq(item_id))
+            .into_tuple()
+            .all(db)
+            .await
+            .with_context(|| {
+                format!(
+                    "collection_item::find_collection_ids_containing({item_type},{item_id}) failed"
+                )
+            })
+    }
+
+    pub async fn max_sort<C: ConnectionTrait>(
+        db: &C,
+        collection_id: i32,
+    ) -> anyhow::Result<Option<i32>> {
+        Entity::find()
+            .select_only()
+            .column_as(Column::Sort.max(), "sort")
+            .filter(Column::CollectionId.eq(collection_id))
+            .into_tuple::<Option<i32>>()
+            .one(db)
+            .await
+            .with_context(|| format!("collection_item::max_sort({collection_id}) failed"))
+            .map(Option::flatten)
+    }
+
+    pub async fn update_sort<C: ConnectionTrait>(
+        db: &C,
+        collection_id: i32,
+        item_type: CollectionItemType,
+        item_id: i32,
+        sort: i32,
+    ) -> anyhow::Result<()> {
+        Entity::update_many()
+            .col_expr(Column::Sort, Expr::value(sort))
+            .filter(Column::CollectionId.eq(collection_id))
+            .filter(Column::ItemType.eq(item_type))
+            .filter(Column::ItemId.eq(item_id))
+            .exec(db)
+            .await
+            .with_context(|| {
+                format!(
+                    "collection_item::update_sort({collection_id},{item_type},{item_id}) failed"
+                )
+            })?;
+        Ok(())
+    }
+
+    /// 返回是否修改成功
+    pub async fn update_tags<C: ConnectionTrait>(
+        db: &C,
+        collection_id: i32,
+        item_type: CollectionItemType,
+        item_id: i32,
+        tags: ItemTags,
+    ) -> anyhow::Result<bool> {
+        let result = Entity::update_many()
+            .col_expr(Column::Tags, Expr::value(tags))
+            .filter(Column::CollectionId.eq(collection_id))
+            .filter(Column::ItemType.eq(item_type))
+            .filter(Column::ItemId.eq(item_id))
+            .exec(db)
+            .await
+            .with_context(|| {
+                format!(
+                    "collection_item::update_tags({collection_id},{item_type},{item_id}) failed"
+                )
+            })?;
+        Ok(result.rows_affected > 0)
+    }
+
+    /// 返回是否删除成功
+    pub async fn delete_item<C: ConnectionTrait>(
+        db: &C,
+        collection_id: i32,
+        item_type: CollectionItemType,
+        item_id: i32,
+    ) -> anyhow::Result<bool> {
+        let result = Entity::delete_many()
+            .filter(Column::CollectionId.eq(collection_id))
+            .filter(Column::ItemType.eq(item_type))
+            .filter(Column::ItemId.eq(item_id))
+            .exec(db)
+            .await
+            .with_context(|| {
+                format!(
+                    "collection_item::delete_item({collection_id},{item_type},{item_id}) failed"
+                )
+            })?;
+        Ok(result.rows_affected > 0)
+    }
+
+    pub async fn delete_by_collection<C: ConnectionTrait>(
+        db: &C,
+        collection_id: i32,
+    ) -> anyhow::Result<()> {
+        Entity::delete_many()
+            .filter(Column::CollectionId.eq(collection_id))
+            .exec(db)
+            .await
+            .with_context(|| {
+                format!("collection_item::delete_by_collection({collection_id}) failed")
+            })?;
+        Ok(())
+    }
+}
@@ -1,5 +1,8 @@
 mod _entities;
+pub mod collection;
+pub mod collection_item;
 pub mod idiom;
 pub mod idiom_ref;
+pub mod review_card;
 pub use _entities::prelude::*;
 pub use _entities::*;
@@ -0,0 +1,120 @@
+pub use super::_entities::review_card::*;
+use super::sea_orm_active_enums::ReviewItemType;
+use anyhow::Context as _;
+use sea_orm::{
+    prelude::{Date, DateTime},
+    sea_query::OnConflict,
+    sqlx::types::chrono::Local,
+    ActiveModelBehavior,
+    ActiveValue::Set,
+    ColumnTrait, ConnectionTrait, DbErr, EntityTrait as _, PaginatorTrait as _, QueryFilter,
+    QueryOrder, QuerySelect as _,
+};
+use spring::async_trait;
+
+#[async_trait]
+impl ActiveModelBehavior for ActiveModel {
+    async fn before_save<C>(mut self, _db: &C, insert: bool) -> Result<Self, DbErr>
+    where
+        C: ConnectionTrait,
+    {
+        if insert {
+            self.created = Set(Local::now().naive_local());
+        }
+        self.modified = Set(Local::now().naive_local());
+        Ok(self)
+    }
+}
+
+impl ActiveModel {
+    pub async fn insert_on_conflict<C: ConnectionTrait>(self, db: &C) -> Result<Model, DbErr> {
+        let am = ActiveModelBehavior::before_save(self, db, true).await?;
+        let model = Entity::insert(am)
+            .on_conflict(
+                OnConflict::columns([Column::UserId, Column::ItemType, Column::ItemId])
+                    .update_columns([
+                        Column::Ease,
+                        Column::IntervalDays,
+                        Column::Repetitions,
+                        Column::Lapses,
+                        Column::Due,
+                        Column::Modified,
+                    ])
+                    .to_owned(),
+            )
+            .exec_with_returning(db)
+            .await?;
+        Self::after_save(model, db, true).await
+    }
+}
+
+impl Entity {
+    pub async fn find_due<C: ConnectionTrait>(
+        db: &C,
+        user_id: i32,
+        today: Date,
+        limit: u64,
+    ) -> anyhow::Result<Vec<Model>> {
+        Entity::find()
+            .filter(Column::UserId.eq(user_id).and(Column::Due.lte(today)))
+            .order_by_asc(Column::Due)
+            .limit(limit)
+            .all(db)
+            .await
+            .with_context(|| format!("review_card::find_due({user_id},{today}) failed"))
+    }
+
+    pub async fn find_by_item<C: ConnectionTrait>(
+        db: &C,
+        user_id: i32,
+        item_type: ReviewItemType,
+        item_id: i32,
+    ) -> anyhow::Result<Option<Model>> {
+        Entity::find_by_id((user_id, item_type, item_id))
+            .one(db)
+            .await
+            .with_context(|| {
+                format!("review_card::find_by_item({user_id},{item_type},{item_id}) failed")
+            })
+    }
+
+    pub async fn find_item_ids<C: ConnectionTrait>(
+        db: &C,
+        user_id: i32,
+        item_type: ReviewItemType,
+    ) -> anyhow::Result<Vec<i32>> {
+        Entity::find()
+            .select_only()
+            .column(Column::ItemId)
+            .filter(
+                Column::UserId
+                    .eq(user_id)
+                    .and(Column::ItemType.eq(item_type)),
+            )
+            .into_tuple()
+            .all(db)
+            .await
+            .with_context(|| format!("review_card::find_item_ids({user_id},{item_type}) failed"))
+    }
+
+    /// 统计某时间之后新加入复习的卡片数，用于限制每日新卡数量
+    pub async fn count_created_since<C: ConnectionTrait>(
+        db: &C,
+        user_id: i32,
+        item_type: ReviewItemType,
+        since: DateTime,
+    ) -> anyhow::Result<u64> {
+        Entity::find()
+            .filter(
+                Column::UserId
+                    .eq(user_id)
+                    .and(Column::ItemType.eq(item_type))
+                    .and(Column::Created.gte(since)),
+            )
+            .count(db)
+            .await
+            .with_context(|| {
+                format!("review_card::count_created_since({user_id},{item_type}) failed")
+            })
+    }
+}
@@ -0,0 +1,332 @@
+use crate::{
+    domain::collection::{
+        apply_order, AddItemOutcome, CollectionDetail, CollectionItemView, CollectionSummary,
+        ItemKey, MAX_ITEMS,
+    },
+    model::{
+        collection, collection_item::ItemTags, idiom, sea_orm_active_enums::CollectionItemType,
+        Collection, CollectionItem, Idiom,
+    },
+};
+use anyhow::Context;
+use dtiku_bbs::model::Issue;
+use dtiku_paper::{
+    domain::material::material_text,
+    model::{Paper, Question},
+};
+use itertools::Itertools;
+use sea_orm::{
+    ActiveModelTrait, ActiveValue::Set, ColumnTrait, DbConn, EntityTrait, IntoActiveModel,
+    QueryFilter, QuerySelect, TransactionTrait,
+};
+use spring::plugin::service::Service;
+use std::collections::HashMap;
+
+/// 题目标题截取的字数
+const TITLE_LENGTH: usize = 60;
+
+#[derive(Clone, Service)]
+pub struct CollectionService {
+    #[inject(component)]
+    db: DbConn,
+}
+
+impl CollectionService {
+    /// 用户的收藏夹，传入item时标记哪些收藏夹已收藏了该条目
+    pub async fn find_user_collections(
+        &self,
+        user_id: i32,
+        item: Option<ItemKey>,
+    ) -> anyhow::Result<Vec<CollectionSummary>> {
+        let collections = Collection::find_by_user(&self.db, user_id).await?;
+        let cids = collections.iter().map(|c| c.id).collect_vec();
+        let counts = CollectionItem::count_by_collections(&self.db, cids.clone()).await?;
+        let contains = match item {
+            Some(ItemKey { item_type, item_id }) => {
+                CollectionItem::find_collection_ids_containing(&self.db, cids, item_type, item_id)
+                    .await?
+            }
+            None => vec![],
+        };
+        Ok(collections
+            .into_iter()
+            .map(|c| CollectionSummary {
+                item_count: counts.get(&c.id).copied().unwrap_or_default(),
+                contains: contains.contains(&c.id),
+                collection: c,
+            })
+            .collect())
+    }
+
+    pub async fn create(&self, user_id: i32, name: String) -> anyhow::Result<collection::Model> {
+        collection::ActiveModel {
+            user_id: Set(user_id),
+            name: Set(name),
+            ..Default::default()
+        }
+        .insert(&self.db)
+        .await
+        .with_context(|| format!("create collection for user#{user_id} failed"))
+    }
+
+    pub async fn rename(
+        &self,
+        user_id: i32,
+        id: i32,
+        name: String,
+    ) -> anyhow::Result<Option<collection::Model>> {
+        let Some(c) = Collection::find_by_user_and_id(&self.db, user_id, id).await? else {
+            return Ok(None);
+        };
+        let mut c = c.into_active_model();
+        c.name = Set(name);
+        c.update(&self.db)
+            .await
+            .with_context(|| format!("rename collection#{id} failed"))
+            .map(Some)
+    }
+
+    /// 开启分享时生成新的分享key，关闭分享后旧链接失效
+    pub async fn set_shared(
+        &self,
+        user_id: i32,
+        id: i32,
+        shared: bool,
+    ) -> anyhow::Result<Option<collection::Model>> {
+        let Some(c) = Collection::find_by_user_and_id(&self.db, user_id, id).await? else {
+            return Ok(None);
+        };
+        let share_key = shared.then(|| uuid::Uuid::new_v4().simple().to_string());
+        let mut c = c.into_active_model();
+        c.share_key = Set(share_key);
+        c.update(&self.db)
+            .await
+            .with_context(|| format!("share collection#{id} failed"))
+            .map(Some)
+    }
+
+    pub async fn delete(&self, user_id: i32, id: i32) -> anyhow::Result<bool> {
+        let tx = self.db.begin().await.context("begin transaction failed")?;
+        let Some(c) = Collection::find_by_user_and_id(&tx, user_id, id).await? else {
+            return Ok(false);
+        };
+        CollectionItem::delete_by_collection(&tx, c.id).await?;
+        Collection::delete_by_id(c.id)
+            .exec(&tx)
+            .await
+            .with_context(|| format!("delete collection#{id} failed"))?;
+        tx.commit().await.context("commit transaction failed")?;
+        Ok(true)
+    }
+
+    pub async fn find_owned(
+        &self,
+        user_id: i32,
+        id: i32,
+    ) -> anyhow::Result<Option<collection::Model>> {
+        Collection::find_by_user_and_id(&self.db, user_id, id).await
+    }
+
+    /// 收藏夹的主人，或者持有分享key的人可以查看
+    pub async fn find_accessible(
+        &self,
+        id: i32,
+        user_id: Option<i32>,
+        share_key: Option<&str>,
+    ) -> anyhow::Result<Option<collection::Model>> {
+        let c = Collection::find_by_id(id)
+            .one(&self.db)
+            .await
+            .with_context(|| format!("find collection#{id} failed"))?;
+        Ok(c.filter(|c| {
+            user_id == Some(c.user_id)
+                || (c.share_key.is_some() && c.share_key.as_deref() == share_key)
+        }))
+    }
+
+    pub async fn find_detail(
+        &self,
+        collection: collection::Model,
+        tag: Option<&str>,
+    ) -> anyhow::Result<CollectionDetail> {
+        let items = CollectionItem::find_by_collection(&self.db, collection.id).await?;
+        let ids_of = |ty: CollectionItemType| {
+            items
+                .iter()
+                .filter(|i| i.item_type == ty)
+                .map(|i| i.item_id)
+                .collect_vec()
+        };
+        let mut titles: HashMap<ItemKey, (String, String)> = HashMap::new();
+        let qids = ids_of(CollectionItemType::Question);
+        if !qids.is_empty() {
+            for q in Question::find_by_ids(&self.db, qids).await? {
+                let title = material_text(&q.content)
+                    .chars()
+                    .take(TITLE_LENGTH)
+                    .collect();
+                let url = format!("/question/detail/{}", q.id);
+                titles.insert(key(CollectionItemType::Question, q.id), (title, url));
+            }
+        }
+        let pids = ids_of(CollectionItemType::Paper);
+        if !pids.is_empty() {
+            for p in Paper::find_by_ids(&self.db, pids).await? {
+                let url = format!("/paper/{}", p.id);
+                titles.insert(key(CollectionItemType::Paper, p.id), (p.title, url));
+            }
+        }
+        let idiom_ids = ids_of(CollectionItemType::Idiom);
+        if !idiom_ids.is_empty() {
+            let idioms: Vec<(i32, String)> = Idiom::find()
+                .select_only()
+                .columns([idiom::Column::Id, idiom::Column::Text])
+                .filter(idiom::Column::Id.is_in(idiom_ids))
+                .into_tuple()
+                .all(&self.db)
+                .await
+                .context("find collected idioms failed")?;
+            for (id, text) in idioms {
+                let url = format!("/idiom/{text}");
+                titles.insert(key(CollectionItemType::Idiom, id), (text, url));
+            }
+        }
+        let issue_ids = ids_of(CollectionItemType::Issue);
+        if !issue_ids.is_empty() {
+            for (id, title) in Issue::find_titles_by_ids(&self.db, issue_ids).await? {
+                let url = format!("/bbs/issue/{id}");
+                titles.insert(key(CollectionItemType::Issue, id), (title, url));
+            }
+        }
+
+        let items = items
+            .into_iter()
+            .filter_map(|item| {
+                let (title, url) = titles.remove(&ItemKey::from(&item))?;
+                Some(CollectionItemView { item, title, url })
+            })
+            .collect();
+        Ok(CollectionDetail::new(collection, items, tag))
+    }
+
+    /// 按收藏顺序返回题目id，用于收藏夹练习
+    pub async fn find_question_ids(
+        &self,
+        collection_id: i32,
+        tag: Option<&str>,
+    ) -> anyhow::Result<Vec<i32>> {
+        Ok(CollectionItem::find_by_collection(&self.db, collection_id)
+            .await?
+            .into_iter()
+            .filter(|i| i.item_type == CollectionItemType::Question)
+            .filter(|i| tag.is_none_or(|tag| i.tags.0.iter().any(|t| t == tag)))
+            .map(|i| i.item_id)
+            .collect())
+    }
+
+    pub async fn add_item(
+        &self,
+        collection_id: i32,
+        item: ItemKey,
+        tags: Vec<String>,
+    ) -> anyhow::Result<AddItemOutcome> {
+        if !self.item_exists(item).await? {
+            return Ok(AddItemOutcome::NotFound);
+        }
+        if CollectionItem::count_by_collection(&self.db, collection_id).await? >= MAX_ITEMS {
+            return Ok(AddItemOutcome::Full);
+        }
+        let sort = CollectionItem::max_sort(&self.db, collection_id)
+            .await?
+            .map_or(0, |s| s + 1);
+        let added = crate::model::collection_item::ActiveModel {
+            collection_id: Set(collection_id),
+            item_type: Set(item.item_type),
+            item_id: Set(item.item_id),
+            sort: Set(sort),
+            tags: Set(ItemTags(tags)),
+            ..Default::default()
+        }
+        .insert_on_conflict_do_nothing(&self.db)
+        .await
+        .with_context(|| {
+            format!(
+                "add {}#{} to collection#{collection_id} failed",
+                item.item_type, item.item_id
+            )
+        })?;
+        Ok(if added.is_some() {
+            AddItemOutcome::Added
+        } else {
+            AddItemOutcome::Existed
+        })
+    }
+
+    pub async fn remove_item(&self, collection_id: i32, item: ItemKey) -> anyhow::Result<bool> {
+        CollectionItem::delete_item(&self.db, collection_id, item.item_type, item.item_id).await
+    }
+
+    pub async fn set_tags(
+        &self,
+        collection_id: i32,
+        item: ItemKey,
+        tags: Vec<String>,
+    ) -> anyhow::Result<bool> {
+        CollectionItem::update_tags(
+            &self.db,
+            collection_id,
+            item.item_type,
+            item.item_id,
+            ItemTags(tags),
+        )
+        .await
+    }
+
+    /// order只需包含需要调整的条目，其余条目顺延
+    pub async fn reorder(&self, collection_id: i32, order: Vec<ItemKey>) -> anyhow::Result<()> {
+        let current = CollectionItem::find_by_collection(&self.db, collection_id)
+            .await?
+            .iter()
+            .map(ItemKey::from)
+            .collect_vec();
+        let ordered = apply_order(&current, &order);
+        let tx = self.db.begin().await.context("begin transaction failed")?;
+        for (sort, item) in ordered.into_iter().enumerate() {
+            CollectionItem::update_sort(
+                &tx,
+                collection_id,
+                item.item_type,
+                item.item_id,
+                sort as i32,
+            )
+            .await?;
+        }
+        tx.commit().await.context("commit transaction failed")
+    }
+
+    async fn item_exists(&self, item: ItemKey) -> anyhow::Result<bool> {
+        let id = item.item_id;
+        Ok(match item.item_type {
+            CollectionItemType::Question => Question::find_by_id(id)
+                .one(&self.db)
+                .await
+                .with_context(|| format!("Question::find_by_id({id}) failed"))?
+                .is_some(),
+            CollectionItemType::Paper => Paper::find_by_id(id)
+                .one(&self.db)
+                .await
+                .with_context(|| format!("Paper::find_by_id({id}) failed"))?
+                .is_some(),
+            CollectionItemType::Idiom => Idiom::find_by_id(id)
+                .one(&self.db)
+                .await
+                .with_context(|| format!("Idiom::find_by_id({id}) failed"))?
+                .is_some(),
+            CollectionItemType::Issue => Issue::find_issue_by_id(&self.db, id).await?.is_some(),
+        })
+    }
+}
+
+fn key(item_type: CollectionItemType, item_id: i32) -> ItemKey {
+    ItemKey { item_type, item_id }
+}
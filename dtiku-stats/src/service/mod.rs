@@ -1 +1,3 @@
+pub mod collection;
 pub mod idiom;
+pub mod review;
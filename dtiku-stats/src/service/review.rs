@@ -0,0 +1,199 @@
+use crate::{
+    domain::review::{ReviewContent, ReviewGrade, ReviewItem, Sm2State},
+    model::{
+        idiom_ref_stats, review_card,
+        sea_orm_active_enums::{IdiomType, ReviewItemType},
+        Idiom, IdiomRefStats, ReviewCard,
+    },
+};
+use anyhow::Context;
+use dtiku_paper::model::{Question, WrongQuestion};
+use itertools::Itertools;
+use sea_orm::{
+    prelude::{DateTime, Expr},
+    sqlx::types::chrono::Local,
+    ActiveValue::Set,
+    ColumnTrait, DbConn, EntityTrait, QueryFilter, QueryOrder, QuerySelect,
+};
+use spring::plugin::service::Service;
+use std::collections::{HashMap, HashSet};
+
+/// 每天最多复习的到期卡片数
+const DAILY_DUE_LIMIT: u64 = 100;
+/// 每天最多新加入复习的错题数
+const DAILY_NEW_QUESTIONS: u64 = 10;
+/// 每天最多新加入复习的成语数
+const DAILY_NEW_IDIOMS: u64 = 10;
+
+#[derive(Clone, Service)]
+pub struct ReviewService {
+    #[inject(component)]
+    db: DbConn,
+}
+
+impl ReviewService {
+    /// 今日复习队列：先到期的旧卡片，再补充今天还没学满的新错题和高频成语
+    pub async fn daily_queue(&self, user_id: i32) -> anyhow::Result<Vec<ReviewItem>> {
+        let now = Local::now().naive_local();
+        let today = now.date();
+        let due_cards = ReviewCard::find_due(&self.db, user_id, today, DAILY_DUE_LIMIT).await?;
+
+        let new_question_ids = self.new_question_ids(user_id, today.into()).await?;
+        let new_idiom_ids = self.new_idiom_ids(user_id, today.into()).await?;
+
+        let (mut qids, mut idiom_ids): (Vec<i32>, Vec<i32>) =
+            due_cards.iter().partition_map(|c| match c.item_type {
+                ReviewItemType::Question => itertools::Either::Left(c.item_id),
+                ReviewItemType::Idiom => itertools::Either::Right(c.item_id),
+            });
+        qids.extend(&new_question_ids);
+        idiom_ids.extend(&new_idiom_ids);
+
+        let mut questions: HashMap<i32, _> = Question::find_by_ids_with_solutions(&self.db, qids)
+            .await?
+            .into_iter()
+            .map(|q| (q.id, q))
+            .collect();
+        let mut idioms: HashMap<i32, _> = Idiom::find_brief_in_ids(&self.db, idiom_ids)
+            .await?
+            .into_iter()
+            .map(|i| (i.id, i))
+            .collect();
+
+        let mut content_of = |item_type: ReviewItemType, item_id: i32| match item_type {
+            ReviewItemType::Question => questions.remove(&item_id).map(ReviewContent::Question),
+            ReviewItemType::Idiom => idioms.remove(&item_id).map(ReviewContent::Idiom),
+        };
+        let mut queue = vec![];
+        for card in due_cards {
+            if let Some(content) = content_of(card.item_type, card.item_id) {
+                queue.push(ReviewItem {
+                    item_type: card.item_type,
+                    item_id: card.item_id,
+                    card: Some(card),
+                    content,
+                });
+            }
+        }
+        // 新错题和新成语交替排列
+        let new_items = new_question_ids
+            .into_iter()
+            .map(|id| (ReviewItemType::Question, id))
+            .interleave(
+                new_idiom_ids
+                    .into_iter()
+                    .map(|id| (ReviewItemType::Idiom, id)),
+            );
+        for (item_type, item_id) in new_items {
+            if let Some(content) = content_of(item_type, item_id) {
+                queue.push(ReviewItem {
+                    item_type,
+                    item_id,
+                    card: None,
+                    content,
+                });
+            }
+        }
+        Ok(queue)
+    }
+
+    /// 记录一次复习评分，并按SM-2算法安排下一次复习。
+    /// 还没到期的卡片不接受评分，原样返回，避免提前复习拉长间隔
+    pub async fn grade(
+        &self,
+        user_id: i32,
+        item_type: ReviewItemType,
+        item_id: i32,
+        grade: ReviewGrade,
+    ) -> anyhow::Result<Option<review_card::Model>> {
+        let card = ReviewCard::find_by_item(&self.db, user_id, item_type, item_id).await?;
+        if card.is_none() && !self.item_exists(item_type, item_id).await? {
+            return Ok(None);
+        }
+        let today = Local::now().date_naive();
+        if card.as_ref().is_some_and(|c| c.due > today) {
+            return Ok(card);
+        }
+        let state = card.as_ref().map(Sm2State::from).unwrap_or_default();
+        let state = state.review(grade);
+        let card = review_card::ActiveModel {
+            user_id: Set(user_id),
+            item_type: Set(item_type),
+            item_id: Set(item_id),
+            ease: Set(state.ease),
+            interval_days: Set(state.interval_days),
+            repetitions: Set(state.repetitions),
+            lapses: Set(state.lapses),
+            due: Set(state.due_from(today)),
+            ..Default::default()
+        }
+        .insert_on_conflict(&self.db)
+        .await
+        .with_context(|| format!("save review_card({user_id},{item_type},{item_id}) failed"))?;
+        Ok(Some(card))
+    }
+
+    async fn item_exists(&self, item_type: ReviewItemType, item_id: i32) -> anyhow::Result<bool> {
+        Ok(match item_type {
+            ReviewItemType::Question => Question::find_by_id(item_id)
+                .one(&self.db)
+                .await
+                .with_context(|| format!("Question::find_by_id({item_id}) failed"))?
+                .is_some(),
+            ReviewItemType::Idiom => Idiom::find_by_id(item_id)
+                .one(&self.db)
+                .await
+                .with_context(|| format!("Idiom::find_by_id({item_id}) failed"))?
+                .is_some(),
+        })
+    }
+
+    /// 还没加入复习的错题，按最近答错排序
+    async fn new_question_ids(&self, user_id: i32, today: DateTime) -> anyhow::Result<Vec<i32>> {
+        let learned =
+            ReviewCard::count_created_since(&self.db, user_id, ReviewItemType::Question, today)
+                .await?;
+        let remain = DAILY_NEW_QUESTIONS.saturating_sub(learned) as usize;
+        if remain == 0 {
+            return Ok(vec![]);
+        }
+        let carded = ReviewCard::find_item_ids(&self.db, user_id, ReviewItemType::Question)
+            .await?
+            .into_iter()
+            .collect::<HashSet<_>>();
+        Ok(WrongQuestion::find_by_user_id(&self.db, user_id)
+            .await?
+            .into_iter()
+            .map(|wq| wq.question_id)
+            .filter(|qid| !carded.contains(qid))
+            .take(remain)
+            .collect())
+    }
+
+    /// 还没加入复习的成语，按真题中出现的次数排序
+    async fn new_idiom_ids(&self, user_id: i32, today: DateTime) -> anyhow::Result<Vec<i32>> {
+        let learned =
+            ReviewCard::count_created_since(&self.db, user_id, ReviewItemType::Idiom, today)
+                .await?;
+        let remain = DAILY_NEW_IDIOMS.saturating_sub(learned);
+        if remain == 0 {
+            return Ok(vec![]);
+        }
+        let carded = ReviewCard::find_item_ids(&self.db, user_id, ReviewItemType::Idiom).await?;
+        let mut filter = idiom_ref_stats::Column::Ty.eq(IdiomType::Idiom);
+        if !carded.is_empty() {
+            filter = filter.and(idiom_ref_stats::Column::IdiomId.is_not_in(carded));
+        }
+        IdiomRefStats::find()
+            .select_only()
+            .column(idiom_ref_stats::Column::IdiomId)
+            .filter(filter)
+            .group_by(idiom_ref_stats::Column::IdiomId)
+            .order_by_desc(Expr::cust("SUM(question_count)"))
+            .limit(remain)
+            .into_tuple()
+            .all(&self.db)
+            .await
+            .context("IdiomRefStats::new_idiom_ids() failed")
+    }
+}
@@ -5,8 +5,10 @@ mod rpc;
 mod service;
 mod views;
 
+use dtiku_base::embedding::EmbeddingPlugin;
 use dtiku_pay::PayPlugin;
 use plugins::grpc_client::GrpcClientPlugin;
+use plugins::ocr::OcrPlugin;
 use spring::App;
 use spring_opentelemetry::{
     KeyValue, OpenTelemetryPlugin, ResourceConfigurator, SERVICE_NAME, SERVICE_VERSION,
@@ -33,6 +35,8 @@ async fn main() {
         .add_plugin(StreamPlugin)
         .add_plugin(OpenTelemetryPlugin)
         .add_plugin(GrpcClientPlugin)
+        .add_plugin(EmbeddingPlugin)
+        .add_plugin(OcrPlugin)
         .add_plugin(PayPlugin)
         .run()
         .await
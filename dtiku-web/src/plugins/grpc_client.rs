@@ -6,8 +6,6 @@ use super::GrpcClientConfig;
 use anyhow::Context;
 use artalk::{artalk_service_client::ArtalkServiceClient, UserResp, VoteStats};
 use derive_more::derive::{Deref, DerefMut};
-use itertools::Itertools;
-use reqwest::header::HeaderMap;
 use spring::{
     app::AppBuilder,
     async_trait,
@@ -27,19 +25,6 @@ impl Plugin for GrpcClientPlugin {
             .get_config::<GrpcClientConfig>()
             .expect("load grpc config failed");
 
-        let headers = HeaderMap::new();
-
-        let client = reqwest::Client::builder()
-            .default_headers(headers)
-            .read_timeout(Duration::from_secs(500)) // Set a longer timeout for embedding requests
-            .build()
-            .expect("create embedding client failed");
-
-        app.add_component(Embedding {
-            url: grpc_config.embedding_url,
-            client,
-        });
-
         let channel = Channel::from_shared(grpc_config.artalk_url)
             .expect("url is invalid")
             .keep_alive_while_idle(true)
@@ -55,52 +40,6 @@ impl Plugin for GrpcClientPlugin {
     }
 }
 
-#[derive(Debug, Clone)]
-pub struct Embedding {
-    url: String,
-    client: reqwest::Client,
-}
-
-impl Embedding {
-    pub async fn text_embedding<S: Into<String>>(&self, text: S) -> anyhow::Result<Vec<f32>> {
-        let Self { url, client } = self;
-        let text: String = text.into();
-        let resp = client
-            .post(format!("{url}/text_embedding"))
-            .json(&text)
-            .send()
-            .await
-            .context("embedding service call failed")?;
-        let embedding = resp
-            .json()
-            .await
-            .context("parse embedding response failed")?;
-        Ok(embedding)
-    }
-
-    pub async fn batch_text_embedding<S: Into<String> + Clone>(
-        &self,
-        texts: &[S],
-    ) -> anyhow::Result<Vec<Vec<f32>>> {
-        let Self { url, client } = self;
-        let texts = texts
-            .into_iter()
-            .map(|t| Into::<String>::into(t.clone()))
-            .collect_vec();
-        let resp = client
-            .post(format!("{url}/batch_text_embedding"))
-            .json(&texts)
-            .send()
-            .await
-            .context("embedding service call failed")?;
-        let embeddings = resp
-            .json()
-            .await
-            .context("parse embedding response failed")?;
-        Ok(embeddings)
-    }
-}
-
 #[derive(Debug, Clone, Deref, DerefMut)]
 pub struct Artalk(ArtalkServiceClient<Channel>);
 
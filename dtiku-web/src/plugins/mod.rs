@@ -2,14 +2,59 @@ use serde::Deserialize;
 use spring::config::Configurable;
 
 pub mod grpc_client;
+pub mod ocr;
 
 #[derive(Debug, Configurable, Deserialize)]
 #[config_prefix = "grpc-client"]
 pub struct GrpcClientConfig {
-    pub(crate) embedding_url: String,
     pub(crate) artalk_url: String,
 }
 
+#[derive(Debug, Configurable, Deserialize)]
+#[config_prefix = "ocr"]
+pub struct OcrConfig {
+    #[serde(default)]
+    pub(crate) engine: OcrEngineType,
+    /// tesseract可执行文件
+    #[serde(default = "default_ocr_command")]
+    pub(crate) command: String,
+    /// tesseract语言包，简体中文加英文
+    #[serde(default = "default_ocr_languages")]
+    pub(crate) languages: String,
+    #[serde(default = "default_ocr_timeout_secs")]
+    pub(crate) timeout_secs: u64,
+    /// 同时运行的tesseract进程数，超过时排队等待
+    #[serde(default = "default_ocr_concurrency")]
+    pub(crate) concurrency: usize,
+    /// stub引擎固定返回的文本
+    #[serde(default)]
+    pub(crate) stub_text: String,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum OcrEngineType {
+    #[default]
+    Tesseract,
+    Stub,
+}
+
+fn default_ocr_command() -> String {
+    "tesseract".to_string()
+}
+
+fn default_ocr_languages() -> String {
+    "chi_sim+eng".to_string()
+}
+
+fn default_ocr_timeout_secs() -> u64 {
+    20
+}
+
+fn default_ocr_concurrency() -> usize {
+    2
+}
+
 #[derive(Debug, Clone, Configurable, Deserialize)]
 #[config_prefix = "dtiku"]
 pub struct DtikuConfig {
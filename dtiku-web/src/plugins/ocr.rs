@@ -0,0 +1,116 @@
+use super::{OcrConfig, OcrEngineType};
+use anyhow::Context;
+use derive_more::derive::Deref;
+use spring::{
+    app::AppBuilder,
+    async_trait,
+    config::ConfigRegistry,
+    plugin::{MutableComponentRegistry, Plugin},
+};
+use std::{process::Stdio, sync::Arc, time::Duration};
+use tokio::{io::AsyncWriteExt, process::Command, sync::Semaphore};
+
+/// 图片文字识别
+#[async_trait]
+pub trait OcrEngine: Send + Sync {
+    /// 识别图片中的文字，返回原始识别结果
+    async fn recognize(&self, image: &[u8]) -> anyhow::Result<String>;
+}
+
+/// 注册为组件的OCR引擎
+#[derive(Clone, Deref)]
+pub struct Ocr(Arc<dyn OcrEngine>);
+
+pub struct OcrPlugin;
+
+#[async_trait]
+impl Plugin for OcrPlugin {
+    async fn build(&self, app: &mut AppBuilder) {
+        let config = app
+            .get_config::<OcrConfig>()
+            .expect("load ocr config failed");
+
+        let engine: Arc<dyn OcrEngine> = match config.engine {
+            OcrEngineType::Tesseract => Arc::new(TesseractOcr {
+                command: config.command,
+                languages: config.languages,
+                timeout: Duration::from_secs(config.timeout_secs),
+                permits: Semaphore::new(config.concurrency.max(1)),
+            }),
+            OcrEngineType::Stub => Arc::new(StubOcr(config.stub_text)),
+        };
+        app.add_component(Ocr(engine));
+    }
+}
+
+/// 调用本机的tesseract命令行在CPU上识别，图片通过stdin传入
+pub struct TesseractOcr {
+    command: String,
+    languages: String,
+    timeout: Duration,
+    permits: Semaphore,
+}
+
+#[async_trait]
+impl OcrEngine for TesseractOcr {
+    async fn recognize(&self, image: &[u8]) -> anyhow::Result<String> {
+        // 每个请求都会启动一个进程，限制并发避免匿名请求占满CPU
+        let _permit = tokio::time::timeout(self.timeout, self.permits.acquire())
+            .await
+            .context("wait for tesseract permit timeout")?
+            .context("tesseract semaphore closed")?;
+        let mut child = Command::new(&self.command)
+            // psm 6: 按单个文本块识别，适合拍摄的整道题目
+            .args(["stdin", "stdout", "-l", &self.languages, "--psm", "6"])
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .kill_on_drop(true)
+            .spawn()
+            .with_context(|| format!("spawn {} failed", self.command))?;
+
+        let mut stdin = child.stdin.take().context("tesseract stdin unavailable")?;
+        stdin
+            .write_all(image)
+            .await
+            .context("write image to tesseract failed")?;
+        drop(stdin);
+
+        let output = tokio::time::timeout(self.timeout, child.wait_with_output())
+            .await
+            .context("tesseract timeout")?
+            .context("wait tesseract failed")?;
+        if !output.status.success() {
+            anyhow::bail!(
+                "tesseract exit with {}: {}",
+                output.status,
+                String::from_utf8_lossy(&output.stderr)
+            );
+        }
+        Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+    }
+}
+
+/// 总是返回固定文本，用于测试和没有安装tesseract的开发环境
+pub struct StubOcr(pub String);
+
+#[async_trait]
+impl OcrEngine for StubOcr {
+    async fn recognize(&self, _image: &[u8]) -> anyhow::Result<String> {
+        Ok(self.0.clone())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Ocr, StubOcr};
+    use dtiku_paper::domain::search::clean_ocr_text;
+    use std::sync::Arc;
+
+    #[tokio::test]
+    async fn test_stub_ocr_feeds_search_text() {
+        let ocr = Ocr(Arc::new(StubOcr("5、资 料 分 析\n\n12\n".to_string())));
+        let text = ocr.recognize(b"fake image").await.unwrap();
+        assert_eq!(clean_ocr_text(&text), "资料分析");
+    }
+}
@@ -19,3 +19,9 @@ pub struct PaperTitleLikeQuery {
     #[serde(default, rename = "q")]
     pub title: String,
 }
+
+#[derive(Debug, Deserialize)]
+pub struct MockPaperReq {
+    pub paper_type: i16,
+    pub label_id: i32,
+}
@@ -1,3 +1,4 @@
+use dtiku_paper::domain::irt::DEFAULT_TARGET_PROBABILITY;
 use serde::Deserialize;
 
 #[derive(Debug, Deserialize)]
@@ -5,3 +6,48 @@ pub struct DetailQuery {
     #[serde(default, rename = "onlyComment")]
     pub only_comment: bool,
 }
+
+#[derive(Debug, Deserialize)]
+pub struct AdaptiveQuery {
+    #[serde(default = "default_paper_type_prefix", rename = "ty")]
+    pub paper_type_prefix: String,
+    /// 一级知识点id
+    #[serde(default, rename = "kp")]
+    pub keypoint_id: Option<i32>,
+    /// 目标答对概率
+    #[serde(default)]
+    pub target: Option<f32>,
+}
+
+fn default_paper_type_prefix() -> String {
+    "xingce".to_string()
+}
+
+impl AdaptiveQuery {
+    pub fn keypoint_path(&self) -> Option<String> {
+        self.keypoint_id.map(|id| id.to_string())
+    }
+
+    pub fn target(&self) -> f32 {
+        self.target.unwrap_or(DEFAULT_TARGET_PROBABILITY)
+    }
+
+    pub fn is_keypoint(&self, keypoint_id: &i32) -> bool {
+        self.keypoint_id.as_ref() == Some(keypoint_id)
+    }
+
+    pub fn is_target(&self, target: &f32) -> bool {
+        (self.target() - target).abs() < 1e-3
+    }
+
+    pub fn url(&self) -> String {
+        let mut url = format!("/question/adaptive?ty={}", self.paper_type_prefix);
+        if let Some(kp) = self.keypoint_id {
+            url.push_str(&format!("&kp={kp}"));
+        }
+        if let Some(target) = self.target {
+            url.push_str(&format!("&target={target}"));
+        }
+        url
+    }
+}
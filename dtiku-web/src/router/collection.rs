@@ -0,0 +1,304 @@
+use crate::{
+    router::{error_messages, user::xingce_label_tree, Claims},
+    views::{
+        collection::{CollectionDetailTemplate, CollectionListTemplate},
+        question::QuestionSectionTemplate,
+        GlobalVariables,
+    },
+};
+use dtiku_paper::{
+    query::question::{PaperQuestionQuery, SectionType},
+    service::{label::LabelService, question::QuestionService},
+};
+use dtiku_stats::{
+    domain::collection::{normalize_tags, validate_name, AddItemOutcome, ItemKey},
+    model::{collection, sea_orm_active_enums::CollectionItemType},
+    service::collection::CollectionService,
+};
+use serde::Deserialize;
+use spring_web::{
+    axum::{
+        response::{IntoResponse, Json, Redirect},
+        Extension, Form,
+    },
+    error::{KnownWebError, Result},
+    extractor::{Component, Path, Query},
+    get, post,
+};
+
+#[derive(Debug, Deserialize)]
+struct CollectionNameReq {
+    name: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct CollectionShareReq {
+    shared: bool,
+}
+
+#[derive(Debug, Deserialize)]
+struct CollectionQuery {
+    /// 分享链接中的key
+    key: Option<String>,
+    tag: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct UserCollectionsQuery {
+    item_type: Option<CollectionItemType>,
+    item_id: Option<i32>,
+}
+
+#[derive(Debug, Deserialize)]
+struct CollectionItemReq {
+    #[serde(flatten)]
+    item: ItemKey,
+    #[serde(default)]
+    tags: Vec<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct CollectionOrderReq {
+    items: Vec<ItemKey>,
+}
+
+/// 我的收藏夹
+/// GET /user/collections
+#[get("/user/collections")]
+async fn user_collections(
+    claims: Claims,
+    Component(cs): Component<CollectionService>,
+    Extension(global): Extension<GlobalVariables>,
+) -> Result<impl IntoResponse> {
+    let collections = cs.find_user_collections(claims.user_id, None).await?;
+    Ok(CollectionListTemplate {
+        global,
+        collections,
+    })
+}
+
+/// 新建收藏夹
+/// POST /user/collections
+#[post("/user/collections")]
+async fn create_collection(
+    claims: Claims,
+    Component(cs): Component<CollectionService>,
+    Form(req): Form<CollectionNameReq>,
+) -> Result<impl IntoResponse> {
+    let name = validate_name(&req.name).map_err(KnownWebError::bad_request)?;
+    let c = cs.create(claims.user_id, name).await?;
+    Ok(Redirect::to(&format!("/collection/{}", c.id)))
+}
+
+/// 收藏夹详情，主人或者持有分享链接的人可以查看
+/// GET /collection/{id}?key=&tag=
+#[get("/collection/{id}")]
+async fn collection_detail(
+    Path(id): Path<i32>,
+    Query(query): Query<CollectionQuery>,
+    Component(cs): Component<CollectionService>,
+    Extension(global): Extension<GlobalVariables>,
+) -> Result<impl IntoResponse> {
+    let c = find_accessible(&cs, &global, id, query.key.as_deref()).await?;
+    let owner = global.user.as_ref().is_some_and(|u| u.id == c.user_id);
+    let detail = cs.find_detail(c, query.tag.as_deref()).await?;
+    Ok(CollectionDetailTemplate {
+        global,
+        detail,
+        tag: query.tag,
+        key: query.key,
+        owner,
+    })
+}
+
+/// 练习收藏夹中的题目
+/// GET /collection/{id}/practice?key=&tag=
+#[get("/collection/{id}/practice")]
+async fn collection_practice(
+    Path(id): Path<i32>,
+    Query(query): Query<CollectionQuery>,
+    Component(cs): Component<CollectionService>,
+    Component(qs): Component<QuestionService>,
+    Component(ls): Component<LabelService>,
+    Extension(global): Extension<GlobalVariables>,
+) -> Result<impl IntoResponse> {
+    let c = find_accessible(&cs, &global, id, query.key.as_deref()).await?;
+    let qids = cs.find_question_ids(c.id, query.tag.as_deref()).await?;
+    let (questions, papers) = qs.find_single_paper_by_ids(qids).await?;
+    let label_tree = xingce_label_tree(&global, &ls).await?;
+    Ok(QuestionSectionTemplate {
+        global,
+        papers,
+        questions,
+        label_tree,
+        query: PaperQuestionQuery {
+            section_type: SectionType::Together,
+            ..Default::default()
+        },
+        kp_paths: vec![],
+        collection: Some(c),
+    })
+}
+
+/// 重命名收藏夹
+/// POST /collection/{id}/rename
+#[post("/collection/{id}/rename")]
+async fn rename_collection(
+    claims: Claims,
+    Path(id): Path<i32>,
+    Component(cs): Component<CollectionService>,
+    Form(req): Form<CollectionNameReq>,
+) -> Result<impl IntoResponse> {
+    let name = validate_name(&req.name).map_err(KnownWebError::bad_request)?;
+    cs.rename(claims.user_id, id, name)
+        .await?
+        .ok_or_else(|| KnownWebError::not_found(error_messages::COLLECTION_NOT_FOUND))?;
+    Ok(Redirect::to(&format!("/collection/{id}")))
+}
+
+/// 开启或关闭分享
+/// POST /collection/{id}/share
+#[post("/collection/{id}/share")]
+async fn share_collection(
+    claims: Claims,
+    Path(id): Path<i32>,
+    Component(cs): Component<CollectionService>,
+    Form(req): Form<CollectionShareReq>,
+) -> Result<impl IntoResponse> {
+    cs.set_shared(claims.user_id, id, req.shared)
+        .await?
+        .ok_or_else(|| KnownWebError::not_found(error_messages::COLLECTION_NOT_FOUND))?;
+    Ok(Redirect::to(&format!("/collection/{id}")))
+}
+
+/// 删除收藏夹
+/// POST /collection/{id}/delete
+#[post("/collection/{id}/delete")]
+async fn delete_collection(
+    claims: Claims,
+    Path(id): Path<i32>,
+    Component(cs): Component<CollectionService>,
+) -> Result<impl IntoResponse> {
+    if !cs.delete(claims.user_id, id).await? {
+        return Err(KnownWebError::not_found(error_messages::COLLECTION_NOT_FOUND).into());
+    }
+    Ok(Redirect::to("/user/collections"))
+}
+
+/// 收藏弹窗中的收藏夹列表，标记已收藏当前条目的收藏夹
+/// GET /api/user/collections?item_type=&item_id=
+#[get("/api/user/collections")]
+async fn api_user_collections(
+    claims: Claims,
+    Query(query): Query<UserCollectionsQuery>,
+    Component(cs): Component<CollectionService>,
+) -> Result<impl IntoResponse> {
+    let item = query
+        .item_type
+        .zip(query.item_id)
+        .map(|(item_type, item_id)| ItemKey { item_type, item_id });
+    let collections = cs.find_user_collections(claims.user_id, item).await?;
+    Ok(Json(collections))
+}
+
+/// 在收藏弹窗中新建收藏夹
+/// POST /api/user/collections
+#[post("/api/user/collections")]
+async fn api_create_collection(
+    claims: Claims,
+    Component(cs): Component<CollectionService>,
+    Json(req): Json<CollectionNameReq>,
+) -> Result<impl IntoResponse> {
+    let name = validate_name(&req.name).map_err(KnownWebError::bad_request)?;
+    let c = cs.create(claims.user_id, name).await?;
+    Ok(Json(c))
+}
+
+/// 收藏题目、试卷、成语或帖子
+/// POST /api/collection/{id}/items
+#[post("/api/collection/{id}/items")]
+async fn add_collection_item(
+    claims: Claims,
+    Path(id): Path<i32>,
+    Component(cs): Component<CollectionService>,
+    Json(req): Json<CollectionItemReq>,
+) -> Result<impl IntoResponse> {
+    let tags = normalize_tags(&req.tags).map_err(KnownWebError::bad_request)?;
+    let c = find_owned(&cs, claims.user_id, id).await?;
+    match cs.add_item(c.id, req.item, tags).await? {
+        AddItemOutcome::Added | AddItemOutcome::Existed => Ok(Json("success")),
+        AddItemOutcome::NotFound => {
+            Err(KnownWebError::not_found(error_messages::COLLECTION_ITEM_NOT_FOUND).into())
+        }
+        AddItemOutcome::Full => {
+            Err(KnownWebError::bad_request(error_messages::COLLECTION_FULL).into())
+        }
+    }
+}
+
+/// 从收藏夹中移除
+/// POST /api/collection/{id}/items/delete
+#[post("/api/collection/{id}/items/delete")]
+async fn remove_collection_item(
+    claims: Claims,
+    Path(id): Path<i32>,
+    Component(cs): Component<CollectionService>,
+    Json(item): Json<ItemKey>,
+) -> Result<impl IntoResponse> {
+    let c = find_owned(&cs, claims.user_id, id).await?;
+    if !cs.remove_item(c.id, item).await? {
+        return Err(KnownWebError::not_found(error_messages::COLLECTION_ITEM_NOT_FOUND).into());
+    }
+    Ok(Json("success"))
+}
+
+/// 修改条目的标签
+/// POST /api/collection/{id}/items/tags
+#[post("/api/collection/{id}/items/tags")]
+async fn set_collection_item_tags(
+    claims: Claims,
+    Path(id): Path<i32>,
+    Component(cs): Component<CollectionService>,
+    Json(req): Json<CollectionItemReq>,
+) -> Result<impl IntoResponse> {
+    let tags = normalize_tags(&req.tags).map_err(KnownWebError::bad_request)?;
+    let c = find_owned(&cs, claims.user_id, id).await?;
+    if !cs.set_tags(c.id, req.item, tags).await? {
+        return Err(KnownWebError::not_found(error_messages::COLLECTION_ITEM_NOT_FOUND).into());
+    }
+    Ok(Json("success"))
+}
+
+/// 调整条目顺序
+/// POST /api/collection/{id}/items/order
+#[post("/api/collection/{id}/items/order")]
+async fn reorder_collection_items(
+    claims: Claims,
+    Path(id): Path<i32>,
+    Component(cs): Component<CollectionService>,
+    Json(req): Json<CollectionOrderReq>,
+) -> Result<impl IntoResponse> {
+    let c = find_owned(&cs, claims.user_id, id).await?;
+    cs.reorder(c.id, req.items).await?;
+    Ok(Json("success"))
+}
+
+async fn find_owned(cs: &CollectionService, user_id: i32, id: i32) -> Result<collection::Model> {
+    Ok(cs
+        .find_owned(user_id, id)
+        .await?
+        .ok_or_else(|| KnownWebError::not_found(error_messages::COLLECTION_NOT_FOUND))?)
+}
+
+async fn find_accessible(
+    cs: &CollectionService,
+    global: &GlobalVariables,
+    id: i32,
+    key: Option<&str>,
+) -> Result<collection::Model> {
+    let user_id = global.user.as_ref().map(|u| u.id);
+    Ok(cs
+        .find_accessible(id, user_id, key)
+        .await?
+        .ok_or_else(|| KnownWebError::not_found(error_messages::COLLECTION_NOT_FOUND))?)
+}
@@ -4,10 +4,24 @@
 // ==================== 试卷相关 ====================
 pub const PAPER_TYPE_NOT_FOUND: &str = "试卷类型不存在";
 pub const PAPER_NOT_FOUND: &str = "试卷未找到";
+pub const MOCK_EXAM_NO_REFERENCE: &str = "该地区还没有可参考的真题，暂时无法生成模拟卷";
+pub const MOCK_EXAM_TOO_MANY: &str = "模拟卷生成太频繁，请先完成已生成的模拟卷或明天再试";
+
+// ==================== 练习相关 ====================
+pub const EXERCISE_NOT_FOUND: &str = "练习记录不存在";
+pub const EXERCISE_OWNER_UNKNOWN: &str = "请登录或开启浏览器Cookie后再保存练习记录";
+pub const TIMED_EXAM_UNSUPPORTED: &str = "该试卷不支持限时模考";
+pub const TIMED_EXAM_NOT_STARTED: &str = "限时模考未开始或已交卷";
+pub const ESSAY_ANSWER_TOO_LONG: &str = "申论每问的作答不能超过3000字";
 
 // ==================== 题目相关 ====================
 pub const QUESTION_NOT_FOUND: &str = "题目不存在";
+pub const ADAPTIVE_ANSWER_REQUIRED: &str = "请先作答再提交";
 pub const QUESTION_PAPER_TYPE_REQUIRED: &str = "请指定试卷类型";
+pub const SEARCH_IMAGE_REQUIRED: &str = "请上传题目图片";
+pub const SEARCH_IMAGE_INVALID: &str = "图片读取失败，请上传10M以内的图片";
+pub const SEARCH_IMAGE_NO_TEXT: &str = "没有识别出文字，请拍清楚题目后重试";
+pub const EXPORT_PAPER_REQUIRED: &str = "请先选择要导出的试卷";
 
 // ==================== 成语相关 ====================
 pub const IDIOM_NOT_FOUND: &str = "成语未找到";
@@ -22,6 +36,13 @@ pub const ISSUE_NOT_FOUND: &str = "没找到帖子";
 
 // ==================== 用户相关 ====================
 pub const USER_AVATAR_NOT_FOUND: &str = "用户头像不存在";
+pub const REVIEW_ITEM_NOT_FOUND: &str = "复习内容不存在";
+pub const NOTE_NOT_FOUND: &str = "笔记不存在";
+pub const NOTE_TARGET_NOT_FOUND: &str = "题目或材料不存在，或者高亮的文字与材料不一致";
+pub const NOTE_TOO_LONG: &str = "笔记内容过长";
+pub const COLLECTION_NOT_FOUND: &str = "收藏夹不存在";
+pub const COLLECTION_ITEM_NOT_FOUND: &str = "收藏的内容不存在";
+pub const COLLECTION_FULL: &str = "收藏夹已满，请新建一个收藏夹";
 
 // ==================== 认证相关 ====================
 pub const INVALID_COOKIE: &str = "invalid cookie";
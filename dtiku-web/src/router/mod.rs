@@ -1,4 +1,5 @@
 mod bbs;
+mod collection;
 mod error_messages;
 mod home;
 mod idiom;
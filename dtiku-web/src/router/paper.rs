@@ -1,30 +1,53 @@
 use crate::{
-    query::paper::{ListPaperQuery, PaperQuery, PaperTitleLikeQuery},
-    router::error_messages,
+    query::paper::{ListPaperQuery, MockPaperReq, PaperQuery, PaperTitleLikeQuery},
+    router::{error_messages, Claims},
     views::{
-        paper::{ChapterPaperTemplate, ClusterPaperTemplate, ListPaperTemplate},
+        paper::{
+            ChapterPaperTemplate, ClusterPaperTemplate, ListPaperTemplate, PaperHistoryTemplate,
+        },
         GlobalVariables, IntoTemplate,
     },
 };
 use anyhow::Context;
 use askama::Template;
+use dtiku_base::embedding::Embedding;
 use dtiku_paper::{
-    domain::paper::{self, PaperMode},
-    model::paper::PaperExtra,
+    domain::{
+        essay::{decode_essay_answer, EssayGrading},
+        exercise::TimedExercise,
+        export::ExportOptions,
+        paper::{self, FullPaper, PaperMode},
+        user_note::UserNotes,
+    },
+    model::{essay_grade, paper::PaperExtra, Paper},
     query::paper::ListPaperQuery as PaperListQuery,
-    service::{label::LabelService, paper::PaperService},
+    service::{
+        exercise::ExerciseService,
+        export::{ExportFile, ExportService},
+        label::LabelService,
+        mock_exam::{MockExamService, PersonalMock},
+        paper::PaperService,
+        user_note::UserNoteService,
+    },
 };
-use spring_sea_orm::pagination::Pagination;
+use sea_orm::EntityTrait;
+use spring_sea_orm::{pagination::Pagination, DbConn};
 use spring_web::{
     axum::{
-        response::{Html, IntoResponse},
+        http::header,
+        response::{Html, IntoResponse, Redirect},
         Extension, Form, Json,
     },
     error::{KnownWebError, Result},
     extractor::{Component, Path, Query},
     get, post,
 };
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
+
+/// 申论每道题最多接收的作答块数，防止恶意表单
+const MAX_ESSAY_BLOCKS: usize = 16;
+/// 申论每问作答的最大字数，超长的作答不送去计算向量
+const MAX_ESSAY_BLOCK_LENGTH: usize = 3000;
 
 #[get("/paper")]
 async fn list_paper(
@@ -62,11 +85,34 @@ async fn list_paper(
     ))
 }
 
+#[post("/paper/mock")]
+async fn generate_mock_paper(
+    claims: Claims,
+    Component(ms): Component<MockExamService>,
+    Form(req): Form<MockPaperReq>,
+) -> Result<impl IntoResponse> {
+    let paper = match ms
+        .personal(req.paper_type, req.label_id, claims.user_id)
+        .await?
+    {
+        PersonalMock::Ready(paper) => paper,
+        PersonalMock::NoReference => {
+            return Err(KnownWebError::not_found(error_messages::MOCK_EXAM_NO_REFERENCE).into())
+        }
+        PersonalMock::RateLimited => {
+            return Err(KnownWebError::too_many_requests(error_messages::MOCK_EXAM_TOO_MANY).into())
+        }
+    };
+    Ok(Redirect::to(&format!("/paper/{}?mode=exercise", paper.id)))
+}
+
 #[get("/paper/{id}")]
 async fn paper_by_id(
     Path(id): Path<i32>,
     Query(query): Query<PaperQuery>,
     Component(ps): Component<PaperService>,
+    Component(es): Component<ExerciseService>,
+    Component(ns): Component<UserNoteService>,
     Extension(global): Extension<GlobalVariables>,
 ) -> Result<impl IntoResponse> {
     let paper = ps
@@ -74,30 +120,450 @@ async fn paper_by_id(
         .await?
         .ok_or_else(|| KnownWebError::not_found(error_messages::PAPER_NOT_FOUND))?;
     let html = match paper.p.extra {
+        PaperExtra::Chapters(_) if matches!(paper.mode, PaperMode::Timed) => {
+            let owner = global.exercise_owner().ok_or_else(|| {
+                KnownWebError::unauthorized(error_messages::EXERCISE_OWNER_UNKNOWN)
+            })?;
+            let paper_model = paper.p.clone();
+            if paper_model.time_limit_secs().is_none() {
+                return Err(
+                    KnownWebError::bad_request(error_messages::TIMED_EXAM_UNSUPPORTED).into(),
+                );
+            }
+            let mut t: ChapterPaperTemplate = paper.to_template(global);
+            // 打开试卷不开始计时，由用户点击开始后才创建考试记录
+            match es
+                .find_timed_exercise(&owner, &paper_model, &t.questions)
+                .await?
+            {
+                Some(TimedExercise::Running {
+                    detail,
+                    remaining_secs,
+                }) => {
+                    t.draft_answer = Some(detail.user_answer);
+                    t.remaining_secs = Some(remaining_secs);
+                }
+                Some(TimedExercise::Submitted(detail) | TimedExercise::Expired(detail)) => {
+                    return Ok(
+                        Redirect::to(&format!("/paper/{id}/report/{}", detail.record.id))
+                            .into_response(),
+                    );
+                }
+                None => {}
+            }
+            t.render().context("render failed")?
+        }
         PaperExtra::Chapters(_) => {
-            let t: ChapterPaperTemplate = paper.to_template(global);
+            let draft = match (paper.mode, global.exercise_owner()) {
+                (PaperMode::Exercise, Some(owner)) => {
+                    es.find_unfinished_exercise(&owner, id).await?
+                }
+                _ => None,
+            };
+            let user_id = global.user.as_ref().map(|u| u.id);
+            let mut t: ChapterPaperTemplate = paper.to_template(global);
+            t.draft_answer = draft.map(|d| d.user_answer);
+            let mids = t
+                .questions
+                .iter()
+                .flat_map(|q| q.materials.iter().flatten().map(|m| m.id))
+                .collect();
+            let qids = t.questions.iter().map(|q| q.id).collect();
+            t.notes = find_user_notes(&ns, user_id, qids, mids).await?;
             t.render().context("render failed")?
         }
+        _ if matches!(paper.mode, PaperMode::Timed) => {
+            return Err(KnownWebError::bad_request(error_messages::TIMED_EXAM_UNSUPPORTED).into());
+        }
         _ => {
-            let t: ClusterPaperTemplate = paper.to_template(global);
+            let user_id = global.user.as_ref().map(|u| u.id);
+            let mut t: ClusterPaperTemplate = paper.to_template(global);
+            let mids = t.materials.iter().map(|m| m.id).collect();
+            let qids = t.questions.iter().map(|q| q.id).collect();
+            t.notes = find_user_notes(&ns, user_id, qids, mids).await?;
             t.render().context("render failed")?
         }
     };
-    Ok(Html(html))
+    Ok(Html(html).into_response())
+}
+
+/// 开始限时模考，从此刻起计时
+#[post("/paper/{id}/start")]
+async fn start_timed_exercise(
+    Path(id): Path<i32>,
+    Component(ps): Component<PaperService>,
+    Component(es): Component<ExerciseService>,
+    Extension(global): Extension<GlobalVariables>,
+) -> Result<impl IntoResponse> {
+    let owner = global
+        .exercise_owner()
+        .ok_or_else(|| KnownWebError::unauthorized(error_messages::EXERCISE_OWNER_UNKNOWN))?;
+    let paper = ps
+        .find_paper_by_id(id, PaperMode::Timed)
+        .await?
+        .ok_or_else(|| KnownWebError::not_found(error_messages::PAPER_NOT_FOUND))?;
+    let paper_model = paper.p.clone();
+    let t: ChapterPaperTemplate = paper.to_template(global);
+    let timed = es
+        .start_timed_exercise(&owner, &paper_model, &t.questions)
+        .await?
+        .ok_or_else(|| KnownWebError::bad_request(error_messages::TIMED_EXAM_UNSUPPORTED))?;
+    Ok(match timed {
+        TimedExercise::Running { .. } => {
+            Redirect::to(&format!("/paper/{id}?mode={}", PaperMode::Timed))
+        }
+        TimedExercise::Submitted(detail) | TimedExercise::Expired(detail) => {
+            Redirect::to(&format!("/paper/{id}/report/{}", detail.record.id))
+        }
+    })
+}
+
+/// 登录用户在试卷上的笔记，未登录时不展示笔记
+pub(crate) async fn find_user_notes(
+    ns: &UserNoteService,
+    user_id: Option<i32>,
+    question_ids: Vec<i32>,
+    material_ids: Vec<i32>,
+) -> Result<Option<UserNotes>> {
+    Ok(match user_id {
+        Some(user_id) => Some(ns.find_notes(user_id, question_ids, material_ids).await?),
+        None => None,
+    })
 }
 
 #[post("/paper/{id}/report")]
 async fn paper_exercise(
     Path(id): Path<i32>,
+    Query(query): Query<PaperQuery>,
     Component(ps): Component<PaperService>,
+    Component(es): Component<ExerciseService>,
+    Component(embedding): Component<Embedding>,
     Extension(global): Extension<GlobalVariables>,
-    Form(params): Form<HashMap<String, String>>,
+    Form(params): Form<Vec<(String, String)>>,
 ) -> Result<impl IntoResponse> {
     let paper = ps
         .find_paper_by_id(id, PaperMode::Exercise)
         .await?
         .ok_or_else(|| KnownWebError::not_found(error_messages::PAPER_NOT_FOUND))?;
+    if !matches!(paper.p.extra, PaperExtra::Chapters(_)) {
+        return essay_exercise(paper, &es, &embedding, global, params.into_iter().collect()).await;
+    }
+
+    let (user_answer, answer_q_time) = parse_exercise_form(params);
+    if matches!(query.mode, Some(PaperMode::Timed)) {
+        return timed_exercise(paper, &es, global, user_answer, answer_q_time).await;
+    }
+    let owner = global.exercise_owner();
+    let paper_model = paper.p.clone();
+    let mut t: ChapterPaperTemplate = paper.to_template(global);
+    if let Some(owner) = owner {
+        es.save_exercise(
+            &owner,
+            &paper_model,
+            &t.questions,
+            &user_answer,
+            &answer_q_time,
+            true,
+        )
+        .await?;
+    }
+    let scheme = es.find_scoring_scheme(&paper_model).await?;
+    t.report = paper::compute_report(
+        &paper_model,
+        &t.questions,
+        &user_answer,
+        &answer_q_time,
+        &scheme,
+    );
+    t.user_answer = Some(user_answer);
+    t.user_time = Some(answer_q_time);
+    Ok(Html(t.render().context("render failed")?))
+}
 
+/// 限时模考交卷：超时提交的作答不予采纳，展示服务端按截止前暂存作答自动交卷的报告
+async fn timed_exercise(
+    paper: FullPaper,
+    es: &ExerciseService,
+    global: GlobalVariables,
+    user_answer: HashMap<i32, String>,
+    user_time: HashMap<i32, u64>,
+) -> Result<Html<String>> {
+    let owner = global
+        .exercise_owner()
+        .ok_or_else(|| KnownWebError::unauthorized(error_messages::EXERCISE_OWNER_UNKNOWN))?;
+    let paper_model = paper.p.clone();
+    let mut t: ChapterPaperTemplate = paper.to_template(global);
+    let timed = es
+        .save_timed_exercise(
+            &owner,
+            &paper_model,
+            &t.questions,
+            &user_answer,
+            &user_time,
+            true,
+        )
+        .await?
+        .ok_or_else(|| KnownWebError::bad_request(error_messages::TIMED_EXAM_NOT_STARTED))?;
+    let detail = match timed {
+        TimedExercise::Submitted(detail) | TimedExercise::Running { detail, .. } => detail,
+        TimedExercise::Expired(detail) => {
+            t.timed_out = true;
+            detail
+        }
+    };
+    let scheme = es.find_scoring_scheme(&paper_model).await?;
+    t.report = paper::compute_report(
+        &paper_model,
+        &t.questions,
+        &detail.user_answer,
+        &detail.user_time,
+        &scheme,
+    );
+    t.user_answer = Some(detail.user_answer);
+    t.user_time = Some(detail.user_time);
+    Ok(Html(t.render().context("render failed")?))
+}
+
+/// 批改申论：按参考答案要点的覆盖情况和字数要求给出每一问的意见
+async fn essay_exercise(
+    paper: FullPaper,
+    es: &ExerciseService,
+    embedding: &Embedding,
+    global: GlobalVariables,
+    params: HashMap<String, String>,
+) -> Result<Html<String>> {
+    let (user_answer, user_time) = parse_essay_form(params);
+    if user_answer
+        .values()
+        .flatten()
+        .any(|a| a.chars().count() > MAX_ESSAY_BLOCK_LENGTH)
+    {
+        return Err(KnownWebError::bad_request(error_messages::ESSAY_ANSWER_TOO_LONG).into());
+    }
+    let owner = global.exercise_owner();
+    let paper_model = paper.p.clone();
+    let mut t: ClusterPaperTemplate = paper.to_template(global);
+
+    let grading = EssayGrading::new(&t.questions, &user_answer);
+    let texts = grading.texts();
+    let embeddings = if texts.is_empty() {
+        vec![]
+    } else {
+        embedding.batch_text_embedding(&texts).await?
+    };
+    let scores = grading.grade(&embeddings)?;
+    let grades = match owner {
+        Some(owner) => {
+            es.save_essay_exercise(
+                &owner,
+                &paper_model,
+                &t.questions,
+                &user_answer,
+                &user_time,
+                scores,
+            )
+            .await?
+            .1
+        }
+        None => scores
+            .into_iter()
+            .map(|s| essay_grade::Model {
+                record_id: 0,
+                question_id: s.question_id,
+                score: s.score,
+                feedback: s.feedback,
+            })
+            .collect(),
+    };
+    t.grades = Some(grades.into_iter().map(|g| (g.question_id, g)).collect());
+    t.user_answer = Some(user_answer);
+    Ok(Html(t.render().context("render failed")?))
+}
+
+/// 暂存未完成的练习，下次以练习模式打开试卷时恢复作答。
+/// 限时模考的暂存超过截止时间时直接交卷
+#[post("/paper/{id}/progress")]
+async fn paper_exercise_progress(
+    Path(id): Path<i32>,
+    Query(query): Query<PaperQuery>,
+    Component(ps): Component<PaperService>,
+    Component(es): Component<ExerciseService>,
+    Extension(global): Extension<GlobalVariables>,
+    Form(params): Form<Vec<(String, String)>>,
+) -> Result<impl IntoResponse> {
+    let owner = global
+        .exercise_owner()
+        .ok_or_else(|| KnownWebError::unauthorized(error_messages::EXERCISE_OWNER_UNKNOWN))?;
+    let paper = ps
+        .find_paper_by_id(id, PaperMode::Exercise)
+        .await?
+        .ok_or_else(|| KnownWebError::not_found(error_messages::PAPER_NOT_FOUND))?;
+
+    let (user_answer, answer_q_time) = parse_exercise_form(params);
+    let paper_model = paper.p.clone();
+    let t: ChapterPaperTemplate = paper.to_template(global);
+    if matches!(query.mode, Some(PaperMode::Timed)) {
+        let timed = es
+            .save_timed_exercise(
+                &owner,
+                &paper_model,
+                &t.questions,
+                &user_answer,
+                &answer_q_time,
+                false,
+            )
+            .await?
+            .ok_or_else(|| KnownWebError::bad_request(error_messages::TIMED_EXAM_NOT_STARTED))?;
+        return Ok(match timed {
+            TimedExercise::Running { .. } => {
+                Redirect::to(&format!("/paper/{id}?mode={}", PaperMode::Timed))
+            }
+            TimedExercise::Submitted(detail) | TimedExercise::Expired(detail) => {
+                Redirect::to(&format!("/paper/{id}/report/{}", detail.record.id))
+            }
+        });
+    }
+    es.save_exercise(
+        &owner,
+        &paper_model,
+        &t.questions,
+        &user_answer,
+        &answer_q_time,
+        false,
+    )
+    .await?;
+    Ok(Redirect::to(&format!(
+        "/paper/{id}?mode={}",
+        PaperMode::Exercise
+    )))
+}
+
+#[get("/paper/{id}/report/{record_id}")]
+async fn paper_exercise_report(
+    Path((id, record_id)): Path<(i32, i32)>,
+    Component(ps): Component<PaperService>,
+    Component(es): Component<ExerciseService>,
+    Extension(global): Extension<GlobalVariables>,
+) -> Result<impl IntoResponse> {
+    let owner = global
+        .exercise_owner()
+        .ok_or_else(|| KnownWebError::unauthorized(error_messages::EXERCISE_OWNER_UNKNOWN))?;
+    let exercise = es
+        .find_exercise(&owner, record_id)
+        .await?
+        .filter(|e| e.record.paper_id == id && e.record.finished)
+        .ok_or_else(|| KnownWebError::not_found(error_messages::EXERCISE_NOT_FOUND))?;
+    let paper = ps
+        .find_paper_by_id(id, PaperMode::Exercise)
+        .await?
+        .ok_or_else(|| KnownWebError::not_found(error_messages::PAPER_NOT_FOUND))?;
+
+    if !matches!(paper.p.extra, PaperExtra::Chapters(_)) {
+        let mut t: ClusterPaperTemplate = paper.to_template(global);
+        t.grades = Some(es.find_essay_grades(record_id).await?);
+        t.user_answer = Some(
+            exercise
+                .user_answer
+                .into_iter()
+                .map(|(qid, answer)| (qid, decode_essay_answer(&answer)))
+                .collect(),
+        );
+        return Ok(Html(t.render().context("render failed")?));
+    }
+
+    let paper_model = paper.p.clone();
+    let scheme = es.find_scoring_scheme(&paper_model).await?;
+    let mut t: ChapterPaperTemplate = paper.to_template(global);
+    t.report = paper::compute_report(
+        &paper_model,
+        &t.questions,
+        &exercise.user_answer,
+        &exercise.user_time,
+        &scheme,
+    );
+    t.user_answer = Some(exercise.user_answer);
+    t.user_time = Some(exercise.user_time);
+    Ok(Html(t.render().context("render failed")?))
+}
+
+/// 练习记录，放弃的限时模考超过截止时间后在这里按暂存的作答交卷
+#[get("/paper/{id}/history")]
+async fn paper_exercise_history(
+    Path(id): Path<i32>,
+    Component(db): Component<DbConn>,
+    Component(ps): Component<PaperService>,
+    Component(es): Component<ExerciseService>,
+    Extension(global): Extension<GlobalVariables>,
+) -> Result<impl IntoResponse> {
+    let owner = global
+        .exercise_owner()
+        .ok_or_else(|| KnownWebError::unauthorized(error_messages::EXERCISE_OWNER_UNKNOWN))?;
+    let paper = Paper::find_by_id(id)
+        .one(&db)
+        .await
+        .with_context(|| format!("Paper::find_by_id({id}) failed"))?
+        .ok_or_else(|| KnownWebError::not_found(error_messages::PAPER_NOT_FOUND))?;
+    let mut records = es.list_exercise_by_paper(&owner, id).await?;
+    let now = chrono::Local::now().naive_local();
+    if records.iter().any(|r| r.is_overdue(now)) {
+        if let Some(full) = ps.find_paper_by_id(id, PaperMode::Timed).await? {
+            let t: ChapterPaperTemplate = full.to_template(global.clone());
+            es.find_timed_exercise(&owner, &paper, &t.questions).await?;
+            records = es.list_exercise_by_paper(&owner, id).await?;
+        }
+    }
+    Ok(PaperHistoryTemplate {
+        global,
+        paper,
+        records,
+    })
+}
+
+/// 导出试卷，供老师打印讲义
+#[get("/paper/{id}/export")]
+async fn export_paper(
+    _claims: Claims,
+    Path(id): Path<i32>,
+    Query(options): Query<ExportOptions>,
+    Component(ps): Component<PaperService>,
+    Component(es): Component<ExportService>,
+) -> Result<impl IntoResponse> {
+    let paper = ps
+        .find_paper_by_id(id, PaperMode::ShowAnswer)
+        .await?
+        .ok_or_else(|| KnownWebError::not_found(error_messages::PAPER_NOT_FOUND))?;
+    let file = es.export_paper(&paper, &options).await?;
+    Ok(attachment(file))
+}
+
+/// 以附件形式下载，文件名按RFC 5987编码以支持中文
+pub(super) fn attachment(file: ExportFile) -> impl IntoResponse {
+    let file_name: String = file
+        .file_name
+        .bytes()
+        .map(|b| match b {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' => {
+                (b as char).to_string()
+            }
+            _ => format!("%{b:02X}"),
+        })
+        .collect();
+    (
+        [
+            (header::CONTENT_TYPE, file.content_type.to_string()),
+            (
+                header::CONTENT_DISPOSITION,
+                format!("attachment; filename*=UTF-8''{file_name}"),
+            ),
+        ],
+        file.data,
+    )
+}
+
+/// 解析练习表单：`{qid}`为作答，多选题的多个选项以逗号拼接，`qt.{qid}`为该题耗时(秒)
+pub(super) fn parse_exercise_form(
+    params: Vec<(String, String)>,
+) -> (HashMap<i32, String>, HashMap<i32, u64>) {
     let mut user_answer = HashMap::new();
     let mut answer_q_time = HashMap::new();
 
@@ -109,20 +575,49 @@ async fn paper_exercise(
                 }
             }
         } else if let Ok(qid) = k.parse::<i32>() {
-            user_answer.insert(qid, v);
+            user_answer
+                .entry(qid)
+                .and_modify(|answer: &mut String| {
+                    answer.push(',');
+                    answer.push_str(&v);
+                })
+                .or_insert(v);
         }
     }
-    let paper_model = paper.p.clone();
-    let mut t: ChapterPaperTemplate = paper.to_template(global);
-    t.report = Some(paper::compute_report(
-        &paper_model,
-        &t.questions,
-        &user_answer,
-        &answer_q_time,
-    ));
-    t.user_answer = Some(user_answer);
-    t.user_time = Some(answer_q_time);
-    Ok(Html(t.render().context("render failed")?))
+    (user_answer, answer_q_time)
+}
+
+/// 解析申论表单：`{qid}.{index}`为第index问的作答，`qt.{qid}`为该题耗时(秒)
+fn parse_essay_form(
+    params: HashMap<String, String>,
+) -> (HashMap<i32, Vec<String>>, HashMap<i32, u64>) {
+    let mut blocks: HashMap<i32, BTreeMap<usize, String>> = HashMap::new();
+    let mut answer_q_time = HashMap::new();
+
+    for (k, v) in params {
+        if let Some(rest_qid) = k.strip_prefix("qt.") {
+            if let (Ok(qid), Ok(time)) = (rest_qid.parse::<i32>(), v.parse::<u64>()) {
+                answer_q_time.insert(qid, time);
+            }
+        } else if let Some((qid, index)) = k.split_once('.') {
+            if let (Ok(qid), Ok(index)) = (qid.parse::<i32>(), index.parse::<usize>()) {
+                if index < MAX_ESSAY_BLOCKS {
+                    blocks.entry(qid).or_default().insert(index, v);
+                }
+            }
+        }
+    }
+    let user_answer = blocks
+        .into_iter()
+        .map(|(qid, mut answers)| {
+            let len = answers.keys().last().map(|i| i + 1).unwrap_or_default();
+            let answers = (0..len)
+                .map(|i| answers.remove(&i).unwrap_or_default())
+                .collect();
+            (qid, answers)
+        })
+        .collect();
+    (user_answer, answer_q_time)
 }
 
 #[get("/api/paper/{prefix}/title/like")]
@@ -1,49 +1,95 @@
 use crate::{
-    query::question::DetailQuery,
-    router::{error_messages, EXAM_ID},
+    plugins::ocr::Ocr,
+    query::question::{AdaptiveQuery, DetailQuery},
+    router::{
+        error_messages,
+        paper::{attachment, find_user_notes, parse_exercise_form},
+        Claims, EXAM_ID,
+    },
     views::{
         question::{
-            OnlyCommentTemplate, QuestionDetailTemplate, QuestionRecommendTemplate,
-            QuestionSearchImgTemplate, QuestionSearchTemplate, QuestionSectionTemplate,
+            AdaptivePracticeTemplate, OnlyCommentTemplate, QuestionDetailTemplate,
+            QuestionRecommendTemplate, QuestionSearchImgResultTemplate, QuestionSearchImgTemplate,
+            QuestionSearchTemplate, QuestionSectionTemplate,
         },
         GlobalVariables,
     },
 };
 use anyhow::Context;
 use askama::Template;
+use axum_extra::extract::Multipart;
+use dtiku_base::embedding::Embedding;
 use dtiku_paper::{
-    domain::{label::LabelTree, question::QuestionSearch},
-    query::question::PaperQuestionQuery,
-    service::{keypoint::KeyPointService, label::LabelService, question::QuestionService},
+    domain::{
+        export::{ExportOptions, SolutionPlacement},
+        label::LabelTree,
+        question::{QuestionSearch, QuestionSearchHit},
+        question_feedback::FeedbackSubmit,
+        search::clean_ocr_text,
+    },
+    model::FeedbackCategory,
+    query::question::{PaperQuestionQuery, SectionType},
+    service::{
+        export::ExportService, keypoint::KeyPointService, label::LabelService,
+        question::QuestionService, question_feedback::QuestionFeedbackService,
+        user_note::UserNoteService,
+    },
 };
+use serde_json::json;
+use spring::tracing;
+use spring_sea_orm::pagination::{Page, Pagination};
 use spring_web::{
     axum::{
-        response::{Html, IntoResponse},
-        Extension,
+        extract::DefaultBodyLimit,
+        response::{Html, IntoResponse, Redirect},
+        Extension, Form, Json,
     },
     error::{KnownWebError, Result},
     extractor::{Component, Path, Query},
-    get,
+    get, middlewares, post,
 };
+use std::collections::HashMap;
+use strum::IntoEnumIterator;
 use validator::Validate;
 
+/// 拍照搜题返回的题目数
+const IMAGE_SEARCH_SIZE: u64 = 10;
+/// 拍照搜题上传图片的大小上限，手机拍摄的照片通常超过默认的2M
+const MAX_IMAGE_SIZE: usize = 10 * 1024 * 1024;
+/// 自适应练习可选的难度档位：目标答对概率
+const ADAPTIVE_TARGETS: [(f32, &str); 3] = [(0.85, "巩固"), (0.7, "适中"), (0.5, "挑战")];
+
 #[get("/question/search")]
 async fn search_question(
     Extension(global): Extension<GlobalVariables>,
     Query(mut query): Query<QuestionSearch>,
     Component(qs): Component<QuestionService>,
+    Component(embedding): Component<Embedding>,
+    page: Pagination,
 ) -> Result<impl IntoResponse> {
-    let questions = if query.content.is_empty() {
-        vec![]
+    let page = if query.content.trim().is_empty() {
+        Page::new(vec![], &page, 0)
     } else {
         query.exam_id = Some(EXAM_ID.get());
-        qs.search_question(&query).await?
+        search_hits(&qs, &embedding, &query, &page).await?
     };
-    Ok(QuestionSearchTemplate {
-        global,
-        questions,
-        query,
-    })
+    Ok(QuestionSearchTemplate::new(global, page, query))
+}
+
+/// 混合检索时先计算查询向量，向量服务不可用则退回关键词检索
+async fn search_hits(
+    qs: &QuestionService,
+    embedding: &Embedding,
+    query: &QuestionSearch,
+    page: &Pagination,
+) -> anyhow::Result<Page<QuestionSearchHit>> {
+    if query.hybrid {
+        match embedding.text_embedding(query.content.trim()).await {
+            Ok(vector) => return qs.hybrid_search_question(query, vector, page).await,
+            Err(e) => tracing::warn!("embedding query({}) failed: {e:?}", query.content),
+        }
+    }
+    qs.search_question(query, page).await
 }
 
 #[get("/question/search/image")]
@@ -51,12 +97,19 @@ async fn search_question_by_img(
     Extension(global): Extension<GlobalVariables>,
     Query(mut query): Query<QuestionSearch>,
     Component(qs): Component<QuestionService>,
+    Component(embedding): Component<Embedding>,
+    page: Pagination,
 ) -> Result<impl IntoResponse> {
-    let questions = if query.content.is_empty() {
+    let questions = if query.content.trim().is_empty() {
         vec![]
     } else {
         query.exam_id = Some(EXAM_ID.get());
-        qs.search_question(&query).await?
+        search_hits(&qs, &embedding, &query, &page)
+            .await?
+            .content
+            .into_iter()
+            .map(|hit| hit.question)
+            .collect()
     };
     Ok(QuestionSearchImgTemplate {
         global,
@@ -65,6 +118,61 @@ async fn search_question_by_img(
     })
 }
 
+/// 拍照搜题：识别上传图片中的文字，清理后按文字搜索
+#[middlewares(DefaultBodyLimit::max(MAX_IMAGE_SIZE))]
+#[post("/question/search/image")]
+async fn search_question_by_upload(
+    Extension(global): Extension<GlobalVariables>,
+    Query(mut query): Query<QuestionSearch>,
+    Component(qs): Component<QuestionService>,
+    Component(embedding): Component<Embedding>,
+    Component(ocr): Component<Ocr>,
+    mut multipart: Multipart,
+) -> Result<impl IntoResponse> {
+    let mut image = None;
+    while let Some(field) = multipart
+        .next_field()
+        .await
+        .map_err(|_| KnownWebError::bad_request(error_messages::SEARCH_IMAGE_INVALID))?
+    {
+        if field.name() == Some("image") {
+            let data = field
+                .bytes()
+                .await
+                .map_err(|_| KnownWebError::bad_request(error_messages::SEARCH_IMAGE_INVALID))?;
+            image = Some(data);
+            break;
+        }
+    }
+    let image =
+        image.ok_or_else(|| KnownWebError::bad_request(error_messages::SEARCH_IMAGE_REQUIRED))?;
+
+    let text = ocr.recognize(&image).await?;
+    query.content = clean_ocr_text(&text);
+    if query.content.chars().count() < 2 {
+        return Err(KnownWebError::bad_request(error_messages::SEARCH_IMAGE_NO_TEXT).into());
+    }
+    query.exam_id = Some(EXAM_ID.get());
+    let page = Pagination {
+        page: 0,
+        size: IMAGE_SEARCH_SIZE,
+    };
+    let questions = search_hits(&qs, &embedding, &query, &page)
+        .await?
+        .content
+        .into_iter()
+        .map(|hit| hit.question)
+        .collect();
+    let content = query.content.clone();
+    let t = QuestionSearchImgResultTemplate {
+        global,
+        questions,
+        query,
+    };
+    let html = t.render().context("render failed")?;
+    Ok(Json(json!({ "content": content, "html": html })))
+}
+
 #[get("/question/section")]
 async fn question_section(
     mut query: axum_extra::extract::Query<PaperQuestionQuery>,
@@ -81,6 +189,7 @@ async fn question_section(
             label_tree: LabelTree::none(),
             query: query.0,
             kp_paths: vec![],
+            collection: None,
         });
     }
     query
@@ -104,9 +213,50 @@ async fn question_section(
         label_tree,
         query: query.0,
         kp_paths,
+        collection: None,
     })
 }
 
+/// 把专项练习筛选出的题目导出为讲义
+#[get("/question/section/export")]
+async fn export_question_section(
+    _claims: Claims,
+    mut query: axum_extra::extract::Query<PaperQuestionQuery>,
+    Query(options): Query<ExportOptions>,
+    Component(qs): Component<QuestionService>,
+    Component(ks): Component<KeyPointService>,
+    Component(es): Component<ExportService>,
+    Extension(global): Extension<GlobalVariables>,
+) -> Result<impl IntoResponse> {
+    if query.paper_ids.is_empty() {
+        return Err(KnownWebError::bad_request(error_messages::EXPORT_PAPER_REQUIRED).into());
+    }
+    query
+        .validate()
+        .map_err(|e| KnownWebError::bad_request(e.to_string()))?;
+    if query.paper_type == 0 {
+        let paper_type = global.get_paper_type_by_prefix("xingce").ok_or_else(|| {
+            KnownWebError::bad_request(error_messages::QUESTION_PAPER_TYPE_REQUIRED)
+        })?;
+        query.paper_type = paper_type.id;
+    }
+    query.section_type = match options.solution {
+        SolutionPlacement::Omit => SectionType::Question,
+        _ => SectionType::Together,
+    };
+    let kp_paths = ks
+        .find_key_point_by_path(query.paper_type, &query.keypoint_path)
+        .await?;
+    let title = kp_paths
+        .last()
+        .and_then(|p| p.kps.iter().find(|kp| kp.id == p.selected))
+        .map(|kp| format!("{}专项练习", kp.name))
+        .unwrap_or_else(|| "专项练习".to_string());
+    let (questions, _papers) = qs.search_question_by_section(&query).await?;
+    let file = es.export_questions(&title, &questions, &options).await?;
+    Ok(attachment(file))
+}
+
 #[get("/question/recommend/{id}")]
 async fn question_recommend(
     Path(id): Path<i32>,
@@ -122,6 +272,7 @@ async fn question_detail(
     Path(id): Path<i32>,
     Query(q): Query<DetailQuery>,
     Component(qs): Component<QuestionService>,
+    Component(ns): Component<UserNoteService>,
     Extension(global): Extension<GlobalVariables>,
 ) -> Result<impl IntoResponse> {
     if q.only_comment {
@@ -133,11 +284,103 @@ async fn question_detail(
             .await?
             .ok_or_else(|| KnownWebError::not_found(error_messages::QUESTION_NOT_FOUND))?;
         let recommends = qs.recommend_question(id).await?;
+        let correct_ratio = qs.find_correct_ratio(id).await?;
+        let mids = question.materials.iter().flatten().map(|m| m.id).collect();
+        let notes = find_user_notes(&ns, global.user.as_ref().map(|u| u.id), vec![id], mids)
+            .await?
+            .unwrap_or_default();
         let t = QuestionDetailTemplate {
             global,
             question,
             recommends,
+            correct_ratio,
+            feedback_categories: FeedbackCategory::iter().collect(),
+            notes,
         };
         Ok(Html(t.render().context("render failed")?))
     }
 }
+
+/// 提交纠错，提交后跳转到我的纠错查看处理进度
+#[post("/question/feedback")]
+async fn question_feedback(
+    claims: Claims,
+    Component(fs): Component<QuestionFeedbackService>,
+    Form(form): Form<FeedbackSubmit>,
+) -> Result<impl IntoResponse> {
+    form.validate().map_err(KnownWebError::bad_request)?;
+    fs.submit(claims.user_id, form)
+        .await?
+        .ok_or_else(|| KnownWebError::not_found(error_messages::QUESTION_NOT_FOUND))?;
+    Ok(Redirect::to("/user/feedback"))
+}
+
+#[get("/question/adaptive")]
+async fn adaptive_practice(
+    claims: Claims,
+    Query(query): Query<AdaptiveQuery>,
+    Component(qs): Component<QuestionService>,
+    Component(ks): Component<KeyPointService>,
+    Extension(global): Extension<GlobalVariables>,
+) -> Result<impl IntoResponse> {
+    let paper_type = global
+        .get_paper_type_by_prefix(&query.paper_type_prefix)
+        .ok_or_else(|| KnownWebError::bad_request(error_messages::PAPER_TYPE_NOT_FOUND))?;
+    let keypoints = ks
+        .find_key_point_by_pid_with_cache(paper_type.id, 0)
+        .await?;
+    let question = qs
+        .next_adaptive_question(
+            claims.user_id,
+            paper_type.id,
+            query.keypoint_path().as_deref(),
+            query.target(),
+        )
+        .await?;
+    Ok(AdaptivePracticeTemplate {
+        global,
+        query,
+        keypoints,
+        targets: ADAPTIVE_TARGETS.to_vec(),
+        question,
+        user_answer: None,
+        user_time: None,
+        correct: None,
+    })
+}
+
+#[post("/question/adaptive")]
+async fn adaptive_practice_answer(
+    claims: Claims,
+    Query(query): Query<AdaptiveQuery>,
+    Component(qs): Component<QuestionService>,
+    Component(ks): Component<KeyPointService>,
+    Extension(global): Extension<GlobalVariables>,
+    Form(params): Form<Vec<(String, String)>>,
+) -> Result<impl IntoResponse> {
+    let paper_type = global
+        .get_paper_type_by_prefix(&query.paper_type_prefix)
+        .ok_or_else(|| KnownWebError::bad_request(error_messages::PAPER_TYPE_NOT_FOUND))?;
+    let (user_answer, user_time) = parse_exercise_form(params);
+    let (qid, answer) = user_answer
+        .into_iter()
+        .next()
+        .ok_or_else(|| KnownWebError::bad_request(error_messages::ADAPTIVE_ANSWER_REQUIRED))?;
+    let (question, correct) = qs
+        .answer_adaptive(claims.user_id, paper_type.id, qid, answer.clone())
+        .await?
+        .ok_or_else(|| KnownWebError::not_found(error_messages::QUESTION_NOT_FOUND))?;
+    let keypoints = ks
+        .find_key_point_by_pid_with_cache(paper_type.id, 0)
+        .await?;
+    Ok(AdaptivePracticeTemplate {
+        global,
+        query,
+        keypoints,
+        targets: ADAPTIVE_TARGETS.to_vec(),
+        question: Some(question),
+        user_answer: Some(HashMap::from([(qid, answer)])),
+        user_time: Some(user_time),
+        correct: Some(correct),
+    })
+}
@@ -1,9 +1,17 @@
 use crate::{
     plugins::AuthConfig,
-    router::{decode, error_messages, jwt, Claims},
+    router::{
+        decode, error_messages, jwt,
+        paper::{attachment, parse_exercise_form},
+        Claims,
+    },
     service::user::UserService,
     views::{
-        user::{ArtalkUser, UserLoginRefreshTemplate, UserProfileTemplate},
+        user::{
+            ArtalkUser, ReviewTemplate, UserFeedbackTemplate, UserLoginRefreshTemplate,
+            UserNotesTemplate, UserProfileTemplate, WrongQuestionRedoTemplate,
+            WrongQuestionTemplate,
+        },
         GlobalVariables,
     },
 };
@@ -15,22 +23,41 @@ use axum_extra::extract::{
 };
 use chrono::Utc;
 use cookie::time::Duration;
+use dtiku_paper::{
+    domain::{
+        label::LabelTree,
+        user_note::{NoteSave, MAX_NOTE_LENGTH},
+    },
+    query::wrong_question::WrongQuestionQuery,
+    service::{
+        label::LabelService,
+        question_feedback::QuestionFeedbackService,
+        user_note::UserNoteService,
+        wrong_question::{WrongQuestionService, REMOVE_AFTER_REDO_CORRECT},
+    },
+};
+use dtiku_stats::{
+    domain::review::ReviewGrade, model::sea_orm_active_enums::ReviewItemType,
+    service::review::ReviewService,
+};
 use serde::{Deserialize, Serialize};
 use sha1::Digest;
 use spring::tracing;
 use spring_redis::redis::AsyncCommands;
 use spring_redis::Redis;
+use spring_sea_orm::pagination::Pagination;
 use spring_web::{
     axum::{
         body::Bytes,
         http::HeaderMap,
         response::{Html, IntoResponse, Json, Redirect},
-        Extension,
+        Extension, Form,
     },
     error::{KnownWebError, Result},
-    extractor::{Component, Path, RawQuery},
+    extractor::{Component, Path, Query, RawQuery},
     get, post,
 };
+use strum::IntoEnumIterator;
 use uuid::Uuid;
 
 #[get("/api/v2/auth/{provider}/callback")]
@@ -84,13 +111,236 @@ async fn user_comment_avatar(
 async fn user_profile(
     claims: Claims,
     Component(us): Component<UserService>,
+    Component(fs): Component<QuestionFeedbackService>,
     Extension(global): Extension<GlobalVariables>,
 ) -> Result<impl IntoResponse> {
     let user = us.get_user_detail(claims.user_id).await?;
-    let template = UserProfileTemplate { global, user };
+    let unread_feedback = fs.count_unread(claims.user_id).await?;
+    let template = UserProfileTemplate {
+        global,
+        user,
+        unread_feedback,
+    };
     Ok(Html(template.render().context("render failed")?))
 }
 
+/// 我的纠错，查看后处理结果标记为已读
+/// GET /user/feedback
+#[get("/user/feedback")]
+async fn user_feedback(
+    claims: Claims,
+    Component(fs): Component<QuestionFeedbackService>,
+    Extension(global): Extension<GlobalVariables>,
+    pagination: Pagination,
+) -> Result<impl IntoResponse> {
+    let page = fs.find_user_page(claims.user_id, &pagination).await?;
+    Ok(UserFeedbackTemplate { global, page })
+}
+
+#[derive(Debug, Deserialize)]
+struct NoteSearchQuery {
+    q: Option<String>,
+}
+
+/// 我的笔记，可按笔记内容或高亮文字搜索
+/// GET /user/notes
+#[get("/user/notes")]
+async fn user_notes(
+    claims: Claims,
+    Query(query): Query<NoteSearchQuery>,
+    Component(ns): Component<UserNoteService>,
+    Extension(global): Extension<GlobalVariables>,
+    pagination: Pagination,
+) -> Result<impl IntoResponse> {
+    let keyword = query
+        .q
+        .map(|q| q.trim().to_string())
+        .filter(|q| !q.is_empty());
+    let page = ns
+        .search(claims.user_id, keyword.as_deref(), &pagination)
+        .await?;
+    Ok(UserNotesTemplate {
+        global,
+        keyword,
+        page,
+    })
+}
+
+/// 导出所有笔记为Markdown
+/// GET /user/notes/export
+#[get("/user/notes/export")]
+async fn user_notes_export(
+    claims: Claims,
+    Component(ns): Component<UserNoteService>,
+    Extension(global): Extension<GlobalVariables>,
+) -> Result<impl IntoResponse> {
+    let site_url = format!("https://{}", global.original_host);
+    let file = ns.export_markdown(claims.user_id, &site_url).await?;
+    Ok(attachment(file))
+}
+
+/// 给题目记笔记或高亮材料中的文字
+/// POST /api/user/notes
+#[post("/api/user/notes")]
+async fn create_user_note(
+    claims: Claims,
+    Component(ns): Component<UserNoteService>,
+    Json(req): Json<NoteSave>,
+) -> Result<impl IntoResponse> {
+    req.validate().map_err(KnownWebError::bad_request)?;
+    let note = ns
+        .create(claims.user_id, req)
+        .await?
+        .ok_or_else(|| KnownWebError::not_found(error_messages::NOTE_TARGET_NOT_FOUND))?;
+    Ok(Json(note))
+}
+
+#[derive(Debug, Deserialize)]
+struct NoteUpdateReq {
+    markdown: String,
+}
+
+/// 修改笔记内容
+/// POST /api/user/notes/{id}
+#[post("/api/user/notes/{id}")]
+async fn update_user_note(
+    claims: Claims,
+    Path(id): Path<i32>,
+    Component(ns): Component<UserNoteService>,
+    Json(req): Json<NoteUpdateReq>,
+) -> Result<impl IntoResponse> {
+    if req.markdown.chars().count() > MAX_NOTE_LENGTH {
+        return Err(KnownWebError::bad_request(error_messages::NOTE_TOO_LONG).into());
+    }
+    let note = ns
+        .update(claims.user_id, id, req.markdown)
+        .await?
+        .ok_or_else(|| KnownWebError::not_found(error_messages::NOTE_NOT_FOUND))?;
+    Ok(Json(note))
+}
+
+/// 删除笔记
+/// POST /api/user/notes/{id}/delete
+#[post("/api/user/notes/{id}/delete")]
+async fn delete_user_note(
+    claims: Claims,
+    Path(id): Path<i32>,
+    Component(ns): Component<UserNoteService>,
+) -> Result<impl IntoResponse> {
+    if !ns.delete(claims.user_id, id).await? {
+        return Err(KnownWebError::not_found(error_messages::NOTE_NOT_FOUND).into());
+    }
+    Ok(Json("success"))
+}
+
+/// 错题本
+/// GET /user/wrong-questions
+#[get("/user/wrong-questions")]
+async fn user_wrong_questions(
+    claims: Claims,
+    Component(ws): Component<WrongQuestionService>,
+    Extension(global): Extension<GlobalVariables>,
+) -> Result<impl IntoResponse> {
+    let notebook = ws.notebook(claims.user_id).await?;
+    Ok(WrongQuestionTemplate {
+        global,
+        notebook,
+        remove_after: REMOVE_AFTER_REDO_CORRECT,
+    })
+}
+
+/// 重做错题，可按一级知识点(kp)或试卷(pid)筛选
+/// GET /user/wrong-questions/redo
+#[get("/user/wrong-questions/redo")]
+async fn user_wrong_questions_redo(
+    claims: Claims,
+    Query(query): Query<WrongQuestionQuery>,
+    Component(ws): Component<WrongQuestionService>,
+    Component(ls): Component<LabelService>,
+    Extension(global): Extension<GlobalVariables>,
+) -> Result<impl IntoResponse> {
+    let (questions, _) = ws.find_redo_questions(claims.user_id, &query).await?;
+    let label_tree = xingce_label_tree(&global, &ls).await?;
+    Ok(WrongQuestionRedoTemplate {
+        global,
+        questions,
+        label_tree,
+        user_answer: None,
+        user_time: None,
+        result: None,
+    })
+}
+
+/// 提交错题重做
+/// POST /user/wrong-questions/redo
+#[post("/user/wrong-questions/redo")]
+async fn user_wrong_questions_redo_submit(
+    claims: Claims,
+    Component(ws): Component<WrongQuestionService>,
+    Component(ls): Component<LabelService>,
+    Extension(global): Extension<GlobalVariables>,
+    Form(params): Form<Vec<(String, String)>>,
+) -> Result<impl IntoResponse> {
+    let (user_answer, user_time) = parse_exercise_form(params);
+    let (questions, _, result) = ws.redo(claims.user_id, &user_answer).await?;
+    let label_tree = xingce_label_tree(&global, &ls).await?;
+    Ok(WrongQuestionRedoTemplate {
+        global,
+        questions,
+        label_tree,
+        user_answer: Some(user_answer),
+        user_time: Some(user_time),
+        result: Some(result),
+    })
+}
+
+/// 每日复习：到期的错题和成语
+/// GET /user/review
+#[get("/user/review")]
+async fn user_review(
+    claims: Claims,
+    Component(rs): Component<ReviewService>,
+    Extension(global): Extension<GlobalVariables>,
+) -> Result<impl IntoResponse> {
+    let items = rs.daily_queue(claims.user_id).await?;
+    Ok(ReviewTemplate {
+        global,
+        items,
+        grades: ReviewGrade::iter().collect(),
+    })
+}
+
+#[derive(Debug, Deserialize)]
+struct ReviewGradeForm {
+    item_type: ReviewItemType,
+    item_id: i32,
+    grade: ReviewGrade,
+}
+
+/// 提交复习评分
+/// POST /user/review/grade
+#[post("/user/review/grade")]
+async fn user_review_grade(
+    claims: Claims,
+    Component(rs): Component<ReviewService>,
+    Form(form): Form<ReviewGradeForm>,
+) -> Result<impl IntoResponse> {
+    rs.grade(claims.user_id, form.item_type, form.item_id, form.grade)
+        .await?
+        .ok_or_else(|| KnownWebError::not_found(error_messages::REVIEW_ITEM_NOT_FOUND))?;
+    Ok(Redirect::to("/user/review"))
+}
+
+pub(super) async fn xingce_label_tree(
+    global: &GlobalVariables,
+    ls: &LabelService,
+) -> Result<LabelTree> {
+    Ok(match global.get_paper_type_by_prefix("xingce") {
+        Some(paper_type) => ls.find_all_label_by_paper_type(paper_type.id).await?,
+        None => LabelTree::none(),
+    })
+}
+
 #[derive(Debug, Deserialize)]
 struct UpdateProfileRequest {
     name: Option<String>,
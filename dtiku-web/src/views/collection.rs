@@ -0,0 +1,64 @@
+use super::GlobalVariables;
+use askama::Template;
+use askama_web::WebTemplate;
+use dtiku_stats::domain::collection::{CollectionDetail, CollectionSummary};
+
+#[derive(Template, WebTemplate)]
+#[template(path = "user/collections.html.min.jinja")]
+pub struct CollectionListTemplate {
+    pub global: GlobalVariables,
+    pub collections: Vec<CollectionSummary>,
+}
+
+#[derive(Template, WebTemplate)]
+#[template(path = "user/collection.html.min.jinja")]
+pub struct CollectionDetailTemplate {
+    pub global: GlobalVariables,
+    pub detail: CollectionDetail,
+    /// 当前筛选的标签
+    pub tag: Option<String>,
+    /// 通过分享链接访问时的key
+    pub key: Option<String>,
+    /// 收藏夹的主人才能编辑
+    pub owner: bool,
+}
+
+impl CollectionDetailTemplate {
+    /// 保留分享key和标签筛选
+    fn url(&self, path: &str, tag: Option<&str>) -> String {
+        let params = self
+            .key
+            .as_deref()
+            .map(|k| ("key", k))
+            .into_iter()
+            .chain(tag.map(|t| ("tag", t)))
+            .collect::<Vec<_>>();
+        let id = self.detail.collection.id;
+        if params.is_empty() {
+            format!("/collection/{id}{path}")
+        } else {
+            let qs = serde_urlencoded::to_string(params).unwrap_or_default();
+            format!("/collection/{id}{path}?{qs}")
+        }
+    }
+
+    fn tag_url(&self, tag: &str) -> String {
+        self.url("", Some(tag))
+    }
+
+    fn all_url(&self) -> String {
+        self.url("", None)
+    }
+
+    fn practice_url(&self) -> String {
+        self.url("/practice", self.tag.as_deref())
+    }
+
+    fn share_url(&self) -> Option<String> {
+        let key = self.detail.collection.share_key.as_deref()?;
+        Some(format!(
+            "https://{}/collection/{}?key={key}",
+            self.global.original_host, self.detail.collection.id
+        ))
+    }
+}
@@ -1,18 +1,21 @@
 use askama::{Result, Values};
 use chinese_number::{ChineseCase, ChineseCountMethod, ChineseVariant, NumberToChinese as _};
 use chrono::NaiveDateTime;
-use pulldown_cmark::{html, Options, Parser};
+use pulldown_cmark::{html, CowStr, Event, Options, Parser, Tag};
 use scraper::Html;
 
-/// Markdown 转 HTML 的 Askama 过滤器
-pub fn markdown(s: &str, _values: &dyn Values) -> Result<String> {
+fn markdown_options() -> Options {
     let mut options = Options::empty();
     options.insert(Options::ENABLE_TABLES);
     options.insert(Options::ENABLE_FOOTNOTES);
     options.insert(Options::ENABLE_STRIKETHROUGH);
     options.insert(Options::ENABLE_TASKLISTS);
+    options
+}
 
-    let parser = Parser::new_ext(s, options);
+/// Markdown 转 HTML 的 Askama 过滤器
+pub fn markdown(s: &str, _values: &dyn Values) -> Result<String> {
+    let parser = Parser::new_ext(s, markdown_options());
 
     let mut html_output = String::new();
     html::push_html(&mut html_output, parser);
@@ -20,6 +23,64 @@ pub fn markdown(s: &str, _values: &dyn Values) -> Result<String> {
     Ok(html_output)
 }
 
+/// 用户输入的 Markdown 转 HTML，防止存储型XSS：
+/// 原始HTML按文本转义，链接和图片只允许http、https、mailto和站内地址
+pub fn user_markdown(s: &str, _values: &dyn Values) -> Result<String> {
+    let parser = Parser::new_ext(s, markdown_options()).map(|event| match event {
+        Event::Html(html) | Event::InlineHtml(html) => Event::Text(html),
+        Event::Start(Tag::Link {
+            link_type,
+            dest_url,
+            title,
+            id,
+        }) => Event::Start(Tag::Link {
+            link_type,
+            dest_url: safe_url(dest_url),
+            title,
+            id,
+        }),
+        Event::Start(Tag::Image {
+            link_type,
+            dest_url,
+            title,
+            id,
+        }) => Event::Start(Tag::Image {
+            link_type,
+            dest_url: safe_url(dest_url),
+            title,
+            id,
+        }),
+        event => event,
+    });
+
+    let mut html_output = String::new();
+    html::push_html(&mut html_output, parser);
+
+    Ok(html_output)
+}
+
+/// 带协议的地址只保留http、https和mailto，其他的如javascript:替换为#
+fn safe_url(url: CowStr) -> CowStr {
+    // 浏览器解析协议时会忽略空白和控制字符，如"java\tscript:"
+    let normalized: String = url
+        .chars()
+        .filter(|c| !c.is_whitespace() && !c.is_control())
+        .collect::<String>()
+        .to_ascii_lowercase();
+    let scheme_end = normalized.find(':');
+    let path_start = normalized.find(['/', '?', '#']);
+    let safe = match (scheme_end, path_start) {
+        (None, _) => true,
+        (Some(colon), Some(path)) if path < colon => true,
+        (Some(colon), _) => matches!(&normalized[..colon], "http" | "https" | "mailto"),
+    };
+    if safe {
+        url
+    } else {
+        CowStr::Borrowed("#")
+    }
+}
+
 pub fn hms(seconds: &u64, _values: &dyn Values) -> Result<String> {
     let h = seconds / 3600;
     let m = (seconds % 3600) / 60;
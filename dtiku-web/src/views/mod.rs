@@ -4,12 +4,15 @@ use askama::Template;
 use axum_extra::extract::CookieJar;
 use chrono::Datelike;
 use dtiku_base::{model::user_info, service};
-use dtiku_paper::domain::{exam_category::ExamPaperType, question::FullQuestion};
+use dtiku_paper::domain::{
+    exam_category::ExamPaperType, exercise::ExerciseOwner, question::FullQuestion,
+};
 use paper::PaperType;
 use spring_sea_orm::pagination::Page;
 use spring_web::axum::http::{StatusCode, Uri};
 
 pub mod bbs;
+pub mod collection;
 pub mod filters;
 pub mod home;
 pub mod idiom;
@@ -73,6 +76,19 @@ impl GlobalVariables {
         self.user.as_ref().map(|u| u.id)
     }
 
+    /// 练习记录归属：登录用户优先，否则使用匿名访客的浏览器指纹
+    pub fn exercise_owner(&self) -> Option<ExerciseOwner> {
+        match &self.user {
+            Some(u) => Some(ExerciseOwner::User(u.id)),
+            None => self
+                .cookies
+                .get("x-fp")
+                .map(|fp| fp.value())
+                .filter(|fp| !fp.is_empty())
+                .map(|fp| ExerciseOwner::Fingerprint(fp.chars().take(64).collect())),
+        }
+    }
+
     pub fn user_is_expired(&self) -> bool {
         self.user
             .as_ref()
@@ -106,10 +122,9 @@ impl GlobalVariables {
             None => return false,
         };
 
-        if let (Some(db), Some(user)) = (q.get_raw_answer(), user_answer.get(&q.id)) {
-            return db.eq_ignore_ascii_case(user);
-        }
-        return false;
+        user_answer
+            .get(&q.id)
+            .is_some_and(|user| q.check_answer(user))
     }
 
     pub fn match_answer_option(
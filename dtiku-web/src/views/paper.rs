@@ -11,8 +11,9 @@ use dtiku_paper::{
     domain::{
         label::{LabelNode, LabelTree},
         paper::FullPaper,
+        user_note::UserNotes,
     },
-    model::{self, material, paper, solution, FromType},
+    model::{self, essay_grade, exercise_record, material, paper, solution, FromType},
     query::paper::ListPaperQuery,
 };
 use itertools::Itertools;
@@ -87,6 +88,21 @@ pub struct ChapterPaperTemplate {
     pub report: Option<Vec<ChapterReport>>,
     pub user_answer: Option<HashMap<i32, String>>,
     pub user_time: Option<HashMap<i32, u64>>,
+    pub draft_answer: Option<HashMap<i32, String>>,
+    /// 限时模考的剩余秒数
+    pub remaining_secs: Option<i64>,
+    /// 限时模考超时后由服务端自动交卷
+    pub timed_out: bool,
+    /// 登录用户的笔记，只在浏览试卷时展示
+    pub notes: Option<UserNotes>,
+}
+
+#[derive(Template, WebTemplate)]
+#[template(path = "paper-history.html.min.jinja")]
+pub struct PaperHistoryTemplate {
+    pub global: GlobalVariables,
+    pub paper: model::paper::Model,
+    pub records: Vec<exercise_record::Model>,
 }
 
 #[derive(Template, WebTemplate)]
@@ -97,6 +113,22 @@ pub struct ClusterPaperTemplate {
     pub mode: String,
     pub materials: Vec<material::Material>,
     pub questions: Vec<FullQuestion>,
+    pub user_answer: Option<HashMap<i32, Vec<String>>>,
+    pub grades: Option<HashMap<i32, essay_grade::Model>>,
+    /// 登录用户的笔记，只在浏览试卷时展示
+    pub notes: Option<UserNotes>,
+}
+
+impl ClusterPaperTemplate {
+    /// 某道申论题第index问的作答
+    fn block_answer(&self, qid: &i32, index: &usize) -> &str {
+        self.user_answer
+            .as_ref()
+            .and_then(|ua| ua.get(qid))
+            .and_then(|answers| answers.get(*index))
+            .map(|a| a.as_str())
+            .unwrap_or_default()
+    }
 }
 
 impl IntoTemplate<ChapterPaperTemplate> for FullPaper {
@@ -134,6 +166,10 @@ impl IntoTemplate<ChapterPaperTemplate> for FullPaper {
             report: Default::default(),
             user_answer: Default::default(),
             user_time: Default::default(),
+            draft_answer: Default::default(),
+            remaining_secs: Default::default(),
+            timed_out: Default::default(),
+            notes: Default::default(),
         }
     }
 }
@@ -163,6 +199,9 @@ impl IntoTemplate<ClusterPaperTemplate> for FullPaper {
             paper: self.p,
             materials: self.ms,
             questions,
+            user_answer: None,
+            grades: None,
+            notes: None,
         }
     }
 }
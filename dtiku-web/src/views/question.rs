@@ -1,23 +1,66 @@
 use super::filters;
 use super::GlobalVariables;
+use super::PageExt;
+use crate::query::question::AdaptiveQuery;
 use askama::Template;
 use askama_web::WebTemplate;
 use dtiku_paper::{
-    domain::{keypoint::KeyPointPath, label::LabelTree, question::QuestionSearch},
+    domain::{
+        keypoint::KeyPointPath,
+        label::LabelTree,
+        question::{QuestionCorrectRatio, QuestionSearch, QuestionSearchHit},
+        search::QUESTION_TYPES,
+        user_note::UserNotes,
+    },
     model::{
-        self,
+        self, key_point,
         question::{QuestionExtra, QuestionSinglePaper, QuestionWithPaper},
+        FeedbackCategory,
     },
     query::question::{PaperQuestionQuery, SectionType},
 };
+use dtiku_stats::model::collection;
+use spring_sea_orm::pagination::Page;
+use std::collections::HashMap;
 use strum::IntoEnumIterator;
 
+/// 搜索可筛选的最早年份
+const MIN_SEARCH_YEAR: i16 = 2000;
+
 #[derive(Template, WebTemplate)]
 #[template(path = "question/search.html.min.jinja")]
 pub struct QuestionSearchTemplate {
     pub global: GlobalVariables,
-    pub questions: Vec<QuestionWithPaper>,
+    pub page: Page<QuestionSearchHit>,
     pub query: QuestionSearch,
+    pub question_types: [(&'static str, &'static str); 8],
+}
+
+impl QuestionSearchTemplate {
+    pub fn new(
+        global: GlobalVariables,
+        page: Page<QuestionSearchHit>,
+        query: QuestionSearch,
+    ) -> Self {
+        Self {
+            global,
+            page,
+            query,
+            question_types: QUESTION_TYPES,
+        }
+    }
+
+    /// 翻页时保留搜索条件
+    fn search_url(&self) -> String {
+        format!(
+            "/question/search?{}",
+            serde_html_form::to_string(&self.query).unwrap_or_default()
+        )
+    }
+
+    fn years(&self) -> Vec<i16> {
+        (MIN_SEARCH_YEAR..=self.global.now_year()).rev().collect()
+    }
 }
 
 #[derive(Template, WebTemplate)]
@@ -28,6 +71,25 @@ pub struct QuestionSearchImgTemplate {
     pub query: QuestionSearch,
 }
 
+/// 拍照搜题的结果片段，由上传接口渲染后返回给页面
+#[derive(Template)]
+#[template(path = "question/search-img-result.html.min.jinja")]
+pub struct QuestionSearchImgResultTemplate {
+    pub global: GlobalVariables,
+    pub questions: Vec<QuestionWithPaper>,
+    pub query: QuestionSearch,
+}
+
+impl QuestionSearchImgResultTemplate {
+    /// 识别结果可能有误，跳转到文字搜索方便修改
+    fn search_url(&self) -> String {
+        format!(
+            "/question/search?{}",
+            serde_html_form::to_string(&self.query).unwrap_or_default()
+        )
+    }
+}
+
 #[derive(Template, WebTemplate)]
 #[template(path = "question/section.html.min.jinja")]
 pub struct QuestionSectionTemplate {
@@ -37,6 +99,21 @@ pub struct QuestionSectionTemplate {
     pub label_tree: LabelTree,
     pub query: PaperQuestionQuery,
     pub kp_paths: Vec<KeyPointPath>,
+    /// 练习收藏夹中的题目时不展示筛选条件
+    pub collection: Option<collection::Model>,
+}
+
+#[derive(Template, WebTemplate)]
+#[template(path = "question/adaptive.html.min.jinja")]
+pub struct AdaptivePracticeTemplate {
+    pub global: GlobalVariables,
+    pub query: AdaptiveQuery,
+    pub keypoints: Vec<key_point::Model>,
+    pub targets: Vec<(f32, &'static str)>,
+    pub question: Option<QuestionSinglePaper>,
+    pub user_answer: Option<HashMap<i32, String>>,
+    pub user_time: Option<HashMap<i32, u64>>,
+    pub correct: Option<bool>,
 }
 
 #[derive(Template, WebTemplate)]
@@ -45,6 +122,9 @@ pub struct QuestionDetailTemplate {
     pub global: GlobalVariables,
     pub question: QuestionWithPaper,
     pub recommends: Vec<QuestionWithPaper>,
+    pub correct_ratio: Option<QuestionCorrectRatio>,
+    pub feedback_categories: Vec<FeedbackCategory>,
+    pub notes: UserNotes,
 }
 
 #[derive(Template, WebTemplate)]
@@ -1,8 +1,24 @@
+use super::filters;
 use super::GlobalVariables;
+use super::PageExt;
 use askama::Template;
 use askama_web::WebTemplate;
 use dtiku_base::model::user_info;
+use dtiku_paper::{
+    domain::{
+        label::LabelTree,
+        user_note::UserNoteView,
+        wrong_question::{WrongQuestionNotebook, WrongQuestionRedo},
+    },
+    model::{
+        question::{QuestionExtra, QuestionSinglePaper},
+        question_feedback, FeedbackStatus,
+    },
+};
+use dtiku_stats::domain::review::{ReviewContent, ReviewGrade, ReviewItem};
 use serde::Serialize;
+use spring_sea_orm::pagination::Page;
+use std::collections::HashMap;
 
 #[derive(Debug, Clone, Serialize)]
 pub struct ArtalkUser {
@@ -24,4 +40,61 @@ pub struct UserLoginRefreshTemplate {
 pub struct UserProfileTemplate {
     pub global: GlobalVariables,
     pub user: user_info::Model,
+    /// 已处理但还没查看的纠错数
+    pub unread_feedback: u64,
+}
+
+#[derive(Template, WebTemplate)]
+#[template(path = "user/wrong-questions.html.min.jinja")]
+pub struct WrongQuestionTemplate {
+    pub global: GlobalVariables,
+    pub notebook: WrongQuestionNotebook,
+    pub remove_after: i16,
+}
+
+#[derive(Template, WebTemplate)]
+#[template(path = "user/wrong-question-redo.html.min.jinja")]
+pub struct WrongQuestionRedoTemplate {
+    pub global: GlobalVariables,
+    pub questions: Vec<QuestionSinglePaper>,
+    pub label_tree: LabelTree,
+    pub user_answer: Option<HashMap<i32, String>>,
+    pub user_time: Option<HashMap<i32, u64>>,
+    pub result: Option<WrongQuestionRedo>,
+}
+
+#[derive(Template, WebTemplate)]
+#[template(path = "user/review.html.min.jinja")]
+pub struct ReviewTemplate {
+    pub global: GlobalVariables,
+    pub items: Vec<ReviewItem>,
+    pub grades: Vec<ReviewGrade>,
+}
+
+#[derive(Template, WebTemplate)]
+#[template(path = "user/feedback.html.min.jinja")]
+pub struct UserFeedbackTemplate {
+    pub global: GlobalVariables,
+    pub page: Page<question_feedback::Model>,
+}
+
+#[derive(Template, WebTemplate)]
+#[template(path = "user/notes.html.min.jinja")]
+pub struct UserNotesTemplate {
+    pub global: GlobalVariables,
+    pub keyword: Option<String>,
+    pub page: Page<UserNoteView>,
+}
+
+impl UserNotesTemplate {
+    /// 翻页时保留搜索关键词
+    fn page_url(&self) -> String {
+        match &self.keyword {
+            Some(q) => format!(
+                "/user/notes?{}",
+                serde_urlencoded::to_string([("q", q)]).unwrap_or_default()
+            ),
+            None => "/user/notes".to_string(),
+        }
+    }
 }
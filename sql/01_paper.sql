@@ -1,8 +1,12 @@
 create extension if not exists vector;
 create extension if not exists ltree;
 create extension if not exists pg_trgm;
-create type from_type as enum ('fenbi', 'huatu', 'offcn', 'chinagwy', 'mock_exam');
+create type from_type as enum ('fenbi', 'huatu', 'offcn', 'chinagwy', 'mock_exam', 'upload');
 create type src_type as enum('question', 'material', 'solution');
+create type duplicate_status as enum('pending', 'merged', 'ignored');
+create type feedback_category as enum('wrong_answer', 'wrong_analysis', 'broken_image', 'garbled_option', 'wrong_content', 'other');
+create type feedback_status as enum('open', 'accepted', 'rejected');
+create type keypoint_tag_status as enum('auto', 'pending', 'accepted', 'rejected');
 -- 考试类型：root_id为exam_id; leaf_id为paper_type
 drop table if exists exam_category;
 create table if not exists exam_category(
@@ -24,7 +28,15 @@ create table if not exists label(
     hidden bool not null default false,
     unique(paper_type, pid, name)
 );
--- 试卷
+-- 考试计分规则：rules按题型覆盖默认计分方式，label_id为0时对该试卷类型下所有地区生效
+drop table if exists exam_scoring;
+create table if not exists exam_scoring(
+    paper_type int2 not null,
+    label_id integer not null default 0,
+    rules jsonb not null,
+    primary key(paper_type, label_id)
+);
+-- 试卷，time_limit为考试时长(分钟)，为空时按题量估算
 drop table if exists paper;
 create table if not exists paper(
     id serial primary key,
@@ -34,6 +46,7 @@ create table if not exists paper(
     paper_type int2 not null,
     label_id integer not null,
     extra jsonb not null,
+    time_limit int2 default null,
     unique(label_id, title)
 );
 create index if not exists idx_paper_title_trgm on paper using gin (title gin_trgm_ops);
@@ -47,6 +60,19 @@ create table if not exists key_point(
     paper_type int2 not null,
     unique(paper_type, pid, name)
 );
+-- 厂商知识点到规范知识点的映射：知识点合并后，厂商同步时按来源找到合并后的知识点
+-- parent_path为厂商知识点树中父知识点名称用/连接的路径，只提供知识点名称的查找为空字符串
+drop table if exists key_point_mapping;
+create table if not exists key_point_mapping(
+    from_ty from_type not null,
+    paper_type int2 not null,
+    parent_path varchar(255) not null,
+    name varchar(64) not null,
+    key_point_id integer not null,
+    created timestamp not null,
+    primary key (from_ty, paper_type, parent_path, name)
+);
+create index if not exists idx_key_point_mapping_kp on key_point_mapping (key_point_id);
 -- 问题
 drop table if exists question;
 create table if not exists question(
@@ -64,6 +90,8 @@ create table if not exists question_key_point(
     question_id integer not null,
     key_point_id integer not null,
     year int2 not null,
+    -- 申论分类任务写入的分类置信度，厂商同步的知识点为null
+    confidence float8,
     primary key (question_id, key_point_id)
 );
 create index concurrently if not exists idx_qkp_for_agg on question_key_point (key_point_id, year, question_id);
@@ -82,6 +110,10 @@ create table if not exists paper_question (
     paper_type int2 not null,
     keypoint_path ltree default null,
     correct_ratio float4 default null,
+    -- 本站用户的作答统计，blended_correct_ratio为与厂商正确率加权后的结果
+    user_correct_ratio float4 default null,
+    user_answer_count integer default null,
+    blended_correct_ratio float4 default null,
     primary key (paper_id, question_id)
 );
 -- 材料
@@ -93,6 +125,7 @@ create table if not exists material (
     extra jsonb not null
 );
 create index on material using hnsw (content_sim_hash bit_hamming_ops);
+drop materialized view if exists material_paper_stats;
 drop table if exists paper_material;
 create table if not exists paper_material (
     paper_id integer not null,
@@ -100,6 +133,14 @@ create table if not exists paper_material (
     sort smallint not null,
     primary key (paper_id, material_id)
 );
+-- 材料被多少份试卷共用，材料合并任务结束后刷新
+create materialized view if not exists material_paper_stats as
+select material_id,
+    count(distinct paper_id) as paper_count
+from paper_material
+group by material_id;
+create unique index if not exists idx_material_paper_stats on material_paper_stats (material_id);
+create index if not exists idx_material_paper_stats_count on material_paper_stats (paper_count, material_id);
 drop table if exists question_material;
 create table if not exists question_material (
     question_id integer not null,
@@ -146,3 +187,241 @@ create table if not exists assets_ref(
     assets_id integer not null,
     primary key(src_id, src_type, assets_id)
 );
+
+-- 练习记录：登录用户记录user_id，匿名用户记录浏览器指纹x-fp
+-- deadline为限时模考的交卷截止时间，普通练习为空
+drop table if exists exercise_record;
+create table if not exists exercise_record(
+    id serial primary key,
+    user_id integer default null,
+    fingerprint varchar(64) default null,
+    paper_id integer not null,
+    paper_type int2 not null,
+    finished bool not null default false,
+    correct int2 not null,
+    error int2 not null,
+    -- 客观题为各题得分率之和，申论为各题得分之和
+    score float4 not null default 0,
+    total_time integer not null,
+    deadline timestamp default null,
+    created timestamp not null,
+    modified timestamp not null
+);
+create index if not exists idx_exercise_record_user on exercise_record(user_id, paper_id);
+create index if not exists idx_exercise_record_fp on exercise_record(fingerprint, paper_id);
+drop table if exists exercise_answer;
+create table if not exists exercise_answer(
+    record_id integer not null,
+    question_id integer not null,
+    answer text not null,
+    correct bool not null,
+    time integer not null,
+    score float4 not null default 0,
+    primary key(record_id, question_id)
+);
+-- 申论批改结果，feedback为每一问的要点覆盖和字数意见
+drop table if exists essay_grade;
+create table if not exists essay_grade(
+    record_id integer not null,
+    question_id integer not null,
+    score float4 not null,
+    feedback jsonb not null,
+    primary key(record_id, question_id)
+);
+-- 错题本：连续答对redo_correct次后移出
+drop table if exists wrong_question;
+create table if not exists wrong_question(
+    user_id integer not null,
+    question_id integer not null,
+    paper_id integer not null,
+    wrong_count int2 not null,
+    redo_correct int2 not null default 0,
+    last_answer text not null,
+    created timestamp not null,
+    modified timestamp not null,
+    primary key(user_id, question_id)
+);
+-- 模拟卷：试卷本身存在paper表中的隐藏标签下，user_id为空表示管理员生成的公共模拟卷
+drop table if exists mock_paper;
+create table if not exists mock_paper(
+    paper_id integer primary key,
+    user_id integer default null,
+    -- 参考真题所在的地区标签
+    label_id integer not null,
+    created timestamp not null
+);
+create index if not exists idx_mock_paper_user on mock_paper(user_id, created);
+-- 题目的IRT参数：difficulty为难度b，discrimination为区分度a，作答不足时按1PL固定为1
+drop table if exists question_irt;
+create table if not exists question_irt(
+    question_id integer primary key,
+    difficulty float4 not null,
+    discrimination float4 not null,
+    answer_count integer not null,
+    modified timestamp not null
+);
+-- 用户在知识点上的能力估计，keypoint_id为0表示该试卷类型下不区分知识点的整体能力
+drop table if exists user_ability;
+create table if not exists user_ability(
+    user_id integer not null,
+    paper_type int2 not null,
+    keypoint_id integer not null,
+    ability float4 not null,
+    answer_count integer not null,
+    modified timestamp not null,
+    primary key(user_id, paper_type, keypoint_id)
+);
+-- 自适应练习的作答记录，同一道题只保留最近一次作答
+drop table if exists adaptive_answer;
+create table if not exists adaptive_answer(
+    user_id integer not null,
+    question_id integer not null,
+    answer text not null,
+    correct bool not null,
+    created timestamp not null,
+    primary key(user_id, question_id)
+);
+-- 题目全文检索：text为题干、选项和材料的纯文本，tokens为jieba分词后的tsvector，
+-- question_type为QuestionExtra的类型标识
+drop table if exists question_search_index;
+create table if not exists question_search_index(
+    question_id integer primary key,
+    exam_id int2 not null,
+    paper_type int2 not null,
+    question_type varchar(16) not null,
+    text text not null,
+    tokens tsvector not null,
+    modified timestamp not null
+);
+create index if not exists idx_question_search_tokens on question_search_index using gin (tokens);
+create index if not exists idx_question_search_text_trgm on question_search_index using gin (text gin_trgm_ops);
+-- 题目、材料、解答、试卷的修订记录：snapshot为修改前的完整数据，
+-- from_ty为同步来源，user_id为后台回滚操作的管理员，diff为修改前后的字段差异
+drop table if exists question_revision;
+create table if not exists question_revision(
+    id serial primary key,
+    target_id integer not null,
+    snapshot jsonb not null,
+    from_ty from_type,
+    user_id integer,
+    diff text not null,
+    created timestamp not null
+);
+create index if not exists idx_question_revision_target on question_revision(target_id, id);
+drop table if exists material_revision;
+create table if not exists material_revision(
+    id serial primary key,
+    target_id integer not null,
+    snapshot jsonb not null,
+    from_ty from_type,
+    user_id integer,
+    diff text not null,
+    created timestamp not null
+);
+create index if not exists idx_material_revision_target on material_revision(target_id, id);
+drop table if exists solution_revision;
+create table if not exists solution_revision(
+    id serial primary key,
+    target_id integer not null,
+    snapshot jsonb not null,
+    from_ty from_type,
+    user_id integer,
+    diff text not null,
+    created timestamp not null
+);
+create index if not exists idx_solution_revision_target on solution_revision(target_id, id);
+drop table if exists paper_revision;
+create table if not exists paper_revision(
+    id serial primary key,
+    target_id integer not null,
+    snapshot jsonb not null,
+    from_ty from_type,
+    user_id integer,
+    diff text not null,
+    created timestamp not null
+);
+create index if not exists idx_paper_revision_target on paper_revision(target_id, id);
+-- 去重时相似但未被判定为重复的题目，等待人工核对：
+-- question_id为新写入的题目，candidate_id为库中相似的题目，reason为各项对比失败的原因
+drop table if exists question_duplicate;
+create table if not exists question_duplicate(
+    id serial primary key,
+    question_id integer not null,
+    candidate_id integer not null,
+    semantic_distance float8 not null,
+    levenshtein integer,
+    jaro_winkler float8 not null,
+    jaccard float8 not null,
+    content_has_media bool not null,
+    candidate_has_media bool not null,
+    reason text not null,
+    status duplicate_status not null default 'pending',
+    created timestamp not null,
+    modified timestamp not null,
+    unique(question_id, candidate_id)
+);
+create index if not exists idx_question_duplicate_status on question_duplicate(status, id);
+-- 按content_sim_hash找到的相似材料，等待人工核对后再合并：
+-- material_id为id较大的材料，candidate_id为库中相似的材料，reason为相似的依据
+drop table if exists material_duplicate;
+create table if not exists material_duplicate(
+    id serial primary key,
+    material_id integer not null,
+    candidate_id integer not null,
+    reason text not null,
+    status duplicate_status not null default 'pending',
+    created timestamp not null,
+    modified timestamp not null,
+    unique(material_id, candidate_id)
+);
+create index if not exists idx_material_duplicate_status on material_duplicate(status, id);
+-- 用户纠错：处理后reply为给用户的回复，user_read为用户是否已查看处理结果
+drop table if exists question_feedback;
+create table if not exists question_feedback(
+    id serial primary key,
+    question_id integer not null,
+    user_id integer not null,
+    category feedback_category not null,
+    content text not null,
+    suggested_answer text,
+    status feedback_status not null default 'open',
+    reply text,
+    handler_id integer,
+    user_read bool not null default true,
+    created timestamp not null,
+    modified timestamp not null
+);
+create index if not exists idx_question_feedback_status on question_feedback(status, id);
+create index if not exists idx_question_feedback_user on question_feedback(user_id, id);
+-- 用户笔记：target_type为question时是题目下的笔记，为material时是材料中高亮的一段文字，
+-- highlight_start/highlight_end为高亮在材料纯文本中的位置(按utf16计数，与浏览器一致)
+drop table if exists user_note;
+create table if not exists user_note(
+    id serial primary key,
+    user_id integer not null,
+    target_type src_type not null,
+    target_id integer not null,
+    markdown text not null,
+    highlight_start integer,
+    highlight_end integer,
+    highlight_text text,
+    created timestamp not null,
+    modified timestamp not null
+);
+create index if not exists idx_user_note_target on user_note(user_id, target_type, target_id);
+create index if not exists idx_user_note_user on user_note(user_id, id);
+-- 知识点自动标注：按embedding近邻投票，confidence为得票占比，
+-- 置信度高的直接写入question_key_point(auto)，低的等待人工审核(pending)
+drop table if exists question_keypoint_tag;
+create table if not exists question_keypoint_tag(
+    id serial primary key,
+    question_id integer not null unique,
+    key_point_id integer not null,
+    keypoint_path varchar(255) not null,
+    confidence float8 not null,
+    votes jsonb not null,
+    status keypoint_tag_status not null,
+    created timestamp not null,
+    modified timestamp not null
+);
+create index if not exists idx_question_keypoint_tag_status on question_keypoint_tag(status, id);
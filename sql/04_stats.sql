@@ -36,3 +36,49 @@ select
   count(distinct paper_id) as paper_count
 from idiom_ref
 group by ty, paper_type, label_id, idiom_id;
+
+create type review_item_type as enum('question', 'idiom');
+
+-- 间隔重复(SM-2)复习卡片：每个用户的每个复习项一张
+create table if not exists review_card(
+    user_id int not null,
+    item_type review_item_type not null,
+    item_id int not null,
+    ease float4 not null,
+    interval_days int not null,
+    repetitions int2 not null,
+    lapses int2 not null,
+    due date not null,
+    created timestamp not null,
+    modified timestamp not null,
+    primary key(user_id, item_type, item_id)
+);
+
+create index if not exists idx_review_card_due on review_card(user_id, due);
+
+create type collection_item_type as enum('question', 'paper', 'idiom', 'issue');
+
+-- 用户收藏夹，share_key不为空时可以通过链接分享给其他人
+create table if not exists collection(
+    id serial primary key,
+    user_id int not null,
+    name varchar(64) not null,
+    share_key varchar(32) unique,
+    created timestamp not null,
+    modified timestamp not null
+);
+
+create index if not exists idx_collection_user on collection(user_id);
+
+-- 收藏夹中的条目，sort越小越靠前，tags是用户给条目打的标签
+create table if not exists collection_item(
+    collection_id int not null,
+    item_type collection_item_type not null,
+    item_id int not null,
+    sort int not null,
+    tags jsonb not null,
+    created timestamp not null,
+    primary key(collection_id, item_type, item_id)
+);
+
+create index if not exists idx_collection_item_sort on collection_item(collection_id, sort);